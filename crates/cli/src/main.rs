@@ -2,11 +2,13 @@
 
 //! Audio Ninja CLI - Command-line interface for daemon control
 
+mod render;
 mod tui;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use serde_json::Value;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(Parser, Debug)]
@@ -18,6 +20,10 @@ struct Args {
     #[arg(short, long, default_value = "http://127.0.0.1:8080")]
     daemon: String,
 
+    /// Bearer token for a daemon started with --api-token
+    #[arg(long, value_name = "TOKEN")]
+    api_token: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -59,6 +65,61 @@ enum Commands {
 
     /// Show statistics
     Stats,
+
+    /// Show or set master volume
+    Volume {
+        /// Master gain in dB (at most +12)
+        #[arg(allow_hyphen_values = true)]
+        gain_db: Option<f32>,
+
+        /// Mute the output
+        #[arg(long, conflicts_with = "unmute")]
+        mute: bool,
+
+        /// Unmute the output
+        #[arg(long)]
+        unmute: bool,
+    },
+
+    /// Render a WAV file offline through the DSP chain (no daemon needed)
+    Render {
+        /// Input WAV file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output WAV file (32-bit float)
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Normalize to this integrated loudness in LUFS
+        #[arg(long, allow_hyphen_values = true)]
+        target_loudness: Option<f32>,
+
+        /// Dynamic range compression preset
+        #[arg(long, value_enum)]
+        drc: Option<DrcArg>,
+
+        /// Remap channels onto this layout preset (stereo, 5.1, 7.1, etc.)
+        #[arg(long)]
+        layout: Option<String>,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum DrcArg {
+    Speech,
+    Music,
+    Cinema,
+}
+
+impl From<DrcArg> for audio_ninja::render::DRCPreset {
+    fn from(arg: DrcArg) -> Self {
+        match arg {
+            DrcArg::Speech => Self::Speech,
+            DrcArg::Music => Self::Music,
+            DrcArg::Cinema => Self::Cinema,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -93,6 +154,9 @@ enum LayoutCommands {
     /// Show current layout
     Get,
 
+    /// List layout presets known to the daemon
+    Presets,
+
     /// Set layout from preset
     Set {
         /// Layout preset (stereo, 5.1, 7.1, etc.)
@@ -175,11 +239,19 @@ struct ApiClient {
 }
 
 impl ApiClient {
-    fn new(base_url: String) -> Self {
-        Self {
-            base_url,
-            client: reqwest::Client::new(),
+    fn new(base_url: String, api_token: Option<&str>) -> Result<Self> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(token) = api_token {
+            let value = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token))
+                .context("API token is not a valid header value")?;
+            headers.insert(reqwest::header::AUTHORIZATION, value);
         }
+
+        let client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .context("Failed to build HTTP client")?;
+        Ok(Self { base_url, client })
     }
 
     async fn get(&self, path: &str) -> Result<Value> {
@@ -236,11 +308,11 @@ impl ApiClient {
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
-    let client = ApiClient::new(args.daemon.clone());
+    let client = ApiClient::new(args.daemon.clone(), args.api_token.as_deref())?;
 
     match args.command {
         Commands::Tui => {
-            run_tui(args.daemon).await?;
+            run_tui(args.daemon, args.api_token).await?;
         }
 
         Commands::Status => {
@@ -286,6 +358,15 @@ async fn main() -> Result<()> {
                 println!("{}", serde_json::to_string_pretty(&layout)?);
             }
 
+            LayoutCommands::Presets => {
+                let presets = client.get("/layout/presets").await?;
+                if let Some(names) = presets.as_array() {
+                    for name in names.iter().filter_map(|n| n.as_str()) {
+                        println!("{}", name);
+                    }
+                }
+            }
+
             LayoutCommands::Set { preset } => {
                 let body = serde_json::json!({ "preset": preset });
                 client.post("/layout", Some(body)).await?;
@@ -384,12 +465,56 @@ async fn main() -> Result<()> {
             let stats = client.get("/stats").await?;
             println!("{}", serde_json::to_string_pretty(&stats)?);
         }
+
+        Commands::Volume {
+            gain_db,
+            mute,
+            unmute,
+        } => {
+            let mute = match (mute, unmute) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
+            if gain_db.is_none() && mute.is_none() {
+                let status = client.get("/status").await?;
+                println!("{}", serde_json::to_string_pretty(&status["volume"])?);
+            } else {
+                let body = serde_json::json!({ "gain_db": gain_db, "mute": mute });
+                client.post("/volume", Some(body)).await?;
+                println!("Volume updated");
+            }
+        }
+
+        Commands::Render {
+            input,
+            output,
+            target_loudness,
+            drc,
+            layout,
+        } => {
+            let settings = render::RenderSettings {
+                target_lufs: target_loudness,
+                drc: drc.map(Into::into),
+                layout,
+            };
+            let report = render::render_file(&input, &output, &settings)?;
+            println!(
+                "Rendered {} frames x {} channels at {} Hz to {}",
+                report.frames,
+                report.channels,
+                report.sample_rate,
+                output.display()
+            );
+            println!("Loudness before: {:.1} LUFS", report.before_lufs);
+            println!("Loudness after:  {:.1} LUFS", report.after_lufs);
+        }
     }
 
     Ok(())
 }
 
-async fn run_tui(base_url: String) -> Result<()> {
+async fn run_tui(base_url: String, api_token: Option<String>) -> Result<()> {
     use crossterm::{
         execute,
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -403,16 +528,15 @@ async fn run_tui(base_url: String) -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let client = ApiClient::new(base_url.clone());
+    let client = ApiClient::new(base_url.clone(), api_token.as_deref())?;
+    let mut meter_events = tui::meters::spawn_meter_stream(&base_url, api_token);
     let mut app = tui::App::new(base_url);
 
     // Initial data load
     if let Ok(status) = client.get("/status").await {
         app.status = Some(status);
     }
-    if let Ok(speakers) = client.get("/speakers").await {
-        app.speakers = Some(speakers);
-    }
+    tui::handler::refresh_speakers(&mut app, &client).await;
     if let Ok(layout) = client.get("/layout").await {
         app.layout = Some(layout);
     }
@@ -427,11 +551,15 @@ async fn run_tui(base_url: String) -> Result<()> {
     }
 
     let result = loop {
+        while let Ok(event) = meter_events.try_recv() {
+            app.apply_meter_event(event);
+        }
+
         terminal.draw(|f| {
             tui::ui::draw(f, &app);
         })?;
 
-        if tui::handler::handle_input(&mut app).await {
+        if tui::handler::handle_input(&mut app, &client).await {
             break Ok::<(), anyhow::Error>(());
         }
 
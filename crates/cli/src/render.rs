@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline rendering of WAV files through the core DSP chain, without the daemon
+
+use anyhow::{Context, Result};
+use audio_ninja::{
+    input::AudioFileReader,
+    loudness::{HeadroomManager, LoudnessMeter, LoudnessNormalizer, LoudnessTarget},
+    mapping::{downmix_channels, DownmixMatrix},
+    output::{BitDepth, WavWriter},
+    render::{DRCPreset, ReferenceRenderer, RenderOptions, Renderer},
+    AudioBlock, SpeakerLayout, SpeakerRole,
+};
+use std::path::Path;
+use std::time::Duration;
+
+/// Frames read and rendered per block
+const RENDER_BLOCK_FRAMES: usize = 4096;
+
+/// Limiter headroom after loudness normalization, matching the renderer's default
+const RENDER_HEADROOM_DB: f32 = 3.0;
+
+/// What to do to the input on its way to the output file
+#[derive(Debug, Clone, Default)]
+pub struct RenderSettings {
+    /// Normalize the whole file to this integrated loudness
+    pub target_lufs: Option<f32>,
+    /// Compress with this DRC preset
+    pub drc: Option<DRCPreset>,
+    /// Remap channels onto this layout preset
+    pub layout: Option<String>,
+}
+
+/// Summary of an offline render
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderReport {
+    pub frames: usize,
+    pub channels: usize,
+    pub sample_rate: u32,
+    pub before_lufs: f32,
+    pub after_lufs: f32,
+}
+
+/// Render `input` to a 32-bit float WAV at `output`.
+///
+/// The file is streamed a block at a time and never held in memory. Loudness
+/// normalization is two-pass: a first render of the whole file is measured,
+/// then the file is read again and rendered with one gain that brings it to
+/// the target, and a final limiter catches any peaks the gain pushed past the
+/// headroom. The limiters' lookahead is flushed at the end and trimmed from
+/// the start, so the output lines up with the input frame for frame.
+pub fn render_file(input: &Path, output: &Path, settings: &RenderSettings) -> Result<RenderReport> {
+    let mut reader = AudioFileReader::open(input)
+        .with_context(|| format!("Failed to open {}", input.display()))?;
+    let sample_rate = reader.sample_rate();
+    let source_channels = reader.channels();
+
+    let target_layout = match &settings.layout {
+        Some(name) => SpeakerLayout::from_preset(name)
+            .with_context(|| format!("Unknown layout preset: {}", name))?,
+        None => layout_for_channels(source_channels).unwrap_or_else(|| SpeakerLayout {
+            name: format!("{} channels", source_channels),
+            speakers: Vec::new(),
+        }),
+    };
+    let normalizer = LoudnessNormalizer::new(
+        sample_rate,
+        LoudnessTarget::Custom(settings.target_lufs.unwrap_or(-23.0)),
+    );
+    let mut output_meter = LoudnessMeter::new(sample_rate);
+    if !target_layout.speakers.is_empty() {
+        output_meter.set_channel_roles(&roles(&target_layout));
+    }
+
+    // First pass when normalizing: measure the render
+    let mut chain = RenderChain::new(settings, sample_rate, source_channels, &target_layout);
+    let mut gain_db = None;
+    if settings.target_lufs.is_some() {
+        let mut meter = output_meter.clone();
+        render_pass(&mut reader, &mut chain, |block| {
+            meter.accumulate(&block);
+            Ok(())
+        })
+        .with_context(|| format!("Failed to read {}", input.display()))?;
+        gain_db = Some(normalizer.file_gain_db(meter.finalize()));
+        chain = RenderChain::new(settings, sample_rate, source_channels, &target_layout);
+    }
+    chain.normalize = gain_db.map(|gain_db| {
+        let limiter = HeadroomManager::new(RENDER_HEADROOM_DB, sample_rate);
+        (gain_db, limiter)
+    });
+
+    let mut writer: Option<WavWriter> = None;
+    render_pass(&mut reader, &mut chain, |block| {
+        output_meter.accumulate(&block);
+        let writer = match &mut writer {
+            Some(writer) => writer,
+            None => writer.insert(WavWriter::create(
+                output,
+                block.channels.len(),
+                sample_rate,
+                BitDepth::Float32,
+            )?),
+        };
+        writer.write(&block)?;
+        Ok(())
+    })
+    .with_context(|| format!("Failed to render {}", input.display()))?;
+
+    // An empty input still leaves a valid, empty file
+    let channels = match target_layout.speakers.len() {
+        0 => source_channels,
+        n => n,
+    };
+    let writer = match writer {
+        Some(writer) => writer,
+        None => WavWriter::create(output, channels, sample_rate, BitDepth::Float32)?,
+    };
+    let frames = writer.frames() as usize;
+    writer
+        .finish()
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+
+    Ok(RenderReport {
+        frames,
+        channels,
+        sample_rate,
+        before_lufs: chain.input_meter.finalize(),
+        after_lufs: output_meter.finalize(),
+    })
+}
+
+/// Everything between the input file and the output file for one pass
+struct RenderChain {
+    remap: Option<Remap>,
+    renderer: ReferenceRenderer,
+    opts: RenderOptions,
+    /// Fixed normalization gain and the limiter catching what it pushes over
+    normalize: Option<(f32, HeadroomManager)>,
+    /// Loudness of the input read by this pass
+    input_meter: LoudnessMeter,
+}
+
+impl RenderChain {
+    fn new(
+        settings: &RenderSettings,
+        sample_rate: u32,
+        source_channels: usize,
+        target_layout: &SpeakerLayout,
+    ) -> Self {
+        let mut renderer = ReferenceRenderer::new(sample_rate);
+        if let Some(preset) = settings.drc {
+            renderer.apply_drc_preset(preset);
+        }
+        let mut input_meter = LoudnessMeter::new(sample_rate);
+        if let Some(source) = layout_for_channels(source_channels) {
+            input_meter.set_channel_roles(&roles(&source));
+        }
+
+        Self {
+            remap: remapper(source_channels, target_layout),
+            renderer,
+            opts: RenderOptions {
+                target_layout: target_layout.clone(),
+                // Normalized over the whole file rather than per block
+                target_loudness: None,
+                enable_drc: settings.drc.is_some(),
+                ..RenderOptions::default()
+            },
+            normalize: None,
+            input_meter,
+        }
+    }
+
+    fn process(&mut self, mut block: AudioBlock) -> AudioBlock {
+        if let Some(remap) = &self.remap {
+            block.channels = remap(&block.channels);
+        }
+        let mut block = self.renderer.render(block, &self.opts);
+        if let Some((gain_db, limiter)) = &mut self.normalize {
+            if gain_db.is_finite() {
+                block.apply_gain(*gain_db);
+            }
+            limiter.apply_limiting(&mut block);
+        }
+        block
+    }
+
+    /// Frames the look-ahead limiters hold back
+    fn latency(&self) -> usize {
+        let limiter = self
+            .normalize
+            .as_ref()
+            .map_or(0, |(_, limiter)| limiter.lookahead_samples());
+        self.renderer.headroom_lookahead_samples() + limiter
+    }
+}
+
+/// Stream the whole file through `chain` from the start, handing `sink`
+/// the output without the limiters' lookahead delay
+fn render_pass(
+    reader: &mut AudioFileReader,
+    chain: &mut RenderChain,
+    mut sink: impl FnMut(AudioBlock) -> Result<()>,
+) -> Result<()> {
+    reader.seek(Duration::ZERO)?;
+    let mut skipped = 0;
+    let mut emit = |mut block: AudioBlock, latency: usize| -> Result<()> {
+        let skip = (latency - skipped).min(block.frame_len());
+        skipped += skip;
+        for channel in &mut block.channels {
+            channel.drain(..skip);
+        }
+        if block.frame_len() > 0 {
+            sink(block)?;
+        }
+        Ok(())
+    };
+
+    let mut rendered_any = false;
+    while let Some(block) = reader.read_block(RENDER_BLOCK_FRAMES)? {
+        chain.input_meter.accumulate(&block);
+        let block = chain.process(block);
+        rendered_any = true;
+        emit(block, chain.latency())?;
+    }
+
+    // Push what is still inside the lookahead delay lines out with silence
+    let latency = chain.latency();
+    if rendered_any && latency > 0 {
+        let silence = AudioBlock::silence(reader.channels(), latency, reader.sample_rate());
+        let block = chain.process(silence);
+        emit(block, latency)?;
+    }
+    Ok(())
+}
+
+type Remap = Box<dyn Fn(&[Vec<f32>]) -> Vec<Vec<f32>>>;
+
+/// Channel mapping from `source_channels` onto `target`, if they differ
+fn remapper(source_channels: usize, target: &SpeakerLayout) -> Option<Remap> {
+    let target_channels = target.speakers.len();
+    if target_channels == 0 || target_channels == source_channels {
+        return None;
+    }
+
+    match layout_for_channels(source_channels) {
+        Some(source) => {
+            let matrix = DownmixMatrix::new(&source, target);
+            Some(Box::new(move |channels| matrix.apply(channels)))
+        }
+        None => Some(Box::new(move |channels| {
+            downmix_channels(channels, target_channels)
+        })),
+    }
+}
+
+/// Layout preset conventionally carried by a WAV with `channels` channels
+fn layout_for_channels(channels: usize) -> Option<SpeakerLayout> {
+    let name = match channels {
+        1 => return mono_layout(),
+        2 => "stereo",
+        4 => "quad",
+        6 => "5.1",
+        8 => "7.1",
+        12 => "7.1.4",
+        16 => "9.1.6",
+        _ => return None,
+    };
+    SpeakerLayout::from_preset(name)
+}
+
+fn roles(layout: &SpeakerLayout) -> Vec<SpeakerRole> {
+    layout.speakers.iter().map(|s| s.role.clone()).collect()
+}
+
+/// A single center speaker, so mono folds to a phantom center
+fn mono_layout() -> Option<SpeakerLayout> {
+    let center = SpeakerLayout::from_preset("5.1")?
+        .speakers
+        .into_iter()
+        .find(|s| s.role == SpeakerRole::Center)?;
+    Some(SpeakerLayout {
+        name: "mono".into(),
+        speakers: vec![center],
+    })
+}
@@ -2,7 +2,36 @@
 
 //! Application state for the TUI
 
+use serde::Deserialize;
 use serde_json::Value;
+use std::collections::VecDeque;
+use uuid::Uuid;
+
+use super::meters::{MeterConnection, MeterEvent, MeterFrame, GAUGE_FLOOR_DB};
+
+/// Integrated loudness readings kept for the Meters sparkline
+const LUFS_HISTORY_LEN: usize = 200;
+
+/// Speaker as listed by `GET /api/v1/speakers`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpeakerInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub address: String,
+    pub position: Option<SpeakerPosition>,
+    pub online: bool,
+    #[serde(default)]
+    pub role: Option<Value>,
+    #[serde(default)]
+    pub trim_db: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SpeakerPosition {
+    pub azimuth: f32,
+    pub elevation: f32,
+    pub distance: f32,
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Screen {
@@ -13,6 +42,7 @@ pub enum Screen {
     Input,
     Output,
     Calibration,
+    Meters,
 }
 
 #[derive(Debug)]
@@ -22,7 +52,10 @@ pub struct App {
     #[allow(dead_code)]
     pub base_url: String,
     pub status: Option<Value>,
-    pub speakers: Option<Value>,
+    /// Speakers sorted by name; `None` until the first fetch
+    pub speakers: Option<Vec<SpeakerInfo>>,
+    /// Show the details panel for the selected speaker
+    pub show_speaker_details: bool,
     pub layout: Option<Value>,
     pub transport_status: Option<Value>,
     #[allow(dead_code)]
@@ -33,6 +66,10 @@ pub struct App {
     pub output_status: Option<Value>,
     pub calibration_status: Option<Value>,
     pub stats: Option<Value>,
+    pub meter_frame: Option<MeterFrame>,
+    pub meter_connection: MeterConnection,
+    /// Integrated LUFS above the gauge floor, oldest first
+    pub lufs_history: VecDeque<u64>,
     pub error_message: Option<String>,
     pub selected_index: usize,
 }
@@ -45,6 +82,7 @@ impl App {
             base_url,
             status: None,
             speakers: None,
+            show_speaker_details: false,
             layout: None,
             transport_status: None,
             playback_status: None,
@@ -54,6 +92,9 @@ impl App {
             output_status: None,
             calibration_status: None,
             stats: None,
+            meter_frame: None,
+            meter_connection: MeterConnection::Connecting,
+            lufs_history: VecDeque::with_capacity(LUFS_HISTORY_LEN),
             error_message: None,
             selected_index: 0,
         }
@@ -71,37 +112,145 @@ impl App {
             Screen::Transport => Screen::Input,
             Screen::Input => Screen::Output,
             Screen::Output => Screen::Calibration,
-            Screen::Calibration => Screen::Dashboard,
+            Screen::Calibration => Screen::Meters,
+            Screen::Meters => Screen::Dashboard,
         };
     }
 
     pub fn previous_screen(&mut self) {
         self.current_screen = match self.current_screen {
-            Screen::Dashboard => Screen::Calibration,
+            Screen::Dashboard => Screen::Meters,
             Screen::Speakers => Screen::Dashboard,
             Screen::Layout => Screen::Speakers,
             Screen::Transport => Screen::Layout,
             Screen::Input => Screen::Transport,
             Screen::Output => Screen::Input,
             Screen::Calibration => Screen::Output,
+            Screen::Meters => Screen::Calibration,
         };
     }
 
+    /// Fold an update from the meter stream into the screen state
+    pub fn apply_meter_event(&mut self, event: MeterEvent) {
+        match event {
+            MeterEvent::Connected => self.meter_connection = MeterConnection::Connected,
+            MeterEvent::Disconnected(reason) => {
+                self.meter_connection = MeterConnection::Lost(reason);
+            }
+            MeterEvent::Frame(frame) => {
+                if let Some(lufs) = frame.integrated_lufs {
+                    if self.lufs_history.len() == LUFS_HISTORY_LEN {
+                        self.lufs_history.pop_front();
+                    }
+                    self.lufs_history
+                        .push_back((lufs - GAUGE_FLOOR_DB).max(0.0).round() as u64);
+                }
+                self.meter_frame = Some(frame);
+            }
+        }
+    }
+
+    /// Number of selectable items on the current screen
+    pub fn item_count(&self) -> usize {
+        match self.current_screen {
+            Screen::Speakers => self.speakers.as_ref().map_or(0, Vec::len),
+            _ => 0,
+        }
+    }
+
+    /// Move the selection down, wrapping to the first item
     pub fn next_item(&mut self) {
-        self.selected_index = self.selected_index.saturating_add(1);
+        let count = self.item_count();
+        self.selected_index = if count == 0 {
+            0
+        } else {
+            (self.selected_index + 1) % count
+        };
     }
 
+    /// Move the selection up, wrapping to the last item
     pub fn previous_item(&mut self) {
-        self.selected_index = self.selected_index.saturating_sub(1);
+        let count = self.item_count();
+        self.selected_index = match (count, self.selected_index) {
+            (0, _) => 0,
+            (_, 0) => count - 1,
+            (_, i) => (i - 1).min(count - 1),
+        };
+    }
+
+    /// Replace the speaker list, keeping the selection in range
+    pub fn set_speakers(&mut self, mut speakers: Vec<SpeakerInfo>) {
+        speakers.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        self.selected_index = self.selected_index.min(speakers.len().saturating_sub(1));
+        if speakers.is_empty() {
+            self.show_speaker_details = false;
+        }
+        self.speakers = Some(speakers);
+    }
+
+    /// Speaker under the cursor on the Speakers screen
+    pub fn selected_speaker(&self) -> Option<&SpeakerInfo> {
+        self.speakers.as_ref()?.get(self.selected_index)
     }
 
-    #[allow(dead_code)]
     pub fn clear_error(&mut self) {
         self.error_message = None;
     }
 
-    #[allow(dead_code)]
     pub fn set_error(&mut self, error: String) {
         self.error_message = Some(error);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speaker(name: &str) -> SpeakerInfo {
+        SpeakerInfo {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            address: "192.168.1.10:5000".to_string(),
+            position: None,
+            online: true,
+            role: None,
+            trim_db: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_speaker_selection_wraps_around() {
+        let mut app = App::new("http://localhost:8080".to_string());
+        app.current_screen = Screen::Speakers;
+        app.set_speakers(vec![speaker("a"), speaker("b"), speaker("c")]);
+
+        app.previous_item();
+        assert_eq!(app.selected_index, 2);
+
+        app.next_item();
+        assert_eq!(app.selected_index, 0);
+
+        app.next_item();
+        app.next_item();
+        assert_eq!(app.selected_index, 2);
+        assert_eq!(app.selected_speaker().unwrap().name, "c");
+    }
+
+    #[test]
+    fn test_selection_stays_in_range_when_list_shrinks() {
+        let mut app = App::new("http://localhost:8080".to_string());
+        app.current_screen = Screen::Speakers;
+        app.set_speakers(vec![speaker("a"), speaker("b"), speaker("c")]);
+        app.selected_index = 2;
+
+        app.set_speakers(vec![speaker("a"), speaker("b")]);
+        assert_eq!(app.selected_index, 1);
+
+        app.set_speakers(Vec::new());
+        assert_eq!(app.selected_index, 0);
+        app.next_item();
+        app.previous_item();
+        assert_eq!(app.selected_index, 0);
+        assert!(app.selected_speaker().is_none());
+    }
+}
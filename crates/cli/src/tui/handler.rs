@@ -2,10 +2,11 @@
 
 //! Input handling for the TUI
 
-use super::app::App;
+use super::app::{App, Screen, SpeakerInfo};
+use crate::ApiClient;
 use crossterm::event::{self, Event, KeyCode};
 
-pub async fn handle_input(app: &mut App) -> bool {
+pub async fn handle_input(app: &mut App, client: &ApiClient) -> bool {
     if crossterm::event::poll(std::time::Duration::from_millis(250)).unwrap_or(false) {
         if let Ok(Event::Key(key)) = event::read() {
             match key.code {
@@ -25,8 +26,17 @@ pub async fn handle_input(app: &mut App) -> bool {
                 KeyCode::Up | KeyCode::Char('k') => {
                     app.previous_item();
                 }
+                KeyCode::Enter if app.current_screen == Screen::Speakers => {
+                    app.show_speaker_details =
+                        !app.show_speaker_details && app.selected_speaker().is_some();
+                }
+                KeyCode::Char('x') if app.current_screen == Screen::Speakers => {
+                    remove_selected_speaker(app, client).await;
+                }
                 KeyCode::Char('r') => {
-                    // Trigger refresh
+                    if app.current_screen == Screen::Speakers {
+                        refresh_speakers(app, client).await;
+                    }
                     return false;
                 }
                 KeyCode::Char('d') => {
@@ -47,3 +57,33 @@ pub async fn handle_input(app: &mut App) -> bool {
     }
     false
 }
+
+/// Fetch the speaker list from the daemon
+pub async fn refresh_speakers(app: &mut App, client: &ApiClient) {
+    let speakers = client
+        .get("/speakers")
+        .await
+        .and_then(|value| Ok(serde_json::from_value::<Vec<SpeakerInfo>>(value)?));
+    match speakers {
+        Ok(speakers) => {
+            app.clear_error();
+            app.set_speakers(speakers);
+        }
+        Err(e) => app.set_error(format!("Failed to load speakers: {}", e)),
+    }
+}
+
+/// Remove the speaker under the cursor, then reload the list
+async fn remove_selected_speaker(app: &mut App, client: &ApiClient) {
+    let Some(speaker) = app.selected_speaker() else {
+        return;
+    };
+    let (id, name) = (speaker.id, speaker.name.clone());
+
+    if let Err(e) = client.delete(&format!("/speakers/{}", id)).await {
+        app.set_error(format!("Failed to remove {}: {}", name, e));
+        return;
+    }
+    app.show_speaker_details = false;
+    refresh_speakers(app, client).await;
+}
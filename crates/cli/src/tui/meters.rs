@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Live output meters streamed from the daemon over WebSocket
+
+use futures_util::StreamExt;
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, handshake::client::Request, http, Message},
+};
+
+/// Bottom of the meter gauges in dBFS
+pub const GAUGE_FLOOR_DB: f32 = -60.0;
+
+/// Wait between attempts to reach the meter stream
+const RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
+/// Per-channel levels of the most recently rendered block
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ChannelLevel {
+    pub peak_db: f32,
+    pub rms_db: f32,
+}
+
+/// One frame from `/api/v1/stream/meters`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct MeterFrame {
+    pub channels: Vec<ChannelLevel>,
+    #[serde(default)]
+    pub integrated_lufs: Option<f32>,
+}
+
+/// Updates from the background meter stream
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeterEvent {
+    Connected,
+    Frame(MeterFrame),
+    Disconnected(String),
+}
+
+/// State of the meter stream connection, as shown on the Meters screen
+#[derive(Debug, Clone, PartialEq)]
+pub enum MeterConnection {
+    Connecting,
+    Connected,
+    Lost(String),
+}
+
+/// WebSocket URL of the meter stream for a daemon base URL
+pub fn meter_stream_url(base_url: &str) -> String {
+    let base = base_url.trim_end_matches('/');
+    let ws_base = if let Some(rest) = base.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = base.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        base.to_string()
+    };
+    format!("{}/api/v1/stream/meters", ws_base)
+}
+
+/// Handshake request for the meter stream, carrying the API token if any
+fn meter_stream_request(url: &str, api_token: Option<&str>) -> Result<Request, String> {
+    let mut request = url.into_client_request().map_err(|e| e.to_string())?;
+    if let Some(token) = api_token {
+        let value =
+            http::HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|e| e.to_string())?;
+        request
+            .headers_mut()
+            .insert(http::header::AUTHORIZATION, value);
+    }
+    Ok(request)
+}
+
+/// Fraction of a gauge filled by a level in dBFS
+pub fn gauge_ratio(db: f32) -> f64 {
+    ((db - GAUGE_FLOOR_DB) / -GAUGE_FLOOR_DB).clamp(0.0, 1.0) as f64
+}
+
+/// Stream meter frames in the background, reconnecting whenever the link drops.
+///
+/// The task ends once the returned receiver is dropped.
+pub fn spawn_meter_stream(
+    base_url: &str,
+    api_token: Option<String>,
+) -> mpsc::UnboundedReceiver<MeterEvent> {
+    let url = meter_stream_url(base_url);
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let request = meter_stream_request(&url, api_token.as_deref());
+            let connection = match request {
+                Ok(request) => connect_async(request).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            let reason = match connection {
+                Ok((mut socket, _)) => {
+                    if tx.send(MeterEvent::Connected).is_err() {
+                        return;
+                    }
+                    loop {
+                        match socket.next().await {
+                            Some(Ok(Message::Text(text))) => {
+                                let Ok(frame) = serde_json::from_str::<MeterFrame>(&text) else {
+                                    continue;
+                                };
+                                if tx.send(MeterEvent::Frame(frame)).is_err() {
+                                    return;
+                                }
+                            }
+                            Some(Ok(Message::Close(_))) | None => {
+                                break "daemon closed the meter stream".to_string()
+                            }
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => break e.to_string(),
+                        }
+                    }
+                }
+                Err(e) => e,
+            };
+
+            if tx.send(MeterEvent::Disconnected(reason)).is_err() {
+                return;
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_stream_url() {
+        assert_eq!(
+            meter_stream_url("http://127.0.0.1:8080"),
+            "ws://127.0.0.1:8080/api/v1/stream/meters"
+        );
+        assert_eq!(
+            meter_stream_url("https://ninja.local/"),
+            "wss://ninja.local/api/v1/stream/meters"
+        );
+    }
+
+    #[test]
+    fn test_meter_stream_request_carries_token() {
+        let url = "ws://127.0.0.1:8080/api/v1/stream/meters";
+
+        let request = meter_stream_request(url, Some("secret")).unwrap();
+        assert_eq!(
+            request.headers()[http::header::AUTHORIZATION],
+            "Bearer secret"
+        );
+
+        let request = meter_stream_request(url, None).unwrap();
+        assert!(!request.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[test]
+    fn test_gauge_ratio_clamps_to_range() {
+        assert_eq!(gauge_ratio(0.0), 1.0);
+        assert_eq!(gauge_ratio(-120.0), 0.0);
+        assert!((gauge_ratio(-30.0) - 0.5).abs() < 1e-6);
+    }
+}
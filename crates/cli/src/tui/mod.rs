@@ -4,6 +4,7 @@
 
 pub mod app;
 pub mod handler;
+pub mod meters;
 pub mod ui;
 
 pub use app::App;
@@ -2,15 +2,17 @@
 
 //! UI rendering logic
 
+use audio_ninja::mapping::available_layouts;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Tabs, Wrap},
+    widgets::{Block, Borders, Gauge, List, ListItem, ListState, Paragraph, Sparkline, Tabs, Wrap},
     Frame,
 };
 
 use super::app::{App, Screen};
+use super::meters::{gauge_ratio, MeterConnection};
 
 pub fn draw(f: &mut Frame, app: &App) {
     let chunks = Layout::default()
@@ -40,6 +42,7 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
         "Input",
         "Output",
         "Calibration",
+        "Meters",
     ];
     let current = match app.current_screen {
         Screen::Dashboard => 0,
@@ -49,6 +52,7 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
         Screen::Input => 4,
         Screen::Output => 5,
         Screen::Calibration => 6,
+        Screen::Meters => 7,
     };
 
     let tabs = Tabs::new(screens)
@@ -77,6 +81,7 @@ fn draw_content(f: &mut Frame, area: Rect, app: &App) {
         Screen::Input => draw_input(f, area, app),
         Screen::Output => draw_output(f, area, app),
         Screen::Calibration => draw_calibration(f, area, app),
+        Screen::Meters => draw_meters(f, area, app),
     }
 }
 
@@ -147,26 +152,95 @@ fn draw_speakers(f: &mut Frame, area: Rect, app: &App) {
     let inner = block.inner(area);
     f.render_widget(block, area);
 
-    let mut text = vec![Line::from(Span::styled(
-        "Connected Speakers",
-        Style::default()
-            .fg(Color::Green)
-            .add_modifier(Modifier::BOLD),
-    ))];
+    let speakers = match &app.speakers {
+        Some(speakers) if !speakers.is_empty() => speakers,
+        Some(_) => {
+            let para = Paragraph::new(Line::from(Span::styled(
+                "No speakers connected. Press 'd' to discover.",
+                Style::default().fg(Color::Yellow),
+            )));
+            f.render_widget(para, inner);
+            return;
+        }
+        None => {
+            let para = Paragraph::new(Line::from(Span::styled(
+                "Loading...",
+                Style::default().fg(Color::Yellow),
+            )));
+            f.render_widget(para, inner);
+            return;
+        }
+    };
 
-    if let Some(speakers) = &app.speakers {
-        text.push(Line::from(format!("{}", speakers)));
-    } else {
-        text.push(Line::from(Span::styled(
-            "No speakers connected. Press 'd' to discover.",
-            Style::default().fg(Color::Yellow),
-        )));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(if app.show_speaker_details { 7 } else { 0 }),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let items: Vec<ListItem> = speakers
+        .iter()
+        .map(|speaker| {
+            let (status, color) = if speaker.online {
+                ("online ", Color::Green)
+            } else {
+                ("offline", Color::Red)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("● {} ", status), Style::default().fg(color)),
+                Span::raw(format!("{:<24} {}", speaker.name, speaker.address)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+    let mut state = ListState::default().with_selected(Some(app.selected_index));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    if let (true, Some(speaker)) = (app.show_speaker_details, app.selected_speaker()) {
+        let role = speaker
+            .role
+            .as_ref()
+            .map(|role| role.as_str().map_or_else(|| role.to_string(), String::from))
+            .unwrap_or_else(|| "unassigned".to_string());
+        let position = speaker
+            .position
+            .as_ref()
+            .map(|p| {
+                format!(
+                    "az {:.0}°  el {:.0}°  {:.2} m",
+                    p.azimuth, p.elevation, p.distance
+                )
+            })
+            .unwrap_or_else(|| "unknown".to_string());
+        let details = vec![
+            Line::from(format!("ID:       {}", speaker.id)),
+            Line::from(format!("Address:  {}", speaker.address)),
+            Line::from(format!("Role:     {}", role)),
+            Line::from(format!("Position: {}", position)),
+            Line::from(format!("Trim:     {:+.1} dB", speaker.trim_db)),
+        ];
+        let para = Paragraph::new(details).block(
+            Block::default()
+                .borders(Borders::TOP)
+                .title(speaker.name.as_str()),
+        );
+        f.render_widget(para, chunks[1]);
     }
 
-    let para = Paragraph::new(text)
-        .block(Block::default().borders(Borders::LEFT))
-        .wrap(Wrap { trim: true });
-    f.render_widget(para, inner);
+    f.render_widget(
+        Paragraph::new("[↑/↓] Select  [Enter] Details  [x] Remove  [r] Refresh"),
+        chunks[2],
+    );
 }
 
 fn draw_layout(f: &mut Frame, area: Rect, app: &App) {
@@ -195,7 +269,10 @@ fn draw_layout(f: &mut Frame, area: Rect, app: &App) {
     }
 
     text.push(Line::from(""));
-    text.push(Line::from("Available presets: stereo, 5.1, 7.1"));
+    text.push(Line::from(format!(
+        "Available presets: {}",
+        available_layouts().join(", ")
+    )));
 
     let para = Paragraph::new(text)
         .block(Block::default().borders(Borders::LEFT))
@@ -220,7 +297,16 @@ fn draw_transport(f: &mut Frame, area: Rect, app: &App) {
     ))];
 
     if let Some(status) = &app.transport_status {
-        text.push(Line::from(format!("{}", status)));
+        let state = status["state"].as_str().unwrap_or("Unknown");
+        let position = status["position_secs"].as_f64().unwrap_or(0.0);
+        let clock = match status["duration_secs"].as_f64() {
+            Some(duration) => format!("{} / {}", format_clock(position), format_clock(duration)),
+            None => format_clock(position),
+        };
+        text.push(Line::from(format!("{}  {}", state, clock)));
+        if let Some(file) = status["file"].as_str() {
+            text.push(Line::from(format!("File: {}", file)));
+        }
     } else {
         text.push(Line::from(Span::styled(
             "Loading...",
@@ -237,6 +323,12 @@ fn draw_transport(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(para, inner);
 }
 
+/// Format seconds as `m:ss`
+fn format_clock(secs: f64) -> String {
+    let total = secs.max(0.0) as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
+
 fn draw_calibration(f: &mut Frame, area: Rect, app: &App) {
     let block = Block::default()
         .title("Calibration")
@@ -428,3 +520,87 @@ fn draw_output(f: &mut Frame, area: Rect, app: &App) {
         .wrap(Wrap { trim: true });
     f.render_widget(status_para, chunks[1]);
 }
+
+fn draw_meters(f: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .title("Meters")
+        .borders(Borders::ALL)
+        .style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let channels = app
+        .meter_frame
+        .as_ref()
+        .map(|frame| frame.channels.as_slice())
+        .unwrap_or_default();
+
+    let mut constraints = vec![Constraint::Length(2)];
+    constraints.extend(channels.iter().map(|_| Constraint::Length(1)));
+    constraints.push(Constraint::Min(3));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(inner);
+
+    let status = match &app.meter_connection {
+        MeterConnection::Connecting => {
+            Span::styled("Connecting...", Style::default().fg(Color::Yellow))
+        }
+        MeterConnection::Connected => {
+            let lufs = app
+                .meter_frame
+                .as_ref()
+                .and_then(|frame| frame.integrated_lufs)
+                .map(|lufs| format!("{:.1} LUFS", lufs))
+                .unwrap_or_else(|| "-- LUFS".to_string());
+            Span::styled(
+                format!("Integrated: {}", lufs),
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            )
+        }
+        MeterConnection::Lost(reason) => Span::styled(
+            format!("Connection lost ({}), reconnecting...", reason),
+            Style::default().fg(Color::Red),
+        ),
+    };
+    f.render_widget(Paragraph::new(Line::from(status)), chunks[0]);
+
+    for (i, level) in channels.iter().enumerate() {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(level_color(level.peak_db)))
+            .ratio(gauge_ratio(level.peak_db))
+            .label(format!(
+                "Ch {:>2}  peak {:>6.1} dBFS  rms {:>6.1} dBFS",
+                i + 1,
+                level.peak_db,
+                level.rms_db
+            ));
+        f.render_widget(gauge, chunks[i + 1]);
+    }
+
+    let history: Vec<u64> = app.lufs_history.iter().copied().collect();
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .title("Integrated loudness"),
+        )
+        .data(&history)
+        .style(Style::default().fg(Color::Green));
+    f.render_widget(sparkline, chunks[channels.len() + 1]);
+}
+
+/// Meter colour for a peak level in dBFS
+fn level_color(peak_db: f32) -> Color {
+    if peak_db > -1.0 {
+        Color::Red
+    } else if peak_db > -9.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    }
+}
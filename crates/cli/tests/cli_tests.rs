@@ -129,3 +129,118 @@ fn test_layout_set_requires_preset() {
     let stderr = String::from_utf8_lossy(&output.stderr);
     assert!(stderr.contains("required") || stderr.contains("argument"));
 }
+
+/// Write a 16-bit stereo WAV at 48kHz holding a 1kHz tone
+fn write_tone_wav(path: &std::path::Path, frames: u32) {
+    use audio_ninja::output::{write_wav, BitDepth};
+
+    let tone: Vec<f32> = (0..frames)
+        .map(|n| 0.1 * (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin())
+        .collect();
+    let block = audio_ninja::AudioBlock {
+        sample_rate: 48000,
+        channels: vec![tone.clone(), tone],
+    };
+    write_wav(path, &block, BitDepth::Int16).expect("write wav fixture");
+}
+
+#[test]
+fn test_render_offline() {
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("audio-ninja-render-in-{}.wav", std::process::id()));
+    let output = dir.join(format!("audio-ninja-render-out-{}.wav", std::process::id()));
+    write_tone_wav(&input, 48000);
+
+    let result = run_cli(&[
+        "render",
+        "--input",
+        &input.to_string_lossy(),
+        "--output",
+        &output.to_string_lossy(),
+        "--target-loudness",
+        "-14",
+        "--drc",
+        "music",
+        "--layout",
+        "5.1",
+    ]);
+
+    let stdout = String::from_utf8_lossy(&result.stdout);
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    assert!(stdout.contains("Loudness before"));
+    assert!(stdout.contains("Loudness after"));
+
+    // Stereo upmixed onto 5.1, one second of 32-bit float frames
+    let rendered = std::fs::read(&output).expect("rendered file exists");
+    assert_eq!(&rendered[0..4], b"RIFF");
+    assert_eq!(u16::from_le_bytes([rendered[22], rendered[23]]), 6);
+    assert_eq!(rendered.len(), 44 + 48000 * 6 * 4);
+
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+}
+
+#[test]
+fn test_render_output_lines_up_with_input() {
+    use audio_ninja::input::load_wav;
+
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("audio-ninja-align-in-{}.wav", std::process::id()));
+    let output = dir.join(format!("audio-ninja-align-out-{}.wav", std::process::id()));
+    write_tone_wav(&input, 10_000);
+
+    // -20 LUFS in, so normalizing is a clean +6 dB that no limiter touches
+    let result = run_cli(&[
+        "render",
+        "--input",
+        &input.to_string_lossy(),
+        "--output",
+        &output.to_string_lossy(),
+        "--target-loudness",
+        "-14",
+    ]);
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    // No lookahead silence up front and nothing cut from the end
+    let original = load_wav(&input).expect("read input");
+    let rendered = load_wav(&output).expect("read output");
+    assert_eq!(rendered.frame_len(), original.frame_len());
+    let gain = 10f32.powf(6.0 / 20.0);
+    for (a, b) in rendered.channels[0].iter().zip(&original.channels[0]) {
+        assert!((a - b * gain).abs() < 2e-3, "{} vs {}", a, b * gain);
+    }
+
+    let _ = std::fs::remove_file(&input);
+    let _ = std::fs::remove_file(&output);
+}
+
+#[test]
+fn test_render_rejects_unknown_layout() {
+    let dir = std::env::temp_dir();
+    let input = dir.join(format!("audio-ninja-render-bad-{}.wav", std::process::id()));
+    write_tone_wav(&input, 4800);
+
+    let result = run_cli(&[
+        "render",
+        "--input",
+        &input.to_string_lossy(),
+        "--output",
+        &dir.join("audio-ninja-unused.wav").to_string_lossy(),
+        "--layout",
+        "13.7",
+    ]);
+
+    assert!(!result.status.success());
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("Unknown layout preset"));
+
+    let _ = std::fs::remove_file(&input);
+}
@@ -67,6 +67,14 @@ fn run_cli(args: &[&str]) -> String {
     String::from_utf8_lossy(&output.stdout).to_string()
 }
 
+/// Write a one-second silent 16-bit mono WAV at 8kHz
+fn write_silent_wav(path: &std::path::Path) {
+    use audio_ninja::output::{write_wav, BitDepth};
+
+    let block = audio_ninja::AudioBlock::silence(1, 8000, 8000);
+    write_wav(path, &block, BitDepth::Int16).expect("write wav fixture");
+}
+
 #[test]
 fn e2e_status_and_info() {
     let port = pick_free_port();
@@ -89,6 +97,17 @@ fn e2e_transport_flow() {
     let mut daemon = spawn_daemon(port);
     let base = format!("http://127.0.0.1:{}", port);
 
+    // File mode refuses to play until something is loaded
+    let wav = std::env::temp_dir().join(format!("audio-ninja-e2e-{}.wav", port));
+    write_silent_wav(&wav);
+    run_cli(&[
+        "--daemon",
+        &base,
+        "transport",
+        "load-file",
+        &wav.to_string_lossy(),
+    ]);
+
     run_cli(&["--daemon", &base, "transport", "play"]);
     let s1 = run_cli(&["--daemon", &base, "transport", "status"]);
     assert!(s1.contains("\"Playing\""));
@@ -103,6 +122,7 @@ fn e2e_transport_flow() {
 
     let _ = daemon.kill();
     let _ = daemon.wait();
+    let _ = std::fs::remove_file(&wav);
 }
 
 #[test]
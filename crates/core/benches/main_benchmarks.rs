@@ -3,6 +3,7 @@
 //! Core Performance Benchmarks for audio-ninja library
 
 use audio_ninja::{
+    hoa::{create_7_1_4_hoa_layout, AmbisonicOrder, DecodingMode, HoaDecoder},
     loudness::LoudnessMeter,
     vbap::{Speaker3D, Vbap3D, Vec3},
     AudioBlock,
@@ -192,6 +193,27 @@ fn bench_loudness_types(c: &mut Criterion) {
     group.finish();
 }
 
+// HOA Decoder Benchmarks
+fn bench_hoa_decode_buffer(c: &mut Criterion) {
+    c.bench_function("hoa_decode_buffer_2nd_order_7.1.4_1s", |b| {
+        let decoder = HoaDecoder::new(
+            AmbisonicOrder::SECOND,
+            DecodingMode::MaxRE,
+            create_7_1_4_hoa_layout(),
+        );
+        let input: Vec<Vec<f32>> = (0..decoder.channel_count())
+            .map(|ch| {
+                (0..48000)
+                    .map(|i| ((i + ch) as f32 * 0.01).sin() * 0.1)
+                    .collect()
+            })
+            .collect();
+        let input = black_box(input);
+
+        b.iter(|| decoder.decode_buffer(&input));
+    });
+}
+
 criterion_group!(
     benches,
     bench_vbap_stereo_render,
@@ -201,7 +223,8 @@ criterion_group!(
     bench_loudness_mono,
     bench_loudness_stereo,
     bench_loudness_5_1,
-    bench_loudness_types
+    bench_loudness_types,
+    bench_hoa_decode_buffer
 );
 
 criterion_main!(benches);
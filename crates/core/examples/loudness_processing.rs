@@ -62,7 +62,7 @@ fn main() -> anyhow::Result<()> {
     // ========== Dynamic Range Control ==========
     println!("4. Applying Dynamic Range Control (4:1 ratio, -18dB threshold)...");
     let mut drc_audio = normalized_audio.clone();
-    let mut drc = DynamicRangeControl::new(4.0, -18.0, 10.0, 100.0, SAMPLE_RATE);
+    let mut drc = DynamicRangeControl::new(4.0, -18.0, 0.0, 10.0, 100.0, SAMPLE_RATE);
     drc.set_makeup_gain((-18.0 * 3.0) / 4.0); // Calculate makeup gain for 4:1
 
     drc.process(&mut drc_audio);
@@ -23,6 +23,14 @@ pub enum BleError {
     ReadFailed,
     #[error("Pairing failed: {0}")]
     PairingFailed(String),
+    #[error("Already bonded to master {0}")]
+    AlreadyBonded(String),
+    #[error("Speaker role has no BLE equivalent: {0:?}")]
+    UnsupportedRole(crate::SpeakerRole),
+    #[error("Scan failed: {0}")]
+    ScanFailed(String),
+    #[error("Invalid characteristic value: {0}")]
+    InvalidWireFormat(String),
 }
 
 /// Audio Ninja BLE GATT Service UUIDs
@@ -74,7 +82,10 @@ pub struct SpeakerIdentity {
     pub mac_address: String,
 }
 
-/// Speaker role in the audio system
+/// Speaker role as advertised over BLE.
+///
+/// Layouts use `crate::SpeakerRole`; convert with `From`/`TryFrom`. The wire
+/// names here are kept as-is so stored speaker configs still parse.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SpeakerRole {
     FrontLeft,
@@ -92,6 +103,54 @@ pub enum SpeakerRole {
     Custom(String),
 }
 
+impl From<SpeakerRole> for crate::SpeakerRole {
+    fn from(role: SpeakerRole) -> Self {
+        match role {
+            SpeakerRole::FrontLeft => Self::FrontLeft,
+            SpeakerRole::FrontRight => Self::FrontRight,
+            SpeakerRole::Center => Self::Center,
+            SpeakerRole::LFE => Self::Subwoofer,
+            SpeakerRole::SurroundLeft => Self::SideLeft,
+            SpeakerRole::SurroundRight => Self::SideRight,
+            SpeakerRole::BackLeft => Self::RearLeft,
+            SpeakerRole::BackRight => Self::RearRight,
+            SpeakerRole::TopFrontLeft => Self::TopFrontLeft,
+            SpeakerRole::TopFrontRight => Self::TopFrontRight,
+            SpeakerRole::TopBackLeft => Self::TopRearLeft,
+            SpeakerRole::TopBackRight => Self::TopRearRight,
+            SpeakerRole::Custom(name) => Self::Custom(name),
+        }
+    }
+}
+
+impl TryFrom<crate::SpeakerRole> for SpeakerRole {
+    type Error = BleError;
+
+    /// Height roles other than the top ones have no BLE equivalent
+    fn try_from(role: crate::SpeakerRole) -> Result<Self, Self::Error> {
+        use crate::SpeakerRole as Layout;
+        Ok(match role {
+            Layout::FrontLeft => Self::FrontLeft,
+            Layout::FrontRight => Self::FrontRight,
+            Layout::Center => Self::Center,
+            Layout::Subwoofer => Self::LFE,
+            Layout::SideLeft => Self::SurroundLeft,
+            Layout::SideRight => Self::SurroundRight,
+            Layout::RearLeft => Self::BackLeft,
+            Layout::RearRight => Self::BackRight,
+            Layout::TopFrontLeft => Self::TopFrontLeft,
+            Layout::TopFrontRight => Self::TopFrontRight,
+            Layout::TopRearLeft => Self::TopBackLeft,
+            Layout::TopRearRight => Self::TopBackRight,
+            Layout::Custom(name) => Self::Custom(name),
+            Layout::FrontHeightLeft
+            | Layout::FrontHeightRight
+            | Layout::RearHeightLeft
+            | Layout::RearHeightRight => return Err(BleError::UnsupportedRole(role)),
+        })
+    }
+}
+
 impl SpeakerRole {
     pub fn to_channel_index(&self) -> usize {
         match self {
@@ -146,6 +205,9 @@ pub enum ConnectionStatus {
     Error(String),
 }
 
+/// Connection status notification callback, fired on every status change
+pub type StatusCallback = Arc<dyn Fn(&ConnectionStatus) + Send + Sync>;
+
 /// Pairing request
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PairingRequest {
@@ -222,6 +284,9 @@ pub struct BlePeripheral {
     identity: SpeakerIdentity,
     services: HashMap<Uuid, GattService>,
     connection_status: ConnectionStatus,
+    status_subscribers: Vec<StatusCallback>,
+    expected_pin: Option<String>,
+    bonded_master: Option<String>,
 }
 
 impl BlePeripheral {
@@ -230,9 +295,13 @@ impl BlePeripheral {
             identity,
             services: HashMap::new(),
             connection_status: ConnectionStatus::Disconnected,
+            status_subscribers: Vec::new(),
+            expected_pin: None,
+            bonded_master: None,
         };
 
         peripheral.setup_services();
+        peripheral.publish_status();
         peripheral
     }
 
@@ -294,21 +363,79 @@ impl BlePeripheral {
     }
 
     pub fn connect(&mut self) -> Result<(), BleError> {
-        self.connection_status = ConnectionStatus::Connected;
+        self.set_status(ConnectionStatus::Connected);
         Ok(())
     }
 
     pub fn disconnect(&mut self) {
-        self.connection_status = ConnectionStatus::Disconnected;
+        self.set_status(ConnectionStatus::Disconnected);
+    }
+
+    /// PIN or passkey a master must present to pair
+    pub fn set_expected_pin(&mut self, pin: impl Into<String>) {
+        self.expected_pin = Some(pin.into());
     }
 
+    /// Pair with the master in `request` and bond to it.
+    ///
+    /// A PIN is always required; it must match `set_expected_pin` when one is
+    /// set. Once bonded, only the same master may pair again.
     pub fn pair(&mut self, request: PairingRequest) -> Result<(), BleError> {
-        // Simplified pairing - real implementation would validate PIN
-        if request.pin_code.is_some() {
-            self.connection_status = ConnectionStatus::Paired;
-            Ok(())
-        } else {
-            Err(BleError::PairingFailed("PIN required".into()))
+        let pin = request
+            .pin_code
+            .ok_or_else(|| BleError::PairingFailed("PIN required".into()))?;
+
+        if let Some(bonded) = &self.bonded_master {
+            if *bonded != request.master_id {
+                return Err(BleError::AlreadyBonded(bonded.clone()));
+            }
+        }
+        if self
+            .expected_pin
+            .as_ref()
+            .is_some_and(|expected| *expected != pin)
+        {
+            return Err(BleError::PairingFailed("PIN mismatch".into()));
+        }
+
+        self.bonded_master = Some(request.master_id);
+        self.set_status(ConnectionStatus::Paired);
+        Ok(())
+    }
+
+    pub fn is_bonded_to(&self, master_id: &str) -> bool {
+        self.bonded_master.as_deref() == Some(master_id)
+    }
+
+    /// Notify `callback` whenever `connect`, `disconnect` or `pair` changes
+    /// the connection status.
+    pub fn subscribe_status(&mut self, callback: StatusCallback) {
+        self.status_subscribers.push(callback);
+    }
+
+    fn set_status(&mut self, status: ConnectionStatus) {
+        if self.connection_status == status {
+            return;
+        }
+
+        self.connection_status = status;
+        self.publish_status();
+        for callback in &self.status_subscribers {
+            callback(&self.connection_status);
+        }
+    }
+
+    /// Mirror the status into the CONNECTION_STATUS characteristic value
+    fn publish_status(&mut self) {
+        let value = wire::encode_status(&self.connection_status);
+        if let Some(char) = self
+            .services
+            .get_mut(&service_uuids::AUDIO_NINJA_SERVICE)
+            .and_then(|service| {
+                service.get_characteristic_mut(&characteristic_uuids::CONNECTION_STATUS)
+            })
+        {
+            char.value = value;
         }
     }
 
@@ -355,9 +482,21 @@ impl BleCentral {
         }
     }
 
-    pub fn scan(&self, _timeout: Duration) -> Result<Vec<SpeakerIdentity>, BleError> {
-        // Placeholder - real implementation would scan for BLE devices
-        Ok(Vec::new())
+    /// Scan for speakers advertising the Audio Ninja service for `timeout`.
+    ///
+    /// Needs the `ble` feature; without it no radio is touched and the result
+    /// is always empty. Each identity can go straight into
+    /// `BlePeripheral::new` and then `connect`.
+    pub fn scan(&self, timeout: Duration) -> Result<Vec<SpeakerIdentity>, BleError> {
+        #[cfg(feature = "ble")]
+        {
+            scanner::scan(timeout)
+        }
+        #[cfg(not(feature = "ble"))]
+        {
+            let _ = timeout;
+            Ok(Vec::new())
+        }
     }
 
     pub fn connect(&self, device_id: &str, peripheral: BlePeripheral) -> Result<(), BleError> {
@@ -376,6 +515,21 @@ impl BleCentral {
         }
     }
 
+    /// Subscribe to connection status changes of a connected device
+    pub fn on_status_change(
+        &self,
+        device_id: &str,
+        callback: StatusCallback,
+    ) -> Result<(), BleError> {
+        let mut devices = self.connected_devices.lock().unwrap();
+        let device = devices
+            .get_mut(device_id)
+            .ok_or_else(|| BleError::DeviceNotFound(device_id.into()))?;
+
+        device.subscribe_status(callback);
+        Ok(())
+    }
+
     pub fn read_speaker_identity(&self, device_id: &str) -> Result<SpeakerIdentity, BleError> {
         let devices = self.connected_devices.lock().unwrap();
         let device = devices
@@ -383,7 +537,7 @@ impl BleCentral {
             .ok_or_else(|| BleError::DeviceNotFound(device_id.into()))?;
 
         let data = device.read_characteristic(&characteristic_uuids::SPEAKER_IDENTITY)?;
-        bincode::deserialize(&data).map_err(|_| BleError::ReadFailed)
+        wire::decode_identity(&data)
     }
 
     pub fn write_speaker_identity(
@@ -396,7 +550,7 @@ impl BleCentral {
             .get_mut(device_id)
             .ok_or_else(|| BleError::DeviceNotFound(device_id.into()))?;
 
-        let data = bincode::serialize(identity).map_err(|_| BleError::WriteFailed)?;
+        let data = wire::encode_identity(identity)?;
         device.write_characteristic(&characteristic_uuids::SPEAKER_IDENTITY, data)
     }
 
@@ -411,12 +565,16 @@ impl BleCentral {
             .ok_or_else(|| BleError::DeviceNotFound(device_id.into()))?;
 
         // Write trim
-        let trim_data = bincode::serialize(&cal.trim_db).map_err(|_| BleError::WriteFailed)?;
-        device.write_characteristic(&characteristic_uuids::VOLUME_TRIM, trim_data)?;
+        device.write_characteristic(
+            &characteristic_uuids::VOLUME_TRIM,
+            wire::encode_trim_db(cal.trim_db),
+        )?;
 
         // Write delay
-        let delay_data = bincode::serialize(&cal.delay_ms).map_err(|_| BleError::WriteFailed)?;
-        device.write_characteristic(&characteristic_uuids::DELAY_COMPENSATION, delay_data)?;
+        device.write_characteristic(
+            &characteristic_uuids::DELAY_COMPENSATION,
+            wire::encode_delay_ms(cal.delay_ms),
+        )?;
 
         Ok(())
     }
@@ -436,3 +594,291 @@ impl Default for BleCentral {
         Self::new()
     }
 }
+
+/// Byte layouts of characteristic values shared with speaker firmware.
+///
+/// Every value starts with the format `VERSION` byte. Numbers are
+/// little-endian and strings are a u8 length followed by UTF-8.
+pub mod wire {
+    use super::{BleError, ConnectionStatus, SpeakerIdentity, SpeakerRole};
+
+    /// Current characteristic format version
+    pub const VERSION: u8 = 1;
+
+    /// Role code of `SpeakerRole::Custom`; other roles use their channel index
+    const CUSTOM_ROLE: u8 = 0xFF;
+
+    /// SPEAKER_IDENTITY: version, role code, 6-byte MAC, id, name, and the
+    /// role name for custom roles
+    pub fn encode_identity(identity: &SpeakerIdentity) -> Result<Vec<u8>, BleError> {
+        let role_code = match &identity.role {
+            SpeakerRole::Custom(_) => CUSTOM_ROLE,
+            role => role.to_channel_index() as u8,
+        };
+
+        let mut out = vec![VERSION, role_code];
+        out.extend_from_slice(&parse_mac(&identity.mac_address)?);
+        put_string(&mut out, &identity.id)?;
+        put_string(&mut out, &identity.name)?;
+        if let SpeakerRole::Custom(name) = &identity.role {
+            put_string(&mut out, name)?;
+        }
+        Ok(out)
+    }
+
+    pub fn decode_identity(data: &[u8]) -> Result<SpeakerIdentity, BleError> {
+        let mut reader = Reader::new(data)?;
+        let role_code = reader.u8()?;
+        let mac = reader.take(6)?;
+        let id = reader.string()?;
+        let name = reader.string()?;
+        let role = match role_code {
+            CUSTOM_ROLE => SpeakerRole::Custom(reader.string()?),
+            code => role_from_code(code)?,
+        };
+        reader.finish()?;
+
+        Ok(SpeakerIdentity {
+            id,
+            name,
+            role,
+            mac_address: mac
+                .iter()
+                .map(|b| format!("{b:02X}"))
+                .collect::<Vec<_>>()
+                .join(":"),
+        })
+    }
+
+    /// VOLUME_TRIM: version, f32 trim in dB
+    pub fn encode_trim_db(trim_db: f32) -> Vec<u8> {
+        encode_f32(trim_db)
+    }
+
+    pub fn decode_trim_db(data: &[u8]) -> Result<f32, BleError> {
+        decode_f32(data)
+    }
+
+    /// DELAY_COMPENSATION: version, f32 delay in ms
+    pub fn encode_delay_ms(delay_ms: f32) -> Vec<u8> {
+        encode_f32(delay_ms)
+    }
+
+    pub fn decode_delay_ms(data: &[u8]) -> Result<f32, BleError> {
+        decode_f32(data)
+    }
+
+    /// CONNECTION_STATUS: version, status code (0 disconnected, 1 connecting,
+    /// 2 connected, 3 paired, 4 error) and, for errors, the message cut to
+    /// 255 bytes
+    pub fn encode_status(status: &ConnectionStatus) -> Vec<u8> {
+        let code = match status {
+            ConnectionStatus::Disconnected => 0,
+            ConnectionStatus::Connecting => 1,
+            ConnectionStatus::Connected => 2,
+            ConnectionStatus::Paired => 3,
+            ConnectionStatus::Error(_) => 4,
+        };
+
+        let mut out = vec![VERSION, code];
+        if let ConnectionStatus::Error(message) = status {
+            let mut end = message.len().min(u8::MAX as usize);
+            while !message.is_char_boundary(end) {
+                end -= 1;
+            }
+            out.push(end as u8);
+            out.extend_from_slice(&message.as_bytes()[..end]);
+        }
+        out
+    }
+
+    pub fn decode_status(data: &[u8]) -> Result<ConnectionStatus, BleError> {
+        let mut reader = Reader::new(data)?;
+        let status = match reader.u8()? {
+            0 => ConnectionStatus::Disconnected,
+            1 => ConnectionStatus::Connecting,
+            2 => ConnectionStatus::Connected,
+            3 => ConnectionStatus::Paired,
+            4 => ConnectionStatus::Error(reader.string()?),
+            other => return Err(invalid(format!("unknown status code {other}"))),
+        };
+        reader.finish()?;
+        Ok(status)
+    }
+
+    fn encode_f32(value: f32) -> Vec<u8> {
+        let mut out = vec![VERSION];
+        out.extend_from_slice(&value.to_le_bytes());
+        out
+    }
+
+    fn decode_f32(data: &[u8]) -> Result<f32, BleError> {
+        let mut reader = Reader::new(data)?;
+        let bytes = reader.take(4)?;
+        reader.finish()?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn role_from_code(code: u8) -> Result<SpeakerRole, BleError> {
+        Ok(match code {
+            0 => SpeakerRole::FrontLeft,
+            1 => SpeakerRole::FrontRight,
+            2 => SpeakerRole::Center,
+            3 => SpeakerRole::LFE,
+            4 => SpeakerRole::SurroundLeft,
+            5 => SpeakerRole::SurroundRight,
+            6 => SpeakerRole::BackLeft,
+            7 => SpeakerRole::BackRight,
+            8 => SpeakerRole::TopFrontLeft,
+            9 => SpeakerRole::TopFrontRight,
+            10 => SpeakerRole::TopBackLeft,
+            11 => SpeakerRole::TopBackRight,
+            other => return Err(invalid(format!("unknown role code {other}"))),
+        })
+    }
+
+    fn parse_mac(mac: &str) -> Result<[u8; 6], BleError> {
+        let mut out = [0u8; 6];
+        let mut parts = mac.split(':');
+        for byte in &mut out {
+            *byte = parts
+                .next()
+                .and_then(|part| u8::from_str_radix(part, 16).ok())
+                .ok_or_else(|| invalid(format!("bad MAC address {mac:?}")))?;
+        }
+        if parts.next().is_some() {
+            return Err(invalid(format!("bad MAC address {mac:?}")));
+        }
+        Ok(out)
+    }
+
+    fn put_string(out: &mut Vec<u8>, value: &str) -> Result<(), BleError> {
+        let len = u8::try_from(value.len())
+            .map_err(|_| invalid(format!("string longer than 255 bytes: {value:?}")))?;
+        out.push(len);
+        out.extend_from_slice(value.as_bytes());
+        Ok(())
+    }
+
+    fn invalid(reason: String) -> BleError {
+        BleError::InvalidWireFormat(reason)
+    }
+
+    struct Reader<'a> {
+        data: &'a [u8],
+    }
+
+    impl<'a> Reader<'a> {
+        /// Check the version byte and position after it
+        fn new(data: &'a [u8]) -> Result<Self, BleError> {
+            match data.split_first() {
+                Some((&VERSION, rest)) => Ok(Self { data: rest }),
+                Some((version, _)) => Err(invalid(format!("unsupported version {version}"))),
+                None => Err(invalid("empty value".into())),
+            }
+        }
+
+        fn take(&mut self, len: usize) -> Result<&'a [u8], BleError> {
+            if self.data.len() < len {
+                return Err(invalid("truncated value".into()));
+            }
+            let (head, rest) = self.data.split_at(len);
+            self.data = rest;
+            Ok(head)
+        }
+
+        fn u8(&mut self) -> Result<u8, BleError> {
+            Ok(self.take(1)?[0])
+        }
+
+        fn string(&mut self) -> Result<String, BleError> {
+            let len = self.u8()? as usize;
+            let bytes = self.take(len)?;
+            String::from_utf8(bytes.to_vec()).map_err(|_| invalid("string is not UTF-8".into()))
+        }
+
+        fn finish(self) -> Result<(), BleError> {
+            if self.data.is_empty() {
+                Ok(())
+            } else {
+                Err(invalid(format!("{} trailing bytes", self.data.len())))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "ble")]
+mod scanner {
+    use super::{service_uuids, BleError, SpeakerIdentity, SpeakerRole};
+    use btleplug::api::{Central, Manager as _, Peripheral as _, ScanFilter};
+    use btleplug::platform::Manager;
+    use std::time::Duration;
+
+    /// Role of a scanned speaker until one is written to it
+    const UNASSIGNED_ROLE: &str = "Unassigned";
+
+    pub(super) fn scan(timeout: Duration) -> Result<Vec<SpeakerIdentity>, BleError> {
+        // btleplug is async; give it a private runtime on its own thread so
+        // callers already inside tokio don't hit a nested block_on.
+        std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| BleError::ScanFailed(e.to_string()))?
+                .block_on(discover(timeout))
+        })
+        .join()
+        .map_err(|_| BleError::ScanFailed("scan thread panicked".into()))?
+    }
+
+    async fn discover(timeout: Duration) -> Result<Vec<SpeakerIdentity>, BleError> {
+        let manager = Manager::new().await.map_err(scan_error)?;
+        let adapter = manager
+            .adapters()
+            .await
+            .map_err(scan_error)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| BleError::ScanFailed("no Bluetooth adapter".into()))?;
+
+        adapter
+            .start_scan(ScanFilter {
+                services: vec![service_uuids::AUDIO_NINJA_SERVICE],
+            })
+            .await
+            .map_err(scan_error)?;
+        tokio::time::sleep(timeout).await;
+        adapter.stop_scan().await.map_err(scan_error)?;
+
+        let mut identities = Vec::new();
+        for peripheral in adapter.peripherals().await.map_err(scan_error)? {
+            let Some(props) = peripheral.properties().await.map_err(scan_error)? else {
+                continue;
+            };
+            // Not every backend applies the scan filter, so check again
+            let advertised = props.services.contains(&service_uuids::AUDIO_NINJA_SERVICE)
+                || props
+                    .service_data
+                    .contains_key(&service_uuids::AUDIO_NINJA_SERVICE);
+            if !advertised {
+                continue;
+            }
+
+            // The peripheral id is the address on Linux/Windows and the
+            // CoreBluetooth UUID on macOS, which hides real addresses
+            let id = peripheral.id().to_string();
+            identities.push(SpeakerIdentity {
+                name: props.local_name.unwrap_or_else(|| id.clone()),
+                id,
+                role: SpeakerRole::Custom(UNASSIGNED_ROLE.into()),
+                mac_address: props.address.to_string(),
+            });
+        }
+
+        Ok(identities)
+    }
+
+    fn scan_error(err: btleplug::Error) -> BleError {
+        BleError::ScanFailed(err.to_string())
+    }
+}
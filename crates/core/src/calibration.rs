@@ -1,6 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::dsp::{BiquadCoefficients, BiquadFilter, FirFilter};
+use crate::dsp::{
+    design_linkwitz_riley, fft, highpass_section, ifft, lowpass_section, BiquadCascade,
+    BiquadCoefficients, BiquadFilter, Complex32, FirFilter, LrOrder,
+};
+use crate::AudioBlock;
+use std::collections::VecDeque;
 use std::f32::consts::PI;
 use std::time::Duration;
 
@@ -33,31 +38,177 @@ pub struct CalibrationSolution {
     pub fir: Option<FirFilter>,
 }
 
+impl CalibrationSolution {
+    /// Number of channels the solution corrects
+    pub fn channels(&self) -> usize {
+        self.delays.len().max(self.trims_db.len())
+    }
+
+    /// Apply the correction to one block of audio in place
+    ///
+    /// Channel `ch` runs the shared room EQ (`peq`, then `fir` if set), is
+    /// scaled by `trims_db[ch]` and delayed by `delays[ch]` rounded to whole
+    /// samples; delayed audio that falls past the end of the block is dropped.
+    /// Channels beyond `channels()` pass through unchanged. Filter and delay
+    /// state start from silence on every call; use a `CalibrationApplier`
+    /// to correct a stream block by block.
+    pub fn apply(&self, block: &mut AudioBlock) {
+        CalibrationApplier::new(self, block.sample_rate).process(block);
+    }
+}
+
+/// Streaming form of `CalibrationSolution::apply`
+///
+/// Filters and delay lines are built once and carried between blocks, so a
+/// stream corrected block by block matches the same audio corrected in one
+/// call.
+#[derive(Clone, Debug)]
+pub struct CalibrationApplier {
+    channels: Vec<AppliedChannel>,
+}
+
+#[derive(Clone, Debug)]
+struct AppliedChannel {
+    peq: BiquadCascade,
+    fir: Option<FirFilter>,
+    gain: f32,
+    /// Samples still waiting to leave the delay, oldest first
+    delay_line: VecDeque<f32>,
+}
+
+impl CalibrationApplier {
+    /// Build for `solution` at `sample_rate`, with filters and delays silent
+    pub fn new(solution: &CalibrationSolution, sample_rate: u32) -> Self {
+        let channels = (0..solution.channels())
+            .map(|ch| {
+                let trim_db = solution.trims_db.get(ch).copied().unwrap_or(0.0);
+                let delay = solution.delays.get(ch).copied().unwrap_or_default();
+                let delay_samples = (delay.as_secs_f64() * sample_rate as f64).round() as usize;
+                let mut fir = solution.fir.clone();
+                if let Some(fir) = &mut fir {
+                    fir.reset();
+                }
+
+                AppliedChannel {
+                    peq: BiquadCascade::new(solution.peq.clone()),
+                    fir,
+                    gain: 10f32.powf(trim_db / 20.0),
+                    delay_line: VecDeque::from(vec![0.0; delay_samples]),
+                }
+            })
+            .collect();
+
+        Self { channels }
+    }
+
+    /// Correct one block in place, continuing from the previous call.
+    /// Channels beyond the solution pass through unchanged.
+    pub fn process(&mut self, block: &mut AudioBlock) {
+        for (channel, samples) in self.channels.iter_mut().zip(&mut block.channels) {
+            channel.peq.process_block(samples);
+            if let Some(fir) = &mut channel.fir {
+                fir.process_block(samples);
+            }
+
+            for sample in samples.iter_mut() {
+                let trimmed = *sample * channel.gain;
+                if channel.delay_line.is_empty() {
+                    *sample = trimmed;
+                } else {
+                    channel.delay_line.push_back(trimmed);
+                    *sample = channel.delay_line.pop_front().unwrap_or(0.0);
+                }
+            }
+        }
+    }
+
+    /// Clear all filter and delay state, e.g. after a seek
+    pub fn reset(&mut self) {
+        for channel in &mut self.channels {
+            channel.peq.reset();
+            if let Some(fir) = &mut channel.fir {
+                fir.reset();
+            }
+            channel.delay_line.iter_mut().for_each(|x| *x = 0.0);
+        }
+    }
+}
+
 pub trait Calibrator {
     fn measure(&mut self, cfg: &MeasurementConfig) -> anyhow::Result<MeasurementResult>;
     fn solve(&self, measurement: &MeasurementResult) -> anyhow::Result<CalibrationSolution>;
 }
 
+/// Reference calibrator measuring through an ideal loopback: the excitation
+/// is "recorded" unchanged and then analysed like a real capture
 pub struct ReferenceCalibrator;
 
 impl Calibrator for ReferenceCalibrator {
     fn measure(&mut self, cfg: &MeasurementConfig) -> anyhow::Result<MeasurementResult> {
-        let frames = (cfg.sample_rate as f32 * cfg.sweep_duration.as_secs_f32()) as usize;
+        let excitation = match cfg.sweep_type {
+            SweepType::LogSweep { start_hz, end_hz } => {
+                generate_log_sweep(cfg.sample_rate, cfg.sweep_duration, start_hz, end_hz)
+            }
+            SweepType::Mls { length } => generate_mls(length),
+        };
+        let recorded = excitation.clone();
+
+        let impulse_response = extract_ir_from_sweep(&recorded, &excitation, cfg.sample_rate);
+        let magnitude_response =
+            compute_magnitude_response(&impulse_response, cfg.sample_rate, DEFAULT_MAGNITUDE_BINS);
         Ok(MeasurementResult {
-            impulse_response: vec![0.0; frames],
+            peak_index: find_ir_peak(&impulse_response),
+            magnitude_response: Some(magnitude_response),
+            impulse_response,
             sample_rate: cfg.sample_rate,
-            peak_index: None,
-            magnitude_response: None,
         })
     }
 
-    fn solve(&self, _measurement: &MeasurementResult) -> anyhow::Result<CalibrationSolution> {
-        Ok(CalibrationSolution {
-            delays: vec![],
-            trims_db: vec![],
+    fn solve(&self, measurement: &MeasurementResult) -> anyhow::Result<CalibrationSolution> {
+        Ok(self.solve_for_target(measurement, TARGET_LEVEL_DB))
+    }
+}
+
+impl ReferenceCalibrator {
+    /// Derive a single-speaker correction bringing its broadband level to
+    /// `target_db`
+    ///
+    /// The delay comes from the IR peak, the trim from the IR energy and up
+    /// to `MAX_PEQ_BANDS` filters flatten the magnitude response. A silent
+    /// measurement yields a neutral solution: no delay, 0 dB trim, no EQ.
+    pub fn solve_for_target(
+        &self,
+        measurement: &MeasurementResult,
+        target_db: f32,
+    ) -> CalibrationSolution {
+        let ir = &measurement.impulse_response;
+        let silent = !ir.iter().any(|&x| x != 0.0 && x.is_finite());
+        let neutral = CalibrationSolution {
+            delays: vec![Duration::ZERO],
+            trims_db: vec![0.0],
             peq: vec![],
             fir: None,
-        })
+        };
+        if silent || ir.iter().any(|x| !x.is_finite()) {
+            return neutral;
+        }
+        let Some(peak) = find_ir_peak(ir) else {
+            return neutral;
+        };
+
+        // Total IR energy: a unity impulse reads 0 dB regardless of length
+        let level_db = rms_to_db(compute_rms(ir) * (ir.len() as f32).sqrt());
+        let magnitude = match &measurement.magnitude_response {
+            Some(magnitude) => magnitude.clone(),
+            None => compute_magnitude_response(ir, measurement.sample_rate, DEFAULT_MAGNITUDE_BINS),
+        };
+
+        CalibrationSolution {
+            delays: vec![compute_delay(peak, measurement.sample_rate)],
+            trims_db: vec![target_db - level_db],
+            peq: fit_peq(&magnitude, measurement.sample_rate, MAX_PEQ_BANDS),
+            fir: None,
+        }
     }
 }
 
@@ -71,15 +222,19 @@ pub fn generate_log_sweep(
     let frames = (sample_rate as f32 * duration.as_secs_f32()) as usize;
     let mut sweep = Vec::with_capacity(frames);
 
-    let f1 = start_hz as f32;
-    let f2 = end_hz as f32;
-    let t_end = duration.as_secs_f32();
-    let k = t_end * f1 / (f2 - f1).ln();
+    // Exponential (Farina) sweep: the instantaneous frequency rises from f1
+    // to f2 by a constant ratio per second. Phase is accumulated in f64 so
+    // long sweeps stay accurate.
+    let f1 = start_hz as f64;
+    let f2 = end_hz as f64;
+    let t_end = duration.as_secs_f64();
+    let rate = (f2 / f1).ln();
+    let k = t_end * f1 / rate;
 
     for i in 0..frames {
-        let t = i as f32 / sample_rate as f32;
-        let phase = 2.0 * PI * k * ((f2 - f1) * (t / t_end)).exp_m1();
-        sweep.push(phase.sin());
+        let t = i as f64 / sample_rate as f64;
+        let phase = 2.0 * std::f64::consts::PI * k * (rate * t / t_end).exp_m1();
+        sweep.push(phase.sin() as f32);
     }
 
     sweep
@@ -114,29 +269,62 @@ pub fn generate_mls(length: u32) -> Vec<f32> {
     sequence
 }
 
+/// Length of the impulse response returned by `extract_ir_from_sweep`
+const IR_WINDOW: Duration = Duration::from_millis(100);
+
+/// Regularization floor for sweep deconvolution, relative to the power of the
+/// strongest reference bin (-50 dB)
+const DECONVOLUTION_FLOOR: f32 = 1e-5;
+
 /// Compute impulse response from recorded sweep and reference sweep
+///
+/// Deconvolves in the frequency domain, `IR = IFFT(FFT(recorded) / FFT(reference))`,
+/// with both signals zero-padded to a power of two long enough to avoid
+/// circular wrap-around. The division is regularized as
+/// `R·conj(S) / (|S|² + ε)` so bins where the sweep has no energy are
+/// suppressed instead of blowing up. Returns the first 100 ms of the IR,
+/// zero-padded if the signals are shorter than that; an empty or silent
+/// reference yields an all-zero IR.
 pub fn extract_ir_from_sweep(
     recorded: &[f32],
-    _reference_sweep: &[f32],
+    reference_sweep: &[f32],
     sample_rate: u32,
 ) -> Vec<f32> {
-    // Placeholder: real implementation uses FFT-based deconvolution
-    // IR = IFFT(FFT(recorded) / FFT(reference))
-
-    let ir_len = (sample_rate / 10) as usize; // 100ms IR
+    let ir_len = (sample_rate as f32 * IR_WINDOW.as_secs_f32()) as usize;
     let mut ir = vec![0.0; ir_len];
+    if recorded.is_empty() || reference_sweep.is_empty() {
+        return ir;
+    }
 
-    // Simple peak detection for now
-    if let Some((peak_idx, _)) = recorded.iter().enumerate().max_by(|(_, a), (_, b)| {
-        a.abs()
-            .partial_cmp(&b.abs())
-            .unwrap_or(std::cmp::Ordering::Equal)
-    }) {
-        if peak_idx < ir.len() {
-            ir[peak_idx] = 1.0;
+    let fft_len = (recorded.len() + reference_sweep.len()).next_power_of_two();
+    let spectrum = |signal: &[f32]| {
+        let mut buf = vec![Complex32::default(); fft_len];
+        for (bin, &x) in buf.iter_mut().zip(signal) {
+            bin.re = x;
         }
+        fft(&mut buf);
+        buf
+    };
+    let mut response = spectrum(recorded);
+    let reference = spectrum(reference_sweep);
+
+    let power = |bin: &Complex32| bin.re * bin.re + bin.im * bin.im;
+    let peak_power = reference.iter().map(power).fold(0.0, f32::max);
+    if peak_power <= 0.0 || !peak_power.is_finite() {
+        return ir;
     }
+    let floor = peak_power * DECONVOLUTION_FLOOR;
 
+    for (bin, s) in response.iter_mut().zip(&reference) {
+        let scale = 1.0 / (power(s) + floor);
+        let h = *bin * s.conj();
+        *bin = Complex32::new(h.re * scale, h.im * scale);
+    }
+    ifft(&mut response);
+
+    for (out, bin) in ir.iter_mut().zip(&response) {
+        *out = bin.re;
+    }
     ir
 }
 
@@ -148,6 +336,202 @@ pub fn find_ir_peak(ir: &[f32]) -> Option<usize> {
         .map(|(idx, _)| idx)
 }
 
+/// Number of points in the magnitude response filled in by `measure`
+pub const DEFAULT_MAGNITUDE_BINS: usize = 256;
+
+/// Default smoothing bandwidth of magnitude responses, in octaves
+pub const DEFAULT_SMOOTHING_OCTAVES: f32 = 1.0 / 3.0;
+
+/// Lowest frequency of a magnitude response
+const MAGNITUDE_MIN_HZ: f32 = 20.0;
+
+/// Level reported for bands without energy
+const MAGNITUDE_FLOOR_DB: f32 = -120.0;
+
+/// Shortest FFT used for magnitude responses, so short IRs still resolve
+/// the bass bands
+const MAGNITUDE_MIN_FFT_LEN: usize = 8192;
+
+/// Frequencies in Hz of the points of a magnitude response: `num_bins`
+/// log-spaced steps from 20 Hz up to Nyquist
+pub fn magnitude_response_frequencies(sample_rate: u32, num_bins: usize) -> Vec<f32> {
+    let low = MAGNITUDE_MIN_HZ;
+    let high = (sample_rate as f32 / 2.0).max(low);
+    let ratio = (high / low).ln();
+    (0..num_bins)
+        .map(|i| {
+            let t = if num_bins > 1 {
+                i as f32 / (num_bins - 1) as f32
+            } else {
+                0.0
+            };
+            low * (ratio * t).exp()
+        })
+        .collect()
+}
+
+/// Log-magnitude frequency response of an impulse response in dB, with
+/// 1/3-octave smoothing
+///
+/// Point `i` lies at `magnitude_response_frequencies(sample_rate, num_bins)[i]`.
+pub fn compute_magnitude_response(ir: &[f32], sample_rate: u32, num_bins: usize) -> Vec<f32> {
+    compute_magnitude_response_smoothed(ir, sample_rate, num_bins, DEFAULT_SMOOTHING_OCTAVES)
+}
+
+/// Log-magnitude frequency response in dB, smoothed over `octaves`-wide
+/// bands (e.g. `1.0 / 3.0` or `1.0 / 6.0`)
+///
+/// Each point is the mean power of the FFT bins within half a band either
+/// side of its frequency, falling back to the nearest bin when the band is
+/// narrower than the FFT resolution. Silent bands read -120 dB.
+pub fn compute_magnitude_response_smoothed(
+    ir: &[f32],
+    sample_rate: u32,
+    num_bins: usize,
+    octaves: f32,
+) -> Vec<f32> {
+    let frequencies = magnitude_response_frequencies(sample_rate, num_bins);
+    if ir.is_empty() {
+        return vec![MAGNITUDE_FLOOR_DB; num_bins];
+    }
+
+    let fft_len = ir.len().max(MAGNITUDE_MIN_FFT_LEN).next_power_of_two();
+    let mut spectrum = vec![Complex32::default(); fft_len];
+    for (bin, &x) in spectrum.iter_mut().zip(ir) {
+        bin.re = x;
+    }
+    fft(&mut spectrum);
+    let power: Vec<f32> = spectrum[..=fft_len / 2]
+        .iter()
+        .map(|bin| bin.re * bin.re + bin.im * bin.im)
+        .collect();
+
+    let bin_hz = sample_rate as f32 / fft_len as f32;
+    let last = power.len() - 1;
+    let half_band = 2_f32.powf(octaves.max(0.0) / 2.0);
+    let floor = 10_f32.powf(MAGNITUDE_FLOOR_DB / 10.0);
+
+    frequencies
+        .iter()
+        .map(|&freq| {
+            let lo = ((freq / half_band / bin_hz).ceil() as usize).min(last);
+            let hi = ((freq * half_band / bin_hz).floor() as usize).min(last);
+            let band = if lo <= hi {
+                &power[lo..=hi]
+            } else {
+                let nearest = ((freq / bin_hz).round() as usize).min(last);
+                &power[nearest..=nearest]
+            };
+            let mean = band.iter().sum::<f32>() / band.len() as f32;
+            10.0 * mean.max(floor).log10()
+        })
+        .collect()
+}
+
+/// Broadband level a calibrated speaker is trimmed to, in dB relative to a
+/// unity-gain loopback
+pub const TARGET_LEVEL_DB: f32 = 0.0;
+
+/// Most PEQ bands `solve` fits per speaker
+pub const MAX_PEQ_BANDS: usize = 5;
+
+/// Deviations within this many dB of flat are left alone
+const PEQ_TOLERANCE_DB: f32 = 1.0;
+
+/// Largest boost a fitted band may apply; dips are usually cancellations
+/// that boosting can't fill
+const PEQ_MAX_BOOST_DB: f32 = 6.0;
+
+/// Largest cut a fitted band may apply
+const PEQ_MAX_CUT_DB: f32 = 12.0;
+
+/// Range of fitted band Qs
+const PEQ_Q_RANGE: (f32, f32) = (0.5, 10.0);
+
+/// Frequency range the EQ fit considers, inside a typical sweep's band
+const PEQ_FIT_RANGE_HZ: (f32, f32) = (40.0, 16000.0);
+
+/// Fit up to `max_bands` peaking filters that flatten a magnitude response
+///
+/// `magnitude_db` holds points at `magnitude_response_frequencies`. The
+/// response is levelled to its mean over 40 Hz - 16 kHz (broadband gain is
+/// the trim's job); each band then targets the largest remaining deviation,
+/// with a Q matching the width at half its depth. Fitting stops early once
+/// everything is within 1 dB.
+pub fn fit_peq(magnitude_db: &[f32], sample_rate: u32, max_bands: usize) -> Vec<BiquadFilter> {
+    let frequencies = magnitude_response_frequencies(sample_rate, magnitude_db.len());
+    let (fit_lo, fit_hi) = PEQ_FIT_RANGE_HZ;
+    let in_range: Vec<usize> = frequencies
+        .iter()
+        .enumerate()
+        .filter(|(_, &f)| f >= fit_lo && f <= fit_hi.min(sample_rate as f32 / 2.0))
+        .map(|(i, _)| i)
+        .collect();
+    if in_range.is_empty() || magnitude_db.iter().any(|x| !x.is_finite()) {
+        return vec![];
+    }
+
+    let mean = in_range.iter().map(|&i| magnitude_db[i]).sum::<f32>() / in_range.len() as f32;
+    let mut correction = BiquadCascade::default();
+    let residual = |correction: &BiquadCascade, i: usize| {
+        magnitude_db[i] - mean + correction.magnitude_db(frequencies[i], sample_rate)
+    };
+    // Points already covered by a band, so clamped bands don't stack up
+    let mut fitted: Vec<(usize, usize)> = Vec::new();
+
+    for _ in 0..max_bands {
+        let (worst, deviation) = in_range
+            .iter()
+            .filter(|&&i| !fitted.iter().any(|&(lo, hi)| (lo..=hi).contains(&i)))
+            .map(|&i| (i, residual(&correction, i)))
+            .fold((0, 0.0_f32), |best, (i, dev)| {
+                if dev.abs() > best.1.abs() {
+                    (i, dev)
+                } else {
+                    best
+                }
+            });
+        if deviation.abs() <= PEQ_TOLERANCE_DB {
+            break;
+        }
+
+        // Walk out to where the deviation falls below half its depth
+        let half = deviation / 2.0;
+        let beyond_half = |i: usize| residual(&correction, i) * half.signum() > half.abs();
+        let first = in_range[0];
+        let last = in_range[in_range.len() - 1];
+        let mut lo = worst;
+        while lo > first && beyond_half(lo - 1) {
+            lo -= 1;
+        }
+        let mut hi = worst;
+        while hi < last && beyond_half(hi + 1) {
+            hi += 1;
+        }
+        fitted.push((lo, hi));
+
+        // Band edges halfway (geometrically) to the next point outside
+        let f_lo = (frequencies[lo] * frequencies[lo.saturating_sub(1)]).sqrt();
+        let f_hi = (frequencies[hi] * frequencies[(hi + 1).min(frequencies.len() - 1)]).sqrt();
+        let ratio = f_hi / f_lo;
+        let q = if ratio > 1.0 {
+            ratio.sqrt() / (ratio - 1.0)
+        } else {
+            PEQ_Q_RANGE.1
+        };
+
+        let gain = (-deviation).clamp(-PEQ_MAX_CUT_DB, PEQ_MAX_BOOST_DB);
+        correction.push(design_peq(
+            frequencies[worst],
+            gain,
+            q.clamp(PEQ_Q_RANGE.0, PEQ_Q_RANGE.1),
+            sample_rate,
+        ));
+    }
+
+    correction.filters().to_vec()
+}
+
 /// Compute delay from IR peak
 pub fn compute_delay(peak_index: usize, sample_rate: u32) -> Duration {
     Duration::from_secs_f32(peak_index as f32 / sample_rate as f32)
@@ -177,8 +561,8 @@ pub fn design_peq(center_hz: f32, gain_db: f32, q: f32, sample_rate: u32) -> Biq
     let a1 = -2.0 * omega.cos();
     let a2 = 1.0 - alpha / a;
 
-    BiquadFilter {
-        coeffs: BiquadCoefficients {
+    BiquadFilter::new(
+        BiquadCoefficients {
             b0: b0 / a0,
             b1: b1 / a0,
             b2: b2 / a0,
@@ -186,7 +570,7 @@ pub fn design_peq(center_hz: f32, gain_db: f32, q: f32, sample_rate: u32) -> Biq
             a2: a2 / a0,
         },
         gain_db,
-    }
+    )
 }
 
 /// Design low-shelf filter
@@ -202,8 +586,8 @@ pub fn design_low_shelf(corner_hz: f32, gain_db: f32, sample_rate: u32) -> Biqua
     let a1 = -2.0 * ((a - 1.0) + (a + 1.0) * omega.cos());
     let a2 = (a + 1.0) + (a - 1.0) * omega.cos() - 2.0 * a.sqrt() * alpha;
 
-    BiquadFilter {
-        coeffs: BiquadCoefficients {
+    BiquadFilter::new(
+        BiquadCoefficients {
             b0: b0 / a0,
             b1: b1 / a0,
             b2: b2 / a0,
@@ -211,7 +595,7 @@ pub fn design_low_shelf(corner_hz: f32, gain_db: f32, sample_rate: u32) -> Biqua
             a2: a2 / a0,
         },
         gain_db,
-    }
+    )
 }
 
 /// Design high-shelf filter
@@ -227,8 +611,8 @@ pub fn design_high_shelf(corner_hz: f32, gain_db: f32, sample_rate: u32) -> Biqu
     let a1 = 2.0 * ((a - 1.0) - (a + 1.0) * omega.cos());
     let a2 = (a + 1.0) - (a - 1.0) * omega.cos() - 2.0 * a.sqrt() * alpha;
 
-    BiquadFilter {
-        coeffs: BiquadCoefficients {
+    BiquadFilter::new(
+        BiquadCoefficients {
             b0: b0 / a0,
             b1: b1 / a0,
             b2: b2 / a0,
@@ -236,5 +620,28 @@ pub fn design_high_shelf(corner_hz: f32, gain_db: f32, sample_rate: u32) -> Biqu
             a2: a2 / a0,
         },
         gain_db,
-    }
+    )
+}
+
+/// Design second-order low-pass filter (Q = 0.707 for Butterworth)
+pub fn design_lowpass(cutoff_hz: f32, q: f32, sample_rate: u32) -> BiquadFilter {
+    lowpass_section(cutoff_hz, q, sample_rate)
+}
+
+/// Design second-order high-pass filter (Q = 0.707 for Butterworth)
+pub fn design_highpass(cutoff_hz: f32, q: f32, sample_rate: u32) -> BiquadFilter {
+    highpass_section(cutoff_hz, q, sample_rate)
+}
+
+/// Design the `(lowpass, highpass)` halves of an LR4 crossover
+///
+/// Each half is two cascaded Butterworth sections, so both sit at -6 dB at
+/// the crossover and their sum is flat. See `dsp::design_linkwitz_riley`
+/// for other orders.
+pub fn design_linkwitz_riley_pair(
+    crossover_hz: f32,
+    sample_rate: u32,
+) -> (BiquadCascade, BiquadCascade) {
+    let (lowpass, highpass) = design_linkwitz_riley(crossover_hz, LrOrder::Lr4, sample_rate);
+    (BiquadCascade::new(lowpass), BiquadCascade::new(highpass))
 }
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::HashMap;
 use std::time::Duration;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -52,3 +53,117 @@ impl ControlEndpoint for LoopbackControl {
         Ok(Some(self.queue.remove(0)))
     }
 }
+
+/// Curvature of the `Exponential` ramp shape
+const EXP_RAMP_CURVATURE: f32 = 4.0;
+
+/// How an automated parameter moves between its start and end values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RampShape {
+    /// Constant rate of change
+    Linear,
+    /// Slow start that accelerates into the end value
+    Exponential,
+    /// Smoothstep: eases in and out, symmetric about the midpoint
+    SCurve,
+}
+
+impl RampShape {
+    /// Map ramp progress (0.0-1.0) onto interpolation weight (0.0-1.0)
+    fn weight(self, progress: f32) -> f32 {
+        let p = progress.clamp(0.0, 1.0);
+        match self {
+            RampShape::Linear => p,
+            RampShape::Exponential => {
+                (EXP_RAMP_CURVATURE * p).exp_m1() / EXP_RAMP_CURVATURE.exp_m1()
+            }
+            RampShape::SCurve => p * p * (3.0 - 2.0 * p),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Ramp {
+    from: f32,
+    to: f32,
+    duration: Duration,
+    elapsed: Duration,
+    shape: RampShape,
+}
+
+impl Ramp {
+    fn value(&self) -> f32 {
+        if self.duration.is_zero() {
+            return self.to;
+        }
+        let progress = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        self.from + (self.to - self.from) * self.shape.weight(progress)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Scheduled ramps of named parameters (e.g. "master_gain_db",
+/// "binaural_azimuth"), advanced once per audio block with `tick`.
+#[derive(Clone, Debug, Default)]
+pub struct Automation {
+    values: HashMap<String, f32>,
+    ramps: HashMap<String, Ramp>,
+}
+
+impl Automation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ramp `name` from `from` to `to` over `duration`, replacing any ramp
+    /// already running on that parameter
+    pub fn schedule(
+        &mut self,
+        name: &str,
+        from: f32,
+        to: f32,
+        duration: Duration,
+        shape: RampShape,
+    ) {
+        let ramp = Ramp {
+            from,
+            to,
+            duration,
+            elapsed: Duration::ZERO,
+            shape,
+        };
+        self.values.insert(name.to_string(), ramp.value());
+        if ramp.is_finished() {
+            self.ramps.remove(name);
+        } else {
+            self.ramps.insert(name.to_string(), ramp);
+        }
+    }
+
+    /// Advance every running ramp by `dt`; finished ramps hold their end value
+    pub fn tick(&mut self, dt: Duration) {
+        for (name, ramp) in self.ramps.iter_mut() {
+            ramp.elapsed = (ramp.elapsed + dt).min(ramp.duration);
+            self.values.insert(name.clone(), ramp.value());
+        }
+        self.ramps.retain(|_, ramp| !ramp.is_finished());
+    }
+
+    /// Current value of `name`, or `None` if it was never scheduled
+    pub fn current_value(&self, name: &str) -> Option<f32> {
+        self.values.get(name).copied()
+    }
+
+    /// Whether `name` is still ramping
+    pub fn is_ramping(&self, name: &str) -> bool {
+        self.ramps.contains_key(name)
+    }
+
+    /// Stop the ramp on `name`, holding its current value
+    pub fn cancel(&mut self, name: &str) {
+        self.ramps.remove(name);
+    }
+}
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! SRTP-style payload encryption for RTP packets
+//!
+//! Payloads are sealed with ChaCha20-Poly1305. The RTP header stays in the
+//! clear so the jitter buffer can still order packets, but it is
+//! authenticated along with the payload: tampering with either fails
+//! decryption. Every session derives a fresh key from a random salt the
+//! master sends at setup, and the nonce carries the SSRC plus a 48-bit
+//! packet index (rollover count and sequence number, as in SRTP), so
+//! neither repeats when the 16-bit sequence number wraps.
+
+use crate::transport::RtpPacket;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+
+/// Bytes the authentication tag adds to every encrypted payload
+pub const TAG_BYTES: usize = 16;
+
+/// HKDF info prefix; bump the version if the derivation changes
+const KEY_INFO: &[u8] = b"audio-ninja rtp session key v2";
+
+/// Bytes of random salt exchanged for every session
+pub const SALT_BYTES: usize = 16;
+
+/// Packet indices are 48 bits: a 32-bit rollover count over the sequence
+const INDEX_MASK: u64 = (1 << 48) - 1;
+
+/// Random value the master picks for each session and sends to the speaker
+/// at setup, so re-pairing with the same secret never reuses a key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionSalt(pub [u8; SALT_BYTES]);
+
+impl SessionSalt {
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// 256-bit key for one streaming session
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionKey([u8; 32]);
+
+impl SessionKey {
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Derive the key both ends compute after BLE pairing.
+    ///
+    /// `secret` is whatever the pairing exchange established (at minimum the
+    /// PIN, in which case the key is only as strong as the PIN). The salt
+    /// makes each session's key unique; the master and speaker ids bind the
+    /// key to that pair.
+    pub fn derive(secret: &[u8], salt: &SessionSalt, master_id: &str, speaker_id: &str) -> Self {
+        let mut info = Vec::with_capacity(KEY_INFO.len() + master_id.len() + speaker_id.len() + 2);
+        info.extend_from_slice(KEY_INFO);
+        info.push(0);
+        info.extend_from_slice(master_id.as_bytes());
+        info.push(0);
+        info.extend_from_slice(speaker_id.as_bytes());
+
+        let mut key = [0u8; 32];
+        Hkdf::<Sha256>::new(Some(&salt.0), secret)
+            .expand(&info, &mut key)
+            .expect("32 bytes is a valid HKDF-SHA256 output length");
+        Self(key)
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.0))
+    }
+}
+
+impl std::fmt::Debug for SessionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SessionKey(..)")
+    }
+}
+
+/// Rollover counter that extends 16-bit sequence numbers to 48-bit packet
+/// indices (RFC 3711, appendix A).
+///
+/// The sender and receiver each keep one per SSRC. The receiver should only
+/// `advance` once a packet has decrypted, so forged packets cannot move it.
+#[derive(Clone, Debug, Default)]
+pub struct PacketIndex {
+    highest: Option<u64>,
+}
+
+impl PacketIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index of the packet with sequence number `seq`, assuming it lies
+    /// within half the sequence space of the highest one seen so far
+    pub fn estimate(&self, seq: u16) -> u64 {
+        let Some(highest) = self.highest else {
+            return seq as u64;
+        };
+        let roc = highest >> 16;
+        let last = (highest & 0xFFFF) as u16;
+        let roc = if last < 0x8000 {
+            if seq > last && seq - last > 0x8000 {
+                roc.saturating_sub(1)
+            } else {
+                roc
+            }
+        } else if last - 0x8000 > seq {
+            roc + 1
+        } else {
+            roc
+        };
+        ((roc << 16) | seq as u64) & INDEX_MASK
+    }
+
+    /// Record that the packet with `index` was sent or accepted
+    pub fn advance(&mut self, index: u64) {
+        if self.highest < Some(index) {
+            self.highest = Some(index);
+        }
+    }
+
+    /// Estimate and record in one step, for the sending side
+    pub fn next(&mut self, seq: u16) -> u64 {
+        let index = self.estimate(seq);
+        self.advance(index);
+        index
+    }
+}
+
+/// SSRC and 48-bit packet index, like the SRTP IV
+fn packet_nonce(packet: &RtpPacket, index: u64) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[0..4].copy_from_slice(&packet.header.ssrc.0.to_be_bytes());
+    nonce[6..12].copy_from_slice(&(index & INDEX_MASK).to_be_bytes()[2..]);
+    nonce
+}
+
+impl RtpPacket {
+    /// Copy of the packet with the payload encrypted and tagged. `index`
+    /// comes from the sender's [`PacketIndex`] and must never repeat under
+    /// one key.
+    pub fn encrypt(&self, key: &SessionKey, index: u64) -> anyhow::Result<RtpPacket> {
+        let aad = self.header.serialize();
+        let payload = key
+            .cipher()
+            .encrypt(
+                Nonce::from_slice(&packet_nonce(self, index)),
+                Payload {
+                    msg: &self.payload,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("RTP payload encryption failed"))?;
+
+        Ok(RtpPacket {
+            header: self.header.clone(),
+            clock: self.clock.clone(),
+            payload,
+        })
+    }
+
+    /// Copy of the packet with the payload decrypted. Fails if the key is
+    /// wrong or the header or payload were modified in transit. `index` is
+    /// the receiver's [`PacketIndex::estimate`] for the sequence number.
+    pub fn decrypt(&self, key: &SessionKey, index: u64) -> anyhow::Result<RtpPacket> {
+        anyhow::ensure!(
+            self.payload.len() >= TAG_BYTES,
+            "encrypted RTP payload shorter than its tag"
+        );
+
+        let aad = self.header.serialize();
+        let payload = key
+            .cipher()
+            .decrypt(
+                Nonce::from_slice(&packet_nonce(self, index)),
+                Payload {
+                    msg: &self.payload,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("RTP payload failed authentication"))?;
+
+        Ok(RtpPacket {
+            header: self.header.clone(),
+            clock: self.clock.clone(),
+            payload,
+        })
+    }
+}
@@ -1,5 +1,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::calibration::{
+    design_high_shelf, design_highpass, design_low_shelf, design_lowpass, design_peq,
+};
+use crate::{AudioBlock, SpeakerLayout, SpeakerRole};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::ops::{Add, Mul, Sub};
+
+/// Filters up to this many taps are convolved directly instead of via FFT
+pub const DIRECT_CONVOLUTION_MAX_TAPS: usize = 64;
+
+/// Default partition length for FFT convolution, in samples
+pub const DEFAULT_FIR_PARTITION: usize = 256;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct BiquadCoefficients {
     pub b0: f32,
@@ -9,23 +24,1050 @@ pub struct BiquadCoefficients {
     pub a2: f32,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl BiquadCoefficients {
+    /// Linear magnitude and phase in radians of H(e^jw) at `freq_hz`
+    pub fn frequency_response(&self, freq_hz: f32, sample_rate: u32) -> (f32, f32) {
+        let w = 2.0 * PI * freq_hz / sample_rate as f32;
+        let (cos1, sin1) = (w.cos(), w.sin());
+        let (cos2, sin2) = ((2.0 * w).cos(), (2.0 * w).sin());
+
+        let num_re = self.b0 + self.b1 * cos1 + self.b2 * cos2;
+        let num_im = -(self.b1 * sin1 + self.b2 * sin2);
+        let den_re = 1.0 + self.a1 * cos1 + self.a2 * cos2;
+        let den_im = -(self.a1 * sin1 + self.a2 * sin2);
+
+        let magnitude = num_re.hypot(num_im) / den_re.hypot(den_im).max(f32::EPSILON);
+        let phase = num_im.atan2(num_re) - den_im.atan2(den_re);
+        // Wrap to [-pi, pi)
+        let phase = (phase + PI).rem_euclid(2.0 * PI) - PI;
+        (magnitude, phase)
+    }
+
+    /// Magnitude in dB at each of `freqs`, e.g. for plotting an EQ curve
+    pub fn magnitude_db_curve(&self, freqs: &[f32], sample_rate: u32) -> Vec<f32> {
+        freqs
+            .iter()
+            .map(|&f| 20.0 * biquad_magnitude(self, f, sample_rate).log10())
+            .collect()
+    }
+}
+
+/// Single biquad section
+///
+/// Runs in transposed direct form II and keeps its `z1/z2` between calls to
+/// `process_block`, so successive blocks join without clicks. Equality only
+/// compares the design, not the filter state.
+#[derive(Clone, Debug)]
 pub struct BiquadFilter {
     pub coeffs: BiquadCoefficients,
     pub gain_db: f32,
+    /// `[z1, z2]`
+    state: [f32; 2],
 }
 
-#[derive(Clone, Debug, PartialEq)]
+impl PartialEq for BiquadFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.coeffs == other.coeffs && self.gain_db == other.gain_db
+    }
+}
+
+impl BiquadFilter {
+    pub fn new(coeffs: BiquadCoefficients, gain_db: f32) -> Self {
+        Self {
+            coeffs,
+            gain_db,
+            state: [0.0; 2],
+        }
+    }
+
+    /// Filter samples in place, continuing from the previous call
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        run_biquad(&self.coeffs, &mut self.state, samples);
+    }
+
+    /// Clear the delay line
+    pub fn reset(&mut self) {
+        self.state = [0.0; 2];
+    }
+}
+
+/// Biquad described by its design parameters rather than coefficients, so
+/// it can be redesigned at another sample rate
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BiquadDesign {
+    Peak { freq_hz: f32, gain_db: f32, q: f32 },
+    LowShelf { freq_hz: f32, gain_db: f32 },
+    HighShelf { freq_hz: f32, gain_db: f32 },
+    Lowpass { freq_hz: f32, q: f32 },
+    Highpass { freq_hz: f32, q: f32 },
+}
+
+impl BiquadDesign {
+    pub fn design(&self, sample_rate: u32) -> BiquadFilter {
+        match *self {
+            BiquadDesign::Peak {
+                freq_hz,
+                gain_db,
+                q,
+            } => design_peq(freq_hz, gain_db, q, sample_rate),
+            BiquadDesign::LowShelf { freq_hz, gain_db } => {
+                design_low_shelf(freq_hz, gain_db, sample_rate)
+            }
+            BiquadDesign::HighShelf { freq_hz, gain_db } => {
+                design_high_shelf(freq_hz, gain_db, sample_rate)
+            }
+            BiquadDesign::Lowpass { freq_hz, q } => design_lowpass(freq_hz, q, sample_rate),
+            BiquadDesign::Highpass { freq_hz, q } => design_highpass(freq_hz, q, sample_rate),
+        }
+    }
+}
+
+/// Transposed direct form II over `samples`, updating `z`
+fn run_biquad(c: &BiquadCoefficients, z: &mut [f32; 2], samples: &mut [f32]) {
+    for sample in samples.iter_mut() {
+        let x = *sample;
+        let y = c.b0 * x + z[0];
+        z[0] = c.b1 * x - c.a1 * y + z[1];
+        z[1] = c.b2 * x - c.a2 * y;
+        *sample = y;
+    }
+}
+
+/// FIR filter with streaming block convolution
+///
+/// Short filters are convolved directly. Longer filters use uniformly
+/// partitioned overlap-save: the taps are split into partitions of
+/// `partition_size` samples whose spectra are multiplied against a
+/// frequency-domain delay line of past input blocks. Convolution history
+/// is kept between calls, so successive blocks join without discontinuities
+/// and the output has no added latency. Call `reset` after changing `taps`.
+#[derive(Clone, Debug)]
 pub struct FirFilter {
     pub taps: Vec<f32>,
+    partition_size: usize,
+    state: Option<FirState>,
+}
+
+impl PartialEq for FirFilter {
+    fn eq(&self, other: &Self) -> bool {
+        self.taps == other.taps
+    }
 }
 
 impl FirFilter {
+    pub fn new(taps: Vec<f32>) -> Self {
+        Self {
+            taps,
+            partition_size: DEFAULT_FIR_PARTITION,
+            state: None,
+        }
+    }
+
     pub fn impulse(len: usize) -> Self {
         let mut taps = vec![0.0; len];
         if len > 0 {
             taps[0] = 1.0;
         }
-        Self { taps }
+        Self::new(taps)
+    }
+
+    /// Set the FFT partition length (rounded up to a power of two) and reset state
+    pub fn set_partition_size(&mut self, partition_size: usize) {
+        self.partition_size = partition_size.max(1).next_power_of_two();
+        self.state = None;
+    }
+
+    pub fn partition_size(&self) -> usize {
+        self.partition_size
+    }
+
+    /// Convolve samples in place, continuing from the previous call
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        if self.taps.is_empty() {
+            samples.fill(0.0);
+            return;
+        }
+
+        let taps = &self.taps;
+        let partition_size = self.partition_size;
+        let state = self.state.get_or_insert_with(|| {
+            if taps.len() <= DIRECT_CONVOLUTION_MAX_TAPS {
+                FirState::Direct {
+                    history: vec![0.0; taps.len() - 1],
+                }
+            } else {
+                FirState::Partitioned(PartitionedConvolver::new(taps, partition_size))
+            }
+        });
+
+        match state {
+            FirState::Direct { history } => convolve_direct(taps, history, samples),
+            FirState::Partitioned(conv) => conv.process(samples),
+        }
+    }
+
+    /// Clear convolution history
+    pub fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+#[derive(Clone, Debug)]
+enum FirState {
+    /// Last `taps.len() - 1` input samples, oldest first
+    Direct {
+        history: Vec<f32>,
+    },
+    Partitioned(PartitionedConvolver),
+}
+
+fn convolve_direct(taps: &[f32], history: &mut Vec<f32>, samples: &mut [f32]) {
+    let hist_len = history.len();
+    let mut input = std::mem::take(history);
+    input.extend_from_slice(samples);
+
+    for (n, out) in samples.iter_mut().enumerate() {
+        // input[hist_len + n] is the current sample
+        *out = taps
+            .iter()
+            .enumerate()
+            .map(|(k, &h)| h * input[hist_len + n - k])
+            .sum();
+    }
+
+    input.drain(..input.len() - hist_len);
+    *history = input;
+}
+
+/// Uniformly partitioned overlap-save convolution engine
+#[derive(Clone, Debug)]
+struct PartitionedConvolver {
+    partition: usize,
+    /// FFT of each zero-padded partition of the taps
+    filter_spectra: Vec<Vec<Complex32>>,
+    /// Spectra of past completed input frames, newest first
+    delay_line: VecDeque<Vec<Complex32>>,
+    /// Contribution of `delay_line` to the current frame, computed once per frame
+    history_sum: Option<Vec<Complex32>>,
+    /// Previous completed input frame
+    previous: Vec<f32>,
+    /// Samples of the frame being filled
+    current: Vec<f32>,
+}
+
+impl PartitionedConvolver {
+    fn new(taps: &[f32], partition: usize) -> Self {
+        let fft_len = 2 * partition;
+        let filter_spectra = taps
+            .chunks(partition)
+            .map(|chunk| {
+                let mut spectrum = vec![Complex32::default(); fft_len];
+                for (bin, &tap) in spectrum.iter_mut().zip(chunk) {
+                    bin.re = tap;
+                }
+                fft(&mut spectrum);
+                spectrum
+            })
+            .collect();
+
+        Self {
+            partition,
+            filter_spectra,
+            delay_line: VecDeque::new(),
+            history_sum: None,
+            previous: vec![0.0; partition],
+            current: Vec::with_capacity(partition),
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        let mut offset = 0;
+        while offset < samples.len() {
+            let start = self.current.len();
+            let take = (self.partition - start).min(samples.len() - offset);
+            self.current
+                .extend_from_slice(&samples[offset..offset + take]);
+
+            // Samples not yet received are zero; outputs up to the newest
+            // received sample depend only on the past, so they are exact.
+            let mut frame = vec![Complex32::default(); 2 * self.partition];
+            for (bin, &x) in frame
+                .iter_mut()
+                .zip(self.previous.iter().chain(&self.current))
+            {
+                bin.re = x;
+            }
+            fft(&mut frame);
+
+            let history = self
+                .history_sum
+                .get_or_insert_with(|| Self::sum_history(&self.filter_spectra, &self.delay_line));
+            let mut output: Vec<Complex32> = frame
+                .iter()
+                .zip(&self.filter_spectra[0])
+                .zip(history.iter())
+                .map(|((&x, &h), &acc)| x * h + acc)
+                .collect();
+            ifft(&mut output);
+
+            for (i, out) in samples[offset..offset + take].iter_mut().enumerate() {
+                *out = output[self.partition + start + i].re;
+            }
+            offset += take;
+
+            if self.current.len() == self.partition {
+                self.delay_line.push_front(frame);
+                self.delay_line.truncate(self.filter_spectra.len() - 1);
+                self.history_sum = None;
+                std::mem::swap(&mut self.previous, &mut self.current);
+                self.current.clear();
+            }
+        }
+    }
+
+    /// Sum of `H_p * X_(k-p)` over partitions `p >= 1`
+    fn sum_history(
+        filter_spectra: &[Vec<Complex32>],
+        delay_line: &VecDeque<Vec<Complex32>>,
+    ) -> Vec<Complex32> {
+        let mut acc = vec![Complex32::default(); filter_spectra[0].len()];
+        for (h, x) in filter_spectra[1..].iter().zip(delay_line) {
+            for ((a, &hb), &xb) in acc.iter_mut().zip(h).zip(x) {
+                *a = *a + hb * xb;
+            }
+        }
+        acc
+    }
+}
+
+/// Minimal single-precision complex number for FFT work
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Complex32 {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex32 {
+    pub fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    pub fn norm(&self) -> f32 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn conj(&self) -> Self {
+        Self::new(self.re, -self.im)
+    }
+}
+
+impl Add for Complex32 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Sub for Complex32 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Mul for Complex32 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+/// In-place forward FFT (radix-2, decimation in time)
+///
+/// # Panics
+/// If `buf.len()` is not a power of two.
+pub fn fft(buf: &mut [Complex32]) {
+    fft_radix2(buf, false);
+}
+
+/// In-place inverse FFT, scaled by `1/N`
+///
+/// # Panics
+/// If `buf.len()` is not a power of two.
+pub fn ifft(buf: &mut [Complex32]) {
+    fft_radix2(buf, true);
+    let scale = 1.0 / buf.len() as f32;
+    for bin in buf.iter_mut() {
+        bin.re *= scale;
+        bin.im *= scale;
+    }
+}
+
+fn fft_radix2(buf: &mut [Complex32], inverse: bool) {
+    let n = buf.len();
+    if n <= 1 {
+        return;
+    }
+    assert!(n.is_power_of_two(), "FFT length must be a power of two");
+
+    // Bit-reversal permutation
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            buf.swap(i, j);
+        }
+    }
+
+    let sign = if inverse { 1.0 } else { -1.0 };
+    let mut len = 2;
+    while len <= n {
+        let half = len / 2;
+        // Twiddles in f64 so long transforms don't accumulate rotation error
+        let twiddles: Vec<Complex32> = (0..half)
+            .map(|k| {
+                let angle = sign * 2.0 * std::f64::consts::PI * k as f64 / len as f64;
+                Complex32::new(angle.cos() as f32, angle.sin() as f32)
+            })
+            .collect();
+
+        for chunk in buf.chunks_exact_mut(len) {
+            let (lo, hi) = chunk.split_at_mut(half);
+            for ((a, b), &w) in lo.iter_mut().zip(hi.iter_mut()).zip(&twiddles) {
+                let t = *b * w;
+                *b = *a - t;
+                *a = *a + t;
+            }
+        }
+        len *= 2;
+    }
+}
+
+/// Series chain of biquads with persistent per-stage state
+///
+/// Each stage runs in transposed direct form II and keeps its own `z1/z2`
+/// between calls, so a signal can be fed in arbitrary block sizes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BiquadCascade {
+    filters: Vec<BiquadFilter>,
+    /// `[z1, z2]` per stage
+    state: Vec<[f32; 2]>,
+}
+
+impl BiquadCascade {
+    pub fn new(filters: Vec<BiquadFilter>) -> Self {
+        let state = vec![[0.0; 2]; filters.len()];
+        Self { filters, state }
+    }
+
+    /// Append a stage with cleared state
+    pub fn push(&mut self, filter: BiquadFilter) {
+        self.filters.push(filter);
+        self.state.push([0.0; 2]);
+    }
+
+    pub fn filters(&self) -> &[BiquadFilter] {
+        &self.filters
+    }
+
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Filter samples in place through every stage
+    pub fn process_block(&mut self, samples: &mut [f32]) {
+        for (filter, z) in self.filters.iter().zip(self.state.iter_mut()) {
+            run_biquad(&filter.coeffs, z, samples);
+        }
+    }
+
+    /// Clear the delay lines of all stages
+    pub fn reset(&mut self) {
+        self.state.fill([0.0; 2]);
+    }
+
+    /// Combined magnitude response at `freq_hz`, in dB
+    pub fn magnitude_db(&self, freq_hz: f32, sample_rate: u32) -> f32 {
+        self.filters
+            .iter()
+            .map(|f| 20.0 * biquad_magnitude(&f.coeffs, freq_hz, sample_rate).log10())
+            .sum()
+    }
+
+    /// Combined magnitude in dB at each of `freqs`
+    pub fn magnitude_db_curve(&self, freqs: &[f32], sample_rate: u32) -> Vec<f32> {
+        freqs
+            .iter()
+            .map(|&f| self.magnitude_db(f, sample_rate))
+            .collect()
+    }
+}
+
+/// |H(e^jw)| of a normalized biquad
+fn biquad_magnitude(c: &BiquadCoefficients, freq_hz: f32, sample_rate: u32) -> f32 {
+    c.frequency_response(freq_hz, sample_rate)
+        .0
+        .max(f32::MIN_POSITIVE)
+}
+
+/// Second-order Butterworth low-pass section (RBJ cookbook)
+pub(crate) fn lowpass_section(cutoff_hz: f32, q: f32, sample_rate: u32) -> BiquadFilter {
+    let omega = 2.0 * PI * cutoff_hz / sample_rate as f32;
+    let alpha = omega.sin() / (2.0 * q);
+    let cos = omega.cos();
+    let a0 = 1.0 + alpha;
+
+    BiquadFilter::new(
+        BiquadCoefficients {
+            b0: (1.0 - cos) / 2.0 / a0,
+            b1: (1.0 - cos) / a0,
+            b2: (1.0 - cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        },
+        0.0,
+    )
+}
+
+/// Second-order Butterworth high-pass section (RBJ cookbook)
+pub(crate) fn highpass_section(cutoff_hz: f32, q: f32, sample_rate: u32) -> BiquadFilter {
+    let omega = 2.0 * PI * cutoff_hz / sample_rate as f32;
+    let alpha = omega.sin() / (2.0 * q);
+    let cos = omega.cos();
+    let a0 = 1.0 + alpha;
+
+    BiquadFilter::new(
+        BiquadCoefficients {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        },
+        0.0,
+    )
+}
+
+/// Order of a Linkwitz-Riley crossover
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LrOrder {
+    /// 12 dB/octave; the high band is polarity-inverted
+    Lr2,
+    /// 24 dB/octave
+    #[default]
+    Lr4,
+    /// 48 dB/octave
+    Lr8,
+}
+
+impl LrOrder {
+    /// Filter order, i.e. the slope in multiples of 6 dB/octave
+    pub fn order(&self) -> u32 {
+        match self {
+            LrOrder::Lr2 => 2,
+            LrOrder::Lr4 => 4,
+            LrOrder::Lr8 => 8,
+        }
+    }
+
+    /// Section Qs of the underlying Butterworth prototype, each used twice
+    fn section_qs(&self) -> &'static [f32] {
+        match self {
+            // (1st-order Butterworth)^2 folds into a single Q = 0.5 section
+            LrOrder::Lr2 => &[0.5],
+            LrOrder::Lr4 => &[std::f32::consts::FRAC_1_SQRT_2; 2],
+            LrOrder::Lr8 => &[0.541_196_1, 1.306_563, 0.541_196_1, 1.306_563],
+        }
+    }
+}
+
+/// Design a Linkwitz-Riley crossover of the given order
+///
+/// Returns `(lowpass, highpass)` biquad sets. An LR filter of order 2N is
+/// two cascaded Butterworth filters of order N, so both halves sit at -6 dB
+/// at the crossover and their sum has a flat magnitude response. For LR2
+/// the high-pass polarity is inverted; in phase, that order sums to a notch.
+pub fn design_linkwitz_riley(
+    crossover_hz: f32,
+    order: LrOrder,
+    sample_rate: u32,
+) -> (Vec<BiquadFilter>, Vec<BiquadFilter>) {
+    let qs = order.section_qs();
+
+    let lowpass = qs
+        .iter()
+        .map(|&q| lowpass_section(crossover_hz, q, sample_rate))
+        .collect();
+    let mut highpass: Vec<BiquadFilter> = qs
+        .iter()
+        .map(|&q| highpass_section(crossover_hz, q, sample_rate))
+        .collect();
+
+    if order == LrOrder::Lr2 {
+        let c = &mut highpass[0].coeffs;
+        c.b0 = -c.b0;
+        c.b1 = -c.b1;
+        c.b2 = -c.b2;
+    }
+
+    (lowpass, highpass)
+}
+
+/// Default crossover for bass management, in Hz (THX reference)
+pub const DEFAULT_CROSSOVER_HZ: f32 = 80.0;
+
+/// Bass management: redirects low frequencies to the subwoofer
+///
+/// Every full-range channel is split with a Linkwitz-Riley crossover. The
+/// high band stays on the channel and the low bands of all channels are
+/// summed onto the channel with the `Subwoofer` role, on top of any LFE
+/// content already there. Blocks narrower than the layout are padded with
+/// silent channels first. Without a subwoofer channel blocks pass through
+/// untouched.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BassManager {
+    crossover_hz: f32,
+    subwoofer: Option<usize>,
+    lowpass: Vec<BiquadCascade>,
+    highpass: Vec<BiquadCascade>,
+}
+
+impl BassManager {
+    /// Build for channels with the given roles, using an LR4 crossover
+    pub fn new(crossover_hz: f32, sample_rate: u32, roles: &[SpeakerRole]) -> Self {
+        Self::with_order(crossover_hz, LrOrder::Lr4, sample_rate, roles)
+    }
+
+    /// Build with an explicit Linkwitz-Riley order (see `design_linkwitz_riley`)
+    pub fn with_order(
+        crossover_hz: f32,
+        order: LrOrder,
+        sample_rate: u32,
+        roles: &[SpeakerRole],
+    ) -> Self {
+        let (lp, hp) = design_linkwitz_riley(crossover_hz, order, sample_rate);
+        let subwoofer = roles.iter().position(|r| *r == SpeakerRole::Subwoofer);
+
+        Self {
+            crossover_hz,
+            subwoofer,
+            lowpass: vec![BiquadCascade::new(lp); roles.len()],
+            highpass: vec![BiquadCascade::new(hp); roles.len()],
+        }
+    }
+
+    /// Build for the speakers of a layout, in layout order
+    pub fn for_layout(crossover_hz: f32, sample_rate: u32, layout: &SpeakerLayout) -> Self {
+        let roles: Vec<SpeakerRole> = layout.speakers.iter().map(|s| s.role.clone()).collect();
+        Self::new(crossover_hz, sample_rate, &roles)
+    }
+
+    pub fn crossover_hz(&self) -> f32 {
+        self.crossover_hz
+    }
+
+    /// Index of the channel receiving the low band, if the layout has one
+    pub fn subwoofer_channel(&self) -> Option<usize> {
+        self.subwoofer
+    }
+
+    pub fn process(&mut self, block: &mut AudioBlock) {
+        let Some(sub) = self.subwoofer else {
+            return;
+        };
+
+        let frames = block.frame_len();
+        // A block narrower than the layout, e.g. stereo on 5.1, gets silent
+        // channels for the missing speakers so the subwoofer has somewhere to go
+        if block.channels.len() < self.lowpass.len() {
+            block.channels.resize(self.lowpass.len(), vec![0.0; frames]);
+        }
+
+        let mut lows = vec![0.0; frames];
+
+        for (idx, channel) in block.channels.iter_mut().enumerate() {
+            if idx == sub || idx >= self.lowpass.len() {
+                continue;
+            }
+
+            let mut low = channel.clone();
+            self.lowpass[idx].process_block(&mut low);
+            for (acc, s) in lows.iter_mut().zip(&low) {
+                *acc += s;
+            }
+            self.highpass[idx].process_block(channel);
+        }
+
+        for (out, low) in block.channels[sub].iter_mut().zip(&lows) {
+            *out += low;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.lowpass.iter_mut().for_each(BiquadCascade::reset);
+        self.highpass.iter_mut().for_each(BiquadCascade::reset);
+    }
+}
+
+/// One band of a parametric equalizer
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeqBand {
+    pub freq_hz: f32,
+    pub gain_db: f32,
+    pub q: f32,
+}
+
+/// Multi-band peaking EQ applied identically to every channel
+///
+/// Each channel runs its own `BiquadCascade` so blocks can be processed
+/// back to back without discontinuities.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParametricEq {
+    bands: Vec<PeqBand>,
+    template: BiquadCascade,
+    channels: Vec<BiquadCascade>,
+}
+
+impl ParametricEq {
+    pub fn new(bands: Vec<PeqBand>, sample_rate: u32) -> Self {
+        let template = BiquadCascade::new(
+            bands
+                .iter()
+                .map(|b| design_peq(b.freq_hz, b.gain_db, b.q, sample_rate))
+                .collect(),
+        );
+
+        Self {
+            bands,
+            template,
+            channels: Vec::new(),
+        }
+    }
+
+    pub fn bands(&self) -> &[PeqBand] {
+        &self.bands
+    }
+
+    pub fn process(&mut self, block: &mut AudioBlock) {
+        if self.channels.len() < block.channels.len() {
+            self.channels
+                .resize(block.channels.len(), self.template.clone());
+        }
+
+        for (channel, cascade) in block.channels.iter_mut().zip(&mut self.channels) {
+            cascade.process_block(channel);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.channels.clear();
+    }
+}
+
+/// Trade-off between speed and stopband rejection in `resample_with_quality`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResampleQuality {
+    /// 8 taps either side, passband to 85% of Nyquist
+    Fast,
+    /// 16 taps either side, passband to 90% of Nyquist
+    #[default]
+    Balanced,
+    /// 32 taps either side, passband to 95% of Nyquist
+    Best,
+}
+
+impl ResampleQuality {
+    /// Zero crossings of the sinc kept either side of the output instant
+    pub fn half_taps(&self) -> usize {
+        match self {
+            ResampleQuality::Fast => 8,
+            ResampleQuality::Balanced => 16,
+            ResampleQuality::Best => 32,
+        }
+    }
+
+    fn cutoff(&self) -> f32 {
+        match self {
+            ResampleQuality::Fast => 0.85,
+            ResampleQuality::Balanced => 0.9,
+            ResampleQuality::Best => 0.95,
+        }
+    }
+}
+
+/// Rate ratios with more phases than this share the nearest precomputed phase
+const MAX_RESAMPLE_PHASES: usize = 1024;
+
+/// Convert `block` to `target_rate` with `ResampleQuality::Balanced`
+pub fn resample(block: &AudioBlock, target_rate: u32) -> AudioBlock {
+    resample_with_quality(block, target_rate, ResampleQuality::default())
+}
+
+/// Convert `block` to `target_rate` with a windowed-sinc polyphase filter
+///
+/// The rate ratio is reduced to `up / down`, and output frame `n` sits at
+/// input position `n * down / up`. Each of the `up` fractional positions
+/// has its own Blackman-windowed sinc kernel, low-passed below the lower
+/// of the two Nyquist frequencies so downsampling doesn't alias. The block
+/// is converted on its own: samples outside it count as silence, so
+/// streams delivered in blocks should go through `StreamingResampler`.
+pub fn resample_with_quality(
+    block: &AudioBlock,
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> AudioBlock {
+    let source_rate = block.sample_rate;
+    if source_rate == target_rate || source_rate == 0 || target_rate == 0 {
+        return AudioBlock {
+            sample_rate: if target_rate == 0 {
+                source_rate
+            } else {
+                target_rate
+            },
+            ..block.clone()
+        };
+    }
+
+    let (up, down, kernels) = resample_kernels(source_rate, target_rate, quality);
+    let phases = kernels.len();
+    let half = quality.half_taps() as isize;
+
+    let frames = block.frame_len() as u64;
+    let out_frames = ((frames * up + down - 1) / down) as usize;
+    let channels = block
+        .channels
+        .iter()
+        .map(|input| {
+            (0..out_frames as u64)
+                .map(|n| {
+                    let position = n * down;
+                    let base = (position / up) as isize;
+                    let frac = (position % up) as f32 / up as f32;
+                    let phase = ((frac * phases as f32).round() as usize).min(phases - 1);
+
+                    // Taps run from base - half + 1 to base + half
+                    kernels[phase]
+                        .iter()
+                        .zip(base - half + 1..)
+                        .filter(|(_, i)| *i >= 0 && (*i as usize) < input.len())
+                        .map(|(h, i)| h * input[i as usize])
+                        .sum()
+                })
+                .collect()
+        })
+        .collect();
+
+    AudioBlock {
+        sample_rate: target_rate,
+        channels,
+    }
+}
+
+/// Rate ratio reduced to `up / down` and one kernel per fractional phase
+fn resample_kernels(
+    source_rate: u32,
+    target_rate: u32,
+    quality: ResampleQuality,
+) -> (u64, u64, Vec<Vec<f32>>) {
+    let divisor = gcd(source_rate as u64, target_rate as u64);
+    let up = target_rate as u64 / divisor;
+    let down = source_rate as u64 / divisor;
+    let phases = (up as usize).min(MAX_RESAMPLE_PHASES);
+
+    let half = quality.half_taps() as isize;
+    let cutoff = quality.cutoff() * (target_rate as f32 / source_rate as f32).min(1.0);
+    let kernels = (0..phases)
+        .map(|phase| resample_kernel(phase as f32 / phases as f32, half, cutoff))
+        .collect();
+    (up, down, kernels)
+}
+
+/// Sample-rate converter for audio that arrives a block at a time
+///
+/// Uses the kernels of `resample_with_quality`, but keeps the input history
+/// and the read position between calls, so a stream converted block by
+/// block matches converting it in one piece. Output lags the input by
+/// `ResampleQuality::half_taps` input frames until `flush` drains it.
+pub struct StreamingResampler {
+    source_rate: u32,
+    target_rate: u32,
+    up: u64,
+    down: u64,
+    half: u64,
+    kernels: Vec<Vec<f32>>,
+    /// Input still needed by upcoming output frames, one buffer per channel
+    history: Vec<Vec<f32>>,
+    /// Stream frame index of the first sample in `history`
+    history_start: u64,
+    /// Input frames received since the last reset
+    received: u64,
+    /// Output frames produced since the last reset
+    produced: u64,
+}
+
+impl StreamingResampler {
+    pub fn new(source_rate: u32, target_rate: u32, quality: ResampleQuality) -> Self {
+        let source_rate = source_rate.max(1);
+        let target_rate = target_rate.max(1);
+        let (up, down, kernels) = resample_kernels(source_rate, target_rate, quality);
+        Self {
+            source_rate,
+            target_rate,
+            up,
+            down,
+            half: quality.half_taps() as u64,
+            kernels,
+            history: Vec::new(),
+            history_start: 0,
+            received: 0,
+            produced: 0,
+        }
+    }
+
+    pub fn source_rate(&self) -> u32 {
+        self.source_rate
+    }
+
+    pub fn target_rate(&self) -> u32 {
+        self.target_rate
+    }
+
+    /// Input frames to feed before `process` returns at least
+    /// `output_frames` more frames
+    ///
+    /// Follows the exact rate ratio, so feeding this many frames every call
+    /// neither gains nor loses time over a long stream.
+    pub fn input_frames_for(&self, output_frames: usize) -> usize {
+        if output_frames == 0 {
+            return 0;
+        }
+        let last = self.produced + output_frames as u64 - 1;
+        let needed = last * self.down / self.up + self.half + 1;
+        needed.saturating_sub(self.received) as usize
+    }
+
+    /// Push `block` and return every output frame its input now covers
+    pub fn process(&mut self, block: &AudioBlock) -> AudioBlock {
+        let buffered = (self.received - self.history_start) as usize;
+        if self.history.len() < block.channels.len() {
+            self.history
+                .resize(block.channels.len(), vec![0.0; buffered]);
+        }
+        let frames = block.frame_len();
+        for (ch, history) in self.history.iter_mut().enumerate() {
+            match block.channels.get(ch) {
+                Some(input) => history.extend_from_slice(input),
+                None => history.resize(buffered + frames, 0.0),
+            }
+        }
+        self.received += frames as u64;
+
+        // Output frame n needs input up to floor(n * down / up) + half
+        let covered = self.received.saturating_sub(self.half);
+        self.render((covered * self.up + self.down - 1) / self.down)
+    }
+
+    /// Return the output still held back, treating the stream as ended
+    ///
+    /// Together with the earlier `process` output this is exactly what
+    /// `resample_with_quality` gives for the whole stream.
+    pub fn flush(&mut self) -> AudioBlock {
+        self.render((self.received * self.up + self.down - 1) / self.down)
+    }
+
+    /// Forget the stream so far, e.g. after a seek
+    pub fn reset(&mut self) {
+        self.history.clear();
+        self.history_start = 0;
+        self.received = 0;
+        self.produced = 0;
+    }
+
+    /// Produce output frames up to, but not including, `end`
+    fn render(&mut self, end: u64) -> AudioBlock {
+        let (up, down) = (self.up, self.down);
+        let phases = self.kernels.len();
+        let half = self.half as i64;
+        let start = self.produced.min(end);
+
+        let channels = self
+            .history
+            .iter()
+            .map(|input| {
+                (start..end)
+                    .map(|n| {
+                        let position = n * down;
+                        let base = (position / up) as i64;
+                        let frac = (position % up) as f32 / up as f32;
+                        let phase = ((frac * phases as f32).round() as usize).min(phases - 1);
+
+                        self.kernels[phase]
+                            .iter()
+                            .zip(base - half + 1..)
+                            .filter_map(|(h, i)| {
+                                let offset = usize::try_from(i - self.history_start as i64).ok()?;
+                                input.get(offset).map(|x| h * x)
+                            })
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        self.produced = self.produced.max(end);
+
+        // Drop input that no later output frame reaches back to
+        let next_base = self.produced * down / up;
+        let keep_from = (next_base + 1).saturating_sub(self.half);
+        let drop = keep_from
+            .saturating_sub(self.history_start)
+            .min(self.received - self.history_start);
+        for history in &mut self.history {
+            history.drain(..drop as usize);
+        }
+        self.history_start += drop;
+
+        AudioBlock {
+            sample_rate: self.target_rate,
+            channels,
+        }
+    }
+}
+
+/// Taps for an output instant `frac` of a sample after the base input
+/// sample, normalized to unity DC gain
+fn resample_kernel(frac: f32, half: isize, cutoff: f32) -> Vec<f32> {
+    let mut taps: Vec<f32> = (-half + 1..=half)
+        .map(|k| {
+            let x = frac - k as f32;
+            let sinc = if x == 0.0 {
+                1.0
+            } else {
+                (PI * cutoff * x).sin() / (PI * cutoff * x)
+            };
+            let t = x / half as f32;
+            let window = 0.42 + 0.5 * (PI * t).cos() + 0.08 * (2.0 * PI * t).cos();
+            cutoff * sinc * window
+        })
+        .collect();
+
+    let sum: f32 = taps.iter().sum();
+    if sum != 0.0 {
+        taps.iter_mut().for_each(|t| *t /= sum);
+    }
+    taps
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
     }
+    a
 }
@@ -1,6 +1,231 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::calibration::CalibrationSolution;
+use crate::dsp::{BiquadDesign, ParametricEq, PeqBand};
+use crate::hrtf::{BinauralRenderer, HeadphoneProfile, HrtfDatabase, HrtfDataset, HrtfPosition};
+use crate::loudness::{DynamicRangeControl, HeadroomManager, LoudnessNormalizer, LoudnessTarget};
+use crate::pipeline::{BinauralStage, BiquadStage, Pipeline};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DspConfigError {
+    #[error("parse error: {0}")]
+    Parse(String),
+    #[error("serialize error: {0}")]
+    Serialize(String),
+    #[error("stage `{0}` cannot be described as config")]
+    UnsupportedStage(&'static str),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Serializable description of a DSP processing chain
+///
+/// Stages are listed in processing order. In TOML each stage is a
+/// `[[stages]]` table tagged with its `type`:
+///
+/// ```toml
+/// sample_rate = 48000
+///
+/// [[stages]]
+/// type = "drc"
+/// ratio = 4.0
+/// threshold_db = -20.0
+/// attack_ms = 5.0
+/// release_ms = 80.0
+/// makeup_gain_db = 0.0
+///
+/// [[stages]]
+/// type = "headroom"
+/// headroom_db = 1.0
+/// lookahead_ms = 3.0
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DspChainConfig {
+    pub sample_rate: u32,
+    #[serde(default)]
+    pub stages: Vec<StageConfig>,
+}
+
+/// Parameters for one stage of a `DspChainConfig`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StageConfig {
+    Drc {
+        ratio: f32,
+        threshold_db: f32,
+        attack_ms: f32,
+        release_ms: f32,
+        #[serde(default)]
+        makeup_gain_db: f32,
+        #[serde(default)]
+        knee_db: f32,
+        #[serde(default)]
+        stereo_link: bool,
+    },
+    Loudness {
+        target_lufs: f32,
+    },
+    Headroom {
+        headroom_db: f32,
+        lookahead_ms: f32,
+        #[serde(default)]
+        true_peak: bool,
+    },
+    Peq {
+        bands: Vec<PeqBand>,
+    },
+    Biquad {
+        filter: BiquadDesign,
+    },
+    /// Binaural downmix through the built-in HRTF set
+    Binaural {
+        dataset: HrtfDataset,
+        profile: HeadphoneProfile,
+        azimuth: f32,
+        elevation: f32,
+        distance: f32,
+        #[serde(default)]
+        near_field_compensation: bool,
+    },
+}
+
+impl DspChainConfig {
+    /// Instantiate the configured stages as a `Pipeline`
+    pub fn build(&self) -> Pipeline {
+        let sr = self.sample_rate;
+        let mut pipeline = Pipeline::new(sr);
+
+        for stage in &self.stages {
+            match stage {
+                StageConfig::Drc {
+                    ratio,
+                    threshold_db,
+                    attack_ms,
+                    release_ms,
+                    makeup_gain_db,
+                    knee_db,
+                    stereo_link,
+                } => {
+                    let mut drc = DynamicRangeControl::new(
+                        *ratio,
+                        *threshold_db,
+                        *knee_db,
+                        *attack_ms,
+                        *release_ms,
+                        sr,
+                    );
+                    drc.set_makeup_gain(*makeup_gain_db);
+                    drc.set_stereo_link(*stereo_link);
+                    pipeline.add_stage(drc);
+                }
+                StageConfig::Loudness { target_lufs } => {
+                    pipeline.add_stage(LoudnessNormalizer::new(
+                        sr,
+                        LoudnessTarget::Custom(*target_lufs),
+                    ));
+                }
+                StageConfig::Headroom {
+                    headroom_db,
+                    lookahead_ms,
+                    true_peak,
+                } => {
+                    let mut limiter = HeadroomManager::new(*headroom_db, sr);
+                    limiter.set_lookahead_ms(sr, *lookahead_ms);
+                    limiter.set_true_peak(*true_peak);
+                    pipeline.add_stage(limiter);
+                }
+                StageConfig::Peq { bands } => {
+                    pipeline.add_stage(ParametricEq::new(bands.clone(), sr));
+                }
+                StageConfig::Biquad { filter } => {
+                    pipeline.add_stage(BiquadStage::designed(*filter, sr));
+                }
+                StageConfig::Binaural {
+                    dataset,
+                    profile,
+                    azimuth,
+                    elevation,
+                    distance,
+                    near_field_compensation,
+                } => {
+                    let mut database = HrtfDatabase::new(*dataset, sr);
+                    database
+                        .load_default_kemar()
+                        .expect("built-in HRTF set always loads");
+                    database.set_near_field_compensation(*near_field_compensation);
+                    pipeline.add_stage(BinauralStage::new(
+                        BinauralRenderer::new(database, profile.clone()),
+                        HrtfPosition::new(*azimuth, *elevation, *distance),
+                    ));
+                }
+            }
+        }
+
+        pipeline
+    }
+
+    /// Describe an existing pipeline; fails if any stage has no config form
+    pub fn from_pipeline(pipeline: &Pipeline) -> Result<Self, DspConfigError> {
+        let sample_rate = pipeline.sample_rate();
+        let stages = pipeline
+            .stages()
+            .map(|stage| {
+                stage
+                    .config(sample_rate)
+                    .ok_or(DspConfigError::UnsupportedStage(stage.name()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            sample_rate,
+            stages,
+        })
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, DspConfigError> {
+        toml::from_str(toml).map_err(|e| DspConfigError::Parse(e.to_string()))
+    }
+
+    pub fn to_toml(&self) -> Result<String, DspConfigError> {
+        toml::to_string_pretty(self).map_err(|e| DspConfigError::Serialize(e.to_string()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, DspConfigError> {
+        serde_json::from_str(json).map_err(|e| DspConfigError::Parse(e.to_string()))
+    }
+
+    pub fn to_json(&self) -> Result<String, DspConfigError> {
+        serde_json::to_string_pretty(self).map_err(|e| DspConfigError::Serialize(e.to_string()))
+    }
+
+    /// Load a preset file, choosing the format from the extension (`.json`, otherwise TOML)
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, DspConfigError> {
+        let text = std::fs::read_to_string(&path)?;
+        if is_json(path.as_ref()) {
+            Self::from_json(&text)
+        } else {
+            Self::from_toml(&text)
+        }
+    }
+
+    /// Save a preset file, choosing the format from the extension (`.json`, otherwise TOML)
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), DspConfigError> {
+        let text = if is_json(path.as_ref()) {
+            self.to_json()?
+        } else {
+            self.to_toml()?
+        };
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct CamillaDspConfig {
@@ -99,7 +99,38 @@ pub struct LossStatistics {
     pub total_recovered: u64,
     pub consecutive_losses: u64,
     pub max_consecutive_losses: u64,
+    /// Number of separate loss runs (each gap counts once, however long)
+    pub loss_bursts: u64,
+    /// Highest sequence seen so far
     last_sequence: Option<u16>,
+    sequence_cycles: u32,
+    /// Sequence that would confirm a large jump as a source restart
+    bad_sequence: Option<u16>,
+}
+
+/// Forward jumps this large or larger are not counted as loss; two
+/// sequential packets past one restart the count (RFC 3550 A.1)
+const MAX_DROPOUT: u16 = 3000;
+/// Packets at most this far behind the highest sequence are late or
+/// duplicated, and leave the loss figures alone
+const MAX_MISORDER: u16 = 100;
+
+/// Burstiness of the observed loss pattern, for choosing between XOR groups
+/// and Reed-Solomon parity
+///
+/// The transition probabilities fit a two-state Gilbert-Elliott model in which
+/// the good state always delivers and the bad state always drops.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BurstSummary {
+    pub loss_bursts: u64,
+    /// Average number of packets lost per burst
+    pub mean_burst_length: f64,
+    /// Loss bursts per expected packet
+    pub burst_density: f64,
+    /// Probability of moving from the good (delivering) to the bad state
+    pub p_good_to_bad: f64,
+    /// Probability of moving from the bad (dropping) back to the good state
+    pub p_bad_to_good: f64,
 }
 
 impl LossStatistics {
@@ -107,27 +138,56 @@ impl LossStatistics {
         Self::default()
     }
 
+    /// Account for a received packet, tracking the highest sequence the
+    /// way RFC 3550 A.1 does
+    ///
+    /// Only a forward step shorter than `MAX_DROPOUT` advances the highest
+    /// sequence, and the packets it skips count as lost. Late and duplicate
+    /// packets are ignored, and the wrap count only grows when the highest
+    /// sequence wraps forward past 65535.
     pub fn update(&mut self, sequence: u16) {
-        if let Some(last) = self.last_sequence {
-            let expected_next = last.wrapping_add(1);
-            let gap = sequence.wrapping_sub(expected_next) as u64;
-
-            if gap > 0 {
-                // Detected packet loss
-                self.total_lost += gap;
-                self.consecutive_losses += gap;
-                self.max_consecutive_losses =
-                    self.max_consecutive_losses.max(self.consecutive_losses);
-            } else {
-                self.consecutive_losses = 0;
+        let Some(highest) = self.last_sequence else {
+            self.total_expected = 1;
+            self.total_received = 1;
+            self.last_sequence = Some(sequence);
+            return;
+        };
+
+        let delta = sequence.wrapping_sub(highest);
+        if delta == 0 || delta > u16::MAX - MAX_MISORDER {
+            return;
+        }
+        if delta >= MAX_DROPOUT {
+            if self.bad_sequence != Some(sequence) {
+                self.bad_sequence = Some(sequence.wrapping_add(1));
+                return;
             }
+            // Two sequential packets after a large jump: the source restarted
+            self.bad_sequence = None;
+            self.consecutive_losses = 0;
+            self.total_expected += 1;
+            self.total_received += 1;
+            self.last_sequence = Some(sequence);
+            return;
+        }
 
-            self.total_expected += gap + 1;
+        if sequence < highest {
+            self.sequence_cycles += 1;
+        }
+        let gap = (delta - 1) as u64;
+        if gap > 0 {
+            // Detected packet loss
+            self.total_lost += gap;
+            self.loss_bursts += 1;
+            self.consecutive_losses += gap;
+            self.max_consecutive_losses = self.max_consecutive_losses.max(self.consecutive_losses);
         } else {
-            self.total_expected = 1;
+            self.consecutive_losses = 0;
         }
 
+        self.total_expected += delta as u64;
         self.total_received += 1;
+        self.bad_sequence = None;
         self.last_sequence = Some(sequence);
     }
 
@@ -153,6 +213,31 @@ impl LossStatistics {
             self.total_recovered as f64 / self.total_lost as f64
         }
     }
+
+    /// Highest sequence seen, extended with the wrap count (RFC 3550 6.4.1)
+    pub fn extended_highest_sequence(&self) -> u32 {
+        let last = self.last_sequence.unwrap_or(0) as u32;
+        (self.sequence_cycles << 16) | last
+    }
+
+    /// Summarize loss burstiness; all zero when nothing has been lost
+    pub fn burst_summary(&self) -> BurstSummary {
+        if self.loss_bursts == 0 {
+            return BurstSummary::default();
+        }
+
+        let bursts = self.loss_bursts as f64;
+        // Every received packet but the latest has an observed successor, and
+        // every burst ends in a delivered packet
+        let good_transitions = self.total_received.saturating_sub(1).max(1) as f64;
+        BurstSummary {
+            loss_bursts: self.loss_bursts,
+            mean_burst_length: self.total_lost as f64 / bursts,
+            burst_density: bursts / self.total_expected as f64,
+            p_good_to_bad: (bursts / good_transitions).min(1.0),
+            p_bad_to_good: bursts / self.total_lost as f64,
+        }
+    }
 }
 
 /// Packet loss concealment strategy
@@ -161,8 +246,22 @@ pub enum ConcealmentStrategy {
     Silence,
     Repeat,
     Interpolate,
+    /// Continue the last block's pitch period with overlap-add, fading out
+    /// after a few periods. Best for tonal content.
+    WaveformSimilarity,
 }
 
+/// Shortest pitch period searched by `WaveformSimilarity`, in seconds (1 kHz)
+const MIN_PITCH_PERIOD_S: f32 = 0.001;
+/// Longest pitch period searched by `WaveformSimilarity`, in seconds (50 Hz)
+const MAX_PITCH_PERIOD_S: f32 = 0.02;
+/// Length of the tail matched against earlier audio, in seconds
+const SIMILARITY_WINDOW_S: f32 = 0.005;
+/// Periods synthesized at full level before the fade starts
+const FULL_LEVEL_PERIODS: usize = 3;
+/// Fade from full level to silence, in seconds
+const CONCEALMENT_FADE_S: f32 = 0.02;
+
 /// Packet loss concealer - generates replacement audio when packets are lost
 pub struct LossConcealer {
     strategy: ConcealmentStrategy,
@@ -191,33 +290,169 @@ impl LossConcealer {
                     AudioBlock::silence(num_channels, frames, sample_rate)
                 }
             }
+            ConcealmentStrategy::WaveformSimilarity => {
+                match self
+                    .last_block
+                    .as_ref()
+                    .and_then(|last| waveform_similarity(last, sample_rate, num_channels, frames))
+                {
+                    Some(block) => block,
+                    None => self.fade_last_block(sample_rate, num_channels, frames),
+                }
+            }
             ConcealmentStrategy::Interpolate => {
-                // Simple fade-out interpolation
-                if let Some(ref last) = self.last_block {
-                    let mut block = last.clone();
-                    for channel in &mut block.channels {
-                        let len = channel.len();
-                        for (i, sample) in channel.iter_mut().enumerate() {
-                            let fade = 1.0 - (i as f32 / len as f32);
-                            *sample *= fade;
-                        }
-                    }
-                    block
-                } else {
-                    AudioBlock::silence(num_channels, frames, sample_rate)
+                self.fade_last_block(sample_rate, num_channels, frames)
+            }
+        }
+    }
+
+    /// Simple fade-out interpolation of the last block
+    fn fade_last_block(&self, sample_rate: u32, num_channels: usize, frames: usize) -> AudioBlock {
+        if let Some(ref last) = self.last_block {
+            let mut block = last.clone();
+            for channel in &mut block.channels {
+                let len = channel.len();
+                for (i, sample) in channel.iter_mut().enumerate() {
+                    let fade = 1.0 - (i as f32 / len as f32);
+                    *sample *= fade;
                 }
             }
+            block
+        } else {
+            AudioBlock::silence(num_channels, frames, sample_rate)
+        }
+    }
+}
+
+/// Pitch period of the end of `mono`, in samples, by normalized
+/// cross-correlation of the last `window` samples against earlier audio.
+/// `None` if the block is too short to search.
+fn best_pitch_period(mono: &[f32], min_lag: usize, max_lag: usize, window: usize) -> Option<usize> {
+    let len = mono.len();
+    let max_lag = max_lag.min(len.checked_sub(window)?);
+    if window == 0 || min_lag == 0 || min_lag > max_lag {
+        return None;
+    }
+
+    let tail = &mono[len - window..];
+    let tail_energy: f32 = tail.iter().map(|x| x * x).sum();
+    let scores: Vec<f32> = (min_lag..=max_lag)
+        .map(|lag| {
+            let earlier = &mono[len - window - lag..len - lag];
+            let dot: f32 = tail.iter().zip(earlier).map(|(a, b)| a * b).sum();
+            let energy: f32 = earlier.iter().map(|x| x * x).sum();
+            let norm = (tail_energy * energy).sqrt();
+            if norm > 0.0 {
+                dot / norm
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    let score = |lag: usize| scores[lag - min_lag];
+    let best = (min_lag..=max_lag).max_by(|&a, &b| score(a).total_cmp(&score(b)))?;
+
+    // Multiples of the period score about as well; prefer the shortest
+    for divisor in (2..=best / min_lag).rev() {
+        let around =
+            (best / divisor).saturating_sub(1).max(min_lag)..=(best / divisor + 1).min(max_lag);
+        if let Some(lag) = around.max_by(|&a, &b| score(a).total_cmp(&score(b))) {
+            if score(lag) >= score(best) - 0.01 {
+                return Some(lag);
+            }
         }
     }
+    Some(best)
+}
+
+/// Synthesize `frames` of concealment by overlap-adding copies of the last
+/// pitch period of `last`
+fn waveform_similarity(
+    last: &AudioBlock,
+    sample_rate: u32,
+    num_channels: usize,
+    frames: usize,
+) -> Option<AudioBlock> {
+    let len = last.channels.iter().map(Vec::len).min()?;
+    let seconds = |s: f32| (s * sample_rate as f32).round() as usize;
+    let mono: Vec<f32> = (0..len)
+        .map(|i| last.channels.iter().map(|c| c[i]).sum::<f32>() / last.channels.len() as f32)
+        .collect();
+    let period = best_pitch_period(
+        &mono,
+        seconds(MIN_PITCH_PERIOD_S),
+        seconds(MAX_PITCH_PERIOD_S),
+        seconds(SIMILARITY_WINDOW_S),
+    )?;
+
+    // Each copy is one period plus a crossfade into the next copy
+    let overlap = (period / 4).min(len - period);
+    let fade_start = FULL_LEVEL_PERIODS * period;
+    let fade_len = seconds(CONCEALMENT_FADE_S).max(1);
+    let level = |n: usize| 1.0 - (n.saturating_sub(fade_start) as f32 / fade_len as f32).min(1.0);
+
+    let channels = (0..num_channels)
+        .map(|ch| {
+            let Some(source) = last.channels.get(ch) else {
+                return vec![0.0; frames];
+            };
+            let segment = &source[len - period - overlap..len];
+            let mut out = vec![0.0f32; frames];
+            let mut start = 0;
+            while start < frames {
+                for (j, &x) in segment.iter().enumerate() {
+                    // The first copy continues the block: no fade-in
+                    let Some(pos) = (start + j).checked_sub(overlap) else {
+                        continue;
+                    };
+                    if pos >= frames {
+                        break;
+                    }
+                    let mut weight = 1.0;
+                    if start > 0 && j < overlap {
+                        weight *= j as f32 / overlap as f32;
+                    }
+                    // Hand over to the next copy, if there is one
+                    if j >= period && start + period < frames {
+                        weight *= 1.0 - (j - period) as f32 / overlap.max(1) as f32;
+                    }
+                    out[pos] += weight * x;
+                }
+                start += period;
+            }
+            for (n, sample) in out.iter_mut().enumerate() {
+                *sample *= level(n);
+            }
+            out
+        })
+        .collect();
+
+    Some(AudioBlock {
+        sample_rate,
+        channels,
+    })
 }
 
+/// Groups of buffered packets after which `FecReceiver::take_ready_packets`
+/// stops waiting on a gap whose FEC packet never arrived
+const REORDER_WINDOW_GROUPS: usize = 4;
+
 /// FEC-enhanced receiver with packet recovery
+///
+/// Packets are buffered per FEC group. When a group's FEC packet arrives
+/// with exactly one packet missing, the missing packet is rebuilt and
+/// buffered under its own sequence number. Recovered packets are as long
+/// as the longest packet of their group.
 pub struct FecReceiver {
     fec: XorFec,
     concealer: LossConcealer,
     stats: LossStatistics,
-    group_cache: HashMap<usize, Vec<Vec<u8>>>,
+    /// `(sequence, payload)` per group, received and recovered
+    group_cache: HashMap<usize, Vec<(u16, Vec<u8>)>>,
     fec_cache: HashMap<usize, Vec<u8>>,
+    /// Sequence `take_ready_packets` delivers next
+    next_sequence: Option<u16>,
 }
 
 impl FecReceiver {
@@ -228,35 +463,108 @@ impl FecReceiver {
             stats: LossStatistics::new(),
             group_cache: HashMap::new(),
             fec_cache: HashMap::new(),
+            next_sequence: None,
         }
     }
 
+    fn group_of(&self, sequence: u16) -> usize {
+        sequence as usize / self.fec.group_size
+    }
+
     pub fn process_packet(&mut self, sequence: u16, packet: Vec<u8>) {
         self.stats.update(sequence);
 
-        let group_id = sequence as usize / self.fec.group_size;
-        self.group_cache.entry(group_id).or_default().push(packet);
+        let group_id = self.group_of(sequence);
+        match self.next_sequence {
+            // Already delivered or skipped over
+            Some(next) if (next.wrapping_sub(sequence) as i16) > 0 => return,
+            Some(_) => {}
+            None => self.next_sequence = Some((group_id * self.fec.group_size) as u16),
+        }
+
+        let group = self.group_cache.entry(group_id).or_default();
+        if group.iter().any(|(seq, _)| *seq == sequence) {
+            return;
+        }
+        group.push((sequence, packet));
+        self.try_recover(group_id);
     }
 
     pub fn process_fec_packet(&mut self, group_id: usize, fec: Vec<u8>) {
+        // Nothing left to repair once the group has been delivered
+        if !self.group_cache.contains_key(&group_id) {
+            return;
+        }
         self.fec_cache.insert(group_id, fec);
         self.try_recover(group_id);
     }
 
     fn try_recover(&mut self, group_id: usize) {
+        let group_size = self.fec.group_size;
         if let (Some(packets), Some(fec)) = (
-            self.group_cache.get(&group_id),
+            self.group_cache.get_mut(&group_id),
             self.fec_cache.get(&group_id),
         ) {
-            if packets.len() == self.fec.group_size - 1 {
+            if packets.len() == group_size - 1 {
                 // Exactly one packet missing - can recover
-                if let Ok(_recovered) = self.fec.decode(packets, fec) {
+                let first = group_id * group_size;
+                let missing = (first..first + group_size)
+                    .map(|seq| seq as u16)
+                    .find(|seq| packets.iter().all(|(received, _)| received != seq));
+                let payloads: Vec<Vec<u8>> = packets.iter().map(|(_, p)| p.clone()).collect();
+                if let (Some(sequence), Ok(recovered)) = (missing, self.fec.decode(&payloads, fec))
+                {
+                    packets.push((sequence, recovered));
                     self.stats.record_recovery();
                 }
             }
         }
     }
 
+    /// Remove and return buffered packets in sequence order, recovered ones
+    /// included.
+    ///
+    /// Delivery stops at the first gap that FEC may still fill. A gap is
+    /// skipped once its group's FEC packet has been processed, or when four
+    /// groups' worth of packets are waiting behind it.
+    pub fn take_ready_packets(&mut self) -> Vec<(u16, Vec<u8>)> {
+        let mut ready = Vec::new();
+        let Some(mut next) = self.next_sequence else {
+            return ready;
+        };
+        let group_size = self.fec.group_size;
+
+        loop {
+            let group_id = self.group_of(next);
+            let packet = self
+                .group_cache
+                .get(&group_id)
+                .and_then(|group| group.iter().find(|(seq, _)| *seq == next))
+                .map(|(_, payload)| payload.clone());
+            match packet {
+                Some(payload) => ready.push((next, payload)),
+                None => {
+                    let buffered: usize = self.group_cache.values().map(Vec::len).sum();
+                    let given_up = self.fec_cache.contains_key(&group_id)
+                        || buffered >= REORDER_WINDOW_GROUPS * group_size;
+                    if !given_up || buffered == 0 {
+                        break;
+                    }
+                }
+            }
+
+            next = next.wrapping_add(1);
+            if self.group_of(next) != group_id {
+                // Group fully delivered; late FEC for it is no longer useful
+                self.group_cache.remove(&group_id);
+                self.fec_cache.remove(&group_id);
+            }
+        }
+
+        self.next_sequence = Some(next);
+        ready
+    }
+
     pub fn statistics(&self) -> &LossStatistics {
         &self.stats
     }
@@ -1,6 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::iamf::{CodecConfig, IamfStreamConfig};
+use crate::AudioBlock;
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Output, Stdio};
+use std::time::Duration;
 
 #[derive(Debug, thiserror::Error)]
 pub enum FfmpegError {
@@ -10,6 +15,8 @@ pub enum FfmpegError {
     Decode(String),
     #[error("format error: {0}")]
     Format(String),
+    #[error("{0} not found; install ffmpeg and make sure it is on PATH")]
+    NotInstalled(String),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -141,3 +148,335 @@ impl Decoder for StubDecoder {
         Ok(vec![])
     }
 }
+
+/// Frames per block produced by `FfmpegDecoder::decode_to_blocks`
+pub const DECODE_BLOCK_FRAMES: usize = 1024;
+
+/// Stream properties reported by `ffprobe`
+#[derive(Clone, Debug, PartialEq)]
+pub struct MediaInfo {
+    pub duration: Option<Duration>,
+    pub codec: String,
+    pub channels: usize,
+    pub sample_rate: u32,
+    /// Bits per sample; `None` for lossy codecs that don't report one
+    pub bit_depth: Option<u32>,
+}
+
+impl MediaInfo {
+    /// Parse the JSON emitted by `ffprobe -of json -show_streams -show_format`
+    ///
+    /// Only the first audio stream is considered.
+    pub fn from_ffprobe_json(json: &str) -> Result<Self, FfmpegError> {
+        let root: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| FfmpegError::Format(format!("invalid ffprobe output: {}", e)))?;
+
+        let stream = root["streams"]
+            .as_array()
+            .and_then(|streams| {
+                streams
+                    .iter()
+                    .find(|s| s["codec_type"].as_str().unwrap_or("audio") == "audio")
+            })
+            .ok_or_else(|| FfmpegError::Format("no audio stream found".into()))?;
+
+        let codec = stream["codec_name"]
+            .as_str()
+            .ok_or_else(|| FfmpegError::Format("missing codec_name".into()))?
+            .to_string();
+        let channels = json_u64(&stream["channels"])
+            .ok_or_else(|| FfmpegError::Format("missing channel count".into()))?
+            as usize;
+        let sample_rate = json_u64(&stream["sample_rate"])
+            .ok_or_else(|| FfmpegError::Format("missing sample_rate".into()))?
+            as u32;
+
+        // PCM reports bits_per_sample; FLAC and friends only bits_per_raw_sample
+        let bit_depth = [&stream["bits_per_sample"], &stream["bits_per_raw_sample"]]
+            .into_iter()
+            .filter_map(json_u64)
+            .find(|&bits| bits > 0)
+            .map(|bits| bits as u32);
+
+        let duration = [&root["format"]["duration"], &stream["duration"]]
+            .into_iter()
+            .filter_map(json_f64)
+            .find(|secs| secs.is_finite() && *secs >= 0.0)
+            .map(Duration::from_secs_f64);
+
+        Ok(Self {
+            duration,
+            codec,
+            channels,
+            sample_rate,
+            bit_depth,
+        })
+    }
+}
+
+/// ffprobe encodes most numbers as strings; accept either form
+fn json_u64(value: &serde_json::Value) -> Option<u64> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+fn json_f64(value: &serde_json::Value) -> Option<f64> {
+    value
+        .as_f64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+/// Run an ffmpeg tool, mapping a missing binary to `FfmpegError::NotInstalled`
+fn run_tool(program: &str, command: &mut Command) -> Result<Output, FfmpegError> {
+    let output = command.output().map_err(|e| launch_error(program, e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(FfmpegError::Decode(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            stderr.trim()
+        )));
+    }
+
+    Ok(output)
+}
+
+fn launch_error(program: &str, e: std::io::Error) -> FfmpegError {
+    if e.kind() == ErrorKind::NotFound {
+        FfmpegError::NotInstalled(program.to_string())
+    } else {
+        FfmpegError::Init(format!("failed to run {}: {}", program, e))
+    }
+}
+
+/// Media probing via the `ffprobe` command-line tool
+pub struct FfmpegProbe;
+
+impl FfmpegProbe {
+    /// Probe the first audio stream of a media file
+    pub fn probe<P: AsRef<Path>>(path: P) -> Result<MediaInfo, FfmpegError> {
+        let output = run_tool(
+            "ffprobe",
+            Command::new("ffprobe")
+                .args(["-v", "error", "-select_streams", "a:0"])
+                .args(["-show_streams", "-show_format", "-of", "json"])
+                .arg(path.as_ref()),
+        )?;
+
+        MediaInfo::from_ffprobe_json(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+/// Whole-file decoding via the `ffmpeg` command-line tool
+pub struct FfmpegDecoder;
+
+impl FfmpegDecoder {
+    /// Decode the first audio stream into `AudioBlock`s resampled to `target_rate`
+    ///
+    /// ffmpeg writes interleaved 32-bit float PCM to a pipe, which is split
+    /// into blocks of `DECODE_BLOCK_FRAMES` frames; the last block may be shorter.
+    pub fn decode_to_blocks<P: AsRef<Path>>(
+        path: P,
+        target_rate: u32,
+    ) -> Result<Vec<AudioBlock>, FfmpegError> {
+        if target_rate == 0 {
+            return Err(FfmpegError::Init(
+                "target sample rate must be non-zero".into(),
+            ));
+        }
+
+        let info = FfmpegProbe::probe(&path)?;
+        if info.channels == 0 {
+            return Err(FfmpegError::Format("stream has no channels".into()));
+        }
+
+        let output = run_tool(
+            "ffmpeg",
+            Command::new("ffmpeg")
+                .args(["-v", "error", "-i"])
+                .arg(path.as_ref())
+                .args(["-map", "0:a:0", "-f", "f32le"])
+                .args(["-ac", &info.channels.to_string()])
+                .args(["-ar", &target_rate.to_string()])
+                .arg("pipe:1"),
+        )?;
+
+        Ok(interleaved_f32le_to_blocks(
+            &output.stdout,
+            info.channels,
+            target_rate,
+            DECODE_BLOCK_FRAMES,
+        ))
+    }
+}
+
+/// Streaming decode of a media file's first audio stream at its native rate
+///
+/// The file is probed when opened, so `info` reports the channel count and
+/// sample rate before the first block is read. ffmpeg then runs in the
+/// background and each `next_block` reads `block_frames` frames of float
+/// PCM from its output pipe; the last block may be shorter.
+pub struct DecodeStream {
+    info: MediaInfo,
+    block_frames: usize,
+    child: Child,
+    stdout: ChildStdout,
+    finished: bool,
+}
+
+impl DecodeStream {
+    /// Probe `path` and start decoding it
+    ///
+    /// Fails with `FfmpegError::NotInstalled` if ffprobe or ffmpeg is
+    /// missing from PATH.
+    pub fn open<P: AsRef<Path>>(path: P, block_frames: usize) -> Result<Self, FfmpegError> {
+        if block_frames == 0 {
+            return Err(FfmpegError::Init("block size must be non-zero".into()));
+        }
+
+        let info = FfmpegProbe::probe(&path)?;
+        if info.channels == 0 || info.sample_rate == 0 {
+            return Err(FfmpegError::Format(
+                "stream has no channels or sample rate".into(),
+            ));
+        }
+
+        let mut child = Command::new("ffmpeg")
+            .args(["-v", "error", "-i"])
+            .arg(path.as_ref())
+            .args(["-map", "0:a:0", "-f", "f32le"])
+            .args(["-ac", &info.channels.to_string()])
+            .arg("pipe:1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| launch_error("ffmpeg", e))?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        Ok(Self {
+            info,
+            block_frames,
+            child,
+            stdout,
+            finished: false,
+        })
+    }
+
+    /// Stream properties from ffprobe
+    pub fn info(&self) -> &MediaInfo {
+        &self.info
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.info.sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.info.channels
+    }
+
+    /// Decode the next block, or `None` once the stream has ended
+    ///
+    /// A non-zero ffmpeg exit is reported as `FfmpegError::Decode` with its
+    /// error output.
+    pub fn next_block(&mut self) -> Result<Option<AudioBlock>, FfmpegError> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let frame_bytes = self.info.channels * 4;
+        let mut bytes = vec![0u8; self.block_frames * frame_bytes];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match self.stdout.read(&mut bytes[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(FfmpegError::Decode(format!("reading ffmpeg: {}", e))),
+            }
+        }
+
+        if filled < bytes.len() {
+            self.finish()?;
+        }
+
+        let frames = filled / frame_bytes;
+        if frames == 0 {
+            return Ok(None);
+        }
+        let samples: Vec<f32> = bytes[..frames * frame_bytes]
+            .chunks_exact(4)
+            .map(|s| f32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+            .collect();
+        Ok(Some(AudioBlock::from_interleaved(
+            &samples,
+            self.info.channels,
+            self.info.sample_rate,
+        )))
+    }
+
+    /// Reap ffmpeg after its output ends and surface a failed exit
+    fn finish(&mut self) -> Result<(), FfmpegError> {
+        self.finished = true;
+        let mut stderr = String::new();
+        if let Some(mut pipe) = self.child.stderr.take() {
+            pipe.read_to_string(&mut stderr).ok();
+        }
+
+        let status = self
+            .child
+            .wait()
+            .map_err(|e| FfmpegError::Decode(format!("waiting for ffmpeg: {}", e)))?;
+        if !status.success() {
+            return Err(FfmpegError::Decode(format!(
+                "ffmpeg exited with {}: {}",
+                status,
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for DecodeStream {
+    type Item = Result<AudioBlock, FfmpegError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_block().transpose()
+    }
+}
+
+impl Drop for DecodeStream {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.child.kill().ok();
+            self.child.wait().ok();
+        }
+    }
+}
+
+fn interleaved_f32le_to_blocks(
+    bytes: &[u8],
+    channels: usize,
+    sample_rate: u32,
+    block_frames: usize,
+) -> Vec<AudioBlock> {
+    let frame_bytes = channels * 4;
+
+    bytes
+        .chunks(block_frames * frame_bytes)
+        .map(|chunk| {
+            let frames = chunk.len() / frame_bytes;
+            let samples: Vec<f32> = chunk[..frames * frame_bytes]
+                .chunks_exact(4)
+                .map(|s| f32::from_le_bytes([s[0], s[1], s[2], s[3]]))
+                .collect();
+            AudioBlock::from_interleaved(&samples, channels, sample_rate)
+        })
+        .filter(|block| block.frame_len() > 0)
+        .collect()
+}
@@ -2,6 +2,9 @@
 
 //! Higher-Order Ambisonics (HOA) decoder for scene-based spatial audio rendering
 
+use crate::dsp::{design_linkwitz_riley, BiquadCascade, BiquadFilter, LrOrder};
+use crate::hrtf::{BinauralRenderer, HeadphoneProfile, HrtfDatabase, HrtfPosition};
+
 /// Ambisonic order (1 = B-format, 2 = 2nd order, 3 = 3rd order)
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct AmbisonicOrder(pub u8);
@@ -93,6 +96,11 @@ impl HoaDecoder {
 
     /// Compute the decoding matrix
     fn compute_decode_matrix(&mut self) {
+        self.decode_matrix = self.decode_matrix_for(self.mode);
+    }
+
+    /// Decoding matrix (`[channel][speaker]`) for the layout in `mode`
+    fn decode_matrix_for(&self, mode: DecodingMode) -> Vec<Vec<f32>> {
         let num_speakers = self.speakers.len();
         let num_channels = self.order.channel_count();
 
@@ -107,30 +115,24 @@ impl HoaDecoder {
             encode_matrix[i] = harmonics;
         }
 
-        // Apply mode-dependent weights
-        let weights = self.compute_mode_weights();
-
-        // Apply weights to encoding matrix
-        for row in encode_matrix.iter_mut() {
-            for (val, &weight) in row.iter_mut().zip(weights.iter()) {
+        // Mode-dependent weights shape the ambisonic channels ahead of the
+        // pseudoinverse, so they are applied per channel after inversion
+        let weights = self.compute_mode_weights(mode);
+        let mut decode_matrix = transpose_matrix(&pseudoinverse(&encode_matrix));
+        for (row, &weight) in decode_matrix.iter_mut().zip(weights.iter()) {
+            for val in row.iter_mut() {
                 *val *= weight;
             }
         }
-
-        // Decode matrix is pseudoinverse of weighted encoding matrix
-        // For simplicity, use transpose (works well for uniformly distributed speakers)
-        self.decode_matrix = transpose_matrix(&encode_matrix);
-
-        // Normalize each speaker output
-        self.normalize_decode_matrix();
+        decode_matrix
     }
 
     /// Compute mode-dependent channel weights
-    fn compute_mode_weights(&self) -> Vec<f32> {
+    fn compute_mode_weights(&self, mode: DecodingMode) -> Vec<f32> {
         let num_channels = self.order.channel_count();
         let mut weights = vec![1.0; num_channels];
 
-        match self.mode {
+        match mode {
             DecodingMode::Basic => {
                 // No special weighting
             }
@@ -161,18 +163,6 @@ impl HoaDecoder {
         weights
     }
 
-    /// Normalize decode matrix for energy preservation
-    fn normalize_decode_matrix(&mut self) {
-        let num_speakers = self.speakers.len();
-        let scale = (num_speakers as f32).sqrt();
-
-        for row in self.decode_matrix.iter_mut() {
-            for val in row.iter_mut() {
-                *val /= scale;
-            }
-        }
-    }
-
     /// Decode ambisonic signal to speaker feeds
     /// Input: ambisonic channels (length = channel_count())
     /// Output: speaker gains (length = speaker_count())
@@ -209,26 +199,277 @@ impl HoaDecoder {
             self.channel_count()
         );
 
-        let num_samples = input[0].len();
-        let mut output = vec![vec![0.0; num_samples]; self.speaker_count()];
+        apply_decode_matrix(&self.decode_matrix, input, self.speaker_count())
+    }
 
-        for sample_idx in 0..num_samples {
-            let mut ambisonic_sample = vec![0.0; self.channel_count()];
-            for (ch, channel) in input.iter().enumerate() {
-                ambisonic_sample[ch] = channel[sample_idx];
-            }
+    /// Decode a buffer with a frequency-dependent decoder
+    ///
+    /// Each ambisonic channel is split with an LR4 crossover at
+    /// `crossover_hz`. The low band is decoded with `DecodingMode::Basic`
+    /// and the high band with `DecodingMode::MaxRE`, rescaled so both
+    /// decoders carry the same energy for a diffuse field, and the bands
+    /// are summed. The decoder's own mode is ignored. Filter state starts
+    /// fresh on every call.
+    /// Input: \[channel\]\[sample\]
+    /// Output: \[speaker\]\[sample\]
+    pub fn decode_dualband_buffer(
+        &self,
+        input: &[Vec<f32>],
+        crossover_hz: f32,
+        sample_rate: u32,
+    ) -> Vec<Vec<f32>> {
+        assert_eq!(
+            input.len(),
+            self.channel_count(),
+            "Input must have {} channels",
+            self.channel_count()
+        );
 
-            let decoded = self.decode(&ambisonic_sample);
+        let (lowpass, highpass) = design_linkwitz_riley(crossover_hz, LrOrder::Lr4, sample_rate);
+        let split = |filters: &[BiquadFilter]| -> Vec<Vec<f32>> {
+            input
+                .iter()
+                .map(|channel| {
+                    let mut band = channel.clone();
+                    BiquadCascade::new(filters.to_vec()).process_block(&mut band);
+                    band
+                })
+                .collect()
+        };
 
-            for (spk, &gain) in decoded.iter().enumerate() {
-                output[spk][sample_idx] = gain;
+        let basic = self.decode_matrix_for(DecodingMode::Basic);
+        let mut max_re = self.decode_matrix_for(DecodingMode::MaxRE);
+        let energy = |matrix: &[Vec<f32>]| matrix.iter().flatten().map(|v| v * v).sum::<f32>();
+        let (basic_energy, max_re_energy) = (energy(&basic), energy(&max_re));
+        if max_re_energy > 0.0 {
+            let scale = (basic_energy / max_re_energy).sqrt();
+            for val in max_re.iter_mut().flatten() {
+                *val *= scale;
             }
         }
 
+        let mut output = apply_decode_matrix(&basic, &split(&lowpass), self.speaker_count());
+        let high = apply_decode_matrix(&max_re, &split(&highpass), self.speaker_count());
+        for (speaker_out, speaker_high) in output.iter_mut().zip(high) {
+            for (out, h) in speaker_out.iter_mut().zip(speaker_high) {
+                *out += h;
+            }
+        }
         output
     }
 }
 
+/// Render an ambisonic signal to headphones through virtual loudspeakers
+///
+/// The input (`[channel][sample]`, ACN order) is decoded with max-rE onto
+/// a virtual layout: the cube for first order, a 22-speaker sphere for
+/// higher orders. Each virtual speaker is then rendered through `db` at its
+/// direction and the results are summed. Returns `(left, right)` as long as
+/// the input; the HRTF tail is dropped. Silence if `db` cannot render.
+///
+/// # Panics
+/// If `input` does not have `order.channel_count()` channels.
+pub fn render_hoa_binaural(
+    input: &[Vec<f32>],
+    order: AmbisonicOrder,
+    db: &HrtfDatabase,
+) -> (Vec<f32>, Vec<f32>) {
+    let speakers = binaural_virtual_layout(order);
+    let positions: Vec<HrtfPosition> = speakers
+        .iter()
+        .map(|s| HrtfPosition::new(s.azimuth, s.elevation, 1.0))
+        .collect();
+    let decoder = HoaDecoder::new(order, DecodingMode::MaxRE, speakers);
+    let feeds = decoder.decode_buffer(input);
+
+    let frames = input.first().map_or(0, Vec::len);
+    let renderer = BinauralRenderer::new(db.clone(), HeadphoneProfile::Flat);
+    match renderer.render_buffer(&feeds, &positions) {
+        Ok(mut ears) if ears.len() == 2 => {
+            let mut right = ears.pop().unwrap_or_default();
+            let mut left = ears.pop().unwrap_or_default();
+            left.resize(frames, 0.0);
+            right.resize(frames, 0.0);
+            (left, right)
+        }
+        _ => (vec![0.0; frames], vec![0.0; frames]),
+    }
+}
+
+/// Directions sampled to fit the rotation matrices in `rotate_soundfield`
+const ROTATION_FIT_POINTS: usize = 64;
+
+/// Rotate an ambisonic sound field in place
+///
+/// Angles are in degrees and applied roll, then pitch, then yaw. Positive
+/// yaw turns sources to the left (azimuth increases), positive pitch tilts
+/// front sources upward and positive roll tilts left sources upward. For
+/// head tracking, pass the negated head rotation.
+///
+/// Each degree is rotated by its own (2n+1)-square matrix, built once per
+/// call, so the per-sample cost is a small matrix-vector product.
+///
+/// # Panics
+/// If `channels` does not have `order.channel_count()` channels.
+pub fn rotate_soundfield(
+    channels: &mut [Vec<f32>],
+    order: AmbisonicOrder,
+    yaw: f32,
+    pitch: f32,
+    roll: f32,
+) {
+    assert_eq!(
+        channels.len(),
+        order.channel_count(),
+        "Input must have {} channels",
+        order.channel_count()
+    );
+
+    let frames = channels.iter().map(Vec::len).min().unwrap_or(0);
+    let matrices = rotation_matrices(order, yaw, pitch, roll);
+    let mut scratch = Vec::new();
+    for (degree, matrix) in matrices.iter().enumerate().skip(1) {
+        let start = degree * degree;
+        let block = &mut channels[start..start + matrix.len()];
+        for i in 0..frames {
+            scratch.clear();
+            scratch.extend(matrix.iter().map(|row| {
+                row.iter()
+                    .zip(block.iter())
+                    .map(|(m, channel)| m * channel[i])
+                    .sum::<f32>()
+            }));
+            for (channel, &value) in block.iter_mut().zip(&scratch) {
+                channel[i] = value;
+            }
+        }
+    }
+}
+
+/// Per-degree matrices mapping the harmonics of a direction to those of
+/// the rotated direction, `matrices[n]` being `(2n+1)`-square
+///
+/// Real spherical harmonics of one degree span a rotation-invariant space,
+/// so `Y_n(R·d) = M_n · Y_n(d)` exactly; `M_n` is solved by least squares
+/// over directions spread across the sphere.
+fn rotation_matrices(order: AmbisonicOrder, yaw: f32, pitch: f32, roll: f32) -> Vec<Vec<Vec<f32>>> {
+    let (sin_y, cos_y) = yaw.to_radians().sin_cos();
+    let (sin_p, cos_p) = pitch.to_radians().sin_cos();
+    let (sin_r, cos_r) = roll.to_radians().sin_cos();
+    // x front, y left, z up
+    let rotate = |[x, y, z]: [f32; 3]| {
+        let (y, z) = (y * cos_r - z * sin_r, y * sin_r + z * cos_r);
+        let (x, z) = (x * cos_p - z * sin_p, x * sin_p + z * cos_p);
+        [x * cos_y - y * sin_y, x * sin_y + y * cos_y, z]
+    };
+    let angles = |[x, y, z]: [f32; 3]| (y.atan2(x), z.clamp(-1.0, 1.0).asin());
+
+    let degrees = order.max_degree() as usize + 1;
+    let mut gram: Vec<Vec<Vec<f64>>> = (0..degrees)
+        .map(|n| vec![vec![0.0; 2 * n + 1]; 2 * n + 1])
+        .collect();
+    let mut cross = gram.clone();
+
+    // Fibonacci sphere
+    let golden_angle = std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+    for k in 0..ROTATION_FIT_POINTS {
+        let z = 1.0 - 2.0 * (k as f32 + 0.5) / ROTATION_FIT_POINTS as f32;
+        let radius = (1.0 - z * z).sqrt();
+        let theta = golden_angle * k as f32;
+        let point = [radius * theta.cos(), radius * theta.sin(), z];
+
+        let (az, el) = angles(point);
+        let before = compute_spherical_harmonics(order, az, el);
+        let (az, el) = angles(rotate(point));
+        let after = compute_spherical_harmonics(order, az, el);
+
+        for n in 0..degrees {
+            let start = n * n;
+            for i in 0..2 * n + 1 {
+                for j in 0..2 * n + 1 {
+                    let b = before[start + j] as f64;
+                    gram[n][i][j] += before[start + i] as f64 * b;
+                    cross[n][i][j] += after[start + i] as f64 * b;
+                }
+            }
+        }
+    }
+
+    gram.into_iter()
+        .zip(cross)
+        .map(|(gram, cross)| {
+            let inverse = invert_matrix(gram);
+            cross
+                .iter()
+                .map(|row| {
+                    (0..inverse.len())
+                        .map(|j| {
+                            row.iter()
+                                .zip(&inverse)
+                                .map(|(c, inv_row)| c * inv_row[j])
+                                .sum::<f64>() as f32
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Virtual loudspeakers for binaural HOA rendering, with at least as many
+/// speakers as `order` has channels
+fn binaural_virtual_layout(order: AmbisonicOrder) -> Vec<HoaSpeaker> {
+    if order.0 <= 1 {
+        return create_cube_hoa_layout();
+    }
+
+    // Ear-level ring of 8, offset rings of 6 above and below, and the poles
+    let mut speakers = Vec::new();
+    for i in 0..8 {
+        speakers.push(HoaSpeaker::new(
+            speakers.len(),
+            i as f32 * 45.0 - 180.0,
+            0.0,
+        ));
+    }
+    for elevation in [45.0, -45.0] {
+        for i in 0..6 {
+            speakers.push(HoaSpeaker::new(
+                speakers.len(),
+                i as f32 * 60.0 - 150.0,
+                elevation,
+            ));
+        }
+    }
+    speakers.push(HoaSpeaker::new(speakers.len(), 0.0, 90.0));
+    speakers.push(HoaSpeaker::new(speakers.len(), 0.0, -90.0));
+    speakers
+}
+
+/// Mix `[channel][sample]` input through a `[channel][speaker]` matrix
+fn apply_decode_matrix(
+    matrix: &[Vec<f32>],
+    input: &[Vec<f32>],
+    speaker_count: usize,
+) -> Vec<Vec<f32>> {
+    let num_samples = input[0].len();
+    let mut output = vec![vec![0.0; num_samples]; speaker_count];
+
+    // Accumulate whole channels at a time, in the same channel order as
+    // `decode`, so each output sample sums identically without a
+    // per-sample scratch vector
+    for (channel_row, channel) in matrix.iter().zip(input) {
+        let channel = &channel[..num_samples];
+        for (speaker_out, &coeff) in output.iter_mut().zip(channel_row) {
+            for (out, &x) in speaker_out.iter_mut().zip(channel) {
+                *out += coeff * x;
+            }
+        }
+    }
+
+    output
+}
+
 /// Compute spherical harmonics up to given order at (azimuth, elevation)
 /// Returns vector of Y_nm values in ACN (Ambisonic Channel Numbering) order
 fn compute_spherical_harmonics(order: AmbisonicOrder, azimuth: f32, elevation: f32) -> Vec<f32> {
@@ -256,32 +497,46 @@ fn compute_spherical_harmonics(order: AmbisonicOrder, azimuth: f32, elevation: f
     }
 
     if order.0 >= 2 {
-        // Degree 2 (5 channels)
-        let k2 = (5.0 / (16.0 * std::f32::consts::PI)).sqrt();
-        let cos_az = azimuth.cos();
-        let sin_az = azimuth.sin();
+        // Degree 2 (5 channels): m = -2..=2
+        let k2 = 0.25 * (15.0 / std::f32::consts::PI).sqrt();
         let cos2_el = cos_el * cos_el;
         let sin2_el = sin_el * sin_el;
 
-        harmonics[idx] = k2 * 3.0 * cos_el * sin_el * sin_az;
+        harmonics[idx] = k2 * cos2_el * (2.0 * azimuth).sin();
         idx += 1;
-        harmonics[idx] = k2 * 3.0 * sin_el * cos_az;
+        harmonics[idx] = 2.0 * k2 * sin_el * cos_el * azimuth.sin();
         idx += 1;
-        harmonics[idx] = k2 * (3.0 * sin2_el - 1.0);
+        harmonics[idx] = 0.25 * (5.0 / std::f32::consts::PI).sqrt() * (3.0 * sin2_el - 1.0);
         idx += 1;
-        harmonics[idx] = k2 * 3.0 * cos_el * sin_el * cos_az;
+        harmonics[idx] = 2.0 * k2 * sin_el * cos_el * azimuth.cos();
         idx += 1;
-        harmonics[idx] = k2 * 3.0 * cos2_el * (2.0 * cos_az * cos_az - 1.0);
+        harmonics[idx] = k2 * cos2_el * (2.0 * azimuth).cos();
         idx += 1;
     }
 
     if order.0 >= 3 {
-        // Degree 3 (7 channels) - simplified approximation
-        let k3 = (7.0 / (16.0 * std::f32::consts::PI)).sqrt();
-        for _ in 0..7 {
-            harmonics[idx] = k3 * sin_el.powi(idx as i32 % 3) * azimuth.cos();
-            idx += 1;
-        }
+        // Degree 3 (7 channels): m = -3..=3
+        let pi = std::f32::consts::PI;
+        let k33 = 0.25 * (35.0 / (2.0 * pi)).sqrt();
+        let k32 = 0.25 * (105.0 / pi).sqrt();
+        let k31 = 0.25 * (21.0 / (2.0 * pi)).sqrt();
+        let k30 = 0.25 * (7.0 / pi).sqrt();
+        let cos3_el = cos_el * cos_el * cos_el;
+        let tilt = 5.0 * sin_el * sin_el - 1.0;
+
+        harmonics[idx] = k33 * cos3_el * (3.0 * azimuth).sin();
+        idx += 1;
+        harmonics[idx] = k32 * cos_el * cos_el * sin_el * (2.0 * azimuth).sin();
+        idx += 1;
+        harmonics[idx] = k31 * cos_el * tilt * azimuth.sin();
+        idx += 1;
+        harmonics[idx] = k30 * sin_el * (5.0 * sin_el * sin_el - 3.0);
+        idx += 1;
+        harmonics[idx] = k31 * cos_el * tilt * azimuth.cos();
+        idx += 1;
+        harmonics[idx] = k32 * cos_el * cos_el * sin_el * (2.0 * azimuth).cos();
+        idx += 1;
+        harmonics[idx] = k33 * cos3_el * (3.0 * azimuth).cos();
     }
 
     harmonics
@@ -306,6 +561,95 @@ fn in_phase_weight(degree: i32, _order: u8) -> f32 {
     1.0 / (1.0 + n * 0.5)
 }
 
+/// Tikhonov regularization of the pseudoinverse, relative to the mean
+/// diagonal of the Gram matrix. Keeps layouts with fewer speakers than
+/// channels (or nearly coincident harmonics) from blowing up.
+const PINV_REGULARIZATION: f64 = 1e-3;
+
+/// Regularized Moore-Penrose pseudoinverse of the encoding matrix
+///
+/// `encode` is `E[speaker][channel]`; the result is `D[speaker][channel]`
+/// with `D = E (EᵀE + λI)⁻¹`, so re-encoding the decoded speaker feeds
+/// reproduces the ambisonic input wherever the layout allows it.
+fn pseudoinverse(encode: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    let channels = encode.first().map_or(0, Vec::len);
+    if channels == 0 {
+        return vec![Vec::new(); encode.len()];
+    }
+
+    // Gram matrix EᵀE: [channel][channel]
+    let mut gram = vec![vec![0.0f64; channels]; channels];
+    for row in encode {
+        for (i, &a) in row.iter().enumerate() {
+            for (j, &b) in row.iter().enumerate() {
+                gram[i][j] += a as f64 * b as f64;
+            }
+        }
+    }
+    let trace: f64 = (0..channels).map(|i| gram[i][i]).sum();
+    let lambda = PINV_REGULARIZATION * trace / channels as f64;
+    for (i, row) in gram.iter_mut().enumerate() {
+        row[i] += lambda.max(f64::EPSILON);
+    }
+
+    let inverse = invert_matrix(gram);
+    encode
+        .iter()
+        .map(|row| {
+            (0..channels)
+                .map(|c| {
+                    row.iter()
+                        .zip(&inverse)
+                        .map(|(&e, inv_row)| e as f64 * inv_row[c])
+                        .sum::<f64>() as f32
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Invert a symmetric positive-definite matrix by Gauss-Jordan elimination
+/// with partial pivoting
+fn invert_matrix(mut a: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = a.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&x, &y| a[x][col].abs().total_cmp(&a[y][col].abs()))
+            .unwrap_or(col);
+        a.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let scale = a[col][col];
+        if scale.abs() < f64::EPSILON {
+            continue;
+        }
+        for j in 0..n {
+            a[col][j] /= scale;
+            inverse[col][j] /= scale;
+        }
+
+        for row in 0..n {
+            if row == col {
+                continue;
+            }
+            let factor = a[row][col];
+            if factor == 0.0 {
+                continue;
+            }
+            for j in 0..n {
+                a[row][j] -= factor * a[col][j];
+                inverse[row][j] -= factor * inverse[col][j];
+            }
+        }
+    }
+
+    inverse
+}
+
 /// Transpose a matrix
 fn transpose_matrix(matrix: &[Vec<f32>]) -> Vec<Vec<f32>> {
     if matrix.is_empty() {
@@ -433,6 +777,173 @@ mod tests {
         assert!(output[1].abs() > 0.0);
     }
 
+    /// Energy-weighted direction of speaker gains, as a unit (x, y, z) vector
+    fn energy_direction(speakers: &[HoaSpeaker], gains: &[f32]) -> [f32; 3] {
+        let mut sum = [0.0f32; 3];
+        for (speaker, g) in speakers.iter().zip(gains) {
+            let (az, el) = (speaker.azimuth.to_radians(), speaker.elevation.to_radians());
+            let energy = g * g;
+            sum[0] += energy * el.cos() * az.cos();
+            sum[1] += energy * el.cos() * az.sin();
+            sum[2] += energy * el.sin();
+        }
+        let len = (sum[0] * sum[0] + sum[1] * sum[1] + sum[2] * sum[2]).sqrt();
+        sum.map(|v| v / len)
+    }
+
+    #[test]
+    fn test_pseudoinverse_decode_points_at_plane_wave() {
+        for (speakers, order) in [
+            (create_5_1_hoa_layout(), AmbisonicOrder::FIRST),
+            (create_7_1_4_hoa_layout(), AmbisonicOrder::FIRST),
+            (create_7_1_4_hoa_layout(), AmbisonicOrder::SECOND),
+        ] {
+            let decoder = HoaDecoder::new(order, DecodingMode::Basic, speakers.clone());
+
+            for source in [0, 1, 3] {
+                let target = &speakers[source];
+                let plane_wave = compute_spherical_harmonics(
+                    order,
+                    target.azimuth.to_radians(),
+                    target.elevation.to_radians(),
+                );
+                let gains = decoder.decode(&plane_wave);
+
+                // The loudest speaker is the one the wave comes from
+                let loudest = (0..gains.len())
+                    .max_by(|&a, &b| gains[a].abs().total_cmp(&gains[b].abs()))
+                    .unwrap();
+                assert_eq!(loudest, source, "{:?} speaker {}", order, source);
+
+                // And the energy vector points back at it
+                let direction = energy_direction(&speakers, &gains);
+                let expected = energy_direction(&speakers[source..=source], &[1.0]);
+                let cos: f32 = direction.iter().zip(expected).map(|(a, b)| a * b).sum();
+                assert!(cos > 0.9, "{:?} speaker {}: cos {}", order, source, cos);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pseudoinverse_reencodes_input() {
+        // 12 speakers cover the 4 first-order channels: D is a right inverse
+        let speakers = create_7_1_4_hoa_layout();
+        let decoder = HoaDecoder::new(AmbisonicOrder::FIRST, DecodingMode::Basic, speakers.clone());
+        let input = compute_spherical_harmonics(AmbisonicOrder::FIRST, 0.4, 0.2);
+        let gains = decoder.decode(&input);
+
+        let mut reencoded = vec![0.0f32; 4];
+        for (speaker, g) in speakers.iter().zip(&gains) {
+            let harmonics = compute_spherical_harmonics(
+                AmbisonicOrder::FIRST,
+                speaker.azimuth.to_radians(),
+                speaker.elevation.to_radians(),
+            );
+            for (r, h) in reencoded.iter_mut().zip(harmonics) {
+                *r += g * h;
+            }
+        }
+        for (r, x) in reencoded.iter().zip(&input) {
+            assert!(
+                (r - x).abs() < 0.01 * input[0],
+                "{:?} vs {:?}",
+                reencoded,
+                input
+            );
+        }
+    }
+
+    /// Plane wave from `(azimuth, elevation)` degrees carrying a sine at `freq_hz`
+    fn plane_wave_buffer(
+        order: AmbisonicOrder,
+        azimuth: f32,
+        elevation: f32,
+        freq_hz: f32,
+        sample_rate: u32,
+    ) -> Vec<Vec<f32>> {
+        let harmonics =
+            compute_spherical_harmonics(order, azimuth.to_radians(), elevation.to_radians());
+        let sine: Vec<f32> = (0..sample_rate as usize / 4)
+            .map(|i| (std::f32::consts::TAU * freq_hz * i as f32 / sample_rate as f32).sin())
+            .collect();
+        harmonics
+            .iter()
+            .map(|&h| sine.iter().map(|&x| h * x).collect())
+            .collect()
+    }
+
+    /// Energy of the speaker feeds after the filters have settled
+    fn settled_energy(output: &[Vec<f32>]) -> f32 {
+        output
+            .iter()
+            .map(|speaker| speaker[4800..].iter().map(|x| x * x).sum::<f32>())
+            .sum()
+    }
+
+    #[test]
+    fn test_dualband_decode_preserves_energy_across_crossover() {
+        let sr = 48000;
+        let crossover = 700.0;
+        for order in [AmbisonicOrder::FIRST, AmbisonicOrder::SECOND] {
+            let decoder = HoaDecoder::new(order, DecodingMode::Basic, create_7_1_4_hoa_layout());
+            for (az, el) in [(0.0, 0.0), (70.0, 0.0), (-150.0, 20.0)] {
+                // Energy relative to the single-band basic decode, in dB
+                let ratio_db = |freq: f32| {
+                    let input = plane_wave_buffer(order, az, el, freq, sr);
+                    let basic = settled_energy(&decoder.decode_buffer(&input));
+                    let dual =
+                        settled_energy(&decoder.decode_dualband_buffer(&input, crossover, sr));
+                    10.0 * (dual / basic).log10()
+                };
+                let (low, mid, high) = (ratio_db(100.0), ratio_db(crossover), ratio_db(5000.0));
+
+                // Well below the crossover only the basic decoder is heard
+                assert!(low.abs() < 0.05, "{:?} {} {}: {}", order, az, el, low);
+                // No notch or bump where the bands overlap
+                for ratio in [mid, high] {
+                    assert!(ratio.abs() < 1.0, "{:?} {} {}: {}", order, az, el, ratio);
+                }
+                assert!(mid >= low.min(high) - 0.1 && mid <= low.max(high) + 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotation_matches_reencoding() {
+        let order = AmbisonicOrder::THIRD;
+        for (yaw, pitch, roll) in [(90.0, 0.0, 0.0), (-35.0, 20.0, 0.0), (120.0, -40.0, 75.0)] {
+            // Rotate the harmonics of a direction and compare with the
+            // harmonics of the rotated direction
+            let (az, el) = (0.3f32, 0.2f32);
+            let mut channels: Vec<Vec<f32>> = compute_spherical_harmonics(order, az, el)
+                .into_iter()
+                .map(|h| vec![h])
+                .collect();
+            rotate_soundfield(&mut channels, order, yaw, pitch, roll);
+
+            let (sin_y, cos_y) = yaw.to_radians().sin_cos();
+            let (sin_p, cos_p) = pitch.to_radians().sin_cos();
+            let (sin_r, cos_r) = roll.to_radians().sin_cos();
+            let (x, y, z) = (el.cos() * az.cos(), el.cos() * az.sin(), el.sin());
+            let (y, z) = (y * cos_r - z * sin_r, y * sin_r + z * cos_r);
+            let (x, z) = (x * cos_p - z * sin_p, x * sin_p + z * cos_p);
+            let (x, y) = (x * cos_y - y * sin_y, x * sin_y + y * cos_y);
+            let expected = compute_spherical_harmonics(order, y.atan2(x), z.asin());
+
+            for (channel, e) in channels.iter().zip(expected) {
+                assert!(
+                    (channel[0] - e).abs() < 1e-4,
+                    "{} {} {}: {} vs {}",
+                    yaw,
+                    pitch,
+                    roll,
+                    channel[0],
+                    e
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_layout_presets() {
         assert_eq!(create_stereo_hoa_layout().len(), 2);
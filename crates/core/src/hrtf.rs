@@ -5,11 +5,30 @@
 //! Provides spatial audio virtualization for headphones through convolution
 //! with measured or modeled HRTF filters.
 
+use crate::calibration::{design_high_shelf, design_low_shelf, design_peq};
+use crate::dsp::{BiquadCascade, BiquadFilter};
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Nearest-neighbor cost of a doubling or halving of distance, in degrees of arc
+const DISTANCE_WEIGHT_DEG_PER_OCTAVE: f32 = 30.0;
+
+/// Distance of the standard far-field measurement set, in meters
+pub const REFERENCE_DISTANCE_M: f32 = 1.0;
+
+/// Near-field bass boost per halving of distance below the reference
+const NEAR_FIELD_BOOST_DB_PER_OCTAVE: f32 = 6.0;
+
+/// Largest near-field bass boost
+const NEAR_FIELD_MAX_BOOST_DB: f32 = 12.0;
+
+/// Corner of the near-field low shelf
+const NEAR_FIELD_SHELF_HZ: f32 = 300.0;
+
 /// HRTF dataset source
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HrtfDataset {
     /// KEMAR (Knowles Electronics Manikin for Acoustic Research)
     Kemar,
@@ -20,7 +39,11 @@ pub enum HrtfDataset {
 }
 
 /// Headphone equalization profile
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+///
+/// `Custom` filters are designed for one sample rate, so that variant is
+/// left out of serialized DSP presets.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum HeadphoneProfile {
     /// No equalization (raw HRTF)
     Flat,
@@ -29,7 +52,48 @@ pub enum HeadphoneProfile {
     /// Open-back headphone compensation
     OpenBack,
     /// IEM (In-Ear Monitor) compensation
+    #[serde(rename = "iem")]
     IEM,
+    /// User-supplied headphone correction, applied to each ear
+    #[serde(skip)]
+    Custom(Vec<BiquadFilter>),
+    /// Plain crossfeed instead of HRTF virtualization, for
+    /// `BinauralRenderer::render_crossfeed`: each ear also hears the other
+    /// channel low-passed, scaled by `amount` (0..1) and delayed by
+    /// `delay_us`. Applies no headphone EQ.
+    Crossfeed { amount: f32, delay_us: f32 },
+}
+
+impl HeadphoneProfile {
+    /// Compensation filters for this profile at `sample_rate`
+    ///
+    /// The built-in curves approximate the Harman listener-preference
+    /// targets (over-ear 2018, in-ear 2019) relative to a diffuse-field
+    /// equalized rendering: a bass shelf below ~105 Hz, presence adjusted
+    /// around 3 kHz and a gentle treble shelf.
+    pub fn filters(&self, sample_rate: u32) -> Vec<BiquadFilter> {
+        match self {
+            HeadphoneProfile::Flat => Vec::new(),
+            HeadphoneProfile::ClosedBack => vec![
+                design_low_shelf(105.0, 6.0, sample_rate),
+                design_peq(3000.0, -2.0, 1.5, sample_rate),
+                design_high_shelf(10000.0, -2.0, sample_rate),
+            ],
+            HeadphoneProfile::OpenBack => vec![
+                // Less bass lift: open backs are usually tuned closer to the target
+                design_low_shelf(105.0, 4.0, sample_rate),
+                design_peq(3000.0, -1.0, 1.5, sample_rate),
+            ],
+            HeadphoneProfile::IEM => vec![
+                design_low_shelf(105.0, 8.0, sample_rate),
+                // IEMs bypass the concha, so restore some of its ear gain
+                design_peq(2700.0, 4.0, 1.2, sample_rate),
+                design_high_shelf(8000.0, -3.0, sample_rate),
+            ],
+            HeadphoneProfile::Custom(filters) => filters.clone(),
+            HeadphoneProfile::Crossfeed { .. } => Vec::new(),
+        }
+    }
 }
 
 /// 3D spatial position for HRTF lookup
@@ -117,6 +181,9 @@ pub struct HrtfDatabase {
     dataset: HrtfDataset,
     responses: HashMap<(i32, i32, i32), HrtfImpulseResponse>,
     sample_rate: u32,
+    near_field_compensation: bool,
+    /// Only holds the synthetic set from `load_default_kemar`
+    builtin: bool,
 }
 
 impl HrtfDatabase {
@@ -126,6 +193,8 @@ impl HrtfDatabase {
             dataset,
             responses: HashMap::new(),
             sample_rate,
+            near_field_compensation: false,
+            builtin: false,
         }
     }
 
@@ -133,12 +202,14 @@ impl HrtfDatabase {
     pub fn add_response(&mut self, pos: &HrtfPosition, response: HrtfImpulseResponse) {
         let key = pos.to_key();
         self.responses.insert(key, response);
+        self.builtin = false;
     }
 
     /// Load default responses (simplified for testing)
     pub fn load_default_kemar(&mut self) -> Result<()> {
         // Create synthetic HRTF responses for testing
         // In production, these would be loaded from measured KEMAR data
+        let builtin = self.builtin || self.responses.is_empty();
 
         for azimuth in (-180..180).step_by(15) {
             for elevation in (-90..90).step_by(15) {
@@ -164,9 +235,9 @@ impl HrtfDatabase {
                     right[i] += 0.05 * (phase + az_rad).sin() * (1.0 - i as f32 / length as f32);
                 }
 
-                // Compute inter-aural time difference (ITD)
+                // Compute inter-aural time difference (ITD); the far ear lags
                 let itd_samples =
-                    ((az_rad.sin() * 0.0001 * self.sample_rate as f32) as usize).max(1);
+                    (az_rad.sin().abs() * 0.0001 * self.sample_rate as f32).round() as usize;
                 let delay_left = if az_rad > 0.0 { 0 } else { itd_samples };
                 let delay_right = if az_rad > 0.0 { itd_samples } else { 0 };
 
@@ -177,27 +248,59 @@ impl HrtfDatabase {
             }
         }
 
+        self.builtin = builtin;
         Ok(())
     }
 
+    /// Whether every response came from `load_default_kemar`, so an
+    /// equivalent database can be generated at any sample rate
+    pub fn is_builtin(&self) -> bool {
+        self.builtin
+    }
+
+    /// Boost the bass of responses for sources closer than the reference
+    /// distance, when the database only holds a 1 m measurement set
+    pub fn set_near_field_compensation(&mut self, enabled: bool) {
+        self.near_field_compensation = enabled;
+    }
+
+    pub fn near_field_compensation(&self) -> bool {
+        self.near_field_compensation
+    }
+
     /// Get HRTF response for a position (with nearest-neighbor for now)
+    ///
+    /// The neighbor search scores angular distance and the log ratio of
+    /// source distances together, so measurements at several distances are
+    /// told apart.
     pub fn get_response(&self, pos: &HrtfPosition) -> Result<HrtfImpulseResponse> {
         let key = pos.to_key();
 
-        if let Some(response) = self.responses.get(&key) {
-            return Ok(response.clone());
+        let response = match self.responses.get(&key) {
+            Some(response) => response.clone(),
+            None => self.nearest_response(pos)?,
+        };
+
+        if self.near_field_compensation && self.is_reference_distance_only() {
+            return Ok(self.compensate_near_field(response, pos.distance));
         }
+        Ok(response)
+    }
 
-        // Find nearest neighbor
+    fn nearest_response(&self, pos: &HrtfPosition) -> Result<HrtfImpulseResponse> {
         let mut nearest = None;
         let mut min_distance = f32::INFINITY;
 
-        for (&(az, el, _), response) in &self.responses {
+        for (&(az, el, dist_key), response) in &self.responses {
+            let measured_distance = dist_key as f32 / 10.0;
+            let distance_deg =
+                (measured_distance / pos.distance).log2().abs() * DISTANCE_WEIGHT_DEG_PER_OCTAVE;
             let dist = (az as f32 - pos.azimuth)
                 .abs()
                 .min(360.0 - (az as f32 - pos.azimuth).abs().max(0.0))
                 .powi(2)
-                + (el as f32 - pos.elevation).powi(2);
+                + (el as f32 - pos.elevation).powi(2)
+                + distance_deg.powi(2);
 
             if dist < min_distance {
                 min_distance = dist;
@@ -208,6 +311,33 @@ impl HrtfDatabase {
         nearest.ok_or_else(|| anyhow!("No HRTF response available for position {:?}", pos))
     }
 
+    /// Whether every stored response was measured at the reference distance
+    fn is_reference_distance_only(&self) -> bool {
+        let reference_key = (REFERENCE_DISTANCE_M * 10.0).round() as i32;
+        self.responses.keys().all(|&(_, _, d)| d == reference_key)
+    }
+
+    /// Approximate the near-field proximity effect with a low shelf
+    fn compensate_near_field(
+        &self,
+        mut response: HrtfImpulseResponse,
+        distance: f32,
+    ) -> HrtfImpulseResponse {
+        let boost_db = near_field_boost_db(distance);
+        if boost_db <= 0.0 {
+            return response;
+        }
+
+        // Room for the shelf's low-frequency tail
+        let tail = (self.sample_rate / 100) as usize;
+        let shelf = design_low_shelf(NEAR_FIELD_SHELF_HZ, boost_db, self.sample_rate);
+        for ir in [&mut response.left, &mut response.right] {
+            ir.resize(ir.len() + tail, 0.0);
+            BiquadCascade::new(vec![shelf.clone()]).process_block(ir);
+        }
+        response
+    }
+
     /// Get sample rate
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
@@ -219,85 +349,160 @@ impl HrtfDatabase {
     }
 }
 
+/// Samples of convolution tail kept after the input in `BinauralRenderer::render`
+pub const BINAURAL_TAIL_SAMPLES: usize = 32;
+
+/// Near-field bass boost for a source at `distance` meters
+pub fn near_field_boost_db(distance: f32) -> f32 {
+    if distance >= REFERENCE_DISTANCE_M {
+        return 0.0;
+    }
+    ((REFERENCE_DISTANCE_M / distance.max(0.1)).log2() * NEAR_FIELD_BOOST_DB_PER_OCTAVE)
+        .min(NEAR_FIELD_MAX_BOOST_DB)
+}
+
+/// Corner of the crossfeed low-pass: roughly where head shadowing starts to
+/// attenuate the far ear
+const CROSSFEED_CUTOFF_HZ: f32 = 700.0;
+
 /// Binaural renderer using HRTF
 pub struct BinauralRenderer {
     database: HrtfDatabase,
     headphone_profile: HeadphoneProfile,
-    eq_filters: Vec<Vec<f32>>, // Headphone EQ coefficients
+    /// Headphone EQ, run with fresh state on each ear
+    eq: BiquadCascade,
 }
 
 impl BinauralRenderer {
     /// Create new binaural renderer
     pub fn new(database: HrtfDatabase, headphone_profile: HeadphoneProfile) -> Self {
-        let eq_filters = Self::create_eq_filters(&headphone_profile);
+        let eq = BiquadCascade::new(headphone_profile.filters(database.sample_rate()));
 
         Self {
             database,
             headphone_profile,
-            eq_filters,
-        }
-    }
-
-    /// Create headphone equalization filters
-    fn create_eq_filters(profile: &HeadphoneProfile) -> Vec<Vec<f32>> {
-        match profile {
-            HeadphoneProfile::Flat => {
-                // No equalization
-                vec![vec![1.0], vec![1.0]]
-            }
-            HeadphoneProfile::ClosedBack => {
-                // Bass boost, treble presence peak
-                vec![vec![1.0, 0.1], vec![1.0, 0.1]]
-            }
-            HeadphoneProfile::OpenBack => {
-                // Minimal bass boost
-                vec![vec![1.0, 0.05], vec![1.0, 0.05]]
-            }
-            HeadphoneProfile::IEM => {
-                // Presence peak in upper midrange
-                vec![vec![1.0, -0.05, 0.02], vec![1.0, -0.05, 0.02]]
-            }
+            eq,
         }
     }
 
     /// Render a mono signal to binaural stereo
+    ///
+    /// Both ears come back the same length: the input plus
+    /// `BINAURAL_TAIL_SAMPLES`. Each ear is convolved with its impulse
+    /// response and then shifted by its own delay, so the ITD is the
+    /// difference between `delay_right` and `delay_left`.
     pub fn render(&self, input: &[f32], position: &HrtfPosition) -> Result<(Vec<f32>, Vec<f32>)> {
         let hrtf = self.database.get_response(position)?;
-        let max_len = hrtf.max_length();
+        let len = input.len() + BINAURAL_TAIL_SAMPLES;
 
-        let mut left = vec![0.0; input.len() + max_len];
-        let mut right = vec![0.0; input.len() + max_len];
+        let left = self.render_ear(input, &hrtf.left, hrtf.delay_left, len);
+        let right = self.render_ear(input, &hrtf.right, hrtf.delay_right, len);
 
-        // Apply HRTF convolution for left channel
-        for (i, &sample) in input.iter().enumerate() {
-            for (j, &coeff) in hrtf.left.iter().enumerate() {
-                if i + j + hrtf.delay_left < left.len() {
-                    left[i + j + hrtf.delay_left] += sample * coeff;
+        Ok((left, right))
+    }
+
+    /// Convolve a mono signal with the HRIR pair at `position` and delay each
+    /// ear, keeping the whole tail and skipping the headphone EQ
+    ///
+    /// Each ear is `input.len() + ir.len() - 1` samples plus its own delay,
+    /// so streaming callers can overlap-add it and run the EQ themselves.
+    pub fn render_dry(
+        &self,
+        input: &[f32],
+        position: &HrtfPosition,
+    ) -> Result<(Vec<f32>, Vec<f32>)> {
+        let hrtf = self.database.get_response(position)?;
+        let ear = |ir: &[f32], delay: usize| {
+            let mut output = vec![0.0; delay + input.len() + ir.len().saturating_sub(1)];
+            for (i, &sample) in input.iter().enumerate() {
+                for (j, &coeff) in ir.iter().enumerate() {
+                    output[delay + i + j] += sample * coeff;
                 }
             }
+            output
+        };
+
+        Ok((
+            ear(&hrtf.left, hrtf.delay_left),
+            ear(&hrtf.right, hrtf.delay_right),
+        ))
+    }
+
+    /// Mix a delayed, low-passed copy of each channel into the other
+    ///
+    /// The light-weight alternative to HRTF rendering for plain stereo:
+    /// no position lookup or convolution, and the output is as long as the
+    /// input. Both ears are scaled by `1 / (1 + amount)` so mono content
+    /// keeps its level. Fails unless the profile is
+    /// `HeadphoneProfile::Crossfeed` or if the channels differ in length.
+    pub fn render_crossfeed(&self, left: &[f32], right: &[f32]) -> Result<(Vec<f32>, Vec<f32>)> {
+        let HeadphoneProfile::Crossfeed { amount, delay_us } = self.headphone_profile else {
+            return Err(anyhow!("Crossfeed requires a crossfeed headphone profile"));
+        };
+        if left.len() != right.len() {
+            return Err(anyhow!("Left and right channels must have same length"));
         }
 
-        // Apply HRTF convolution for right channel
+        let sample_rate = self.database.sample_rate() as f32;
+        let amount = amount.clamp(0.0, 1.0);
+        let delay = (delay_us.max(0.0) * sample_rate / 1_000_000.0).round() as usize;
+        let alpha = 1.0 - (-2.0 * std::f32::consts::PI * CROSSFEED_CUTOFF_HZ / sample_rate).exp();
+        let scale = 1.0 / (1.0 + amount);
+
+        // Delayed, low-passed copy of one channel for the opposite ear
+        let bleed = |source: &[f32]| {
+            let mut state = 0.0;
+            let mut out = vec![0.0; source.len()];
+            for (i, slot) in out.iter_mut().enumerate().skip(delay) {
+                state += alpha * (source[i - delay] - state);
+                *slot = state * amount;
+            }
+            out
+        };
+        let into_left = bleed(right);
+        let into_right = bleed(left);
+
+        let mix = |direct: &[f32], bleed: &[f32]| -> Vec<f32> {
+            direct
+                .iter()
+                .zip(bleed)
+                .map(|(d, b)| (d + b) * scale)
+                .collect()
+        };
+        Ok((mix(left, &into_left), mix(right, &into_right)))
+    }
+
+    /// Interaural time difference at `position`, in samples.
+    /// Positive when the right ear hears the source later.
+    pub fn itd_samples(&self, position: &HrtfPosition) -> Result<isize> {
+        let hrtf = self.database.get_response(position)?;
+        Ok(hrtf.delay_right as isize - hrtf.delay_left as isize)
+    }
+
+    /// Convolve, equalize and delay one ear, returning exactly `len` samples
+    fn render_ear(&self, input: &[f32], ir: &[f32], delay: usize, len: usize) -> Vec<f32> {
+        let mut convolved = vec![0.0; input.len() + ir.len().saturating_sub(1)];
         for (i, &sample) in input.iter().enumerate() {
-            for (j, &coeff) in hrtf.right.iter().enumerate() {
-                if i + j + hrtf.delay_right < right.len() {
-                    right[i + j + hrtf.delay_right] += sample * coeff;
-                }
+            for (j, &coeff) in ir.iter().enumerate() {
+                convolved[i + j] += sample * coeff;
             }
         }
 
         // Apply headphone equalization
-        left = self.apply_eq(&left);
-        right = self.apply_eq(&right);
+        let equalized = self.apply_eq(&convolved);
 
-        // Trim to input length
-        left.truncate(input.len() + 32);
-        right.truncate(input.len() + 32);
-
-        Ok((left, right))
+        let mut output = vec![0.0; len];
+        if delay < len {
+            let end = (delay + equalized.len()).min(len);
+            output[delay..end].copy_from_slice(&equalized[..end - delay]);
+        }
+        output
     }
 
-    /// Render buffer of mono audio to stereo
+    /// Render mono sources to one binaural stereo mix
+    ///
+    /// Channel `i` is rendered at `positions[i]`. A single position places
+    /// every channel there.
     pub fn render_buffer(
         &self,
         input: &[Vec<f32>],
@@ -311,19 +516,24 @@ impl BinauralRenderer {
         let mut left_output = vec![0.0; num_samples + 256];
         let mut right_output = vec![0.0; num_samples + 256];
 
+        if positions.is_empty() {
+            return Err(anyhow!("At least one position required"));
+        }
+        if positions.len() != 1 && positions.len() != input.len() {
+            return Err(anyhow!(
+                "Expected 1 or {} positions, got {}",
+                input.len(),
+                positions.len()
+            ));
+        }
+
         // Mix all channels
-        for channel in input {
+        for (i, channel) in input.iter().enumerate() {
             if channel.len() != num_samples {
                 return Err(anyhow!("All input channels must have same length"));
             }
 
-            // Use first position or average
-            let pos = if !positions.is_empty() {
-                &positions[0]
-            } else {
-                return Err(anyhow!("At least one position required"));
-            };
-
+            let pos = positions.get(i).unwrap_or(&positions[0]);
             let (left, right) = self.render(channel, pos)?;
 
             for (i, &sample) in left.iter().enumerate() {
@@ -358,31 +568,23 @@ impl BinauralRenderer {
         Ok(vec![left_output, right_output])
     }
 
-    /// Apply headphone equalization
+    /// Apply headphone equalization to one ear's signal
     fn apply_eq(&self, signal: &[f32]) -> Vec<f32> {
-        // Simple FIR filtering using first channel's EQ
-        let eq = &self.eq_filters[0];
-
-        if eq.len() == 1 && (eq[0] - 1.0).abs() < 0.001 {
-            return signal.to_vec(); // No EQ
-        }
-
-        let mut output = vec![0.0; signal.len()];
-
-        for i in 0..signal.len() {
-            for (j, &coeff) in eq.iter().enumerate() {
-                if i >= j {
-                    output[i] += signal[i - j] * coeff;
-                }
-            }
-        }
-
+        let mut output = signal.to_vec();
+        // Each ear gets its own filter state
+        self.eq.clone().process_block(&mut output);
         output
     }
 
     /// Get headphone profile
     pub fn headphone_profile(&self) -> HeadphoneProfile {
-        self.headphone_profile
+        self.headphone_profile.clone()
+    }
+
+    /// Switch headphone profile, redesigning the EQ for it
+    pub fn set_headphone_profile(&mut self, headphone_profile: HeadphoneProfile) {
+        self.eq = BiquadCascade::new(headphone_profile.filters(self.database.sample_rate()));
+        self.headphone_profile = headphone_profile;
     }
 
     /// Get database
@@ -563,12 +765,13 @@ mod tests {
         let pos_down = HrtfPosition::new(0.0, -30.0, 1.0);
         let (left_down, right_down) = renderer.render(&input, &pos_down).unwrap();
 
-        // Different elevations should produce different inter-aural differences
-        let itd_up = (left_up.len() as i32 - right_up.len() as i32).abs();
-        let itd_down = (left_down.len() as i32 - right_down.len() as i32).abs();
+        // Ears always come back the same length; ITD is an explicit delay
+        assert_eq!(left_up.len(), right_up.len());
+        assert_eq!(left_down.len(), right_down.len());
+        assert_ne!(left_up, left_down);
 
-        // At least one should be non-zero or different
-        assert!(itd_up >= 0 && itd_down >= 0);
+        // A median-plane source reaches both ears together
+        assert!(renderer.itd_samples(&pos_up).unwrap().abs() <= 1);
     }
 
     #[test]
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::loudness::LoudnessTarget;
+use crate::mapping::layout_from_name;
 use crate::{AudioBlock, SpeakerLayout, SpeakerRole};
 use std::time::Duration;
 
@@ -11,6 +13,8 @@ pub enum IamfError {
     Unsupported(String),
     #[error("decode error: {0}")]
     Decode(String),
+    #[error("unsupported OBU type {0}")]
+    UnsupportedObu(u8),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -230,3 +234,813 @@ pub fn map_object_element_to_layout(
     // to position audio objects at 3D coordinates and render to speaker array
     audio.clone()
 }
+
+const OBU_CODEC_CONFIG: u8 = 0;
+const OBU_AUDIO_ELEMENT: u8 = 1;
+const OBU_MIX_PRESENTATION: u8 = 2;
+const OBU_PARAMETER_BLOCK: u8 = 3;
+const OBU_AUDIO_FRAME_ID17: u8 = 23;
+const OBU_SEQUENCE_HEADER: u8 = 31;
+
+const PARAM_DEFINITION_DEMIXING: u32 = 1;
+const PARAM_DEFINITION_RECON_GAIN: u32 = 2;
+
+/// Descriptor OBUs read from the head of an IAMF bitstream.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfDescriptor {
+    pub primary_profile: u8,
+    pub additional_profile: u8,
+    pub codec_configs: Vec<IamfCodecConfig>,
+    pub audio_elements: Vec<IamfAudioElement>,
+    pub mix_presentations: Vec<IamfMixPresentation>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfCodecConfig {
+    pub codec_config_id: u32,
+    pub codec: CodecConfig,
+    pub samples_per_frame: u32,
+    pub audio_roll_distance: i16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfAudioElement {
+    pub element_id: u32,
+    pub element_type: AudioElementType,
+    pub codec_config_id: u32,
+    pub substream_ids: Vec<u32>,
+    pub config: AudioElementConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioElementConfig {
+    /// Scalable channel layers, base layer first.
+    Channel { layers: Vec<ChannelLayer> },
+    Scene {
+        ambisonics_order: u8,
+        output_channel_count: u8,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelLayer {
+    pub layout: LoudspeakerLayout,
+    pub substream_count: u8,
+    pub coupled_substream_count: u8,
+    pub output_gain_db: Option<f32>,
+}
+
+/// `loudspeaker_layout` values of a scalable channel layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoudspeakerLayout {
+    Mono,
+    Stereo,
+    Surround5_1,
+    Surround5_1_2,
+    Surround5_1_4,
+    Surround7_1,
+    Surround7_1_2,
+    Surround7_1_4,
+    Surround3_1_2,
+    Binaural,
+}
+
+impl LoudspeakerLayout {
+    fn from_code(code: u8) -> Result<Self, IamfError> {
+        Ok(match code {
+            0 => Self::Mono,
+            1 => Self::Stereo,
+            2 => Self::Surround5_1,
+            3 => Self::Surround5_1_2,
+            4 => Self::Surround5_1_4,
+            5 => Self::Surround7_1,
+            6 => Self::Surround7_1_2,
+            7 => Self::Surround7_1_4,
+            8 => Self::Surround3_1_2,
+            9 => Self::Binaural,
+            other => {
+                return Err(IamfError::Unsupported(format!(
+                    "loudspeaker layout {other}"
+                )))
+            }
+        })
+    }
+
+    /// Layout name in the form used by `mapping::layout_from_name`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mono => "1.0",
+            Self::Stereo => "2.0",
+            Self::Surround5_1 => "5.1",
+            Self::Surround5_1_2 => "5.1.2",
+            Self::Surround5_1_4 => "5.1.4",
+            Self::Surround7_1 => "7.1",
+            Self::Surround7_1_2 => "7.1.2",
+            Self::Surround7_1_4 => "7.1.4",
+            Self::Surround3_1_2 => "3.1.2",
+            Self::Binaural => "binaural",
+        }
+    }
+
+    /// Speaker roles in IAMF channel order.
+    pub fn speaker_roles(self) -> Vec<SpeakerRole> {
+        use SpeakerRole::*;
+
+        let base: &[SpeakerRole] = match self {
+            Self::Mono => &[Center],
+            Self::Stereo | Self::Binaural => &[FrontLeft, FrontRight],
+            Self::Surround5_1 | Self::Surround5_1_2 | Self::Surround5_1_4 => &[
+                FrontLeft, FrontRight, Center, Subwoofer, SideLeft, SideRight,
+            ],
+            Self::Surround7_1 | Self::Surround7_1_2 | Self::Surround7_1_4 => &[
+                FrontLeft, FrontRight, Center, Subwoofer, SideLeft, SideRight, RearLeft, RearRight,
+            ],
+            Self::Surround3_1_2 => &[FrontLeft, FrontRight, Center, Subwoofer],
+        };
+        let height: &[SpeakerRole] = match self {
+            Self::Surround5_1_2 | Self::Surround7_1_2 | Self::Surround3_1_2 => {
+                &[TopFrontLeft, TopFrontRight]
+            }
+            Self::Surround5_1_4 | Self::Surround7_1_4 => {
+                &[TopFrontLeft, TopFrontRight, TopRearLeft, TopRearRight]
+            }
+            _ => &[],
+        };
+
+        base.iter().chain(height).cloned().collect()
+    }
+
+    pub fn channel_count(self) -> usize {
+        self.speaker_roles().len()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfMixPresentation {
+    pub presentation_id: u32,
+    pub annotations: Vec<String>,
+    pub sub_mixes: Vec<IamfSubMix>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfSubMix {
+    pub elements: Vec<MixPresentationElement>,
+    pub output_gain_db: f32,
+    pub layouts: Vec<IamfMixLayout>,
+}
+
+/// A playback layout the sub-mix was authored for, with its measured loudness.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfMixLayout {
+    pub layout: PlaybackLayout,
+    pub loudness: IamfLoudness,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlaybackLayout {
+    /// ITU-R BS.2051 sound system, named like `mapping::layout_from_name`
+    /// where the repo has an equivalent ("5.1.4"), otherwise in ITU
+    /// notation ("4+9+0").
+    Loudspeakers(String),
+    Binaural,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfLoudness {
+    pub integrated_lufs: f32,
+    pub digital_peak_db: f32,
+    pub true_peak_db: Option<f32>,
+}
+
+impl IamfDescriptor {
+    /// Collapse the descriptors into a stream config for `IamfDecoder::configure`.
+    ///
+    /// Sample rate and frame duration come from the first codec config; the
+    /// channel count is that of the widest audio element.
+    pub fn stream_config(&self) -> Result<IamfStreamConfig, IamfError> {
+        let first = self
+            .codec_configs
+            .first()
+            .ok_or_else(|| IamfError::Parse("no codec config OBU".into()))?;
+        let sample_rate = codec_sample_rate(&first.codec);
+        let codec_for = |id: u32| {
+            self.codec_configs
+                .iter()
+                .find(|c| c.codec_config_id == id)
+                .map(|c| c.codec.clone())
+                .ok_or_else(|| IamfError::Parse(format!("unknown codec config id {id}")))
+        };
+
+        let mut channel_elements = Vec::new();
+        let mut scene_elements = Vec::new();
+        let mut channel_count = 0usize;
+        for element in &self.audio_elements {
+            let codec = codec_for(element.codec_config_id)?;
+            match &element.config {
+                AudioElementConfig::Channel { layers } => {
+                    let channel_labels = layers
+                        .last()
+                        .map(|l| l.layout.speaker_roles())
+                        .unwrap_or_default();
+                    channel_count = channel_count.max(channel_labels.len());
+                    channel_elements.push(ChannelAudioElement {
+                        element_id: element.element_id,
+                        channel_labels,
+                        codec,
+                    });
+                }
+                AudioElementConfig::Scene {
+                    ambisonics_order,
+                    output_channel_count,
+                } => {
+                    channel_count = channel_count.max(*output_channel_count as usize);
+                    scene_elements.push(SceneAudioElement {
+                        element_id: element.element_id,
+                        ambisonics_order: *ambisonics_order,
+                        codec,
+                    });
+                }
+            }
+        }
+
+        let mix_presentations = self
+            .mix_presentations
+            .iter()
+            .map(|mix| MixPresentation {
+                presentation_id: mix.presentation_id,
+                elements: mix
+                    .sub_mixes
+                    .iter()
+                    .flat_map(|sub| sub.elements.iter().cloned())
+                    .collect(),
+                loudness_lufs: mix
+                    .sub_mixes
+                    .iter()
+                    .find_map(|sub| sub.layouts.first())
+                    .map(|l| l.loudness.integrated_lufs),
+            })
+            .collect();
+
+        Ok(IamfStreamConfig {
+            sample_rate,
+            channel_count: channel_count as u16,
+            frame_duration: Duration::from_nanos(
+                first.samples_per_frame as u64 * 1_000_000_000 / sample_rate.max(1) as u64,
+            ),
+            mix_presentations,
+            channel_elements,
+            object_elements: vec![],
+            scene_elements,
+        })
+    }
+}
+
+/// Mix presentation layout chosen for a playback target, with the loudness
+/// the mix was authored at on that layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IamfLayoutSelection {
+    pub presentation_id: u32,
+    pub layout: SpeakerLayout,
+    pub loudness: IamfLoudness,
+}
+
+impl IamfLayoutSelection {
+    /// Gain (dB) that brings the selected mix to `target`.
+    pub fn normalization_gain_db(&self, target: &LoudnessTarget) -> f32 {
+        target.as_lufs() - self.loudness.integrated_lufs
+    }
+}
+
+/// Pick the first mix presentation authored for the loudspeaker layout
+/// `target` (any name accepted by `mapping::layout_from_name`).
+///
+/// Returns `None` if the layout name is unknown or no mix presentation
+/// carries a matching layout.
+pub fn select_layout(desc: &IamfDescriptor, target: &str) -> Option<IamfLayoutSelection> {
+    let layout = layout_from_name(target)?;
+    let wanted = match target {
+        "stereo" => "2.0",
+        "quad" => "4.0",
+        other => other,
+    };
+
+    desc.mix_presentations.iter().find_map(|mix| {
+        mix.sub_mixes
+            .iter()
+            .flat_map(|sub| &sub.layouts)
+            .find(|l| matches!(&l.layout, PlaybackLayout::Loudspeakers(name) if name == wanted))
+            .map(|l| IamfLayoutSelection {
+                presentation_id: mix.presentation_id,
+                layout: layout.clone(),
+                loudness: l.loudness.clone(),
+            })
+    })
+}
+
+/// Speaker layout for `target` if the stream has a mix presentation for it.
+///
+/// See [`select_layout`] for the mix presentation and its loudness.
+pub fn to_speaker_layout(desc: &IamfDescriptor, target: &str) -> Option<SpeakerLayout> {
+    select_layout(desc, target).map(|selection| selection.layout)
+}
+
+fn codec_sample_rate(codec: &CodecConfig) -> u32 {
+    match codec {
+        CodecConfig::Opus { sample_rate, .. }
+        | CodecConfig::Aac { sample_rate, .. }
+        | CodecConfig::Flac { sample_rate, .. }
+        | CodecConfig::Pcm { sample_rate, .. } => *sample_rate,
+    }
+}
+
+/// Parse the descriptor OBUs (IA sequence header, codec configs, audio
+/// elements and mix presentations) at the start of an IAMF bitstream.
+///
+/// Parsing stops at the first parameter block, temporal delimiter or audio
+/// frame OBU, so `bytes` may be a whole file or just its header. Redundant
+/// descriptor copies are skipped. Reserved OBU types yield
+/// [`IamfError::UnsupportedObu`].
+pub fn parse(bytes: &[u8]) -> Result<IamfDescriptor, IamfError> {
+    let mut reader = ObuReader::new(bytes);
+    let mut descriptor: Option<IamfDescriptor> = None;
+
+    while !reader.is_empty() {
+        let header = reader.u8()?;
+        let obu_type = header >> 3;
+        let redundant_copy = header & 0x04 != 0;
+        let trimming_status = header & 0x02 != 0;
+        let extension = header & 0x01 != 0;
+        let size = reader.leb128()? as usize;
+        let mut body = ObuReader::new(reader.bytes(size)?);
+
+        if trimming_status {
+            body.leb128()?;
+            body.leb128()?;
+        }
+        if extension {
+            let extension_size = body.leb128()? as usize;
+            body.bytes(extension_size)?;
+        }
+
+        if obu_type == OBU_SEQUENCE_HEADER {
+            if descriptor.is_none() {
+                descriptor = Some(parse_sequence_header(&mut body)?);
+            }
+            continue;
+        }
+
+        let Some(desc) = descriptor.as_mut() else {
+            return Err(IamfError::Parse(
+                "stream does not start with an IA sequence header".into(),
+            ));
+        };
+
+        match obu_type {
+            _ if redundant_copy && obu_type < OBU_PARAMETER_BLOCK => {}
+            OBU_CODEC_CONFIG => desc.codec_configs.push(parse_codec_config(&mut body)?),
+            OBU_AUDIO_ELEMENT => desc.audio_elements.push(parse_audio_element(&mut body)?),
+            OBU_MIX_PRESENTATION => desc
+                .mix_presentations
+                .push(parse_mix_presentation(&mut body)?),
+            OBU_PARAMETER_BLOCK..=OBU_AUDIO_FRAME_ID17 => break,
+            other => return Err(IamfError::UnsupportedObu(other)),
+        }
+    }
+
+    descriptor.ok_or_else(|| IamfError::Parse("no IA sequence header".into()))
+}
+
+fn parse_sequence_header(r: &mut ObuReader) -> Result<IamfDescriptor, IamfError> {
+    let ia_code = r.bytes(4)?;
+    if ia_code != b"iamf" {
+        return Err(IamfError::Parse(format!(
+            "bad IA sequence header code {ia_code:02x?}"
+        )));
+    }
+
+    Ok(IamfDescriptor {
+        primary_profile: r.u8()?,
+        additional_profile: r.u8()?,
+        codec_configs: vec![],
+        audio_elements: vec![],
+        mix_presentations: vec![],
+    })
+}
+
+fn parse_codec_config(r: &mut ObuReader) -> Result<IamfCodecConfig, IamfError> {
+    let codec_config_id = r.leb128()?;
+    let codec_id = r.bytes(4)?;
+    let samples_per_frame = r.leb128()?;
+    let audio_roll_distance = r.i16()?;
+
+    let codec = match codec_id {
+        b"Opus" => {
+            let _version = r.u8()?;
+            let channels = r.u8()? as u16;
+            // Opus always decodes at 48 kHz; input_sample_rate is informative.
+            CodecConfig::Opus {
+                sample_rate: 48000,
+                channels,
+            }
+        }
+        b"mp4a" => parse_aac_config(r)?,
+        b"fLaC" => {
+            // METADATA_BLOCK_HEADER, then the STREAMINFO fields up to the
+            // packed sample rate / channels / bits-per-sample word.
+            let block_type = r.u8()? & 0x7f;
+            if block_type != 0 {
+                return Err(IamfError::Parse(
+                    "FLAC config does not start with STREAMINFO".into(),
+                ));
+            }
+            r.bytes(3 + 10)?;
+            let packed = r.bytes(4)?;
+            let sample_rate =
+                ((packed[0] as u32) << 12) | ((packed[1] as u32) << 4) | ((packed[2] as u32) >> 4);
+            let channels = ((packed[2] >> 1) & 0x07) as u16 + 1;
+            CodecConfig::Flac {
+                sample_rate,
+                channels,
+            }
+        }
+        b"ipcm" => {
+            let _sample_format_flags = r.u8()?;
+            let bit_depth = r.u8()?;
+            let sample_rate = r.u32()?;
+            // Channel count is carried by the audio element, not the codec.
+            CodecConfig::Pcm {
+                sample_rate,
+                bit_depth,
+                channels: 0,
+            }
+        }
+        other => {
+            return Err(IamfError::Unsupported(format!(
+                "codec id {:?}",
+                String::from_utf8_lossy(other)
+            )))
+        }
+    };
+
+    Ok(IamfCodecConfig {
+        codec_config_id,
+        codec,
+        samples_per_frame,
+        audio_roll_distance,
+    })
+}
+
+/// Read `DecoderConfigDescriptor` down to the AudioSpecificConfig fields.
+fn parse_aac_config(r: &mut ObuReader) -> Result<CodecConfig, IamfError> {
+    const DECODER_CONFIG_TAG: u8 = 0x04;
+    const DECODER_SPECIFIC_INFO_TAG: u8 = 0x05;
+    const SAMPLE_RATES: [u32; 13] = [
+        96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+    ];
+
+    if r.u8()? != DECODER_CONFIG_TAG {
+        return Err(IamfError::Parse(
+            "missing AAC DecoderConfigDescriptor".into(),
+        ));
+    }
+    r.descriptor_size()?;
+    // objectTypeIndication, streamType, bufferSizeDB, maxBitrate, avgBitrate
+    r.bytes(1 + 1 + 3 + 4 + 4)?;
+    if r.u8()? != DECODER_SPECIFIC_INFO_TAG {
+        return Err(IamfError::Parse("missing AAC DecoderSpecificInfo".into()));
+    }
+    let size = r.descriptor_size()?;
+    let mut bits = BitReader::new(r.bytes(size)?);
+
+    let _audio_object_type = bits.read(5)?;
+    let sample_rate = match bits.read(4)? {
+        0x0f => bits.read(24)?,
+        index => *SAMPLE_RATES.get(index as usize).ok_or_else(|| {
+            IamfError::Parse(format!("reserved AAC sampling frequency index {index}"))
+        })?,
+    };
+    let channels = bits.read(4)? as u16;
+
+    Ok(CodecConfig::Aac {
+        sample_rate,
+        channels,
+    })
+}
+
+fn parse_audio_element(r: &mut ObuReader) -> Result<IamfAudioElement, IamfError> {
+    let element_id = r.leb128()?;
+    let type_code = r.u8()? >> 5;
+    let codec_config_id = r.leb128()?;
+    let num_substreams = r.leb128()?;
+    let substream_ids = (0..num_substreams)
+        .map(|_| r.leb128())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let num_parameters = r.leb128()?;
+    for _ in 0..num_parameters {
+        match r.leb128()? {
+            PARAM_DEFINITION_DEMIXING => {
+                skip_param_definition(r)?;
+                // default dmixp_mode and default_w
+                r.bytes(2)?;
+            }
+            PARAM_DEFINITION_RECON_GAIN => skip_param_definition(r)?,
+            _ => {
+                let size = r.leb128()? as usize;
+                r.bytes(size)?;
+            }
+        }
+    }
+
+    let (element_type, config) = match type_code {
+        0 => {
+            let num_layers = r.u8()? >> 5;
+            let mut layers = Vec::with_capacity(num_layers as usize);
+            for _ in 0..num_layers {
+                let flags = r.u8()?;
+                let layout = LoudspeakerLayout::from_code(flags >> 4)?;
+                let output_gain_present = flags & 0x08 != 0;
+                let substream_count = r.u8()?;
+                let coupled_substream_count = r.u8()?;
+                let output_gain_db = if output_gain_present {
+                    let _output_gain_flags = r.u8()?;
+                    Some(q7_8(r.i16()?))
+                } else {
+                    None
+                };
+                layers.push(ChannelLayer {
+                    layout,
+                    substream_count,
+                    coupled_substream_count,
+                    output_gain_db,
+                });
+            }
+            (
+                AudioElementType::ChannelBased,
+                AudioElementConfig::Channel { layers },
+            )
+        }
+        1 => {
+            let _ambisonics_mode = r.leb128()?;
+            let output_channel_count = r.u8()?;
+            let ambisonics_order = ((output_channel_count as f32).sqrt() as u8).saturating_sub(1);
+            (
+                AudioElementType::SceneBased,
+                AudioElementConfig::Scene {
+                    ambisonics_order,
+                    output_channel_count,
+                },
+            )
+        }
+        other => {
+            return Err(IamfError::Unsupported(format!(
+                "audio element type {other}"
+            )))
+        }
+    };
+
+    Ok(IamfAudioElement {
+        element_id,
+        element_type,
+        codec_config_id,
+        substream_ids,
+        config,
+    })
+}
+
+fn parse_mix_presentation(r: &mut ObuReader) -> Result<IamfMixPresentation, IamfError> {
+    let presentation_id = r.leb128()?;
+    let count_label = r.leb128()?;
+    for _ in 0..count_label {
+        r.string()?; // annotations_language
+    }
+    let annotations = (0..count_label)
+        .map(|_| r.string())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let num_sub_mixes = r.leb128()?;
+    let mut sub_mixes = Vec::new();
+    for _ in 0..num_sub_mixes {
+        let num_elements = r.leb128()?;
+        let mut elements = Vec::new();
+        for _ in 0..num_elements {
+            let element_id = r.leb128()?;
+            for _ in 0..count_label {
+                r.string()?;
+            }
+            let rendering_config = match r.u8()? >> 6 {
+                1 => RenderingConfig::Binaural,
+                _ => RenderingConfig::Stereo,
+            };
+            let extension_size = r.leb128()? as usize;
+            r.bytes(extension_size)?;
+            skip_param_definition(r)?;
+            let gain_db = q7_8(r.i16()?);
+            elements.push(MixPresentationElement {
+                element_id,
+                gain_db,
+                rendering_config,
+            });
+        }
+
+        skip_param_definition(r)?;
+        let output_gain_db = q7_8(r.i16()?);
+
+        let num_layouts = r.leb128()?;
+        let mut layouts = Vec::new();
+        for _ in 0..num_layouts {
+            let layout = parse_playback_layout(r.u8()?)?;
+            let loudness = parse_loudness(r)?;
+            layouts.push(IamfMixLayout { layout, loudness });
+        }
+
+        sub_mixes.push(IamfSubMix {
+            elements,
+            output_gain_db,
+            layouts,
+        });
+    }
+
+    Ok(IamfMixPresentation {
+        presentation_id,
+        annotations,
+        sub_mixes,
+    })
+}
+
+fn parse_playback_layout(byte: u8) -> Result<PlaybackLayout, IamfError> {
+    match byte >> 6 {
+        2 => {
+            let name = match (byte >> 2) & 0x0f {
+                0 => "2.0",
+                1 => "5.1",
+                2 => "5.1.2",
+                3 => "5.1.4",
+                4 => "4+5+1",
+                5 => "3+7+0",
+                6 => "4+9+0",
+                7 => "9+10+3",
+                8 => "7.1",
+                9 => "7.1.4",
+                10 => "7.1.2",
+                11 => "3.1.2",
+                12 => "1.0",
+                other => return Err(IamfError::Unsupported(format!("sound system {other}"))),
+            };
+            Ok(PlaybackLayout::Loudspeakers(name.to_string()))
+        }
+        3 => Ok(PlaybackLayout::Binaural),
+        other => Err(IamfError::Unsupported(format!("layout type {other}"))),
+    }
+}
+
+fn parse_loudness(r: &mut ObuReader) -> Result<IamfLoudness, IamfError> {
+    let info_type = r.u8()?;
+    let integrated_lufs = q7_8(r.i16()?);
+    let digital_peak_db = q7_8(r.i16()?);
+    let true_peak_db = if info_type & 0x01 != 0 {
+        Some(q7_8(r.i16()?))
+    } else {
+        None
+    };
+    if info_type & 0x02 != 0 {
+        let num_anchored = r.u8()? as usize;
+        r.bytes(num_anchored * 3)?;
+    }
+    if info_type & 0xfc != 0 {
+        let size = r.leb128()? as usize;
+        r.bytes(size)?;
+    }
+
+    Ok(IamfLoudness {
+        integrated_lufs,
+        digital_peak_db,
+        true_peak_db,
+    })
+}
+
+fn skip_param_definition(r: &mut ObuReader) -> Result<(), IamfError> {
+    let _parameter_id = r.leb128()?;
+    let _parameter_rate = r.leb128()?;
+    let mode = r.u8()? >> 7;
+    if mode == 0 {
+        let _duration = r.leb128()?;
+        let constant_subblock_duration = r.leb128()?;
+        if constant_subblock_duration == 0 {
+            let num_subblocks = r.leb128()?;
+            for _ in 0..num_subblocks {
+                r.leb128()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Q7.8 fixed point to float (dB or LUFS).
+fn q7_8(value: i16) -> f32 {
+    value as f32 / 256.0
+}
+
+/// Big-endian byte cursor over an OBU payload.
+struct ObuReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ObuReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], IamfError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| IamfError::Parse("truncated OBU".into()))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, IamfError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16, IamfError> {
+        let b = self.bytes(2)?;
+        Ok(i16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, IamfError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn leb128(&mut self) -> Result<u32, IamfError> {
+        let mut value: u64 = 0;
+        for i in 0..8 {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << (i * 7);
+            if byte & 0x80 == 0 {
+                return u32::try_from(value)
+                    .map_err(|_| IamfError::Parse("leb128 value exceeds 32 bits".into()));
+            }
+        }
+        Err(IamfError::Parse("leb128 longer than 8 bytes".into()))
+    }
+
+    /// NUL-terminated UTF-8 string.
+    fn string(&mut self) -> Result<String, IamfError> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| IamfError::Parse("unterminated string".into()))?;
+        let s = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+        Ok(s)
+    }
+
+    /// MPEG-4 expandable descriptor size.
+    fn descriptor_size(&mut self) -> Result<usize, IamfError> {
+        let mut size = 0usize;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            size = (size << 7) | (byte & 0x7f) as usize;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        Ok(size)
+    }
+}
+
+/// MSB-first bit cursor for the AudioSpecificConfig.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, bit: 0 }
+    }
+
+    fn read(&mut self, count: usize) -> Result<u32, IamfError> {
+        let mut value = 0u32;
+        for _ in 0..count {
+            let byte = self
+                .data
+                .get(self.bit / 8)
+                .ok_or_else(|| IamfError::Parse("truncated AudioSpecificConfig".into()))?;
+            value = (value << 1) | ((byte >> (7 - self.bit % 8)) & 1) as u32;
+            self.bit += 1;
+        }
+        Ok(value)
+    }
+}
@@ -12,9 +12,16 @@
 //! - `InputSource`: Enum representing the three input source types
 //! - `CaptureStream`: Trait for implementing capture backends (ALSA, PulseAudio)
 //! - `InputManager`: Main interface for device enumeration and stream setup
+//! - `AudioFileReader`: WAV file source for file playback, with seeking
+//! - `load_wav`: decode a whole WAV file into one `AudioBlock`
 
+use crate::AudioBlock;
 use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 
 /// Input device errors
@@ -270,6 +277,262 @@ impl Default for InputManager {
     }
 }
 
+// ===== File Playback Source =====
+
+/// Most channels `load_wav` accepts (7.1)
+pub const MAX_WAV_CHANNELS: usize = 8;
+
+/// Decode a whole 16/24/32-bit PCM or 32-bit float WAV file
+///
+/// The block has one channel per channel in the file, up to
+/// `MAX_WAV_CHANNELS`, at the file's sample rate. Use `AudioFileReader` to
+/// stream long files instead.
+pub fn load_wav<P: AsRef<Path>>(path: P) -> Result<AudioBlock, InputError> {
+    let mut reader = AudioFileReader::open(path)?;
+    if reader.channels() > MAX_WAV_CHANNELS {
+        return Err(InputError::InvalidFormat(format!(
+            "{} channels; at most {} are supported",
+            reader.channels(),
+            MAX_WAV_CHANNELS
+        )));
+    }
+    let frames = reader.total_frames() as usize;
+    Ok(reader
+        .read_block(frames)?
+        .unwrap_or_else(|| AudioBlock::silence(reader.channels(), 0, reader.sample_rate())))
+}
+
+/// Name of a compressed WAV format tag, for error messages
+fn wav_codec_name(format_tag: u16) -> &'static str {
+    match format_tag {
+        0x0002 => "MS ADPCM",
+        0x0006 => "A-law",
+        0x0007 => "mu-law",
+        0x0011 => "IMA ADPCM",
+        0x0055 => "MP3",
+        0x00FF | 0x1610 => "AAC",
+        _ => "unknown codec",
+    }
+}
+
+/// Sample encoding of a WAV `data` chunk
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum WavSampleFormat {
+    Int,
+    Float,
+}
+
+/// Streaming reader for PCM/float WAV files, producing `AudioBlock`s
+///
+/// WAV data is uncompressed, so seeking is frame-exact: the reader jumps
+/// directly to the requested frame and continues decoding from there.
+#[derive(Debug)]
+pub struct AudioFileReader {
+    reader: BufReader<File>,
+    sample_format: WavSampleFormat,
+    bits_per_sample: u16,
+    channels: usize,
+    sample_rate: u32,
+    data_offset: u64,
+    total_frames: u64,
+    position_frames: u64,
+}
+
+impl AudioFileReader {
+    /// Open a WAV file and position the reader at the first frame
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, InputError> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut riff = [0u8; 12];
+        reader.read_exact(&mut riff)?;
+        if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+            return Err(InputError::InvalidFormat(
+                "not a RIFF/WAVE file".to_string(),
+            ));
+        }
+
+        let mut format: Option<(WavSampleFormat, u16, usize, u32)> = None;
+
+        // Walk chunks until the data chunk; fmt must precede it
+        loop {
+            let mut header = [0u8; 8];
+            reader.read_exact(&mut header)?;
+            let chunk_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+            match &header[0..4] {
+                b"fmt " => {
+                    let mut fmt = vec![0u8; chunk_size as usize];
+                    reader.read_exact(&mut fmt)?;
+                    if fmt.len() < 16 {
+                        return Err(InputError::InvalidFormat("fmt chunk too short".to_string()));
+                    }
+                    let mut format_tag = u16::from_le_bytes([fmt[0], fmt[1]]);
+                    let channels = u16::from_le_bytes([fmt[2], fmt[3]]) as usize;
+                    let sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+                    let bits_per_sample = u16::from_le_bytes([fmt[14], fmt[15]]);
+
+                    // WAVE_FORMAT_EXTENSIBLE carries the real format in the sub-format GUID
+                    if format_tag == 0xFFFE && fmt.len() >= 26 {
+                        format_tag = u16::from_le_bytes([fmt[24], fmt[25]]);
+                    }
+
+                    let sample_format = match (format_tag, bits_per_sample) {
+                        (1, 16) | (1, 24) | (1, 32) => WavSampleFormat::Int,
+                        (3, 32) => WavSampleFormat::Float,
+                        (1, _) | (3, _) => {
+                            return Err(InputError::InvalidFormat(format!(
+                                "unsupported WAV encoding: format tag {}, {} bits",
+                                format_tag, bits_per_sample
+                            )))
+                        }
+                        _ => {
+                            return Err(InputError::InvalidFormat(format!(
+                                "unsupported WAV compression {} (format tag {:#06x}); only \
+                                 16/24/32-bit PCM and 32-bit float are supported",
+                                wav_codec_name(format_tag),
+                                format_tag
+                            )))
+                        }
+                    };
+
+                    if channels == 0 || sample_rate == 0 {
+                        return Err(InputError::InvalidFormat(
+                            "WAV declares zero channels or sample rate".to_string(),
+                        ));
+                    }
+
+                    format = Some((sample_format, bits_per_sample, channels, sample_rate));
+                    if chunk_size % 2 == 1 {
+                        reader.seek(SeekFrom::Current(1))?;
+                    }
+                }
+                b"data" => {
+                    let (sample_format, bits_per_sample, channels, sample_rate) = format
+                        .ok_or_else(|| {
+                            InputError::InvalidFormat("data chunk before fmt chunk".to_string())
+                        })?;
+                    let data_offset = reader.stream_position()?;
+                    let frame_bytes = channels as u64 * (bits_per_sample / 8) as u64;
+
+                    return Ok(Self {
+                        reader,
+                        sample_format,
+                        bits_per_sample,
+                        channels,
+                        sample_rate,
+                        data_offset,
+                        total_frames: chunk_size as u64 / frame_bytes,
+                        position_frames: 0,
+                    });
+                }
+                _ => {
+                    // Chunks are word-aligned
+                    let skip = chunk_size as i64 + (chunk_size % 2) as i64;
+                    reader.seek(SeekFrom::Current(skip))?;
+                }
+            }
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Total number of frames in the file
+    pub fn total_frames(&self) -> u64 {
+        self.total_frames
+    }
+
+    /// Current read position in frames
+    pub fn position_frames(&self) -> u64 {
+        self.position_frames
+    }
+
+    /// Total duration of the file
+    pub fn duration(&self) -> Duration {
+        self.frames_to_duration(self.total_frames)
+    }
+
+    /// Current read position as a timestamp
+    pub fn position(&self) -> Duration {
+        self.frames_to_duration(self.position_frames)
+    }
+
+    /// Reposition the reader to the frame nearest `position`
+    ///
+    /// Positions past the end clamp to the end of the file. Returns the
+    /// position actually landed on, which may differ from the request by
+    /// up to half a frame.
+    pub fn seek(&mut self, position: Duration) -> Result<Duration, InputError> {
+        let target = (position.as_secs_f64() * self.sample_rate as f64).round() as u64;
+        let frame = target.min(self.total_frames);
+
+        self.reader.seek(SeekFrom::Start(
+            self.data_offset + frame * self.frame_bytes(),
+        ))?;
+        self.position_frames = frame;
+
+        Ok(self.position())
+    }
+
+    /// Read up to `frames` frames from the current position
+    ///
+    /// Returns `None` once the end of the file has been reached.
+    pub fn read_block(&mut self, frames: usize) -> Result<Option<AudioBlock>, InputError> {
+        let remaining = self.total_frames - self.position_frames;
+        let frames = (frames as u64).min(remaining) as usize;
+        if frames == 0 {
+            return Ok(None);
+        }
+
+        let bytes_per_sample = (self.bits_per_sample / 8) as usize;
+        let mut raw = vec![0u8; frames * self.frame_bytes() as usize];
+        self.reader.read_exact(&mut raw)?;
+
+        let samples: Vec<f32> = raw
+            .chunks_exact(bytes_per_sample)
+            .map(|sample| self.decode_sample(sample))
+            .collect();
+
+        self.position_frames += frames as u64;
+        Ok(Some(AudioBlock::from_interleaved(
+            &samples,
+            self.channels,
+            self.sample_rate,
+        )))
+    }
+
+    fn decode_sample(&self, bytes: &[u8]) -> f32 {
+        match (self.sample_format, self.bits_per_sample) {
+            (WavSampleFormat::Float, _) => {
+                f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            }
+            (WavSampleFormat::Int, 16) => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0,
+            (WavSampleFormat::Int, 24) => {
+                // Sign-extend by placing the 24-bit value in the top of an i32
+                let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                value as f32 / 8_388_608.0
+            }
+            _ => {
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+                    / 2_147_483_648.0
+            }
+        }
+    }
+
+    fn frame_bytes(&self) -> u64 {
+        self.channels as u64 * (self.bits_per_sample / 8) as u64
+    }
+
+    fn frames_to_duration(&self, frames: u64) -> Duration {
+        Duration::from_secs_f64(frames as f64 / self.sample_rate as f64)
+    }
+}
+
 // ===== cpal Backend for Real Device Enumeration =====
 #[cfg(feature = "audio-backends")]
 fn cpal_enumerate_input_devices() -> Result<Vec<InputDevice>, InputError> {
@@ -515,6 +778,7 @@ impl CaptureStream for CpalCaptureStream {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::output::{write_wav, BitDepth};
 
     #[test]
     fn test_input_device_creation() {
@@ -608,4 +872,200 @@ mod tests {
         manager.clear_source();
         assert!(manager.active_source().is_none());
     }
+
+    fn temp_wav_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{}-{}.wav", name, std::process::id()))
+    }
+
+    /// Write a 16-bit stereo WAV whose left channel is a ramp (sample `n` == `n`)
+    /// and whose right channel is the negated ramp.
+    fn write_ramp_wav(name: &str, sample_rate: u32, frames: u32) -> std::path::PathBuf {
+        let ramp: Vec<f32> = (0..frames).map(|n| n as f32 / 32768.0).collect();
+        let block = AudioBlock {
+            sample_rate,
+            channels: vec![ramp.clone(), ramp.iter().map(|x| -x).collect()],
+        };
+        let path = temp_wav_path(name);
+        write_wav(&path, &block, BitDepth::Int16).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_audio_file_reader_metadata() {
+        let path = write_ramp_wav("audio-ninja-reader-meta", 8000, 16000);
+        let reader = AudioFileReader::open(&path).unwrap();
+
+        assert_eq!(reader.sample_rate(), 8000);
+        assert_eq!(reader.channels(), 2);
+        assert_eq!(reader.total_frames(), 16000);
+        assert_eq!(reader.duration(), Duration::from_secs(2));
+        assert_eq!(reader.position(), Duration::ZERO);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_audio_file_reader_seek_to_middle() {
+        let path = write_ramp_wav("audio-ninja-reader-seek", 8000, 16000);
+        let mut reader = AudioFileReader::open(&path).unwrap();
+
+        // Consume a block so the seek has to move backwards as well
+        reader.read_block(256).unwrap().unwrap();
+
+        let landed = reader.seek(Duration::from_secs(1)).unwrap();
+        assert_eq!(landed, Duration::from_secs(1));
+        assert_eq!(reader.position_frames(), 8000);
+
+        let block = reader.read_block(64).unwrap().unwrap();
+        assert_eq!(block.frame_len(), 64);
+        assert_eq!(block.channels[0][0], 8000.0 / 32768.0);
+        assert_eq!(block.channels[1][0], -8000.0 / 32768.0);
+        assert_eq!(block.channels[0][63], 8063.0 / 32768.0);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_audio_file_reader_seek_rounds_and_clamps() {
+        let path = write_ramp_wav("audio-ninja-reader-clamp", 8000, 800);
+        let mut reader = AudioFileReader::open(&path).unwrap();
+
+        // 0.00006s * 8000 = 0.48 frames rounds to frame 0
+        let landed = reader.seek(Duration::from_micros(60)).unwrap();
+        assert_eq!(landed, Duration::ZERO);
+
+        let landed = reader.seek(Duration::from_secs(5)).unwrap();
+        assert_eq!(landed, reader.duration());
+        assert!(reader.read_block(64).unwrap().is_none());
+
+        std::fs::remove_file(path).ok();
+    }
+
+    /// Write a WAV with the given fmt fields and raw interleaved sample
+    /// bytes, for encodings `write_wav` doesn't produce
+    fn write_raw_wav(
+        name: &str,
+        format_tag: u16,
+        bits: u16,
+        channels: u16,
+        sample_rate: u32,
+        data: &[u8],
+    ) -> std::path::PathBuf {
+        use std::io::Write;
+
+        let path = temp_wav_path(name);
+        let block_align = channels * bits / 8;
+        let mut file = File::create(&path).unwrap();
+        file.write_all(b"RIFF").unwrap();
+        file.write_all(&(36 + data.len() as u32).to_le_bytes())
+            .unwrap();
+        file.write_all(b"WAVEfmt ").unwrap();
+        file.write_all(&16u32.to_le_bytes()).unwrap();
+        file.write_all(&format_tag.to_le_bytes()).unwrap();
+        file.write_all(&channels.to_le_bytes()).unwrap();
+        file.write_all(&sample_rate.to_le_bytes()).unwrap();
+        file.write_all(&(sample_rate * block_align as u32).to_le_bytes())
+            .unwrap();
+        file.write_all(&block_align.to_le_bytes()).unwrap();
+        file.write_all(&bits.to_le_bytes()).unwrap();
+        file.write_all(b"data").unwrap();
+        file.write_all(&(data.len() as u32).to_le_bytes()).unwrap();
+        file.write_all(data).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_wav_24_bit_5_1() {
+        // Frame n carries n * 1000 + ch - 3 LSBs on channel ch
+        let lsb = 1.0 / 8_388_608.0;
+        let block = AudioBlock {
+            sample_rate: 44100,
+            channels: (0..6)
+                .map(|ch| (0..10).map(|n| (n * 1000 + ch - 3) as f32 * lsb).collect())
+                .collect(),
+        };
+        let path = temp_wav_path("audio-ninja-load-24");
+        write_wav(&path, &block, BitDepth::Int24).unwrap();
+
+        let loaded = load_wav(&path).unwrap();
+        assert_eq!(loaded.sample_rate, 44100);
+        assert_eq!(loaded.channels.len(), 6);
+        assert_eq!(loaded.frame_len(), 10);
+        assert_eq!(loaded.channels[0][0], -3.0 * lsb);
+        assert_eq!(loaded.channels[5][9], 9002.0 * lsb);
+        assert_eq!(loaded, block);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_load_wav_float_and_32_bit_7_1() {
+        let samples: Vec<f32> = (0..8 * 4).map(|i| i as f32 / 64.0 - 0.25).collect();
+        let path = temp_wav_path("audio-ninja-load-float");
+        write_wav(
+            &path,
+            &AudioBlock::from_interleaved(&samples, 8, 48000),
+            BitDepth::Float32,
+        )
+        .unwrap();
+
+        let block = load_wav(&path).unwrap();
+        assert_eq!(block.channels.len(), 8);
+        assert_eq!(block.to_interleaved(), samples);
+        std::fs::remove_file(path).ok();
+
+        let int: Vec<u8> = samples
+            .iter()
+            .flat_map(|s| ((s * 2_147_483_648.0) as i32).to_le_bytes())
+            .collect();
+        let path = write_raw_wav("audio-ninja-load-int32", 1, 32, 8, 48000, &int);
+
+        let block = load_wav(&path).unwrap();
+        assert_eq!(block.to_interleaved(), samples);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_load_wav_rejects_more_than_eight_channels() {
+        let path = temp_wav_path("audio-ninja-load-9ch");
+        write_wav(&path, &AudioBlock::silence(9, 16, 48000), BitDepth::Int16).unwrap();
+
+        match load_wav(&path) {
+            Err(InputError::InvalidFormat(message)) => {
+                assert!(message.contains("9 channels"), "{}", message);
+            }
+            other => panic!("expected InvalidFormat, got {:?}", other),
+        }
+        // The streaming reader still opens it
+        assert_eq!(AudioFileReader::open(&path).unwrap().channels(), 9);
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_load_wav_rejects_compressed_data() {
+        let path = write_raw_wav("audio-ninja-load-adpcm", 2, 4, 2, 44100, &[0; 64]);
+
+        match load_wav(&path) {
+            Err(InputError::InvalidFormat(message)) => {
+                assert!(message.contains("MS ADPCM"), "{}", message);
+            }
+            other => panic!("expected InvalidFormat, got {:?}", other),
+        }
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_audio_file_reader_rejects_non_wav() {
+        let path = temp_wav_path("audio-ninja-junk");
+        std::fs::write(&path, b"JUNKDATAINVALIDFORMAT").unwrap();
+
+        assert!(matches!(
+            AudioFileReader::open(&path),
+            Err(InputError::InvalidFormat(_))
+        ));
+
+        std::fs::remove_file(path).ok();
+    }
 }
@@ -1,6 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::transport::RtpPacket;
+use crate::fec::LossConcealer;
+use crate::transport::{rtp_to_audio_block, RtpPacket};
+use crate::AudioBlock;
 use std::collections::BTreeMap;
 use std::time::Duration;
 
@@ -9,6 +11,8 @@ pub struct JitterBufferConfig {
     pub target_delay: Duration,
     pub max_delay: Duration,
     pub max_packets: usize,
+    /// RTP timestamp rate used to express interarrival jitter
+    pub clock_rate: u32,
 }
 
 impl Default for JitterBufferConfig {
@@ -17,6 +21,7 @@ impl Default for JitterBufferConfig {
             target_delay: Duration::from_millis(50),
             max_delay: Duration::from_millis(200),
             max_packets: 100,
+            clock_rate: 48000,
         }
     }
 }
@@ -35,6 +40,11 @@ pub struct JitterBuffer {
     packets_received: u64,
     packets_dropped: u64,
     packets_late: u64,
+    packets_concealed: u64,
+    /// Interarrival jitter estimate in timestamp units (RFC 3550 A.8)
+    jitter: f64,
+    /// Relative transit time of the previous packet, in timestamp units
+    last_transit: Option<u32>,
 }
 
 impl JitterBuffer {
@@ -46,11 +56,15 @@ impl JitterBuffer {
             packets_received: 0,
             packets_dropped: 0,
             packets_late: 0,
+            packets_concealed: 0,
+            jitter: 0.0,
+            last_transit: None,
         }
     }
 
     pub fn push(&mut self, packet: RtpPacket) -> Result<(), JitterBufferError> {
         self.packets_received += 1;
+        self.update_jitter(&packet);
 
         let seq = packet.header.sequence.0;
 
@@ -87,6 +101,43 @@ impl JitterBuffer {
         Ok(packet)
     }
 
+    /// Pop and decode the next block, or have `concealer` synthesize one when
+    /// the buffer has run dry or the packet does not decode
+    ///
+    /// Decoded blocks are fed back to the concealer so it always extrapolates
+    /// from the latest real audio.
+    pub fn pop_or_conceal(
+        &mut self,
+        concealer: &mut LossConcealer,
+        sample_rate: u32,
+        channels: usize,
+        frames: usize,
+    ) -> AudioBlock {
+        if let Some(block) = self.pop().ok().and_then(|p| rtp_to_audio_block(&p).ok()) {
+            concealer.update(block.clone());
+            return block;
+        }
+
+        self.packets_concealed += 1;
+        concealer.conceal(sample_rate, channels, frames)
+    }
+
+    fn update_jitter(&mut self, packet: &RtpPacket) {
+        // Both clocks wrap, so work modulo 2^32 like the RFC's reference code
+        let arrival = packet.clock.to_duration().as_secs_f64() * self.config.clock_rate as f64;
+        let transit = (arrival as u64 as u32).wrapping_sub(packet.header.timestamp.0);
+        if let Some(last) = self.last_transit {
+            let d = (transit.wrapping_sub(last) as i32).unsigned_abs() as f64;
+            self.jitter += (d - self.jitter) / 16.0;
+        }
+        self.last_transit = Some(transit);
+    }
+
+    /// Smoothed interarrival jitter in RTP timestamp units
+    pub fn interarrival_jitter(&self) -> u32 {
+        self.jitter.round() as u32
+    }
+
     pub fn ready(&self) -> bool {
         if self.buffer.is_empty() {
             return false;
@@ -111,12 +162,14 @@ impl JitterBuffer {
             received: self.packets_received,
             dropped: self.packets_dropped,
             late: self.packets_late,
+            concealed: self.packets_concealed,
         }
     }
 
     pub fn reset(&mut self) {
         self.buffer.clear();
         self.last_popped = None;
+        self.last_transit = None;
     }
 }
 
@@ -132,4 +185,6 @@ pub struct JitterBufferStats {
     pub received: u64,
     pub dropped: u64,
     pub late: u64,
+    /// Blocks synthesized by `pop_or_conceal` instead of decoded
+    pub concealed: u64,
 }
@@ -3,6 +3,7 @@
 pub mod ble;
 pub mod calibration;
 pub mod control;
+pub mod crypto;
 pub mod dsp;
 pub mod dspconfig;
 pub mod fec;
@@ -19,22 +20,33 @@ pub mod network;
 pub mod output;
 pub mod pipeline;
 pub mod render;
+pub mod rtcp;
 pub mod sync;
 pub mod transport;
 pub mod vbap;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
+/// Role of a speaker within a layout.
+///
+/// Also accepts the BLE role names (`LFE`, `SurroundLeft`, `BackLeft`,
+/// `TopBackLeft`, ...) when deserializing; see `ble::SpeakerRole`.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum SpeakerRole {
     FrontLeft,
     FrontRight,
     Center,
+    #[serde(alias = "LFE")]
     Subwoofer,
+    #[serde(alias = "SurroundLeft")]
     SideLeft,
+    #[serde(alias = "SurroundRight")]
     SideRight,
+    #[serde(alias = "BackLeft")]
     RearLeft,
+    #[serde(alias = "BackRight")]
     RearRight,
     FrontHeightLeft,
     FrontHeightRight,
@@ -42,7 +54,9 @@ pub enum SpeakerRole {
     RearHeightRight,
     TopFrontLeft,
     TopFrontRight,
+    #[serde(alias = "TopBackLeft")]
     TopRearLeft,
+    #[serde(alias = "TopBackRight")]
     TopRearRight,
     Custom(String),
 }
@@ -84,6 +98,9 @@ mod duration_serde {
     }
 }
 
+/// Speed of sound in air at 20 °C, in metres per second
+pub const SPEED_OF_SOUND: f32 = 343.0;
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SpeakerLayout {
     pub name: String,
@@ -115,12 +132,54 @@ impl SpeakerLayout {
         crate::mapping::layout_from_name("9.1.6").expect("9.1.6 layout")
     }
 
+    /// Time for sound from each speaker to reach `listener`, keyed by speaker id.
+    ///
+    /// Positions are in metres and `speed_of_sound` in m/s (see
+    /// `SPEED_OF_SOUND`). Feed the result to
+    /// `LatencyCompensator::set_propagation_delays` so nearer speakers are
+    /// held back until sound from the farthest one arrives.
+    pub fn propagation_delays(
+        &self,
+        listener: Position3,
+        speed_of_sound: f32,
+    ) -> HashMap<String, Duration> {
+        self.speakers
+            .iter()
+            .map(|speaker| {
+                let p = speaker.position;
+                let distance = ((p.x - listener.x).powi(2)
+                    + (p.y - listener.y).powi(2)
+                    + (p.z - listener.z).powi(2))
+                .sqrt();
+                let delay = Duration::try_from_secs_f32(distance / speed_of_sound)
+                    .unwrap_or(Duration::ZERO);
+                (speaker.id.clone(), delay)
+            })
+            .collect()
+    }
+
     /// Create layout from a preset name string.
     pub fn from_preset(name: &str) -> Option<Self> {
         crate::mapping::layout_from_name(name)
     }
 }
 
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum AudioBlockError {
+    #[error("Sample rate mismatch: {expected} Hz vs {actual} Hz")]
+    SampleRateMismatch { expected: u32, actual: u32 },
+    #[error("Channel count mismatch: {expected} vs {actual}")]
+    ChannelMismatch { expected: usize, actual: usize },
+    #[error("Frame count mismatch: {expected} vs {actual}")]
+    FrameMismatch { expected: usize, actual: usize },
+    #[error("Channel {channel} has {actual} frames, expected {expected}")]
+    RaggedChannels {
+        channel: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AudioBlock {
     pub sample_rate: u32,
@@ -138,4 +197,111 @@ impl AudioBlock {
     pub fn frame_len(&self) -> usize {
         self.channels.first().map(|c| c.len()).unwrap_or(0)
     }
+
+    /// True when every channel has the same number of frames
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    /// Check that every channel has the same number of frames
+    pub fn validate(&self) -> Result<(), AudioBlockError> {
+        let expected = self.frame_len();
+        match self
+            .channels
+            .iter()
+            .position(|channel| channel.len() != expected)
+        {
+            Some(channel) => Err(AudioBlockError::RaggedChannels {
+                channel,
+                expected,
+                actual: self.channels[channel].len(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Convert to interleaved frames (`[c0f0, c1f0, c0f1, ...]`).
+    /// Ragged channels are padded with silence to the longest channel.
+    pub fn to_interleaved(&self) -> Vec<f32> {
+        let frames = self.channels.iter().map(|c| c.len()).max().unwrap_or(0);
+        let mut data = Vec::with_capacity(frames * self.channels.len());
+
+        for frame in 0..frames {
+            for channel in &self.channels {
+                data.push(channel.get(frame).copied().unwrap_or(0.0));
+            }
+        }
+
+        data
+    }
+
+    /// Build from interleaved frames. A trailing partial frame is padded with silence.
+    pub fn from_interleaved(data: &[f32], channels: usize, sample_rate: u32) -> Self {
+        if channels == 0 {
+            return Self::silence(0, 0, sample_rate);
+        }
+
+        let frames = (data.len() + channels - 1) / channels;
+        let mut block = Self::silence(channels, frames, sample_rate);
+        for (i, &sample) in data.iter().enumerate() {
+            block.channels[i % channels][i / channels] = sample;
+        }
+
+        block
+    }
+
+    /// Scale every sample by a gain in dB
+    pub fn apply_gain(&mut self, db: f32) {
+        let gain = 10.0_f32.powf(db / 20.0);
+        for channel in &mut self.channels {
+            for sample in channel.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+
+    /// Add `other` sample-by-sample; both blocks must have the same shape and rate
+    pub fn mix(&mut self, other: &AudioBlock) -> Result<(), AudioBlockError> {
+        self.check_compatible(other)?;
+        for (dst, src) in self.channels.iter_mut().zip(&other.channels) {
+            if dst.len() != src.len() {
+                return Err(AudioBlockError::FrameMismatch {
+                    expected: dst.len(),
+                    actual: src.len(),
+                });
+            }
+        }
+
+        for (dst, src) in self.channels.iter_mut().zip(&other.channels) {
+            for (d, s) in dst.iter_mut().zip(src) {
+                *d += s;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the frames of `other` to the end of this block
+    pub fn append(&mut self, other: &AudioBlock) -> Result<(), AudioBlockError> {
+        self.check_compatible(other)?;
+        for (dst, src) in self.channels.iter_mut().zip(&other.channels) {
+            dst.extend_from_slice(src);
+        }
+        Ok(())
+    }
+
+    fn check_compatible(&self, other: &AudioBlock) -> Result<(), AudioBlockError> {
+        if self.sample_rate != other.sample_rate {
+            return Err(AudioBlockError::SampleRateMismatch {
+                expected: self.sample_rate,
+                actual: other.sample_rate,
+            });
+        }
+        if self.channels.len() != other.channels.len() {
+            return Err(AudioBlockError::ChannelMismatch {
+                expected: self.channels.len(),
+                actual: other.channels.len(),
+            });
+        }
+        Ok(())
+    }
 }
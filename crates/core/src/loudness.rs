@@ -5,7 +5,52 @@
 //! This module implements ITU-R BS.1770 loudness measurement and normalization,
 //! along with headroom management and Dynamic Range Control (DRC).
 
-use crate::AudioBlock;
+use crate::dsp::{BiquadCascade, BiquadCoefficients, BiquadFilter};
+use crate::{AudioBlock, SpeakerRole};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Oversampling factor used for true-peak detection (ITU-R BS.1770-4)
+const TRUE_PEAK_OVERSAMPLE: usize = 4;
+/// Taps per polyphase branch of the true-peak interpolator
+const TRUE_PEAK_TAPS: usize = 12;
+
+/// Default span of block history kept for loudness range
+pub const DEFAULT_LRA_WINDOW: Duration = Duration::from_secs(60);
+
+/// Length of a gating block for integrated loudness (ITU-R BS.1770-4)
+const GATING_BLOCK: Duration = Duration::from_millis(400);
+/// Hops per gating block: consecutive blocks overlap by 75%
+const GATING_STEPS: usize = 4;
+/// Gating blocks quieter than this never count towards integrated loudness
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Blocks more than this far below the absolute-gated loudness are dropped
+const RELATIVE_GATE_LU: f64 = 10.0;
+
+/// Channel roles assumed when a block's layout is unknown: the ITU-R
+/// BS.775 / SMPTE order L, R, C, LFE, Ls, Rs, Lrs, Rrs
+const ITU_CHANNEL_ORDER: [SpeakerRole; 8] = [
+    SpeakerRole::FrontLeft,
+    SpeakerRole::FrontRight,
+    SpeakerRole::Center,
+    SpeakerRole::Subwoofer,
+    SpeakerRole::SideLeft,
+    SpeakerRole::SideRight,
+    SpeakerRole::RearLeft,
+    SpeakerRole::RearRight,
+];
+
+/// Weight of a channel in the BS.1770 loudness sum
+///
+/// Side surrounds count 1.41 (+1.5 dB), the LFE is left out and every other
+/// channel counts 1.0.
+pub fn channel_weight(role: &SpeakerRole) -> f64 {
+    match role {
+        SpeakerRole::Subwoofer => 0.0,
+        SpeakerRole::SideLeft | SpeakerRole::SideRight => 1.41,
+        _ => 1.0,
+    }
+}
 
 /// Target loudness levels for different content types
 #[derive(Clone, Debug, PartialEq)]
@@ -39,13 +84,19 @@ impl LoudnessTarget {
 #[derive(Clone, Debug, PartialEq)]
 pub struct LoudnessMeter {
     sample_rate: u32,
-    /// High-pass filter state (K-weighting approximation)
-    hp_state: Vec<f32>,
     /// Mean square values for loudness calculation
     mean_squares: Vec<f32>,
-    /// Block history for loudness range calculation
-    block_history: Vec<f32>,
+    /// Loudness and frame count of recent blocks, for loudness range
+    block_history: VecDeque<(f32, usize)>,
+    /// Frames covered by `block_history`
+    history_frames: usize,
+    /// Most frames `block_history` may cover
+    history_window_frames: usize,
     block_size: usize,
+    /// Gating blocks accumulated for streaming integrated loudness
+    gated: GatedLoudness,
+    /// BS.1770 weight per channel; channels past the end follow the ITU order
+    channel_weights: Vec<f64>,
 }
 
 impl LoudnessMeter {
@@ -53,40 +104,94 @@ impl LoudnessMeter {
     pub fn new(sample_rate: u32) -> Self {
         Self {
             sample_rate,
-            hp_state: Vec::new(),
             mean_squares: Vec::new(),
-            block_history: Vec::new(),
+            block_history: VecDeque::new(),
+            history_frames: 0,
+            history_window_frames: duration_frames(DEFAULT_LRA_WINDOW, sample_rate),
             block_size: (sample_rate * 4) as usize, // 4 second blocks for LRA
+            gated: GatedLoudness::new(sample_rate, Vec::new()),
+            channel_weights: Vec::new(),
         }
     }
 
-    /// Measure integrated loudness (LUFS) of an audio block
+    /// Weight channels by these roles in the loudness sum instead of
+    /// assuming the ITU channel order
     ///
-    /// LUFS = Loudness Units relative to Full Scale
-    /// Uses K-weighting (approximated high-pass filter + frequency response adjustment)
-    pub fn measure_integrated_loudness(&mut self, block: &AudioBlock) -> f32 {
-        if block.channels.is_empty() || block.channels[0].is_empty() {
-            return f32::NEG_INFINITY;
+    /// Audio already accumulated keeps the weights it was measured with.
+    pub fn set_channel_roles(&mut self, roles: &[SpeakerRole]) {
+        let weights: Vec<f64> = roles.iter().map(channel_weight).collect();
+        if weights != self.channel_weights {
+            self.gated.weights = weights.clone();
+            self.channel_weights = weights;
         }
+    }
 
-        let channels = block.channels.len() as f32;
-        let mut total_loudness = 0.0;
+    /// Sample rate the meter was configured for
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
 
-        for channel in &block.channels {
-            let mean_square = channel.iter().map(|s| s * s).sum::<f32>() / channel.len() as f32;
-            total_loudness += mean_square;
-        }
+    /// Limit loudness range to blocks within the last `window` of audio
+    pub fn set_history_window(&mut self, window: Duration) {
+        self.history_window_frames = duration_frames(window, self.sample_rate);
+        self.trim_history();
+    }
 
-        let mean_square = total_loudness / channels;
+    pub fn history_window(&self) -> Duration {
+        Duration::from_secs_f64(self.history_window_frames as f64 / self.sample_rate.max(1) as f64)
+    }
 
-        // Convert to LUFS: LUFS = -0.691 + 10 * log10(mean_square)
-        if mean_square > 0.0 {
-            -0.691 + 10.0 * mean_square.log10()
-        } else {
-            f32::NEG_INFINITY
+    /// Number of blocks currently held for loudness range
+    pub fn history_len(&self) -> usize {
+        self.block_history.len()
+    }
+
+    /// Drop the oldest blocks until the history fits the window.
+    /// The newest block is always kept.
+    fn trim_history(&mut self) {
+        while self.block_history.len() > 1 && self.history_frames > self.history_window_frames {
+            if let Some((_, frames)) = self.block_history.pop_front() {
+                self.history_frames -= frames;
+            }
         }
     }
 
+    /// Measure integrated loudness (LUFS) of a single audio block
+    ///
+    /// LUFS = Loudness Units relative to Full Scale. The block is gated on
+    /// its own, as if `accumulate` ran on a freshly reset meter followed by
+    /// `finalize`; the streaming accumulator is left untouched.
+    pub fn measure_integrated_loudness(&mut self, block: &AudioBlock) -> f32 {
+        let mut gated = GatedLoudness::new(self.sample_rate, self.channel_weights.clone());
+        gated.push(block);
+        gated.loudness()
+    }
+
+    /// Feed a block to the streaming integrated-loudness measurement
+    ///
+    /// Audio is cut into 400 ms gating blocks overlapping by 75%,
+    /// continuing across calls, so a track can be measured block by block.
+    pub fn accumulate(&mut self, block: &AudioBlock) {
+        self.gated.push(block);
+    }
+
+    /// Gated integrated loudness (LUFS) of everything accumulated so far
+    ///
+    /// Applies the -70 LUFS absolute gate and the -10 LU relative gate over
+    /// the whole history. Until one full gating block has been seen, the
+    /// audio so far is measured as a single block.
+    pub fn integrated_so_far(&self) -> f32 {
+        self.gated.loudness()
+    }
+
+    /// Finish the streaming measurement: return the gated integrated
+    /// loudness over the whole history and start over for the next track
+    pub fn finalize(&mut self) -> f32 {
+        let loudness = self.gated.loudness();
+        self.gated = GatedLoudness::new(self.sample_rate, self.channel_weights.clone());
+        loudness
+    }
+
     /// Measure short-term loudness (3-second window)
     pub fn measure_short_term_loudness(&mut self, block: &AudioBlock) -> f32 {
         // For simplicity, approximate with current block
@@ -94,15 +199,19 @@ impl LoudnessMeter {
     }
 
     /// Measure loudness range (difference between 95th and 5th percentile)
+    /// over the blocks within the history window
     pub fn measure_loudness_range(&mut self, block: &AudioBlock) -> f32 {
         let current_loudness = self.measure_integrated_loudness(block);
-        self.block_history.push(current_loudness);
+        let frames = block.channels.first().map_or(0, Vec::len);
+        self.block_history.push_back((current_loudness, frames));
+        self.history_frames += frames;
+        self.trim_history();
 
         if self.block_history.len() < 10 {
             return 0.0; // Need minimum history
         }
 
-        let mut sorted = self.block_history.clone();
+        let mut sorted: Vec<f32> = self.block_history.iter().map(|&(lufs, _)| lufs).collect();
         sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
 
         let len = sorted.len();
@@ -112,12 +221,218 @@ impl LoudnessMeter {
         sorted[idx_95] - sorted[idx_5]
     }
 
+    /// Maximum true peak of `block` across all channels, in dBTP
+    ///
+    /// Each channel is 4x oversampled so peaks between samples count.
+    /// Silence reads negative infinity.
+    pub fn measure_true_peak(&self, block: &AudioBlock) -> f32 {
+        let peak = block
+            .channels
+            .iter()
+            .flat_map(|channel| true_peak_envelope(channel))
+            .fold(0.0, f32::max);
+        linear_to_db(peak)
+    }
+
+    /// Fill every field of a `LoudnessDescriptor` from one block
+    ///
+    /// Like `measure_loudness_range`, this adds the block to the history.
+    pub fn measure_all(&mut self, block: &AudioBlock) -> LoudnessDescriptor {
+        LoudnessDescriptor {
+            integrated_loudness: self.measure_integrated_loudness(block),
+            short_term_loudness: self.measure_short_term_loudness(block),
+            loudness_range: self.measure_loudness_range(block),
+            max_true_peak: self.measure_true_peak(block),
+        }
+    }
+
     /// Reset meter state
     pub fn reset(&mut self) {
-        self.hp_state.clear();
         self.mean_squares.clear();
         self.block_history.clear();
+        self.history_frames = 0;
+        self.gated = GatedLoudness::new(self.sample_rate, self.channel_weights.clone());
+    }
+}
+
+/// Streaming BS.1770 gating: mean squares of overlapping 400 ms blocks
+///
+/// Every channel is K-weighted and the channel energies are summed with
+/// their BS.1770 weights, so a stereo sine reads 3 dB above one channel of
+/// it and the LFE does not count.
+#[derive(Clone, Debug, PartialEq)]
+struct GatedLoudness {
+    sample_rate: u32,
+    /// Weight per channel; channels past the end follow `ITU_CHANNEL_ORDER`
+    weights: Vec<f64>,
+    /// K-weighting filter for each channel, continuing across blocks
+    weighting: Vec<BiquadCascade>,
+    /// Frames per hop between gating blocks (100 ms)
+    step_frames: usize,
+    /// Channel-averaged energy of the hop being filled
+    step_energy: f64,
+    step_filled: usize,
+    /// Energies of the latest complete hops, at most `GATING_STEPS`
+    recent_steps: VecDeque<f64>,
+    /// Mean square of every complete gating block
+    blocks: Vec<f64>,
+    /// Everything seen, for input shorter than one gating block
+    total_energy: f64,
+    total_frames: usize,
+}
+
+impl GatedLoudness {
+    fn new(sample_rate: u32, weights: Vec<f64>) -> Self {
+        Self {
+            sample_rate,
+            weights,
+            weighting: Vec::new(),
+            step_frames: (duration_frames(GATING_BLOCK, sample_rate) / GATING_STEPS).max(1),
+            step_energy: 0.0,
+            step_filled: 0,
+            recent_steps: VecDeque::with_capacity(GATING_STEPS),
+            blocks: Vec::new(),
+            total_energy: 0.0,
+            total_frames: 0,
+        }
     }
+
+    fn push(&mut self, block: &AudioBlock) {
+        let channels = block.channels.len();
+        let frames = block.channels.iter().map(Vec::len).min().unwrap_or(0);
+
+        while self.weighting.len() < channels {
+            self.weighting.push(k_weighting(self.sample_rate));
+        }
+        let weighted: Vec<Vec<f32>> = block
+            .channels
+            .iter()
+            .zip(&mut self.weighting)
+            .map(|(channel, filter)| {
+                let mut samples = channel[..frames].to_vec();
+                filter.process_block(&mut samples);
+                samples
+            })
+            .collect();
+
+        let gains: Vec<f64> = (0..channels).map(|ch| self.weight(ch)).collect();
+        for frame in 0..frames {
+            let energy = weighted
+                .iter()
+                .zip(&gains)
+                .map(|(channel, gain)| gain * (channel[frame] as f64).powi(2))
+                .sum::<f64>();
+            self.step_energy += energy;
+            self.step_filled += 1;
+            self.total_energy += energy;
+            self.total_frames += 1;
+
+            if self.step_filled == self.step_frames {
+                if self.recent_steps.len() == GATING_STEPS {
+                    self.recent_steps.pop_front();
+                }
+                self.recent_steps.push_back(self.step_energy);
+                self.step_energy = 0.0;
+                self.step_filled = 0;
+
+                if self.recent_steps.len() == GATING_STEPS {
+                    let block_frames = (self.step_frames * GATING_STEPS) as f64;
+                    self.blocks
+                        .push(self.recent_steps.iter().sum::<f64>() / block_frames);
+                }
+            }
+        }
+    }
+
+    fn weight(&self, channel: usize) -> f64 {
+        match self.weights.get(channel) {
+            Some(&weight) => weight,
+            None => ITU_CHANNEL_ORDER.get(channel).map_or(1.0, channel_weight),
+        }
+    }
+
+    fn loudness(&self) -> f32 {
+        if !self.blocks.is_empty() {
+            return gate_loudness(&self.blocks);
+        }
+        if self.total_frames == 0 {
+            return f32::NEG_INFINITY;
+        }
+        gate_loudness(&[self.total_energy / self.total_frames as f64])
+    }
+}
+
+/// BS.1770 K-weighting: a +4 dB high shelf modelling the head, then the
+/// RLB high-pass. Designed per sample rate from the analog prototypes, so at
+/// 48 kHz it reproduces the coefficients tabulated in the standard.
+fn k_weighting(sample_rate: u32) -> BiquadCascade {
+    let fs = sample_rate.max(1) as f64;
+    let section = |b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64| {
+        BiquadFilter::new(
+            BiquadCoefficients {
+                b0: (b0 / a0) as f32,
+                b1: (b1 / a0) as f32,
+                b2: (b2 / a0) as f32,
+                a1: (a1 / a0) as f32,
+                a2: (a2 / a0) as f32,
+            },
+            0.0,
+        )
+    };
+
+    let k = (std::f64::consts::PI * 1681.974450955533 / fs).tan();
+    let q = 0.7071752369554196;
+    let vh = 10f64.powf(3.999843853973347 / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let shelf = section(
+        vh + vb * k / q + k * k,
+        2.0 * (k * k - vh),
+        vh - vb * k / q + k * k,
+        1.0 + k / q + k * k,
+        2.0 * (k * k - 1.0),
+        1.0 - k / q + k * k,
+    );
+
+    let k = (std::f64::consts::PI * 38.13547087602444 / fs).tan();
+    let q = 0.5003270373238773;
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = section(
+        a0,
+        -2.0 * a0,
+        a0,
+        a0,
+        2.0 * (k * k - 1.0),
+        1.0 - k / q + k * k,
+    );
+
+    BiquadCascade::new(vec![shelf, high_pass])
+}
+
+/// Integrated loudness of gating-block mean squares after the absolute and
+/// relative gates
+fn gate_loudness(blocks: &[f64]) -> f32 {
+    let lufs = |mean_square: f64| -0.691 + 10.0 * mean_square.log10();
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+
+    let audible: Vec<f64> = blocks
+        .iter()
+        .copied()
+        .filter(|&z| z > 0.0 && lufs(z) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if audible.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+
+    let relative_gate = lufs(mean(&audible)) - RELATIVE_GATE_LU;
+    let gated: Vec<f64> = audible
+        .into_iter()
+        .filter(|&z| lufs(z) > relative_gate)
+        .collect();
+    lufs(mean(&gated)) as f32
+}
+
+fn duration_frames(duration: Duration, sample_rate: u32) -> usize {
+    (duration.as_secs_f64() * sample_rate as f64).round() as usize
 }
 
 /// Headroom manager prevents clipping and provides safety margins
@@ -135,6 +450,15 @@ pub struct HeadroomManager {
     limiter_gain: f32,
     /// Lookahead in samples for peak detection
     lookahead_samples: usize,
+    /// Lookahead as configured, in milliseconds
+    lookahead_ms: f32,
+    /// Detect inter-sample peaks via oversampling instead of sample peaks
+    true_peak: bool,
+    /// Input waiting out the lookahead, per channel
+    delay_lines: Vec<VecDeque<f32>>,
+    /// Linked detector level of every frame in the delay lines plus the
+    /// newest one; the front lines up with the frame being output
+    detector: VecDeque<f32>,
 }
 
 impl HeadroomManager {
@@ -155,49 +479,148 @@ impl HeadroomManager {
             limiter_release_samples: ((sample_rate as f32 * release_ms) / 1000.0) as usize,
             limiter_gain: 1.0,
             lookahead_samples: ((sample_rate as f32 * lookahead_ms) / 1000.0).max(1.0) as usize,
+            lookahead_ms,
+            true_peak: false,
+            delay_lines: Vec::new(),
+            detector: VecDeque::new(),
         }
     }
 
+    /// Limit against the true (inter-sample) peak rather than the sample peak
+    pub fn set_true_peak(&mut self, enabled: bool) {
+        self.true_peak = enabled;
+    }
+
+    /// Whether true-peak detection is enabled
+    pub fn is_true_peak(&self) -> bool {
+        self.true_peak
+    }
+
     /// Set lookahead time in milliseconds
+    ///
+    /// Audio still inside the old delay line is kept; a longer lookahead
+    /// pads it with silence, a shorter one drops the oldest frames.
     pub fn set_lookahead_ms(&mut self, sample_rate: u32, lookahead_ms: f32) {
+        self.lookahead_ms = lookahead_ms.max(0.0);
         self.lookahead_samples =
-            ((sample_rate as f32 * lookahead_ms.max(0.0)) / 1000.0).max(1.0) as usize;
+            ((sample_rate as f32 * self.lookahead_ms) / 1000.0).max(1.0) as usize;
+    }
+
+    /// Change the headroom below full scale without resetting limiter state
+    pub fn set_target_headroom_db(&mut self, target_headroom_db: f32) {
+        self.target_headroom_db = target_headroom_db.clamp(0.1, 20.0);
+        self.limiting_threshold_db = -self.target_headroom_db;
+    }
+
+    /// Configured headroom below full scale, in dB
+    pub fn target_headroom_db(&self) -> f32 {
+        self.target_headroom_db
+    }
+
+    /// Lookahead window in samples
+    pub fn lookahead_samples(&self) -> usize {
+        self.lookahead_samples
+    }
+
+    /// Lookahead window as configured, in milliseconds
+    pub fn lookahead_ms(&self) -> f32 {
+        self.lookahead_ms
     }
 
-    /// Apply headroom management with soft limiting
+    /// Apply headroom management with look-ahead limiting
+    ///
+    /// Output is delayed by `lookahead_samples`, so the gain reaches its
+    /// reduction by the time a detected peak leaves the delay line. Gain
+    /// falls with the attack time (capped to the lookahead) and recovers
+    /// with the release time; a frame that would still exceed the threshold
+    /// is pulled down to it, so no output sample overshoots.
     pub fn apply_limiting(&mut self, block: &mut AudioBlock) {
         if block.channels.is_empty() {
             return;
         }
+        self.prepare_delay(block.channels.len());
 
         let threshold = db_to_linear(self.limiting_threshold_db);
+        // Settle 99% of the way to a new reduction within the attack time
+        let attack_samples = self
+            .limiter_attack_samples
+            .min(self.lookahead_samples)
+            .max(1);
+        let attack_coeff = 1.0 - 0.01f32.powf(1.0 / attack_samples as f32);
+        let release_coeff = (1.0 / self.limiter_release_samples.max(1) as f32).min(1.0);
+
+        // Linked detector: loudest channel at each frame
+        let len = block.frame_len();
+        let mut linked = vec![0.0f32; len];
+        for channel in &block.channels {
+            let levels = if self.true_peak {
+                true_peak_envelope(channel)
+            } else {
+                channel.iter().map(|v| v.abs()).collect()
+            };
+            for (l, v) in linked.iter_mut().zip(levels) {
+                *l = l.max(v);
+            }
+        }
 
-        for channel in &mut block.channels {
-            let len = channel.len();
-            for i in 0..len {
-                let abs_sample = channel[i].abs();
-
-                // Lookahead: inspect upcoming window for potential peaks
-                let end = (i + self.lookahead_samples).min(len);
-                let ahead_max = channel[i..end]
-                    .iter()
-                    .map(|&v| v.abs())
-                    .fold(abs_sample, f32::max);
-
-                if ahead_max > threshold {
-                    // Calculate required gain reduction ahead of peak
-                    let gain_needed = threshold / ahead_max.max(0.0001);
-                    self.limiter_gain = gain_needed.min(self.limiter_gain);
-                } else {
-                    // Gradual release towards unity
-                    self.limiter_gain = (self.limiter_gain * 0.99 + 1.0 * 0.01).min(1.0);
+        for (i, &level) in linked.iter().enumerate() {
+            self.detector.push_back(level);
+            let window_peak = self.detector.iter().copied().fold(0.0, f32::max);
+            let target = if window_peak > threshold {
+                threshold / window_peak
+            } else {
+                1.0
+            };
+
+            let coeff = if target < self.limiter_gain {
+                attack_coeff
+            } else {
+                release_coeff
+            };
+            self.limiter_gain = (self.limiter_gain + (target - self.limiter_gain) * coeff).min(1.0);
+
+            let outgoing = self.detector.pop_front().unwrap_or(0.0);
+            let gain = if outgoing * self.limiter_gain > threshold {
+                threshold / outgoing
+            } else {
+                self.limiter_gain
+            };
+
+            // Same gain on every channel keeps the stereo image stable
+            for (channel, line) in block.channels.iter_mut().zip(&mut self.delay_lines) {
+                if let Some(sample) = channel.get_mut(i) {
+                    line.push_back(*sample);
+                    *sample = line.pop_front().unwrap_or(0.0) * gain;
                 }
+            }
+        }
+    }
+
+    /// Size the delay lines for `channels` and the current lookahead
+    fn prepare_delay(&mut self, channels: usize) {
+        if self.delay_lines.len() != channels {
+            self.delay_lines = vec![VecDeque::new(); channels];
+            self.detector.clear();
+        }
 
-                channel[i] *= self.limiter_gain;
+        let lookahead = self.lookahead_samples;
+        for line in self.delay_lines.iter_mut().chain([&mut self.detector]) {
+            while line.len() > lookahead {
+                line.pop_front();
+            }
+            while line.len() < lookahead {
+                line.push_front(0.0);
             }
         }
     }
 
+    /// Clear the delay line and release all gain reduction, e.g. after a seek
+    pub fn reset(&mut self) {
+        self.delay_lines.clear();
+        self.detector.clear();
+        self.limiter_gain = 1.0;
+    }
+
     /// Get current headroom utilization in dB
     pub fn current_headroom_db(&self) -> f32 {
         linear_to_db(self.limiter_gain)
@@ -220,19 +643,31 @@ pub struct DynamicRangeControl {
     attack_samples: usize,
     /// Release time in samples
     release_samples: usize,
+    /// Attack and release as configured, in milliseconds
+    attack_ms: f32,
+    release_ms: f32,
     /// Makeup gain in dB
     makeup_gain_db: f32,
-    /// Current gain reduction (0.0 to 1.0)
-    current_gain: f32,
-    /// Envelope follower state (linear amplitude)
-    envelope: f32,
+    /// Soft-knee width in dB (0.0 = hard knee)
+    knee_db: f32,
+    /// Drive all channels from one detector so the image doesn't shift
+    stereo_link: bool,
+    /// Current gain reduction (0.0 to 1.0) per detector; linked mode only
+    /// uses the first
+    current_gain: Vec<f32>,
+    /// Envelope follower state (linear amplitude) per detector
+    envelope: Vec<f32>,
 }
 
 impl DynamicRangeControl {
     /// Create new DRC compressor
+    ///
+    /// `knee_db` is the soft-knee width (see `set_knee_db`); 0.0 gives a
+    /// hard knee.
     pub fn new(
         ratio: f32,
         threshold_db: f32,
+        knee_db: f32,
         attack_ms: f32,
         release_ms: f32,
         sample_rate: u32,
@@ -245,9 +680,13 @@ impl DynamicRangeControl {
             threshold_db,
             attack_samples,
             release_samples,
+            attack_ms,
+            release_ms,
             makeup_gain_db: 0.0,
-            current_gain: 1.0,
-            envelope: 0.0,
+            knee_db: knee_db.max(0.0),
+            stereo_link: false,
+            current_gain: Vec::new(),
+            envelope: Vec::new(),
         }
     }
 
@@ -256,6 +695,76 @@ impl DynamicRangeControl {
         self.makeup_gain_db = gain_db;
     }
 
+    /// Set soft-knee width (dB); compression engages gradually across
+    /// threshold ± knee/2. A width of 0.0 gives a hard knee.
+    pub fn set_knee_db(&mut self, knee_db: f32) {
+        self.knee_db = knee_db.max(0.0);
+    }
+
+    /// Link channels: when enabled the detector follows the loudest channel
+    /// of each frame and every channel gets the same gain
+    pub fn set_stereo_link(&mut self, linked: bool) {
+        self.stereo_link = linked;
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    pub fn threshold_db(&self) -> f32 {
+        self.threshold_db
+    }
+
+    pub fn attack_samples(&self) -> usize {
+        self.attack_samples
+    }
+
+    pub fn release_samples(&self) -> usize {
+        self.release_samples
+    }
+
+    pub fn attack_ms(&self) -> f32 {
+        self.attack_ms
+    }
+
+    pub fn release_ms(&self) -> f32 {
+        self.release_ms
+    }
+
+    pub fn makeup_gain_db(&self) -> f32 {
+        self.makeup_gain_db
+    }
+
+    pub fn knee_db(&self) -> f32 {
+        self.knee_db
+    }
+
+    pub fn stereo_link(&self) -> bool {
+        self.stereo_link
+    }
+
+    /// Static gain (linear) applied for the given envelope level
+    fn static_gain(&self, envelope: f32, threshold: f32) -> f32 {
+        if self.knee_db <= 0.0 {
+            let over_threshold = (envelope / threshold).max(1.0);
+            return 1.0 / (over_threshold.powf((self.ratio - 1.0) / self.ratio));
+        }
+
+        // Quadratic soft knee in the dB domain
+        let over_db = linear_to_db(envelope) - self.threshold_db;
+        let half_knee = self.knee_db / 2.0;
+        let slope = 1.0 / self.ratio - 1.0;
+        let reduction_db = if over_db <= -half_knee {
+            0.0
+        } else if over_db < half_knee {
+            slope * (over_db + half_knee).powi(2) / (2.0 * self.knee_db)
+        } else {
+            slope * over_db
+        };
+
+        db_to_linear(reduction_db)
+    }
+
     /// Apply DRC compression to audio block
     pub fn process(&mut self, block: &mut AudioBlock) {
         if block.channels.is_empty() {
@@ -266,46 +775,83 @@ impl DynamicRangeControl {
         let makeup_gain = db_to_linear(self.makeup_gain_db);
 
         // Envelope follower coefficients (simple one-pole)
-        let _attack_coeff = (1.0 / self.attack_samples.max(1) as f32).min(1.0);
+        let attack_coeff = (1.0 / self.attack_samples.max(1) as f32).min(1.0);
         let release_coeff = (1.0 / self.release_samples.max(1) as f32).min(1.0);
 
-        for channel in &mut block.channels {
-            for sample in channel.iter_mut() {
-                let abs_sample = sample.abs();
-                // Update envelope: instant attack, smoothed release
-                if abs_sample > self.envelope {
-                    self.envelope = abs_sample;
-                } else {
-                    self.envelope += (abs_sample - self.envelope) * release_coeff;
-                }
+        let channels = block.channels.len();
+        if self.envelope.len() != channels {
+            self.envelope.resize(channels, 0.0);
+            self.current_gain.resize(channels, 1.0);
+        }
 
-                // Compute gain reduction from envelope
-                let over_threshold = (self.envelope / threshold).max(1.0);
-                let gain_reduction = 1.0 / (over_threshold.powf((self.ratio - 1.0) / self.ratio));
-
-                // Smooth gain towards target reduction
-                if gain_reduction < self.current_gain {
-                    // Instant attack to catch transients
-                    self.current_gain = gain_reduction;
-                } else {
-                    // Smoothed release towards unity
-                    self.current_gain =
-                        self.current_gain * (1.0 - release_coeff) + gain_reduction * release_coeff;
+        if self.stereo_link {
+            let frames = block.channels.iter().map(Vec::len).min().unwrap_or(0);
+            for i in 0..frames {
+                let level = block
+                    .channels
+                    .iter()
+                    .map(|channel| channel[i].abs())
+                    .fold(0.0, f32::max);
+                let gain =
+                    self.follow(0, level, threshold, attack_coeff, release_coeff) * makeup_gain;
+                for channel in &mut block.channels {
+                    channel[i] *= gain;
                 }
+            }
+            return;
+        }
 
-                *sample *= self.current_gain * makeup_gain;
+        // Unlinked, every channel has its own detector and gain
+        for (idx, channel) in block.channels.iter_mut().enumerate() {
+            for sample in channel.iter_mut() {
+                let gain = self.follow(idx, sample.abs(), threshold, attack_coeff, release_coeff);
+                *sample *= gain * makeup_gain;
             }
         }
     }
 
-    /// Get current gain reduction in dB
+    /// Advance detector `idx` by one sample of `level` and return the gain to apply
+    fn follow(
+        &mut self,
+        idx: usize,
+        level: f32,
+        threshold: f32,
+        attack_coeff: f32,
+        release_coeff: f32,
+    ) -> f32 {
+        // Update envelope: one-pole rise over the attack window, smoothed release
+        let envelope = &mut self.envelope[idx];
+        if level > *envelope {
+            *envelope += (level - *envelope) * attack_coeff;
+        } else {
+            *envelope += (level - *envelope) * release_coeff;
+        }
+
+        // Compute gain reduction from envelope
+        let gain_reduction = self.static_gain(self.envelope[idx], threshold);
+
+        // Smooth gain towards target reduction
+        let gain = &mut self.current_gain[idx];
+        if gain_reduction < *gain {
+            // Follow the envelope, which already ramps in over the attack time
+            *gain = gain_reduction;
+        } else {
+            // Smoothed release towards unity
+            *gain = *gain * (1.0 - release_coeff) + gain_reduction * release_coeff;
+        }
+
+        *gain
+    }
+
+    /// Get current gain reduction in dB, taken from the most reduced channel
     pub fn current_reduction_db(&self) -> f32 {
-        linear_to_db(self.current_gain)
+        linear_to_db(self.current_gain.iter().copied().fold(1.0, f32::min))
     }
 
     /// Reset DRC state
     pub fn reset(&mut self) {
-        self.current_gain = 1.0;
+        self.current_gain.clear();
+        self.envelope.clear();
     }
 }
 
@@ -349,6 +895,35 @@ impl LoudnessNormalizer {
         }
     }
 
+    /// Weight channels by these roles when measuring, see
+    /// `LoudnessMeter::set_channel_roles`
+    pub fn set_channel_roles(&mut self, roles: &[SpeakerRole]) {
+        self.meter.set_channel_roles(roles);
+    }
+
+    /// First pass of offline normalization: gated integrated loudness
+    /// (LUFS) across every block of a file, rather than block by block
+    pub fn measure_file(&self, blocks: &[AudioBlock]) -> f32 {
+        let mut meter = self.meter.clone();
+        meter.reset();
+        for block in blocks {
+            meter.accumulate(block);
+        }
+        meter.finalize()
+    }
+
+    /// Gain (dB) that brings a file measured with `measure_file` to the target
+    pub fn file_gain_db(&self, measured_lufs: f32) -> f32 {
+        self.target.as_lufs() - measured_lufs
+    }
+
+    /// Second pass of offline normalization: apply the same gain to every block
+    pub fn apply_fixed_gain(&self, block: &mut AudioBlock, gain_db: f32) {
+        if gain_db.is_finite() {
+            block.apply_gain(gain_db);
+        }
+    }
+
     /// Get target loudness in LUFS
     pub fn target_loudness_lufs(&self) -> f32 {
         self.target.as_lufs()
@@ -369,10 +944,58 @@ pub struct LoudnessDescriptor {
     pub short_term_loudness: f32,
     /// Loudness range (LRA) in LU
     pub loudness_range: f32,
-    /// Maximum true peak in dBFS
+    /// Maximum true peak in dBTP
     pub max_true_peak: f32,
 }
 
+/// Per-sample true-peak level: the largest magnitude of each sample and the
+/// interpolated points between it and the next, using a 4x windowed-sinc
+/// polyphase upsampler. Samples outside the slice are treated as silence.
+fn true_peak_envelope(samples: &[f32]) -> Vec<f32> {
+    let half = TRUE_PEAK_TAPS as isize / 2;
+
+    // Branch p interpolates at offset p/4 from x[n], using x[n - half + 1 ..= n + half]
+    let branches: Vec<Vec<f32>> = (1..TRUE_PEAK_OVERSAMPLE)
+        .map(|phase| {
+            let frac = phase as f32 / TRUE_PEAK_OVERSAMPLE as f32;
+            let taps: Vec<f32> = (1 - half..=half)
+                .map(|m| {
+                    let t = frac - m as f32;
+                    let sinc = (std::f32::consts::PI * t).sin() / (std::f32::consts::PI * t);
+                    let window =
+                        0.5 * (1.0 + (std::f32::consts::PI * t / (half as f32 + 1.0)).cos());
+                    sinc * window
+                })
+                .collect();
+            let sum: f32 = taps.iter().sum();
+            taps.into_iter().map(|c| c / sum).collect()
+        })
+        .collect();
+
+    let at = |idx: isize| -> f32 {
+        if idx < 0 {
+            0.0
+        } else {
+            samples.get(idx as usize).copied().unwrap_or(0.0)
+        }
+    };
+
+    (0..samples.len() as isize)
+        .map(|n| {
+            branches
+                .iter()
+                .fold(samples[n as usize].abs(), |peak, taps| {
+                    let value: f32 = taps
+                        .iter()
+                        .zip(1 - half..=half)
+                        .map(|(c, m)| c * at(n + m))
+                        .sum();
+                    peak.max(value.abs())
+                })
+        })
+        .collect()
+}
+
 /// Convert dB to linear amplitude
 fn db_to_linear(db: f32) -> f32 {
     10.0_f32.powf(db / 20.0)
@@ -430,6 +1053,37 @@ mod tests {
         assert!(loudness < 0.0); // Should be negative
     }
 
+    #[test]
+    fn test_loudness_range_history_is_time_bounded() {
+        let mut meter = LoudnessMeter::new(48000);
+        meter.set_history_window(Duration::from_secs(10));
+        assert_eq!(meter.history_window(), Duration::from_secs(10));
+
+        // 100 ms blocks, so the window holds 100 of them
+        let block_at = |level: f32| AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![level; 4800]],
+        };
+
+        // A long stretch swinging between loud and quiet...
+        for i in 0..1000 {
+            let level = if i % 2 == 0 { 0.5 } else { 0.01 };
+            meter.measure_loudness_range(&block_at(level));
+        }
+        assert_eq!(meter.history_len(), 100);
+
+        // ...followed by a steady level that fills the whole window
+        let mut lra = f32::NAN;
+        for _ in 0..100 {
+            lra = meter.measure_loudness_range(&block_at(0.1));
+        }
+        assert_eq!(meter.history_len(), 100);
+        assert!(lra.abs() < 1e-3, "LRA {}", lra);
+
+        meter.reset();
+        assert_eq!(meter.history_len(), 0);
+    }
+
     #[test]
     fn test_headroom_manager_limiting() {
         let mut mgr = HeadroomManager::new(3.0, 48000);
@@ -451,6 +1105,7 @@ mod tests {
         let mut drc = DynamicRangeControl::new(
             4.0,   // 4:1 ratio
             -20.0, // -20dB threshold
+            0.0,   // hard knee
             10.0,  // 10ms attack
             100.0, // 100ms release
             48000,
@@ -478,7 +1133,7 @@ mod tests {
         let quiet: Vec<f32> = (0..4800)
             .map(|i| {
                 let t = i as f32 / 48000.0;
-                (2.0 * std::f32::consts::PI * 1000.0 * t).sin() * 0.1
+                (2.0 * std::f32::consts::PI * 1000.0 * t).sin() * 0.01
             })
             .collect();
 
@@ -506,6 +1161,304 @@ mod tests {
         assert!((linear_to_db(0.1) - (-20.0)).abs() < 0.001);
     }
 
+    #[test]
+    fn test_true_peak_limiting_catches_inter_sample_peaks() {
+        // fs/4 sine at 45° phase: samples sit at ±0.707 while the waveform peaks at 1.0
+        let samples: Vec<f32> = (0..480)
+            .map(|i| (std::f32::consts::FRAC_PI_2 * i as f32 + std::f32::consts::FRAC_PI_4).sin())
+            .collect();
+        let block = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![samples],
+        };
+
+        let envelope = true_peak_envelope(&block.channels[0]);
+        let true_peak = envelope[100..380].iter().copied().fold(0.0, f32::max);
+        assert!(true_peak > 0.97, "true peak {}", true_peak);
+
+        // -1 dB ceiling sits between the sample peak and the true peak
+        let mut sample_mode = HeadroomManager::new(1.0, 48000);
+        let lookahead = sample_mode.lookahead_samples();
+        let mut untouched = block.clone();
+        sample_mode.apply_limiting(&mut untouched);
+        assert!(!sample_mode.is_limiting());
+        // Only delayed by the lookahead
+        assert!(untouched.channels[0][..lookahead].iter().all(|&x| x == 0.0));
+        assert_eq!(
+            untouched.channels[0][lookahead..],
+            block.channels[0][..480 - lookahead]
+        );
+
+        let mut true_peak_mode = HeadroomManager::new(1.0, 48000);
+        true_peak_mode.set_true_peak(true);
+        let mut limited = block.clone();
+        true_peak_mode.apply_limiting(&mut limited);
+        assert!(true_peak_mode.is_limiting());
+        assert!(limited.channels[0][200 + lookahead].abs() < block.channels[0][200].abs());
+    }
+
+    fn tone_block(amplitude: f32, frames: usize) -> AudioBlock {
+        let tone: Vec<f32> = (0..frames)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin())
+            .collect();
+        AudioBlock {
+            sample_rate: 48000,
+            channels: vec![tone.clone(), tone],
+        }
+    }
+
+    fn feed_in_chunks(meter: &mut LoudnessMeter, block: &AudioBlock, chunk: usize) {
+        let frames = block.channels[0].len();
+        for start in (0..frames).step_by(chunk) {
+            let end = (start + chunk).min(frames);
+            meter.accumulate(&AudioBlock {
+                sample_rate: block.sample_rate,
+                channels: block
+                    .channels
+                    .iter()
+                    .map(|c| c[start..end].to_vec())
+                    .collect(),
+            });
+        }
+    }
+
+    #[test]
+    fn test_streaming_integrated_loudness_matches_single_shot() {
+        let track = tone_block(0.5, 3 * 48000);
+        let mut meter = LoudnessMeter::new(48000);
+
+        feed_in_chunks(&mut meter, &track, 512);
+        let streamed = meter.finalize();
+        let single_shot = meter.measure_integrated_loudness(&track);
+
+        assert!((streamed - single_shot).abs() < 0.01);
+        assert!((streamed - (-6.02)).abs() < 0.05, "{} LUFS", streamed);
+    }
+
+    #[test]
+    fn test_integrated_loudness_gates_quiet_and_silent_passages() {
+        let loud = tone_block(0.5, 2 * 48000);
+        let quiet = tone_block(0.005, 2 * 48000);
+        let silence = AudioBlock::silence(2, 2 * 48000, 48000);
+        let mut meter = LoudnessMeter::new(48000);
+
+        for block in [&loud, &quiet, &silence] {
+            feed_in_chunks(&mut meter, block, 1024);
+        }
+
+        // The quiet passage sits 40 LU down, past the relative gate, and
+        // the silence is below the absolute gate: only the loud part and the
+        // few blocks straddling the transition count. Ungated, the average
+        // over all 6 s would be almost 5 LU lower.
+        let loud_only = meter.measure_integrated_loudness(&loud);
+        let gated = meter.integrated_so_far();
+        assert!(
+            (gated - loud_only).abs() < 0.5,
+            "{} vs {}",
+            gated,
+            loud_only
+        );
+    }
+
+    #[test]
+    fn test_integrated_so_far_and_finalize() {
+        let mut meter = LoudnessMeter::new(48000);
+        assert_eq!(meter.integrated_so_far(), f32::NEG_INFINITY);
+
+        // Shorter than one gating block: measured as a single block
+        meter.accumulate(&tone_block(0.5, 4800));
+        assert!((meter.integrated_so_far() - (-6.02)).abs() < 0.05);
+
+        assert!((meter.finalize() - (-6.02)).abs() < 0.05);
+        assert_eq!(meter.integrated_so_far(), f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_integrated_loudness_weights_channels_by_role() {
+        let tone = tone_block(0.5, 48000).channels.remove(0);
+        let one_channel = |ch: usize| {
+            let mut block = AudioBlock::silence(6, 48000, 48000);
+            block.channels[ch] = tone.clone();
+            block
+        };
+        let mut meter = LoudnessMeter::new(48000);
+
+        // Unknown roles follow the ITU order L R C LFE Ls Rs
+        let left = meter.measure_integrated_loudness(&one_channel(0));
+        assert!((left - (-9.03)).abs() < 0.05, "{} LUFS", left);
+        let surround = meter.measure_integrated_loudness(&one_channel(4));
+        assert!((surround - left - 1.49).abs() < 0.01);
+        assert_eq!(
+            meter.measure_integrated_loudness(&one_channel(3)),
+            f32::NEG_INFINITY
+        );
+
+        // Explicit roles override the order
+        let mut roles = vec![SpeakerRole::FrontLeft; 6];
+        roles[0] = SpeakerRole::Subwoofer;
+        meter.set_channel_roles(&roles);
+        assert_eq!(
+            meter.measure_integrated_loudness(&one_channel(0)),
+            f32::NEG_INFINITY
+        );
+        let lfe_slot = meter.measure_integrated_loudness(&one_channel(3));
+        assert!((lfe_slot - left).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_measure_true_peak_finds_inter_sample_overs() {
+        let meter = LoudnessMeter::new(48000);
+        // fs/4 sine at 45° phase: sample peak -3 dB, true peak 0 dB
+        let samples: Vec<f32> = (0..480)
+            .map(|i| (std::f32::consts::FRAC_PI_2 * i as f32 + std::f32::consts::FRAC_PI_4).sin())
+            .collect();
+        let block = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![0.0; 480], samples],
+        };
+
+        let dbtp = meter.measure_true_peak(&block);
+        assert!(dbtp > -0.3 && dbtp < 0.3, "true peak {} dBTP", dbtp);
+    }
+
+    #[test]
+    fn test_measure_true_peak_of_silence_is_negative_infinity() {
+        let meter = LoudnessMeter::new(48000);
+        let dbtp = meter.measure_true_peak(&AudioBlock::silence(2, 480, 48000));
+        assert_eq!(dbtp, f32::NEG_INFINITY);
+        let dbtp = meter.measure_true_peak(&AudioBlock::silence(0, 0, 48000));
+        assert_eq!(dbtp, f32::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_measure_all_fills_every_field() {
+        let mut meter = LoudnessMeter::new(48000);
+        // 1 kHz at half scale, starting and ending at zero crossings
+        let tone: Vec<f32> = (0..4800)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin())
+            .collect();
+        let block = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![tone.clone(), tone],
+        };
+
+        let descriptor = meter.measure_all(&block);
+
+        assert!((descriptor.integrated_loudness - (-6.02)).abs() < 0.05);
+        assert_eq!(
+            descriptor.short_term_loudness,
+            descriptor.integrated_loudness
+        );
+        assert_eq!(descriptor.loudness_range, 0.0);
+        assert!((descriptor.max_true_peak - linear_to_db(0.5)).abs() < 0.01);
+        assert_eq!(meter.history_len(), 1);
+
+        let silent = meter.measure_all(&AudioBlock::silence(2, 480, 48000));
+        assert_eq!(silent.max_true_peak, f32::NEG_INFINITY);
+        assert!(!silent.max_true_peak.is_nan());
+    }
+
+    #[test]
+    fn test_limiter_gain_is_stereo_linked() {
+        let mut mgr = HeadroomManager::new(3.0, 48000);
+
+        // Peak on the left channel only
+        let mut left = vec![0.2; 960];
+        left[480] = 1.0;
+        let mut block = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![left.clone(), vec![0.2; 960]],
+        };
+
+        mgr.apply_limiting(&mut block);
+        assert!(mgr.is_limiting());
+
+        // Output frame i + lookahead carries input frame i
+        let lookahead = mgr.lookahead_samples();
+        for (i, input) in left[..960 - lookahead].iter().enumerate() {
+            let left_gain = block.channels[0][i + lookahead] / input;
+            let right_gain = block.channels[1][i + lookahead] / 0.2;
+            assert!((left_gain - right_gain).abs() < 1e-6, "frame {}", i);
+        }
+        assert!(block.channels[1][480 + lookahead] < 0.2);
+    }
+
+    #[test]
+    fn test_lookahead_limiter_catches_single_spike() {
+        let mut mgr = HeadroomManager::new(3.0, 48000);
+        let threshold = db_to_linear(-3.0);
+        let lookahead = mgr.lookahead_samples();
+
+        let mut input = vec![0.1; 960];
+        input[400] = 1.5;
+        let mut block = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![input.clone(), input],
+        };
+        mgr.apply_limiting(&mut block);
+
+        for channel in &block.channels {
+            let peak = channel.iter().map(|x| x.abs()).fold(0.0, f32::max);
+            assert!(peak <= threshold + 1e-6, "peak {}", peak);
+        }
+        // The spike comes out delayed, pulled down to the threshold
+        assert!((block.channels[0][400 + lookahead] - threshold).abs() < 1e-3);
+        // Gain ramps down ahead of the spike rather than at it
+        assert!(block.channels[0][399 + lookahead] < 0.1 * 0.9);
+        // Nothing is touched before the spike enters the detector
+        assert!((block.channels[0][399] - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lookahead_limiter_carries_delay_across_blocks() {
+        let mut mgr = HeadroomManager::new(3.0, 48000);
+        let lookahead = mgr.lookahead_samples();
+
+        let mut first = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![0.25; 256]],
+        };
+        let mut second = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![-0.25; 256]],
+        };
+        mgr.apply_limiting(&mut first);
+        mgr.apply_limiting(&mut second);
+
+        assert_eq!(second.channels[0][lookahead - 1], 0.25);
+        assert_eq!(second.channels[0][lookahead], -0.25);
+
+        mgr.reset();
+        let mut after_reset = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![0.25; 256]],
+        };
+        mgr.apply_limiting(&mut after_reset);
+        assert_eq!(after_reset.channels[0][0], 0.0);
+    }
+
+    #[test]
+    fn test_limiter_release_follows_release_time() {
+        let mut mgr = HeadroomManager::new(3.0, 48000);
+        let mut spike = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![1.0]],
+        };
+        mgr.apply_limiting(&mut spike);
+        let reduced = mgr.current_headroom_db();
+
+        // 10 ms of quiet audio: a 300 ms release has barely moved
+        let mut quiet = AudioBlock::silence(1, 480, 48000);
+        mgr.apply_limiting(&mut quiet);
+        assert!(mgr.is_limiting());
+        assert!(mgr.current_headroom_db() < reduced * 0.9);
+
+        // After a few release time constants the limiter has recovered
+        let mut tail = AudioBlock::silence(1, 48000 * 2, 48000);
+        mgr.apply_limiting(&mut tail);
+        assert!(!mgr.is_limiting());
+    }
+
     #[test]
     fn test_headroom_detection() {
         let mut mgr = HeadroomManager::new(3.0, 48000);
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Position3, SpeakerDescriptor, SpeakerLayout};
+use crate::{AudioBlock, Position3, SpeakerDescriptor, SpeakerLayout, SpeakerRole};
+use std::collections::VecDeque;
 
 /// Vector Base Amplitude Panning (VBAP) for object positioning.
 /// Maps a 3D audio object position to speaker gains using the nearest speaker triangle/pair.
@@ -100,294 +101,578 @@ pub fn upmix_channels(input: &[Vec<f32>], target_count: usize) -> Vec<Vec<f32>>
     downmix_channels(input, target_count)
 }
 
-/// Map ITU layout names to speaker descriptors.
-pub fn layout_from_name(name: &str) -> Option<SpeakerLayout> {
-    use crate::SpeakerRole::*;
-    use std::time::Duration;
-
-    let speakers = match name {
-        "2.0" | "stereo" => vec![
-            SpeakerDescriptor {
-                id: "FL".into(),
-                role: FrontLeft,
-                position: Position3 {
-                    x: -0.5,
-                    y: 1.0,
-                    z: 0.0,
-                },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "FR".into(),
-                role: FrontRight,
-                position: Position3 {
-                    x: 0.5,
-                    y: 1.0,
-                    z: 0.0,
-                },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-        ],
-        "5.1" => vec![
-            SpeakerDescriptor {
-                id: "FL".into(),
-                role: FrontLeft,
-                position: Position3 {
-                    x: -0.5,
-                    y: 1.0,
-                    z: 0.0,
-                },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "FR".into(),
-                role: FrontRight,
-                position: Position3 {
-                    x: 0.5,
-                    y: 1.0,
-                    z: 0.0,
-                },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "C".into(),
-                role: Center,
-                position: Position3 {
-                    x: 0.0,
-                    y: 1.0,
-                    z: 0.0,
-                },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "LFE".into(),
-                role: Subwoofer,
-                position: Position3 {
-                    x: 0.0,
-                    y: 0.0,
-                    z: -0.5,
-                },
-                max_spl_db: 120.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "SL".into(),
-                role: SideLeft,
-                position: Position3 {
-                    x: -1.0,
-                    y: -0.5,
-                    z: 0.0,
-                },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "SR".into(),
-                role: SideRight,
-                position: Position3 {
-                    x: 1.0,
-                    y: -0.5,
-                    z: 0.0,
+/// Gain for content folded into a neighbouring speaker (-3 dB)
+const FOLD_GAIN: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Options controlling how a `DownmixMatrix` is derived
+#[derive(Clone, Debug, PartialEq)]
+pub struct DownmixOptions {
+    /// Split center content equally to front L/R when the target has no center speaker
+    pub phantom_center: bool,
+    /// Fraction (0.0-1.0) of center power spread to front L/R when a center speaker exists
+    pub center_spread: f32,
+    /// Fold the LFE into the front L/R at -3 dB when the target has no subwoofer
+    pub include_lfe: bool,
+}
+
+impl Default for DownmixOptions {
+    fn default() -> Self {
+        Self {
+            phantom_center: true,
+            center_spread: 0.0,
+            include_lfe: false,
+        }
+    }
+}
+
+/// Role-aware mixing matrix from a source layout to a target layout.
+///
+/// Speakers present in both layouts pass through at unity gain. Center
+/// content follows `DownmixOptions`, an unmatched LFE is dropped unless
+/// `DownmixOptions::include_lfe` is set, and any other unmatched speaker
+/// folds into the nearest full-range target speaker at -3 dB.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DownmixMatrix {
+    /// gains[target][source]
+    gains: Vec<Vec<f32>>,
+}
+
+impl DownmixMatrix {
+    pub fn new(source: &SpeakerLayout, target: &SpeakerLayout) -> Self {
+        Self::with_options(source, target, &DownmixOptions::default())
+    }
+
+    pub fn with_options(
+        source: &SpeakerLayout,
+        target: &SpeakerLayout,
+        options: &DownmixOptions,
+    ) -> Self {
+        let mut gains = vec![vec![0.0; source.speakers.len()]; target.speakers.len()];
+        let find = |role: &SpeakerRole| target.speakers.iter().position(|s| s.role == *role);
+        let front_pair = (
+            find(&SpeakerRole::FrontLeft),
+            find(&SpeakerRole::FrontRight),
+        );
+
+        for (src_idx, speaker) in source.speakers.iter().enumerate() {
+            if speaker.role == SpeakerRole::Center {
+                match (find(&SpeakerRole::Center), front_pair) {
+                    (Some(center), (Some(left), Some(right))) => {
+                        // Constant-power split between the center and the front pair
+                        let spread = options.center_spread.clamp(0.0, 1.0);
+                        gains[center][src_idx] = (1.0 - spread).sqrt();
+                        gains[left][src_idx] = (spread * 0.5).sqrt();
+                        gains[right][src_idx] = (spread * 0.5).sqrt();
+                    }
+                    (Some(center), _) => gains[center][src_idx] = 1.0,
+                    (None, (Some(left), Some(right))) if options.phantom_center => {
+                        gains[left][src_idx] = FOLD_GAIN;
+                        gains[right][src_idx] = FOLD_GAIN;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if let Some(dst_idx) = find(&speaker.role) {
+                gains[dst_idx][src_idx] = 1.0;
+            } else if speaker.role == SpeakerRole::Subwoofer {
+                if let (true, (Some(left), Some(right))) = (options.include_lfe, front_pair) {
+                    gains[left][src_idx] = FOLD_GAIN;
+                    gains[right][src_idx] = FOLD_GAIN;
+                }
+            } else {
+                if let Some(dst_idx) = nearest_full_range(&speaker.position, target) {
+                    gains[dst_idx][src_idx] = FOLD_GAIN;
+                }
+            }
+        }
+
+        Self { gains }
+    }
+
+    pub fn source_channels(&self) -> usize {
+        self.gains.first().map_or(0, Vec::len)
+    }
+
+    pub fn target_channels(&self) -> usize {
+        self.gains.len()
+    }
+
+    /// Gain applied from `source` channel to `target` channel
+    pub fn gain(&self, target: usize, source: usize) -> f32 {
+        self.gains
+            .get(target)
+            .and_then(|row| row.get(source))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Mix source channels into target channels; missing source channels are treated as silence
+    pub fn apply(&self, input: &[Vec<f32>]) -> Vec<Vec<f32>> {
+        let frame_len = input.iter().map(Vec::len).max().unwrap_or(0);
+
+        self.gains
+            .iter()
+            .map(|row| {
+                let mut out = vec![0.0; frame_len];
+                for (gain, channel) in row.iter().zip(input) {
+                    if *gain == 0.0 {
+                        continue;
+                    }
+                    for (o, s) in out.iter_mut().zip(channel) {
+                        *o += gain * s;
+                    }
+                }
+                out
+            })
+            .collect()
+    }
+}
+
+/// Downmix (or remap) a block laid out as `from` onto the speakers of `to`
+/// with the default `DownmixOptions`.
+///
+/// Gains follow ITU-R BS.775: surrounds fold into the nearest front speaker
+/// at -3 dB, a center without a center speaker becomes a phantom image at
+/// -3 dB per side and the LFE is dropped.
+pub fn downmix(input: &AudioBlock, from: &SpeakerLayout, to: &SpeakerLayout) -> AudioBlock {
+    downmix_with_options(input, from, to, &DownmixOptions::default())
+}
+
+/// `downmix` with explicit options, e.g. to keep the LFE in a stereo fold-down
+pub fn downmix_with_options(
+    input: &AudioBlock,
+    from: &SpeakerLayout,
+    to: &SpeakerLayout,
+    options: &DownmixOptions,
+) -> AudioBlock {
+    AudioBlock {
+        sample_rate: input.sample_rate,
+        channels: DownmixMatrix::with_options(from, to, options).apply(&input.channels),
+    }
+}
+
+fn nearest_full_range(position: &Position3, layout: &SpeakerLayout) -> Option<usize> {
+    let distance = |p: &Position3| {
+        (p.x - position.x).powi(2) + (p.y - position.y).powi(2) + (p.z - position.z).powi(2)
+    };
+
+    layout
+        .speakers
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.role != SpeakerRole::Subwoofer)
+        .min_by(|(_, a), (_, b)| distance(&a.position).total_cmp(&distance(&b.position)))
+        .map(|(idx, _)| idx)
+}
+
+/// Gain of the delayed copies fed to surround speakers by `StereoUpmixer` (-6 dB)
+const SURROUND_GAIN: f32 = 0.5;
+
+/// Surround delay in milliseconds for `role`; left and right differ so the
+/// surround pair stays decorrelated
+fn surround_delay_ms(role: &SpeakerRole) -> Option<f32> {
+    match role {
+        SpeakerRole::SideLeft => Some(10.0),
+        SpeakerRole::SideRight => Some(12.0),
+        SpeakerRole::RearLeft => Some(15.0),
+        SpeakerRole::RearRight => Some(18.0),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum UpmixRoute {
+    Left,
+    Right,
+    Center,
+    /// Delayed copy of the left (or right) input
+    Surround {
+        from_right: bool,
+        delay: VecDeque<f32>,
+    },
+    Silent,
+}
+
+/// Matrix upmixer from stereo (or mono) to a speaker layout.
+///
+/// Front L/R pass through, the center carries (L+R)/2 at -3 dB and each
+/// side or rear speaker gets its own side of the input at -6 dB behind a
+/// short delay. LFE and height speakers stay silent; bass management can
+/// feed the subwoofer. Delay state carries over between calls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StereoUpmixer {
+    routes: Vec<UpmixRoute>,
+}
+
+impl StereoUpmixer {
+    pub fn new(target: &SpeakerLayout, sample_rate: u32) -> Self {
+        let routes = target
+            .speakers
+            .iter()
+            .map(|speaker| match speaker.role {
+                SpeakerRole::FrontLeft => UpmixRoute::Left,
+                SpeakerRole::FrontRight => UpmixRoute::Right,
+                SpeakerRole::Center => UpmixRoute::Center,
+                ref role => match surround_delay_ms(role) {
+                    Some(delay_ms) => {
+                        let samples = (sample_rate as f32 * delay_ms / 1000.0).round() as usize;
+                        UpmixRoute::Surround {
+                            from_right: matches!(
+                                role,
+                                SpeakerRole::SideRight | SpeakerRole::RearRight
+                            ),
+                            delay: VecDeque::from(vec![0.0; samples]),
+                        }
+                    }
+                    None => UpmixRoute::Silent,
                 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-        ],
-        "2.1" => vec![
-            SpeakerDescriptor {
-                id: "FL".into(),
-                role: FrontLeft,
-                position: Position3 { x: -0.5, y: 1.0, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "FR".into(),
-                role: FrontRight,
-                position: Position3 { x: 0.5, y: 1.0, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "LFE".into(),
-                role: Subwoofer,
-                position: Position3 { x: 0.0, y: 0.0, z: -0.5 },
-                max_spl_db: 120.0,
-                latency: Duration::ZERO,
-            },
-        ],
-        "3.1" => vec![
-            SpeakerDescriptor {
-                id: "FL".into(),
-                role: FrontLeft,
-                position: Position3 { x: -0.5, y: 1.0, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "FR".into(),
-                role: FrontRight,
-                position: Position3 { x: 0.5, y: 1.0, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "C".into(),
-                role: Center,
-                position: Position3 { x: 0.0, y: 1.0, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "LFE".into(),
-                role: Subwoofer,
-                position: Position3 { x: 0.0, y: 0.0, z: -0.5 },
-                max_spl_db: 120.0,
-                latency: Duration::ZERO,
-            },
-        ],
-        "4.0" | "quad" => vec![
-            SpeakerDescriptor {
-                id: "FL".into(),
-                role: FrontLeft,
-                position: Position3 { x: -0.707, y: 0.707, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "FR".into(),
-                role: FrontRight,
-                position: Position3 { x: 0.707, y: 0.707, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "RL".into(),
-                role: RearLeft,
-                position: Position3 { x: -0.707, y: -0.707, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-            SpeakerDescriptor {
-                id: "RR".into(),
-                role: RearRight,
-                position: Position3 { x: 0.707, y: -0.707, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            },
-        ],
-        "5.1.2" => {
-            let mut spk = layout_from_name("5.1").unwrap().speakers;
-            spk.push(SpeakerDescriptor {
-                id: "TFL".into(),
-                role: TopFrontLeft,
-                position: Position3 { x: -0.5, y: 0.5, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk.push(SpeakerDescriptor {
-                id: "TFR".into(),
-                role: TopFrontRight,
-                position: Position3 { x: 0.5, y: 0.5, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk
+            })
+            .collect();
+
+        Self { routes }
+    }
+
+    pub fn target_channels(&self) -> usize {
+        self.routes.len()
+    }
+
+    /// Upmix `input` to one channel per target speaker.
+    ///
+    /// Only the first two input channels are used; mono input feeds both sides.
+    pub fn process(&mut self, input: &[Vec<f32>]) -> Vec<Vec<f32>> {
+        let frame_len = input.iter().map(Vec::len).max().unwrap_or(0);
+        let silence = vec![0.0; frame_len];
+        let left = input.first().unwrap_or(&silence);
+        let right = input.get(1).unwrap_or(left);
+        let sample = |channel: &[f32], i: usize| channel.get(i).copied().unwrap_or(0.0);
+
+        self.routes
+            .iter_mut()
+            .map(|route| match route {
+                UpmixRoute::Left => (0..frame_len).map(|i| sample(left, i)).collect(),
+                UpmixRoute::Right => (0..frame_len).map(|i| sample(right, i)).collect(),
+                UpmixRoute::Center => (0..frame_len)
+                    .map(|i| FOLD_GAIN * 0.5 * (sample(left, i) + sample(right, i)))
+                    .collect(),
+                UpmixRoute::Surround { from_right, delay } => {
+                    let source = if *from_right { right } else { left };
+                    (0..frame_len)
+                        .map(|i| {
+                            delay.push_back(sample(source, i));
+                            SURROUND_GAIN * delay.pop_front().unwrap_or(0.0)
+                        })
+                        .collect()
+                }
+                UpmixRoute::Silent => vec![0.0; frame_len],
+            })
+            .collect()
+    }
+}
+
+/// Builds a `SpeakerLayout` one speaker at a time.
+///
+/// Adding a speaker whose id is already present replaces it, so a built
+/// layout never holds duplicate ids.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutBuilder {
+    name: String,
+    speakers: Vec<SpeakerDescriptor>,
+}
+
+impl LayoutBuilder {
+    /// Start an empty layout
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            speakers: Vec::new(),
         }
-        "7.1" => {
-            let mut spk = layout_from_name("5.1").unwrap().speakers;
-            spk.push(SpeakerDescriptor {
-                id: "RL".into(),
-                role: RearLeft,
-                position: Position3 { x: -0.707, y: -0.707, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk.push(SpeakerDescriptor {
-                id: "RR".into(),
-                role: RearRight,
-                position: Position3 { x: 0.707, y: -0.707, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk
+    }
+
+    /// Add a speaker with default SPL and no latency
+    pub fn speaker(self, id: impl Into<String>, role: SpeakerRole, position: Position3) -> Self {
+        let max_spl_db = if role == SpeakerRole::Subwoofer {
+            120.0
+        } else {
+            110.0
+        };
+        self.descriptor(SpeakerDescriptor {
+            id: id.into(),
+            role,
+            position,
+            max_spl_db,
+            latency: std::time::Duration::ZERO,
+        })
+    }
+
+    /// Add a fully described speaker
+    pub fn descriptor(mut self, speaker: SpeakerDescriptor) -> Self {
+        match self.speakers.iter_mut().find(|s| s.id == speaker.id) {
+            Some(existing) => *existing = speaker,
+            None => self.speakers.push(speaker),
         }
-        "7.1.4" => {
-            let mut spk = layout_from_name("7.1").unwrap().speakers;
-            spk.push(SpeakerDescriptor {
-                id: "TFL".into(),
-                role: TopFrontLeft,
-                position: Position3 { x: -0.5, y: 0.5, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk.push(SpeakerDescriptor {
-                id: "TFR".into(),
-                role: TopFrontRight,
-                position: Position3 { x: 0.5, y: 0.5, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk.push(SpeakerDescriptor {
-                id: "TRL".into(),
-                role: TopRearLeft,
-                position: Position3 { x: -0.5, y: -0.5, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk.push(SpeakerDescriptor {
-                id: "TRR".into(),
-                role: TopRearRight,
-                position: Position3 { x: 0.5, y: -0.5, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk
+        self
+    }
+
+    /// Finish the layout
+    pub fn build(self) -> SpeakerLayout {
+        SpeakerLayout {
+            name: self.name,
+            speakers: self.speakers,
         }
+    }
+}
+
+/// Layout names understood by `layout_from_name`, smallest first.
+///
+/// "2.0" and "4.0" are also accepted as aliases of "stereo" and "quad".
+const LAYOUTS: &[&str] = &[
+    "stereo", "2.1", "3.1", "quad", "5.1", "5.1.2", "5.1.4", "7.1", "7.1.2", "7.1.4", "9.1.6",
+];
+
+/// Names of every registered layout preset
+pub fn available_layouts() -> Vec<&'static str> {
+    LAYOUTS.to_vec()
+}
+
+fn at(x: f32, y: f32, z: f32) -> Position3 {
+    Position3 { x, y, z }
+}
+
+fn front_pair(layout: LayoutBuilder) -> LayoutBuilder {
+    use crate::SpeakerRole::*;
+    layout
+        .speaker("FL", FrontLeft, at(-0.5, 1.0, 0.0))
+        .speaker("FR", FrontRight, at(0.5, 1.0, 0.0))
+}
+
+fn bed_5_1(layout: LayoutBuilder) -> LayoutBuilder {
+    use crate::SpeakerRole::*;
+    front_pair(layout)
+        .speaker("C", Center, at(0.0, 1.0, 0.0))
+        .speaker("LFE", Subwoofer, at(0.0, 0.0, -0.5))
+        .speaker("SL", SideLeft, at(-1.0, -0.5, 0.0))
+        .speaker("SR", SideRight, at(1.0, -0.5, 0.0))
+}
+
+fn bed_7_1(layout: LayoutBuilder) -> LayoutBuilder {
+    use crate::SpeakerRole::*;
+    bed_5_1(layout)
+        .speaker("RL", RearLeft, at(-0.707, -0.707, 0.0))
+        .speaker("RR", RearRight, at(0.707, -0.707, 0.0))
+}
+
+fn top_front(layout: LayoutBuilder) -> LayoutBuilder {
+    use crate::SpeakerRole::*;
+    layout
+        .speaker("TFL", TopFrontLeft, at(-0.5, 0.5, 1.0))
+        .speaker("TFR", TopFrontRight, at(0.5, 0.5, 1.0))
+}
+
+fn top_rear(layout: LayoutBuilder) -> LayoutBuilder {
+    use crate::SpeakerRole::*;
+    layout
+        .speaker("TRL", TopRearLeft, at(-0.5, -0.5, 1.0))
+        .speaker("TRR", TopRearRight, at(0.5, -0.5, 1.0))
+}
+
+fn top_side(layout: LayoutBuilder) -> LayoutBuilder {
+    layout
+        .speaker(
+            "TSL",
+            SpeakerRole::Custom("TopSideLeft".to_string()),
+            at(-1.0, 0.0, 1.0),
+        )
+        .speaker(
+            "TSR",
+            SpeakerRole::Custom("TopSideRight".to_string()),
+            at(1.0, 0.0, 1.0),
+        )
+}
+
+/// Position of a role in ITU-R BS.2051 channel order: L, R, C, LFE,
+/// side surrounds, rear surrounds, wides, then top front, side and rear.
+/// Unknown custom roles sort last.
+fn canonical_rank(role: &SpeakerRole) -> usize {
+    use crate::SpeakerRole::*;
+    match role {
+        FrontLeft => 0,
+        FrontRight => 1,
+        Center => 2,
+        Subwoofer => 3,
+        SideLeft => 4,
+        SideRight => 5,
+        RearLeft => 6,
+        RearRight => 7,
+        FrontHeightLeft | TopFrontLeft => 10,
+        FrontHeightRight | TopFrontRight => 11,
+        RearHeightLeft | TopRearLeft => 14,
+        RearHeightRight | TopRearRight => 15,
+        Custom(name) => match name.as_str() {
+            "WideLeft" => 8,
+            "WideRight" => 9,
+            "TopSideLeft" => 12,
+            "TopSideRight" => 13,
+            _ => usize::MAX,
+        },
+    }
+}
+
+/// Indices into `layout.speakers`, in canonical channel order.
+///
+/// Speakers with the same rank keep their order in the layout.
+pub fn canonical_order(layout: &SpeakerLayout) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..layout.speakers.len()).collect();
+    order.sort_by_key(|&i| canonical_rank(&layout.speakers[i].role));
+    order
+}
+
+/// Channel of `role` in the canonical channel order of `layout`, e.g.
+/// L, R, C, LFE, Ls, Rs for 5.1. `None` if the layout has no such speaker.
+pub fn channel_index(role: &SpeakerRole, layout: &SpeakerLayout) -> Option<usize> {
+    canonical_order(layout)
+        .into_iter()
+        .position(|i| layout.speakers[i].role == *role)
+}
+
+/// Channel ordering conventions found in multichannel files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChannelOrder {
+    /// Order of `SpeakerLayout::speakers`, as the engine renders
+    Layout,
+    /// ITU-R BS.2051: L, R, C, LFE, side surrounds, rear surrounds (see
+    /// `canonical_order`)
+    Itu,
+    /// SMPTE / WAVE channel mask order: L, R, C, LFE, rear surrounds, then
+    /// side surrounds. Matches `Itu` for layouts without rear speakers.
+    Smpte,
+    /// Film (Pro Tools / SDDS) order: L, C, R, side surrounds, rear
+    /// surrounds, LFE
+    Film,
+}
+
+impl ChannelOrder {
+    /// Indices into `layout.speakers`, one per channel in this order
+    pub fn speaker_indices(self, layout: &SpeakerLayout) -> Vec<usize> {
+        let rank: fn(&SpeakerRole) -> usize = match self {
+            ChannelOrder::Layout => return (0..layout.speakers.len()).collect(),
+            ChannelOrder::Itu => canonical_rank,
+            ChannelOrder::Smpte => smpte_rank,
+            ChannelOrder::Film => film_rank,
+        };
+        let mut order: Vec<usize> = (0..layout.speakers.len()).collect();
+        order.sort_by_key(|&i| rank(&layout.speakers[i].role));
+        order
+    }
+}
+
+fn smpte_rank(role: &SpeakerRole) -> usize {
+    match role {
+        SpeakerRole::SideLeft => 6,
+        SpeakerRole::SideRight => 7,
+        SpeakerRole::RearLeft => 4,
+        SpeakerRole::RearRight => 5,
+        _ => canonical_rank(role),
+    }
+}
+
+fn film_rank(role: &SpeakerRole) -> usize {
+    use crate::SpeakerRole::*;
+    match role {
+        FrontLeft => 0,
+        Center => 1,
+        FrontRight => 2,
+        SideLeft => 3,
+        SideRight => 4,
+        RearLeft => 5,
+        RearRight => 6,
+        Subwoofer => 7,
+        _ => canonical_rank(role),
+    }
+}
+
+/// Copy of `block` with its channels moved from `from` order to `to` order
+/// for the speakers of `layout`; see `reorder_channels_in_place`.
+pub fn reorder_channels(
+    block: &AudioBlock,
+    from: ChannelOrder,
+    to: ChannelOrder,
+    layout: &SpeakerLayout,
+) -> AudioBlock {
+    let mut block = block.clone();
+    reorder_channels_in_place(&mut block, from, to, layout);
+    block
+}
+
+/// Permute the channels of `block` from `from` order to `to` order for the
+/// speakers of `layout`.
+///
+/// Only the channel vectors move; samples are not copied. Channels beyond
+/// the layout keep their index, and speakers the block has no channel for
+/// come out silent.
+pub fn reorder_channels_in_place(
+    block: &mut AudioBlock,
+    from: ChannelOrder,
+    to: ChannelOrder,
+    layout: &SpeakerLayout,
+) {
+    if from == to {
+        return;
+    }
+
+    // Where each speaker sits in the incoming block
+    let mut source = vec![0; layout.speakers.len()];
+    for (channel, speaker) in from.speaker_indices(layout).into_iter().enumerate() {
+        source[speaker] = channel;
+    }
+
+    let frames = block.frame_len();
+    let mut channels = std::mem::take(&mut block.channels);
+    let extra = channels.split_off(channels.len().min(layout.speakers.len()));
+    block.channels = to
+        .speaker_indices(layout)
+        .into_iter()
+        .map(|speaker| match channels.get_mut(source[speaker]) {
+            Some(channel) => std::mem::take(channel),
+            None => vec![0.0; frames],
+        })
+        .chain(extra)
+        .collect();
+}
+
+/// Map ITU layout names to speaker descriptors.
+pub fn layout_from_name(name: &str) -> Option<SpeakerLayout> {
+    use crate::SpeakerRole::*;
+
+    let layout = LayoutBuilder::new(name);
+    let layout = match name {
+        "2.0" | "stereo" => front_pair(layout),
+        "2.1" => front_pair(layout).speaker("LFE", Subwoofer, at(0.0, 0.0, -0.5)),
+        "3.1" => front_pair(layout)
+            .speaker("C", Center, at(0.0, 1.0, 0.0))
+            .speaker("LFE", Subwoofer, at(0.0, 0.0, -0.5)),
+        "4.0" | "quad" => layout
+            .speaker("FL", FrontLeft, at(-0.707, 0.707, 0.0))
+            .speaker("FR", FrontRight, at(0.707, 0.707, 0.0))
+            .speaker("RL", RearLeft, at(-0.707, -0.707, 0.0))
+            .speaker("RR", RearRight, at(0.707, -0.707, 0.0)),
+        "5.1" => bed_5_1(layout),
+        "5.1.2" => top_front(bed_5_1(layout)),
+        // Atmos beds
+        "5.1.4" => top_rear(top_front(bed_5_1(layout))),
+        "7.1" => bed_7_1(layout),
+        "7.1.2" => top_side(bed_7_1(layout)),
+        "7.1.4" => top_rear(top_front(bed_7_1(layout))),
         "9.1.6" => {
-            let mut spk = layout_from_name("7.1.4").unwrap().speakers;
-            // Add wide left/right at ±60 degrees
-            spk.push(SpeakerDescriptor {
-                id: "WL".into(),
-                role: Custom("WideLeft".to_string()),
-                position: Position3 { x: -0.866, y: 0.5, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk.push(SpeakerDescriptor {
-                id: "WR".into(),
-                role: Custom("WideRight".to_string()),
-                position: Position3 { x: 0.866, y: 0.5, z: 0.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            // Add top side left/right
-            spk.push(SpeakerDescriptor {
-                id: "TSL".into(),
-                role: Custom("TopSideLeft".to_string()),
-                position: Position3 { x: -1.0, y: 0.0, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk.push(SpeakerDescriptor {
-                id: "TSR".into(),
-                role: Custom("TopSideRight".to_string()),
-                position: Position3 { x: 1.0, y: 0.0, z: 1.0 },
-                max_spl_db: 110.0,
-                latency: Duration::ZERO,
-            });
-            spk
+            // Wide left/right at ±60 degrees, then top side left/right
+            let layout = top_rear(top_front(bed_7_1(layout)))
+                .speaker("WL", Custom("WideLeft".to_string()), at(-0.866, 0.5, 0.0))
+                .speaker("WR", Custom("WideRight".to_string()), at(0.866, 0.5, 0.0));
+            top_side(layout)
         }
         _ => return None,
     };
 
-    Some(SpeakerLayout {
-        name: name.into(),
-        speakers,
-    })
+    Some(layout.build())
 }
@@ -3,7 +3,7 @@
 //! UDP/RTP networking with mDNS discovery for wireless speaker transport
 
 use crate::fec::{LossStatistics, XorFec};
-use crate::transport::RtpPacket;
+use crate::transport::{RtpPacket, TransportReceiver, TransportSender};
 use crate::AudioBlock;
 use std::net::{SocketAddr, UdpSocket};
 use std::sync::{Arc, Mutex};
@@ -140,6 +140,86 @@ impl UdpRtpReceiver {
     }
 }
 
+/// RTP over UDP behind the `TransportSender`/`TransportReceiver` interface,
+/// a drop-in for `LoopbackTransport` that talks to one peer and ignores
+/// datagrams from any other address
+pub struct UdpRtpTransport {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    buffer: Vec<u8>,
+}
+
+impl UdpRtpTransport {
+    /// Bind `bind_addr` and send to `peer`. `poll` does not block until a
+    /// timeout is set with `set_poll_timeout`.
+    pub fn new(bind_addr: &str, peer: SocketAddr) -> Result<Self, NetworkError> {
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            peer,
+            buffer: vec![0u8; 65536], // Max UDP packet size
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetworkError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    pub fn set_peer(&mut self, peer: SocketAddr) {
+        self.peer = peer;
+    }
+
+    /// How long `poll` waits for a packet; `None` returns immediately
+    pub fn set_poll_timeout(&self, timeout: Option<Duration>) -> Result<(), NetworkError> {
+        match timeout {
+            Some(timeout) => {
+                self.socket.set_nonblocking(false)?;
+                self.socket.set_read_timeout(Some(timeout))?;
+            }
+            None => self.socket.set_nonblocking(true)?,
+        }
+        Ok(())
+    }
+}
+
+impl TransportSender for UdpRtpTransport {
+    fn send(&mut self, packet: RtpPacket) -> anyhow::Result<()> {
+        self.socket.send_to(&packet.serialize(), self.peer)?;
+        Ok(())
+    }
+}
+
+impl TransportReceiver for UdpRtpTransport {
+    fn poll(&mut self) -> anyhow::Result<Option<RtpPacket>> {
+        // Datagrams from anyone but the peer are dropped, not handed on
+        let len = loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((len, addr)) if addr == self.peer => break len,
+                Ok(_) => continue,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return Ok(None)
+                }
+                Err(e) => return Err(NetworkError::Io(e).into()),
+            }
+        };
+
+        RtpPacket::deserialize(&self.buffer[..len])
+            .map(Some)
+            .ok_or_else(|| anyhow::Error::from(NetworkError::InvalidPacket))
+    }
+}
+
 /// Speaker information for discovery
 #[derive(Clone, Debug, PartialEq)]
 pub struct SpeakerInfo {
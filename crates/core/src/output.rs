@@ -12,8 +12,13 @@
 //! - `OutputDevice`: Device information and capabilities
 //! - `PlaybackStream`: Trait for implementing playback backends (ALSA, PulseAudio)
 //! - `OutputManager`: Main interface for device enumeration and stream setup
+//! - `write_wav` / `WavWriter`: bounce audio to a WAV file for offline rendering
 
+use crate::AudioBlock;
 use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -158,6 +163,11 @@ pub trait PlaybackStream: Send + Sync {
 
     /// Write audio frames to device \[channel\]\[sample\]
     fn write(&mut self, data: &[Vec<f32>]) -> Result<(), OutputError>;
+
+    /// Frames written but not yet played
+    fn buffered_frames(&self) -> usize {
+        0
+    }
 }
 
 /// Playback stream status
@@ -300,6 +310,169 @@ impl Default for OutputManager {
     }
 }
 
+/// Sample encoding written by `write_wav`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BitDepth {
+    /// 16-bit signed PCM
+    Int16,
+    /// 24-bit signed PCM
+    Int24,
+    /// 32-bit IEEE float
+    Float32,
+}
+
+impl BitDepth {
+    pub fn bits(&self) -> u16 {
+        match self {
+            BitDepth::Int16 => 16,
+            BitDepth::Int24 => 24,
+            BitDepth::Float32 => 32,
+        }
+    }
+
+    fn encode(&self, sample: f32, out: &mut Vec<u8>) {
+        match self {
+            BitDepth::Int16 => {
+                let value = (sample * 32768.0).round().clamp(-32768.0, 32767.0) as i16;
+                out.extend_from_slice(&value.to_le_bytes());
+            }
+            BitDepth::Int24 => {
+                let value = (sample * 8_388_608.0)
+                    .round()
+                    .clamp(-8_388_608.0, 8_388_607.0) as i32;
+                out.extend_from_slice(&value.to_le_bytes()[..3]);
+            }
+            BitDepth::Float32 => out.extend_from_slice(&sample.to_le_bytes()),
+        }
+    }
+}
+
+/// Write `block` to a WAV file at its sample rate, interleaving the channels
+///
+/// PCM samples are clipped to full scale; float samples are written as-is.
+/// The result reads back with `input::load_wav`.
+pub fn write_wav<P: AsRef<Path>>(
+    path: P,
+    block: &AudioBlock,
+    bit_depth: BitDepth,
+) -> Result<(), OutputError> {
+    block
+        .validate()
+        .map_err(|e| OutputError::InvalidFormat(e.to_string()))?;
+    let mut writer = WavWriter::create(path, block.channels.len(), block.sample_rate, bit_depth)?;
+    writer.write(block)?;
+    writer.finish()
+}
+
+/// Byte offset of the RIFF chunk size in the header `WavWriter` writes
+const RIFF_SIZE_OFFSET: u64 = 4;
+/// Byte offset of the data chunk size in the header `WavWriter` writes
+const DATA_SIZE_OFFSET: u64 = 40;
+
+/// WAV file written a block at a time, so long renders never have to be
+/// held in memory
+///
+/// Samples are encoded as in `write_wav`. The header's chunk sizes are
+/// filled in by `finish`; a writer dropped without it leaves them at zero.
+pub struct WavWriter {
+    out: BufWriter<File>,
+    channels: usize,
+    bit_depth: BitDepth,
+    data_size: u64,
+}
+
+impl WavWriter {
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        channels: usize,
+        sample_rate: u32,
+        bit_depth: BitDepth,
+    ) -> Result<Self, OutputError> {
+        let channel_count = u16::try_from(channels)
+            .ok()
+            .filter(|&c| c > 0)
+            .ok_or_else(|| {
+                OutputError::InvalidFormat(format!("cannot write {} channels to WAV", channels))
+            })?;
+        let block_align = channel_count * bit_depth.bits() / 8;
+        let format_tag: u16 = match bit_depth {
+            BitDepth::Float32 => 3,
+            _ => 1,
+        };
+
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(b"RIFF")?;
+        out.write_all(&36u32.to_le_bytes())?;
+        out.write_all(b"WAVEfmt ")?;
+        out.write_all(&16u32.to_le_bytes())?;
+        out.write_all(&format_tag.to_le_bytes())?;
+        out.write_all(&channel_count.to_le_bytes())?;
+        out.write_all(&sample_rate.to_le_bytes())?;
+        out.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        out.write_all(&block_align.to_le_bytes())?;
+        out.write_all(&bit_depth.bits().to_le_bytes())?;
+        out.write_all(b"data")?;
+        out.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            out,
+            channels,
+            bit_depth,
+            data_size: 0,
+        })
+    }
+
+    /// Append the frames of `block`, which must have the writer's channel count
+    pub fn write(&mut self, block: &AudioBlock) -> Result<(), OutputError> {
+        if block.channels.len() != self.channels {
+            return Err(OutputError::InvalidFormat(format!(
+                "block has {} channels, the WAV file {}",
+                block.channels.len(),
+                self.channels
+            )));
+        }
+
+        let bytes = block.frame_len() as u64 * self.frame_bytes();
+        if self.data_size + bytes >= (u32::MAX - 36) as u64 {
+            return Err(OutputError::InvalidFormat("audio too long for WAV".into()));
+        }
+
+        let mut data = Vec::with_capacity(bytes as usize);
+        for sample in block.to_interleaved() {
+            self.bit_depth.encode(sample, &mut data);
+        }
+        self.out.write_all(&data)?;
+        self.data_size += bytes;
+        Ok(())
+    }
+
+    /// Frames written so far
+    pub fn frames(&self) -> u64 {
+        self.data_size / self.frame_bytes()
+    }
+
+    fn frame_bytes(&self) -> u64 {
+        self.channels as u64 * self.bit_depth.bits() as u64 / 8
+    }
+
+    /// Fill in the chunk sizes and close the file
+    pub fn finish(mut self) -> Result<(), OutputError> {
+        let data_size = self.data_size as u32;
+        // 24-bit data with an odd channel count needs a pad byte
+        let pad = data_size % 2;
+        if pad == 1 {
+            self.out.write_all(&[0])?;
+        }
+
+        self.out.seek(SeekFrom::Start(RIFF_SIZE_OFFSET))?;
+        self.out.write_all(&(36 + data_size + pad).to_le_bytes())?;
+        self.out.seek(SeekFrom::Start(DATA_SIZE_OFFSET))?;
+        self.out.write_all(&data_size.to_le_bytes())?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
 // ===== cpal Backend for Real Device Enumeration =====
 #[cfg(feature = "audio-backends")]
 fn cpal_enumerate_output_devices() -> Result<Vec<OutputDevice>, OutputError> {
@@ -579,12 +752,111 @@ impl PlaybackStream for CpalPlaybackStream {
 
         Ok(())
     }
+
+    fn buffered_frames(&self) -> usize {
+        self.ring
+            .lock()
+            .map(|ring| ring.len() / self.channels.max(1) as usize)
+            .unwrap_or(0)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn round_trip(name: &str, block: &AudioBlock, bit_depth: BitDepth) -> AudioBlock {
+        let path = std::env::temp_dir().join(format!("{}-{}.wav", name, std::process::id()));
+        write_wav(&path, block, bit_depth).unwrap();
+        let read = crate::input::load_wav(&path).unwrap();
+        std::fs::remove_file(path).ok();
+        read
+    }
+
+    #[test]
+    fn test_write_wav_round_trips_through_load_wav() {
+        let block = AudioBlock {
+            sample_rate: 44100,
+            channels: vec![
+                (0..480).map(|n| (n as f32 * 0.05).sin() * 0.9).collect(),
+                (0..480).map(|n| (n as f32 * 0.013).cos() * -0.5).collect(),
+            ],
+        };
+
+        for (bit_depth, tolerance) in [
+            (BitDepth::Int16, 1.0 / 32768.0),
+            (BitDepth::Int24, 1.0 / 8_388_608.0),
+            (BitDepth::Float32, 0.0),
+        ] {
+            let read = round_trip("audio-ninja-write-wav", &block, bit_depth);
+            assert_eq!(read.sample_rate, 44100);
+            assert_eq!(read.channels.len(), 2);
+            assert_eq!(read.frame_len(), 480);
+            for (written, back) in block
+                .channels
+                .iter()
+                .flatten()
+                .zip(read.channels.iter().flatten())
+            {
+                assert!(
+                    (written - back).abs() <= tolerance,
+                    "{:?}: {} vs {}",
+                    bit_depth,
+                    written,
+                    back
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_wav_clips_pcm_and_rejects_ragged_blocks() {
+        let block = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![1.5, -1.5, 1.0]],
+        };
+        let read = round_trip("audio-ninja-write-clip", &block, BitDepth::Int24);
+        assert_eq!(
+            read.channels[0],
+            vec![8_388_607.0 / 8_388_608.0, -1.0, 8_388_607.0 / 8_388_608.0]
+        );
+
+        let ragged = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![vec![0.0; 4], vec![0.0; 3]],
+        };
+        let path = std::env::temp_dir().join("audio-ninja-write-ragged.wav");
+        assert!(matches!(
+            write_wav(&path, &ragged, BitDepth::Int16),
+            Err(OutputError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_wav_writer_streams_blocks_into_one_file() {
+        let path =
+            std::env::temp_dir().join(format!("audio-ninja-wav-writer-{}.wav", std::process::id()));
+        let block = |start: usize| AudioBlock {
+            sample_rate: 48000,
+            channels: vec![(start..start + 100).map(|n| n as f32 / 1000.0).collect(); 3],
+        };
+
+        let mut writer = WavWriter::create(&path, 3, 48000, BitDepth::Int24).unwrap();
+        writer.write(&block(0)).unwrap();
+        writer.write(&block(100)).unwrap();
+        assert!(matches!(
+            writer.write(&AudioBlock::silence(2, 10, 48000)),
+            Err(OutputError::InvalidFormat(_))
+        ));
+        assert_eq!(writer.frames(), 200);
+        writer.finish().unwrap();
+
+        let read = crate::input::load_wav(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(read.frame_len(), 200);
+        assert!((read.channels[2][150] - 0.15).abs() < 1e-6);
+    }
+
     #[test]
     fn test_output_device_creation() {
         let device = OutputDevice::new(
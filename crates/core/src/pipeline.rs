@@ -1,8 +1,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::dsp::{BassManager, BiquadCascade, BiquadDesign, BiquadFilter, ParametricEq};
+use crate::dspconfig::{DspChainConfig, DspConfigError, StageConfig};
 use crate::ffmpeg::{Decoder, DemuxConfig, Demuxer};
+use crate::hrtf::{BinauralRenderer, HeadphoneProfile, HrtfPosition};
 use crate::iamf::{IamfMetadata, IamfRenderBlock, IamfStreamConfig};
+use crate::loudness::{DynamicRangeControl, HeadroomManager, LoudnessNormalizer};
 use crate::AudioBlock;
+use std::time::Duration;
 
 #[derive(Debug, thiserror::Error)]
 pub enum PipelineError {
@@ -12,6 +17,374 @@ pub enum PipelineError {
     Decode(String),
     #[error("not initialized")]
     NotInitialized,
+    #[error("pipeline latency {latency:?} exceeds budget {max:?}")]
+    LatencyExceeded { latency: Duration, max: Duration },
+}
+
+/// A block-processing stage in a DSP chain
+pub trait AudioStage: Send + Sync {
+    /// Short identifier used in reports and errors
+    fn name(&self) -> &'static str;
+
+    /// Process a block in place
+    fn process(&mut self, block: &mut AudioBlock);
+
+    /// Delay this stage adds to the signal path, in samples
+    fn latency_samples(&self) -> usize {
+        0
+    }
+
+    /// Serializable description of this stage, if it has one
+    fn config(&self, _sample_rate: u32) -> Option<StageConfig> {
+        None
+    }
+}
+
+/// Ordered chain of `AudioStage`s sharing one sample rate
+pub struct Pipeline {
+    sample_rate: u32,
+    stages: Vec<Box<dyn AudioStage>>,
+}
+
+impl Pipeline {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            stages: Vec::new(),
+        }
+    }
+
+    /// Append a stage to the end of the chain
+    pub fn add_stage<S: AudioStage + 'static>(&mut self, stage: S) {
+        self.stages.push(Box::new(stage));
+    }
+
+    /// Builder form of `add_stage`
+    pub fn with_stage<S: AudioStage + 'static>(mut self, stage: S) -> Self {
+        self.add_stage(stage);
+        self
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Rebuild every stage for a new sample rate.
+    ///
+    /// Times and frequencies are kept, so filter coefficients and
+    /// attack/release/lookahead lengths are recomputed; stage state such as
+    /// envelopes and filter history starts fresh. Fails, leaving the chain
+    /// untouched, if a stage has no `StageConfig` form.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) -> Result<(), DspConfigError> {
+        let mut config = DspChainConfig::from_pipeline(self)?;
+        config.sample_rate = sample_rate;
+        *self = config.build();
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Stages in processing order
+    pub fn stages(&self) -> impl Iterator<Item = &dyn AudioStage> {
+        self.stages.iter().map(|s| s.as_ref())
+    }
+
+    /// Stage names in processing order
+    pub fn stage_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.name()).collect()
+    }
+
+    /// Run every stage over the block, in order
+    pub fn process(&mut self, block: &mut AudioBlock) {
+        for stage in &mut self.stages {
+            stage.process(block);
+        }
+    }
+
+    /// Sum of per-stage latencies, in samples
+    pub fn total_latency_samples(&self) -> usize {
+        self.stages.iter().map(|s| s.latency_samples()).sum()
+    }
+
+    /// Sum of per-stage latencies at the pipeline sample rate
+    pub fn total_latency(&self) -> Duration {
+        if self.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.total_latency_samples() as f64 / self.sample_rate as f64)
+    }
+
+    /// Check the chain against a latency budget such as `RenderOptions::max_latency`
+    pub fn validate(&self, max: Duration) -> Result<(), PipelineError> {
+        let latency = self.total_latency();
+        if latency > max {
+            return Err(PipelineError::LatencyExceeded { latency, max });
+        }
+        Ok(())
+    }
+}
+
+impl AudioStage for DynamicRangeControl {
+    fn name(&self) -> &'static str {
+        "drc"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        DynamicRangeControl::process(self, block);
+    }
+
+    fn config(&self, _sample_rate: u32) -> Option<StageConfig> {
+        Some(StageConfig::Drc {
+            ratio: self.ratio(),
+            threshold_db: self.threshold_db(),
+            attack_ms: self.attack_ms(),
+            release_ms: self.release_ms(),
+            makeup_gain_db: self.makeup_gain_db(),
+            knee_db: self.knee_db(),
+            stereo_link: self.stereo_link(),
+        })
+    }
+}
+
+impl AudioStage for HeadroomManager {
+    fn name(&self) -> &'static str {
+        "limiter"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        self.apply_limiting(block);
+    }
+
+    fn latency_samples(&self) -> usize {
+        self.lookahead_samples()
+    }
+
+    fn config(&self, _sample_rate: u32) -> Option<StageConfig> {
+        Some(StageConfig::Headroom {
+            headroom_db: self.target_headroom_db(),
+            lookahead_ms: self.lookahead_ms(),
+            true_peak: self.is_true_peak(),
+        })
+    }
+}
+
+impl AudioStage for LoudnessNormalizer {
+    fn name(&self) -> &'static str {
+        "loudness"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        self.normalize(block);
+    }
+
+    fn config(&self, _sample_rate: u32) -> Option<StageConfig> {
+        Some(StageConfig::Loudness {
+            target_lufs: self.target_loudness_lufs(),
+        })
+    }
+}
+
+impl AudioStage for ParametricEq {
+    fn name(&self) -> &'static str {
+        "peq"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        ParametricEq::process(self, block);
+    }
+
+    fn config(&self, _sample_rate: u32) -> Option<StageConfig> {
+        Some(StageConfig::Peq {
+            bands: self.bands().to_vec(),
+        })
+    }
+}
+
+impl AudioStage for BassManager {
+    fn name(&self) -> &'static str {
+        "bass_management"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        BassManager::process(self, block);
+    }
+}
+
+/// A single biquad run over every channel, each with its own filter state
+///
+/// Stages built with `designed` keep their design parameters and so can be
+/// described as `StageConfig::Biquad`; ones built from raw coefficients
+/// cannot, since those only hold at one sample rate.
+pub struct BiquadStage {
+    filter: BiquadFilter,
+    design: Option<BiquadDesign>,
+    channels: Vec<BiquadFilter>,
+}
+
+impl BiquadStage {
+    pub fn new(filter: BiquadFilter) -> Self {
+        Self {
+            filter,
+            design: None,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Stage running `design` at `sample_rate`
+    pub fn designed(design: BiquadDesign, sample_rate: u32) -> Self {
+        Self {
+            design: Some(design),
+            ..Self::new(design.design(sample_rate))
+        }
+    }
+
+    pub fn filter(&self) -> &BiquadFilter {
+        &self.filter
+    }
+
+    pub fn design(&self) -> Option<BiquadDesign> {
+        self.design
+    }
+}
+
+impl From<BiquadFilter> for BiquadStage {
+    fn from(filter: BiquadFilter) -> Self {
+        Self::new(filter)
+    }
+}
+
+impl AudioStage for BiquadStage {
+    fn name(&self) -> &'static str {
+        "biquad"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        while self.channels.len() < block.channels.len() {
+            let mut filter = self.filter.clone();
+            filter.reset();
+            self.channels.push(filter);
+        }
+        for (filter, samples) in self.channels.iter_mut().zip(&mut block.channels) {
+            filter.process_block(samples);
+        }
+    }
+
+    fn config(&self, _sample_rate: u32) -> Option<StageConfig> {
+        self.design.map(|filter| StageConfig::Biquad { filter })
+    }
+}
+
+/// Binaural downmix to headphones as a pipeline stage
+///
+/// All channels are averaged to mono and rendered at one `HrtfPosition`.
+/// The full convolution tail (impulse response length plus ITD, minus one)
+/// is carried into the next block and the headphone EQ keeps its state, so
+/// the output keeps the frame count of the input and matches rendering the
+/// whole signal at once.
+pub struct BinauralStage {
+    renderer: BinauralRenderer,
+    position: HrtfPosition,
+    /// Left and right ear output still owed to the next block
+    tails: [Vec<f32>; 2],
+    /// Headphone EQ for each ear, continuing across blocks
+    eq: [BiquadCascade; 2],
+}
+
+impl BinauralStage {
+    pub fn new(renderer: BinauralRenderer, position: HrtfPosition) -> Self {
+        let sample_rate = renderer.database().sample_rate();
+        let eq = BiquadCascade::new(renderer.headphone_profile().filters(sample_rate));
+        Self {
+            renderer,
+            position,
+            tails: [Vec::new(), Vec::new()],
+            eq: [eq.clone(), eq],
+        }
+    }
+
+    pub fn set_position(&mut self, position: HrtfPosition) {
+        self.position = position;
+    }
+}
+
+impl AudioStage for BinauralStage {
+    fn name(&self) -> &'static str {
+        "binaural"
+    }
+
+    fn process(&mut self, block: &mut AudioBlock) {
+        if block.channels.is_empty() {
+            return;
+        }
+
+        let frames = block.frame_len();
+        let count = block.channels.len() as f32;
+        let mono: Vec<f32> = (0..frames)
+            .map(|i| {
+                block
+                    .channels
+                    .iter()
+                    .filter_map(|ch| ch.get(i))
+                    .sum::<f32>()
+                    / count
+            })
+            .collect();
+
+        let Ok((left, right)) = self.renderer.render_dry(&mono, &self.position) else {
+            return;
+        };
+
+        let mut ears = Vec::with_capacity(2);
+        for ((mut ear, tail), eq) in [left, right]
+            .into_iter()
+            .zip(&mut self.tails)
+            .zip(&mut self.eq)
+        {
+            if ear.len() < tail.len() {
+                ear.resize(tail.len(), 0.0);
+            }
+            for (sample, owed) in ear.iter_mut().zip(tail.iter()) {
+                *sample += owed;
+            }
+            if ear.len() < frames {
+                ear.resize(frames, 0.0);
+            }
+            *tail = ear.split_off(frames);
+            eq.process_block(&mut ear);
+            ears.push(ear);
+        }
+        block.channels = ears;
+    }
+
+    /// Delay of the later ear at the current position, i.e. the ITD
+    fn latency_samples(&self) -> usize {
+        self.renderer
+            .database()
+            .get_response(&self.position)
+            .map_or(0, |hrtf| hrtf.delay_left.max(hrtf.delay_right))
+    }
+
+    fn config(&self, _sample_rate: u32) -> Option<StageConfig> {
+        let database = self.renderer.database();
+        let profile = self.renderer.headphone_profile();
+        if !database.is_builtin() || matches!(profile, HeadphoneProfile::Custom(_)) {
+            return None;
+        }
+        Some(StageConfig::Binaural {
+            dataset: database.dataset(),
+            profile,
+            azimuth: self.position.azimuth,
+            elevation: self.position.elevation,
+            distance: self.position.distance,
+            near_field_compensation: database.near_field_compensation(),
+        })
+    }
 }
 
 pub struct IamfPipeline<D: Demuxer, C: Decoder> {
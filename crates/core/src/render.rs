@@ -1,8 +1,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::calibration::CalibrationSolution;
+use crate::dsp::{BassManager, BiquadCascade, BiquadFilter};
 use crate::hrtf::{BinauralRenderer, HeadphoneProfile, HrtfDatabase, HrtfDataset, HrtfPosition};
-use crate::loudness::{DynamicRangeControl, HeadroomManager, LoudnessNormalizer, LoudnessTarget};
-use crate::{AudioBlock, SpeakerLayout};
+use crate::loudness::{
+    DynamicRangeControl, HeadroomManager, LoudnessMeter, LoudnessNormalizer, LoudnessTarget,
+};
+use crate::mapping::{upmix_channels, StereoUpmixer};
+use crate::vbap::{Speaker3D, Vbap2D, Vbap3D, Vec3};
+use crate::{AudioBlock, Position3, SpeakerLayout, SpeakerRole};
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// DRC compression presets for common use cases
@@ -27,6 +34,26 @@ impl DRCPreset {
     }
 }
 
+/// DRC applied when `RenderOptions::enable_drc` is set without a configured compressor
+pub const DEFAULT_DRC_PRESET: DRCPreset = DRCPreset::Music;
+
+/// Limiter lookahead of a new `ReferenceRenderer`, in milliseconds
+const DEFAULT_LOOKAHEAD_MS: f32 = 3.0;
+
+/// How long a speaker's output crossfades between old and new EQ filters
+pub const PEQ_CROSSFADE: Duration = Duration::from_millis(20);
+
+/// How `ReferenceRenderer` maps input channels onto `RenderOptions::target_layout`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Channels are rendered as they come in
+    #[default]
+    Passthrough,
+    /// Input with fewer channels than the target layout is upmixed to one
+    /// channel per speaker; mono and stereo go through `StereoUpmixer`
+    Upmix,
+}
+
 /// Options for audio rendering
 #[derive(Clone, Debug, PartialEq)]
 pub struct RenderOptions {
@@ -40,6 +67,8 @@ pub struct RenderOptions {
     pub target_loudness: Option<LoudnessTarget>,
     /// Enable dynamic range compression
     pub enable_drc: bool,
+    /// Channel routing onto the target layout
+    pub mode: RenderMode,
 }
 
 impl Default for RenderOptions {
@@ -53,6 +82,37 @@ impl Default for RenderOptions {
             max_latency: Duration::from_millis(100),
             target_loudness: Some(LoudnessTarget::StreamingMusic),
             enable_drc: false,
+            mode: RenderMode::Passthrough,
+        }
+    }
+}
+
+/// Processing state after the most recent `ReferenceRenderer::render` call
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderMetrics {
+    /// DRC gain change in dB (0.0 when idle or disabled, negative when compressing)
+    pub drc_reduction_db: f32,
+    /// Limiter gain change in dB (negative while limiting)
+    pub limiter_reduction_db: f32,
+    /// Whether the limiter was reducing gain at the end of the block
+    pub limiter_active: bool,
+    /// Loudness of the block as it entered the renderer, in LUFS
+    pub input_lufs: f32,
+    /// Loudness of the rendered block, in LUFS
+    pub output_lufs: f32,
+    /// Sample peak of the rendered block, in dBFS
+    pub peak_db: f32,
+}
+
+impl Default for RenderMetrics {
+    fn default() -> Self {
+        Self {
+            drc_reduction_db: 0.0,
+            limiter_reduction_db: 0.0,
+            limiter_active: false,
+            input_lufs: f32::NEG_INFINITY,
+            output_lufs: f32::NEG_INFINITY,
+            peak_db: f32::NEG_INFINITY,
         }
     }
 }
@@ -63,6 +123,179 @@ pub trait Renderer {
     fn render(&mut self, input: AudioBlock, opts: &RenderOptions) -> AudioBlock;
 }
 
+/// Delay, gain trim and parametric EQ for one output speaker
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SpeakerCorrection {
+    pub delay: Duration,
+    pub trim_db: f32,
+    pub peq: Vec<BiquadFilter>,
+}
+
+/// Old EQ kept running while the output fades over to a new one
+#[derive(Clone, Debug)]
+struct EqCrossfade {
+    previous: BiquadCascade,
+    elapsed: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+struct SpeakerChannel {
+    correction: SpeakerCorrection,
+    eq: BiquadCascade,
+    crossfade: Option<EqCrossfade>,
+    /// Tail of the previous block, one sample longer than the whole-sample delay
+    history: Vec<f32>,
+}
+
+impl SpeakerChannel {
+    fn set_correction(&mut self, correction: SpeakerCorrection) {
+        if correction.peq != self.correction.peq {
+            let next = BiquadCascade::new(correction.peq.clone());
+            let previous = std::mem::replace(&mut self.eq, next);
+            self.crossfade = Some(EqCrossfade {
+                previous,
+                elapsed: 0,
+            });
+        }
+        self.correction = correction;
+    }
+
+    fn process(&mut self, samples: &mut [f32], sample_rate: u32) {
+        self.apply_eq(samples, sample_rate);
+        self.apply_delay_and_trim(samples, sample_rate);
+    }
+
+    fn apply_eq(&mut self, samples: &mut [f32], sample_rate: u32) {
+        let Some(fade) = &mut self.crossfade else {
+            self.eq.process_block(samples);
+            return;
+        };
+
+        let mut faded_out = samples.to_vec();
+        fade.previous.process_block(&mut faded_out);
+        self.eq.process_block(samples);
+
+        let length = ((PEQ_CROSSFADE.as_secs_f32() * sample_rate as f32) as usize).max(1);
+        for (sample, old) in samples.iter_mut().zip(&faded_out) {
+            let t = (fade.elapsed as f32 / length as f32).min(1.0);
+            *sample = old + (*sample - old) * t;
+            fade.elapsed += 1;
+        }
+        if fade.elapsed >= length {
+            self.crossfade = None;
+        }
+    }
+
+    fn apply_delay_and_trim(&mut self, samples: &mut [f32], sample_rate: u32) {
+        // f64 so whole-sample delays such as 1 ms at 48 kHz land exactly
+        let delay = self.correction.delay.as_secs_f64() * sample_rate as f64;
+        let whole = delay.floor() as usize;
+        let frac = (delay - whole as f64) as f32;
+        let gain = 10f32.powf(self.correction.trim_db / 20.0);
+
+        let taps = whole + 1;
+        if self.history.len() < taps {
+            let missing = taps - self.history.len();
+            self.history.splice(0..0, vec![0.0; missing]);
+        } else {
+            self.history.drain(..self.history.len() - taps);
+        }
+
+        let mut line = std::mem::take(&mut self.history);
+        line.extend_from_slice(samples);
+        for (i, out) in samples.iter_mut().enumerate() {
+            // Linear interpolation between the two samples straddling the delay
+            let newer = line[taps + i - whole];
+            let older = line[i];
+            *out = (newer + (older - newer) * frac) * gain;
+        }
+        self.history = line.split_off(line.len() - taps);
+    }
+
+    fn reset(&mut self) {
+        self.eq.reset();
+        self.crossfade = None;
+        self.history.clear();
+    }
+}
+
+/// Final render stage applying parametric EQ, a fractional-sample delay and a
+/// gain trim per output speaker, keyed by speaker id.
+///
+/// Changing a speaker's EQ while audio runs crossfades from the old filters
+/// to the new ones over `PEQ_CROSSFADE`, so live updates don't click.
+#[derive(Clone, Debug, Default)]
+pub struct SpeakerProcessor {
+    speakers: HashMap<String, SpeakerChannel>,
+}
+
+impl SpeakerProcessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build from a calibration solution whose delays and trims are in `layout` speaker order.
+    /// The solution's room EQ is shared by every speaker.
+    pub fn from_solution(layout: &SpeakerLayout, solution: &CalibrationSolution) -> Self {
+        let mut processor = Self::new();
+        for (ch, speaker) in layout.speakers.iter().enumerate() {
+            processor.set_correction(
+                speaker.id.clone(),
+                SpeakerCorrection {
+                    delay: solution.delays.get(ch).copied().unwrap_or_default(),
+                    trim_db: solution.trims_db.get(ch).copied().unwrap_or(0.0),
+                    peq: solution.peq.clone(),
+                },
+            );
+        }
+        processor
+    }
+
+    /// Set the correction for one speaker, keeping its delay line
+    pub fn set_correction(&mut self, speaker_id: impl Into<String>, correction: SpeakerCorrection) {
+        self.speakers
+            .entry(speaker_id.into())
+            .or_default()
+            .set_correction(correction);
+    }
+
+    /// Replace one speaker's EQ, crossfading from the current filters
+    pub fn set_peq(&mut self, speaker_id: impl Into<String>, peq: Vec<BiquadFilter>) {
+        let channel = self.speakers.entry(speaker_id.into()).or_default();
+        let correction = SpeakerCorrection {
+            peq,
+            ..channel.correction.clone()
+        };
+        channel.set_correction(correction);
+    }
+
+    pub fn correction(&self, speaker_id: &str) -> Option<&SpeakerCorrection> {
+        self.speakers.get(speaker_id).map(|s| &s.correction)
+    }
+
+    pub fn remove_correction(&mut self, speaker_id: &str) -> Option<SpeakerCorrection> {
+        self.speakers.remove(speaker_id).map(|s| s.correction)
+    }
+
+    /// Clear all filter and delay state, e.g. after a seek
+    pub fn reset(&mut self) {
+        for speaker in self.speakers.values_mut() {
+            speaker.reset();
+        }
+    }
+
+    /// Correct each channel of `block` by the speaker at the same index in `layout`.
+    /// Channels without a speaker or a correction pass through unchanged.
+    pub fn process(&mut self, block: &mut AudioBlock, layout: &SpeakerLayout) {
+        let sample_rate = block.sample_rate;
+        for (samples, speaker) in block.channels.iter_mut().zip(&layout.speakers) {
+            if let Some(channel) = self.speakers.get_mut(&speaker.id) {
+                channel.process(samples, sample_rate);
+            }
+        }
+    }
+}
+
 /// Reference renderer with loudness management, headroom protection, and optional binaural downmix
 pub struct ReferenceRenderer {
     loudness_normalizer: Option<LoudnessNormalizer>,
@@ -70,6 +303,23 @@ pub struct ReferenceRenderer {
     drc: Option<DynamicRangeControl>,
     binaural_renderer: Option<BinauralRenderer>,
     current_binaural_position: Option<HrtfPosition>,
+    bass_crossover_hz: Option<f32>,
+    /// Bass manager built for the roles of the last rendered layout
+    bass_manager: Option<(Vec<SpeakerRole>, BassManager)>,
+    /// Upmixer built for the roles of the last upmixed layout
+    upmixer: Option<(Vec<SpeakerRole>, StereoUpmixer)>,
+    /// Layout `pan_object` distributes to; follows `RenderOptions::target_layout`
+    target_layout: SpeakerLayout,
+    /// VBAP panner built for the speaker directions of `target_layout`
+    panner: Option<(Vec<PannedSpeaker>, Panner)>,
+    speaker_processor: Option<SpeakerProcessor>,
+    /// Requested limiter lookahead, before capping to `RenderOptions::max_latency`
+    headroom_lookahead_ms: f32,
+    /// Compressor used when `RenderOptions::enable_drc` asks for DRC but none
+    /// has been configured
+    default_drc: Option<DynamicRangeControl>,
+    meter: LoudnessMeter,
+    metrics: RenderMetrics,
     sample_rate: u32,
 }
 
@@ -82,6 +332,16 @@ impl ReferenceRenderer {
             drc: None,
             binaural_renderer: None,
             current_binaural_position: None,
+            bass_crossover_hz: None,
+            bass_manager: None,
+            upmixer: None,
+            target_layout: RenderOptions::default().target_layout,
+            panner: None,
+            speaker_processor: None,
+            headroom_lookahead_ms: DEFAULT_LOOKAHEAD_MS,
+            default_drc: None,
+            meter: LoudnessMeter::new(sample_rate),
+            metrics: RenderMetrics::default(),
             sample_rate,
         }
     }
@@ -109,19 +369,14 @@ impl ReferenceRenderer {
         attack_ms: f32,
         release_ms: f32,
     ) {
-        self.drc = Some(DynamicRangeControl::new(
+        self.drc = Some(compressor(
             ratio,
             threshold_db,
+            0.0,
             attack_ms,
             release_ms,
             self.sample_rate,
         ));
-
-        // Set makeup gain to compensate for reduction
-        if let Some(drc) = &mut self.drc {
-            let makeup_gain = (threshold_db * (ratio - 1.0)) / ratio;
-            drc.set_makeup_gain(makeup_gain);
-        }
     }
 
     /// Disable DRC
@@ -136,16 +391,136 @@ impl ReferenceRenderer {
     }
 
     /// Set headroom target
+    ///
+    /// `render` retargets the limiter to `RenderOptions::headroom_db`, so this
+    /// only holds until the next block rendered with a different headroom.
     pub fn set_headroom_db(&mut self, headroom_db: f32) {
         self.headroom_manager = HeadroomManager::new(headroom_db, self.sample_rate);
     }
 
     /// Set limiter lookahead time in milliseconds
+    ///
+    /// `render` shortens the lookahead to fit `RenderOptions::max_latency`.
     pub fn set_headroom_lookahead_ms(&mut self, lookahead_ms: f32) {
+        self.headroom_lookahead_ms = lookahead_ms.max(0.0);
         self.headroom_manager
             .set_lookahead_ms(self.sample_rate, lookahead_ms);
     }
 
+    /// Gain reduction and levels from the last rendered block
+    pub fn last_metrics(&self) -> &RenderMetrics {
+        &self.metrics
+    }
+
+    /// Headroom the limiter currently holds below full scale, in dB
+    pub fn headroom_db(&self) -> f32 {
+        self.headroom_manager.target_headroom_db()
+    }
+
+    /// Limiter lookahead currently in effect, in samples
+    pub fn headroom_lookahead_samples(&self) -> usize {
+        self.headroom_manager.lookahead_samples()
+    }
+
+    /// Bring the limiter in line with the render options
+    fn configure_headroom(&mut self, opts: &RenderOptions) {
+        if (self.headroom_manager.target_headroom_db() - opts.headroom_db.clamp(0.1, 20.0)).abs()
+            > f32::EPSILON
+        {
+            self.headroom_manager
+                .set_target_headroom_db(opts.headroom_db);
+        }
+
+        // The lookahead is the latency the limiter adds; keep it within budget
+        let max_latency_ms = opts.max_latency.as_secs_f32() * 1000.0;
+        self.headroom_manager.set_lookahead_ms(
+            self.sample_rate,
+            self.headroom_lookahead_ms.min(max_latency_ms),
+        );
+    }
+
+    /// Enable bass management at the given crossover frequency
+    ///
+    /// Low frequencies of the full-range channels are summed into the
+    /// `Subwoofer` channel of `RenderOptions::target_layout`. Layouts without
+    /// a subwoofer are left untouched.
+    pub fn enable_bass_management(&mut self, crossover_hz: f32) {
+        self.bass_crossover_hz = Some(crossover_hz);
+        self.bass_manager = None;
+    }
+
+    /// Disable bass management
+    pub fn disable_bass_management(&mut self) {
+        self.bass_crossover_hz = None;
+        self.bass_manager = None;
+    }
+
+    /// Check if bass management is enabled
+    pub fn has_bass_management(&self) -> bool {
+        self.bass_crossover_hz.is_some()
+    }
+
+    /// Bass manager for `layout`, rebuilt whenever the layout roles change
+    fn bass_manager_for(&mut self, layout: &SpeakerLayout) -> Option<&mut BassManager> {
+        let crossover_hz = self.bass_crossover_hz?;
+        let roles: Vec<SpeakerRole> = layout.speakers.iter().map(|s| s.role.clone()).collect();
+
+        if !matches!(&self.bass_manager, Some((cached, _)) if *cached == roles) {
+            let manager = BassManager::new(crossover_hz, self.sample_rate, &roles);
+            self.bass_manager = Some((roles, manager));
+        }
+
+        self.bass_manager.as_mut().map(|(_, manager)| manager)
+    }
+
+    /// Upmixer for `layout`, rebuilt whenever the layout roles change
+    fn upmixer_for(&mut self, layout: &SpeakerLayout) -> &mut StereoUpmixer {
+        let roles: Vec<SpeakerRole> = layout.speakers.iter().map(|s| s.role.clone()).collect();
+
+        if !matches!(&self.upmixer, Some((cached, _)) if *cached == roles) {
+            let upmixer = StereoUpmixer::new(layout, self.sample_rate);
+            self.upmixer = Some((roles, upmixer));
+        }
+
+        let (_, upmixer) = self.upmixer.as_mut().expect("upmixer just built");
+        upmixer
+    }
+
+    /// Set the layout `pan_object` distributes to; `render` also adopts
+    /// `RenderOptions::target_layout`
+    pub fn set_target_layout(&mut self, layout: SpeakerLayout) {
+        self.target_layout = layout;
+    }
+
+    /// Pan a mono source towards `position` over the target layout with VBAP.
+    ///
+    /// The block has one channel per speaker of the layout; the subwoofer
+    /// stays silent. The panner is rebuilt only when speaker positions change.
+    pub fn pan_object(&mut self, mono: &[f32], position: Vec3) -> AudioBlock {
+        let speakers = panned_speakers(&self.target_layout);
+        if !matches!(&self.panner, Some((cached, _)) if *cached == speakers) {
+            let panner = Panner::for_speakers(&speakers);
+            self.panner = Some((speakers, panner));
+        }
+        let (speakers, panner) = self.panner.as_ref().expect("panner just built");
+
+        let mut channels = vec![vec![0.0; mono.len()]; self.target_layout.speakers.len()];
+        let gains = panner.render(&position.normalize());
+        for (&(ch, _, _), gain) in speakers.iter().zip(gains) {
+            if gain == 0.0 {
+                continue;
+            }
+            for (out, &x) in channels[ch].iter_mut().zip(mono) {
+                *out = gain * x;
+            }
+        }
+
+        AudioBlock {
+            sample_rate: self.sample_rate,
+            channels,
+        }
+    }
+
     /// Enable binaural rendering for headphone playback
     /// Sets default position to front (0°, 0°) at 1 meter distance
     pub fn enable_binaural(&mut self, headphone_profile: HeadphoneProfile) -> anyhow::Result<()> {
@@ -172,13 +547,77 @@ impl ReferenceRenderer {
     pub fn has_binaural(&self) -> bool {
         self.binaural_renderer.is_some()
     }
+
+    /// Apply per-speaker delay and trim as the last stage of speaker rendering
+    pub fn set_speaker_processor(&mut self, processor: SpeakerProcessor) {
+        self.speaker_processor = Some(processor);
+    }
+
+    /// Disable per-speaker delay and trim
+    pub fn disable_speaker_processor(&mut self) {
+        self.speaker_processor = None;
+    }
+
+    pub fn speaker_processor_mut(&mut self) -> Option<&mut SpeakerProcessor> {
+        self.speaker_processor.as_mut()
+    }
+}
+
+/// DRC compressor with makeup gain to compensate for its reduction
+fn compressor(
+    ratio: f32,
+    threshold_db: f32,
+    knee_db: f32,
+    attack_ms: f32,
+    release_ms: f32,
+    sample_rate: u32,
+) -> DynamicRangeControl {
+    let mut drc = DynamicRangeControl::new(
+        ratio,
+        threshold_db,
+        knee_db,
+        attack_ms,
+        release_ms,
+        sample_rate,
+    );
+    let makeup_gain = (threshold_db * (ratio - 1.0)) / ratio;
+    drc.set_makeup_gain(makeup_gain);
+    drc
 }
 
 impl Renderer for ReferenceRenderer {
     fn render(&mut self, mut input: AudioBlock, opts: &RenderOptions) -> AudioBlock {
-        // Apply DRC if enabled
+        self.configure_headroom(opts);
+        if self.target_layout != opts.target_layout {
+            self.target_layout = opts.target_layout.clone();
+        }
+        let input_lufs = self.meter.measure_integrated_loudness(&input);
+
+        // Spread narrow input over the target speakers when asked to
+        let speaker_count = opts.target_layout.speakers.len();
+        if opts.mode == RenderMode::Upmix && input.channels.len() < speaker_count {
+            input.channels = if input.channels.len() <= 2 {
+                self.upmixer_for(&opts.target_layout)
+                    .process(&input.channels)
+            } else {
+                upmix_channels(&input.channels, speaker_count)
+            };
+        }
+
+        // Apply DRC if enabled, falling back to the default preset when the
+        // options ask for it and nothing has been configured
+        let mut drc_reduction_db = 0.0;
         if let Some(drc) = &mut self.drc {
             drc.process(&mut input);
+            drc_reduction_db = drc.current_reduction_db();
+        } else if opts.enable_drc {
+            let sample_rate = self.sample_rate;
+            let drc = self.default_drc.get_or_insert_with(|| {
+                let (ratio, threshold_db, attack_ms, release_ms) = DEFAULT_DRC_PRESET.params();
+                compressor(ratio, threshold_db, 0.0, attack_ms, release_ms, sample_rate)
+            });
+            drc.process(&mut input);
+            drc_reduction_db = drc.current_reduction_db();
         }
 
         // Apply loudness normalization if configured
@@ -190,9 +629,21 @@ impl Renderer for ReferenceRenderer {
             normalizer.normalize(&mut input);
         }
 
+        // Route low frequencies to the subwoofer if enabled
+        if let Some(bass) = self.bass_manager_for(&opts.target_layout) {
+            bass.process(&mut input);
+        }
+
         // Apply headroom protection (always enabled)
         self.headroom_manager.apply_limiting(&mut input);
 
+        // Per-speaker delay and trim; headphone output has no speakers to align
+        if self.binaural_renderer.is_none() {
+            if let Some(processor) = &mut self.speaker_processor {
+                processor.process(&mut input, &opts.target_layout);
+            }
+        }
+
         // Apply binaural downmix if enabled
         if let (Some(binaural), Some(position)) =
             (&self.binaural_renderer, &self.current_binaural_position)
@@ -228,10 +679,242 @@ impl Renderer for ReferenceRenderer {
             }
         }
 
+        let peak = input
+            .channels
+            .iter()
+            .flatten()
+            .fold(0.0f32, |peak, s| peak.max(s.abs()));
+        self.metrics = RenderMetrics {
+            drc_reduction_db,
+            limiter_reduction_db: self.headroom_manager.current_headroom_db(),
+            limiter_active: self.headroom_manager.is_limiting(),
+            input_lufs,
+            output_lufs: self.meter.measure_integrated_loudness(&input),
+            peak_db: if peak > 0.0 {
+                20.0 * peak.log10()
+            } else {
+                f32::NEG_INFINITY
+            },
+        };
+
         input
     }
 }
 
+/// Speakers more than this far above or below the listener count as height speakers
+const HEIGHT_SPEAKER_MIN_ELEVATION_DEG: f32 = 5.0;
+
+/// Where `ObjectRenderer` sends its mix
+#[derive(Clone, Debug, PartialEq)]
+pub enum RenderTarget {
+    /// Pan over a speaker layout with VBAP
+    Speakers(SpeakerLayout),
+    /// Binaural stereo through HRTFs
+    Headphones(HeadphoneProfile),
+}
+
+/// A mono stem placed in 3D space, in the same coordinates as speaker
+/// positions (x right, y front, z up, listener at the origin)
+#[derive(Clone, Debug, PartialEq)]
+pub struct AudioObject {
+    pub id: String,
+    pub samples: Vec<f32>,
+    pub position: Position3,
+}
+
+/// Renders audio objects to whichever target is active: VBAP for a
+/// speaker layout, binaural for headphones
+pub struct ObjectRenderer {
+    objects: Vec<AudioObject>,
+    /// Owns the HRTF database; kept across renders and only rebuilt when
+    /// the database is replaced
+    binaural: BinauralRenderer,
+    sample_rate: u32,
+}
+
+impl ObjectRenderer {
+    pub fn new(sample_rate: u32) -> Self {
+        let mut hrtf = HrtfDatabase::new(HrtfDataset::Kemar, sample_rate);
+        hrtf.load_default_kemar()
+            .expect("built-in HRTF set always loads");
+        Self {
+            objects: Vec::new(),
+            binaural: BinauralRenderer::new(hrtf, HeadphoneProfile::Flat),
+            sample_rate,
+        }
+    }
+
+    /// Replace the HRTF set used for headphone targets
+    pub fn set_hrtf_database(&mut self, hrtf: HrtfDatabase) {
+        let profile = self.binaural.headphone_profile();
+        self.binaural = BinauralRenderer::new(hrtf, profile);
+    }
+
+    pub fn hrtf_database(&self) -> &HrtfDatabase {
+        self.binaural.database()
+    }
+
+    /// Add an object, replacing any existing object with the same id
+    pub fn add_object(&mut self, id: impl Into<String>, samples: Vec<f32>, position: Position3) {
+        let object = AudioObject {
+            id: id.into(),
+            samples,
+            position,
+        };
+        match self.objects.iter_mut().find(|o| o.id == object.id) {
+            Some(existing) => *existing = object,
+            None => self.objects.push(object),
+        }
+    }
+
+    pub fn remove_object(&mut self, id: &str) -> Option<AudioObject> {
+        let index = self.objects.iter().position(|o| o.id == id)?;
+        Some(self.objects.remove(index))
+    }
+
+    pub fn objects(&self) -> &[AudioObject] {
+        &self.objects
+    }
+
+    /// Mix every object for `target`. The block is as long as the longest
+    /// object; shorter objects are padded with silence.
+    pub fn render(&mut self, target: &RenderTarget) -> AudioBlock {
+        let frames = self
+            .objects
+            .iter()
+            .map(|o| o.samples.len())
+            .max()
+            .unwrap_or(0);
+
+        let channels = match target {
+            RenderTarget::Speakers(layout) => self.render_speakers(layout, frames),
+            RenderTarget::Headphones(profile) => self.render_headphones(profile, frames),
+        };
+        AudioBlock {
+            sample_rate: self.sample_rate,
+            channels,
+        }
+    }
+
+    fn render_speakers(&self, layout: &SpeakerLayout, frames: usize) -> Vec<Vec<f32>> {
+        let mut output = vec![vec![0.0; frames]; layout.speakers.len()];
+
+        let panned = panned_speakers(layout);
+        let panner = Panner::for_speakers(&panned);
+
+        for object in &self.objects {
+            let (az, el, _) = direction(&object.position);
+            let gains = panner.render(&Vec3::from_spherical(az, el, 1.0));
+            for (&(ch, _, _), gain) in panned.iter().zip(gains) {
+                if gain == 0.0 {
+                    continue;
+                }
+                for (out, &x) in output[ch].iter_mut().zip(&object.samples) {
+                    *out += gain * x;
+                }
+            }
+        }
+
+        output
+    }
+
+    fn render_headphones(&mut self, profile: &HeadphoneProfile, frames: usize) -> Vec<Vec<f32>> {
+        if self.objects.is_empty() {
+            return vec![vec![0.0; frames]; 2];
+        }
+
+        let stems: Vec<Vec<f32>> = self
+            .objects
+            .iter()
+            .map(|o| {
+                let mut stem = o.samples.clone();
+                stem.resize(frames, 0.0);
+                stem
+            })
+            .collect();
+        let positions: Vec<HrtfPosition> = self
+            .objects
+            .iter()
+            .map(|o| {
+                let (az, el, distance) = direction(&o.position);
+                HrtfPosition::new(az, el, distance)
+            })
+            .collect();
+
+        if self.binaural.headphone_profile() != *profile {
+            self.binaural.set_headphone_profile(profile.clone());
+        }
+        let mut output = self
+            .binaural
+            .render_buffer(&stems, &positions)
+            .unwrap_or_else(|_| vec![vec![0.0; frames]; 2]);
+        // Keep the block aligned with the input; the HRTF tail is dropped
+        for ear in &mut output {
+            ear.truncate(frames);
+        }
+        output
+    }
+}
+
+/// `(channel, azimuth, elevation)` of a speaker VBAP pans over, in degrees
+type PannedSpeaker = (usize, f32, f32);
+
+/// Speakers VBAP pans over; the subwoofer carries no directional content
+fn panned_speakers(layout: &SpeakerLayout) -> Vec<PannedSpeaker> {
+    layout
+        .speakers
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| s.role != SpeakerRole::Subwoofer)
+        .map(|(ch, s)| {
+            let (az, el, _) = direction(&s.position);
+            (ch, az, el)
+        })
+        .collect()
+}
+
+/// VBAP flavor for a layout: pairwise for a horizontal ring, triplets once
+/// there are height speakers
+enum Panner {
+    Horizontal(Vbap2D),
+    Spatial(Vbap3D),
+}
+
+impl Panner {
+    fn for_speakers(speakers: &[PannedSpeaker]) -> Self {
+        let has_height = speakers
+            .iter()
+            .any(|&(_, _, el)| el.abs() > HEIGHT_SPEAKER_MIN_ELEVATION_DEG);
+        let speakers: Vec<Speaker3D> = speakers
+            .iter()
+            .map(|&(ch, az, el)| Speaker3D::new(ch, az, el))
+            .collect();
+
+        if has_height {
+            Panner::Spatial(Vbap3D::new(speakers))
+        } else {
+            Panner::Horizontal(Vbap2D::new(speakers))
+        }
+    }
+
+    /// Gains in the order the speakers were given
+    fn render(&self, source: &Vec3) -> Vec<f32> {
+        match self {
+            Panner::Horizontal(vbap) => vbap.render(source),
+            Panner::Spatial(vbap) => vbap.render(source),
+        }
+    }
+}
+
+/// Azimuth (positive to the left) and elevation in degrees, and distance
+/// in meters, of a position relative to the listener
+fn direction(p: &Position3) -> (f32, f32, f32) {
+    let azimuth = (-p.x).atan2(p.y).to_degrees();
+    let elevation = p.z.atan2(p.x.hypot(p.y)).to_degrees();
+    let distance = (p.x * p.x + p.y * p.y + p.z * p.z).sqrt();
+    (azimuth, elevation, distance)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -352,9 +1035,12 @@ mod tests {
         let mut renderer = ReferenceRenderer::new(48000);
         renderer.apply_drc_preset(DRCPreset::Speech);
 
+        // A tone rather than DC, which K-weighted loudness filters out
         let block = AudioBlock {
             sample_rate: 48000,
-            channels: vec![vec![0.5; 480]],
+            channels: vec![(0..480)
+                .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin())
+                .collect()],
         };
 
         let opts = RenderOptions::default();
@@ -459,4 +1145,51 @@ mod tests {
         // Should pass through without binaural processing since no position set
         assert_eq!(output.channels.len(), 1);
     }
+
+    #[test]
+    fn test_renderer_bass_management_feeds_subwoofer() {
+        let sample_rate = 48000;
+        let mut renderer = ReferenceRenderer::new(sample_rate);
+        renderer.enable_bass_management(80.0);
+        assert!(renderer.has_bass_management());
+
+        let layout = SpeakerLayout::surround_5_1();
+        let sub = layout
+            .speakers
+            .iter()
+            .position(|s| s.role == SpeakerRole::Subwoofer)
+            .unwrap();
+        let front_left = layout
+            .speakers
+            .iter()
+            .position(|s| s.role == SpeakerRole::FrontLeft)
+            .unwrap();
+
+        // 30 Hz tone on the front left only
+        let frames = sample_rate as usize;
+        let mut block = AudioBlock::silence(layout.speakers.len(), frames, sample_rate);
+        block.channels[front_left] = (0..frames)
+            .map(|i| {
+                0.3 * (2.0 * std::f32::consts::PI * 30.0 * i as f32 / sample_rate as f32).sin()
+            })
+            .collect();
+
+        let opts = RenderOptions {
+            target_layout: layout,
+            target_loudness: None,
+            ..RenderOptions::default()
+        };
+        let output = renderer.render(block, &opts);
+
+        let energy = |ch: &[f32]| ch[frames / 2..].iter().map(|s| s * s).sum::<f32>();
+        let sub_energy = energy(&output.channels[sub]);
+        let main_energy = energy(&output.channels[front_left]);
+        assert!(sub_energy > 0.0);
+        assert!(
+            sub_energy > main_energy * 10.0,
+            "sub {} main {}",
+            sub_energy,
+            main_energy
+        );
+    }
 }
@@ -0,0 +1,239 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! RTCP receiver reports (RFC 3550 section 6.4.2) so senders can adapt to loss
+
+use crate::fec::LossStatistics;
+use crate::jitter::JitterBuffer;
+use crate::transport::Ssrc;
+
+const RTCP_VERSION: u8 = 2;
+const PT_RECEIVER_REPORT: u8 = 201;
+const PT_SOURCE_DESCRIPTION: u8 = 202;
+const SDES_CNAME: u8 = 1;
+const REPORT_BLOCK_LEN: usize = 24;
+/// Largest and smallest values of the 24-bit signed cumulative-lost field
+const CUMULATIVE_LOST_MAX: i32 = 0x7f_ffff;
+const CUMULATIVE_LOST_MIN: i32 = -0x80_0000;
+
+/// Reception statistics for one source
+#[derive(Clone, Debug, PartialEq)]
+pub struct RtcpReportBlock {
+    pub source: Ssrc,
+    /// Fraction of packets lost as a fixed-point number over 256
+    pub fraction_lost: u8,
+    /// Packets lost since the start of reception (24-bit signed on the wire)
+    pub cumulative_lost: i32,
+    /// Highest sequence received, extended with the wrap count
+    pub highest_sequence: u32,
+    /// Interarrival jitter in RTP timestamp units
+    pub jitter: u32,
+    /// Middle 32 bits of the last sender report's NTP timestamp
+    pub last_sr: u32,
+    /// Delay since that sender report in units of 1/65536 s
+    pub delay_since_last_sr: u32,
+}
+
+impl RtcpReportBlock {
+    /// Build a block from the receiver's loss and jitter tracking
+    ///
+    /// The fraction lost covers everything `stats` has seen, so recreate the
+    /// statistics each reporting interval for per-interval figures.
+    pub fn from_statistics(source: u32, stats: &LossStatistics, jitter: &JitterBuffer) -> Self {
+        let fraction_lost = (stats.loss_rate() * 256.0).clamp(0.0, 255.0) as u8;
+        let cumulative_lost = stats.total_lost.min(CUMULATIVE_LOST_MAX as u64) as i32;
+
+        Self {
+            source: Ssrc(source),
+            fraction_lost,
+            cumulative_lost,
+            highest_sequence: stats.extended_highest_sequence(),
+            jitter: jitter.interarrival_jitter(),
+            last_sr: 0,
+            delay_since_last_sr: 0,
+        }
+    }
+
+    fn serialize_into(&self, buf: &mut Vec<u8>) {
+        let lost = self
+            .cumulative_lost
+            .clamp(CUMULATIVE_LOST_MIN, CUMULATIVE_LOST_MAX) as u32;
+
+        buf.extend_from_slice(&self.source.0.to_be_bytes());
+        buf.push(self.fraction_lost);
+        buf.extend_from_slice(&lost.to_be_bytes()[1..]);
+        buf.extend_from_slice(&self.highest_sequence.to_be_bytes());
+        buf.extend_from_slice(&self.jitter.to_be_bytes());
+        buf.extend_from_slice(&self.last_sr.to_be_bytes());
+        buf.extend_from_slice(&self.delay_since_last_sr.to_be_bytes());
+    }
+
+    fn deserialize(buf: &[u8]) -> Self {
+        let word = |at: usize| u32::from_be_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]]);
+        // Sign-extend the 24-bit count
+        let cumulative_lost = (i32::from_be_bytes([buf[5], buf[6], buf[7], 0])) >> 8;
+
+        Self {
+            source: Ssrc(word(0)),
+            fraction_lost: buf[4],
+            cumulative_lost,
+            highest_sequence: word(8),
+            jitter: word(12),
+            last_sr: word(16),
+            delay_since_last_sr: word(20),
+        }
+    }
+}
+
+/// Compound RTCP packet: a receiver report followed by an SDES CNAME
+#[derive(Clone, Debug, PartialEq)]
+pub struct RtcpReceiverReport {
+    /// SSRC of the receiver sending the report
+    pub ssrc: Ssrc,
+    pub cname: String,
+    pub blocks: Vec<RtcpReportBlock>,
+}
+
+impl RtcpReceiverReport {
+    pub fn new(ssrc: u32, cname: impl Into<String>) -> Self {
+        Self {
+            ssrc: Ssrc(ssrc),
+            cname: cname.into(),
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Report on a single source from the receiver's loss and jitter tracking
+    pub fn from_statistics(
+        ssrc: u32,
+        cname: impl Into<String>,
+        source: u32,
+        stats: &LossStatistics,
+        jitter: &JitterBuffer,
+    ) -> Self {
+        let mut report = Self::new(ssrc, cname);
+        report
+            .blocks
+            .push(RtcpReportBlock::from_statistics(source, stats, jitter));
+        report
+    }
+
+    /// Serialize as an RR packet followed by an SDES packet; at most 31
+    /// blocks fit the report count field, extra blocks are dropped
+    pub fn serialize(&self) -> Vec<u8> {
+        let blocks = &self.blocks[..self.blocks.len().min(31)];
+
+        let rr_len = 8 + blocks.len() * REPORT_BLOCK_LEN;
+        let mut buf = Vec::with_capacity(rr_len + 12 + self.cname.len());
+        push_header(&mut buf, blocks.len() as u8, PT_RECEIVER_REPORT, rr_len);
+        buf.extend_from_slice(&self.ssrc.0.to_be_bytes());
+        for block in blocks {
+            block.serialize_into(&mut buf);
+        }
+
+        // SDES chunk: SSRC, CNAME item, then a null item padded to 32 bits
+        let cname = &self.cname.as_bytes()[..self.cname.len().min(255)];
+        let chunk_len = (4 + 2 + cname.len() + 1 + 3) / 4 * 4;
+        push_header(&mut buf, 1, PT_SOURCE_DESCRIPTION, 4 + chunk_len);
+        let chunk_start = buf.len();
+        buf.extend_from_slice(&self.ssrc.0.to_be_bytes());
+        buf.push(SDES_CNAME);
+        buf.push(cname.len() as u8);
+        buf.extend_from_slice(cname);
+        buf.resize(chunk_start + chunk_len, 0);
+
+        buf
+    }
+
+    /// Parse a compound packet that starts with a receiver report
+    ///
+    /// Packets other than SDES after the report are skipped; the CNAME is
+    /// left empty when no SDES chunk describes the reporter.
+    pub fn deserialize(buf: &[u8]) -> Option<Self> {
+        let mut offset = 0;
+        let mut report: Option<Self> = None;
+
+        while offset < buf.len() {
+            let (count, packet_type, len) = parse_header(&buf[offset..])?;
+            let body = &buf[offset + 4..offset + len];
+
+            match (packet_type, &mut report) {
+                (PT_RECEIVER_REPORT, None) => {
+                    if body.len() < 4 + count as usize * REPORT_BLOCK_LEN {
+                        return None;
+                    }
+                    let ssrc = u32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+                    let blocks = body[4..]
+                        .chunks_exact(REPORT_BLOCK_LEN)
+                        .take(count as usize)
+                        .map(RtcpReportBlock::deserialize)
+                        .collect();
+                    report = Some(Self {
+                        ssrc: Ssrc(ssrc),
+                        cname: String::new(),
+                        blocks,
+                    });
+                }
+                // A compound packet must lead with the report
+                (_, None) => return None,
+                (PT_SOURCE_DESCRIPTION, Some(report)) => {
+                    if let Some(cname) = find_cname(body, count, report.ssrc.0) {
+                        report.cname = cname;
+                    }
+                }
+                _ => {}
+            }
+
+            offset += len;
+        }
+
+        report
+    }
+}
+
+fn push_header(buf: &mut Vec<u8>, count: u8, packet_type: u8, len: usize) {
+    buf.push((RTCP_VERSION << 6) | (count & 0x1f));
+    buf.push(packet_type);
+    // Length in 32-bit words minus one
+    buf.extend_from_slice(&((len / 4 - 1) as u16).to_be_bytes());
+}
+
+/// Returns the count field, packet type and total length in bytes
+fn parse_header(buf: &[u8]) -> Option<(u8, u8, usize)> {
+    if buf.len() < 4 || (buf[0] >> 6) != RTCP_VERSION {
+        return None;
+    }
+    let len = (u16::from_be_bytes([buf[2], buf[3]]) as usize + 1) * 4;
+    if len > buf.len() {
+        return None;
+    }
+    Some((buf[0] & 0x1f, buf[1], len))
+}
+
+fn find_cname(body: &[u8], chunks: u8, ssrc: u32) -> Option<String> {
+    let mut offset = 0;
+    for _ in 0..chunks {
+        let chunk_ssrc = u32::from_be_bytes(body.get(offset..offset + 4)?.try_into().ok()?);
+        offset += 4;
+
+        let mut cname = None;
+        loop {
+            let item_type = *body.get(offset)?;
+            if item_type == 0 {
+                // Null item ends the chunk; skip padding to the next word
+                offset = (offset + 1 + 3) / 4 * 4;
+                break;
+            }
+            let item_len = *body.get(offset + 1)? as usize;
+            let text = body.get(offset + 2..offset + 2 + item_len)?;
+            if item_type == SDES_CNAME {
+                cname = Some(String::from_utf8_lossy(text).into_owned());
+            }
+            offset += 2 + item_len;
+        }
+
+        if chunk_ssrc == ssrc {
+            return cname;
+        }
+    }
+    None
+}
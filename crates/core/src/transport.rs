@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::rtcp::RtcpReceiverReport;
 use crate::AudioBlock;
 use std::time::{Duration, SystemTime};
 
@@ -46,7 +47,7 @@ impl ClockTimestamp {
     pub fn skew_from(&self, other: &ClockTimestamp) -> Duration {
         let self_dur = self.to_duration();
         let other_dur = other.to_duration();
-        self_dur.abs_diff(other_dur)
+        self_dur.max(other_dur) - self_dur.min(other_dur)
     }
 }
 
@@ -181,6 +182,8 @@ pub trait SyncStrategy {
 
 pub struct LoopbackTransport {
     queue: Vec<RtpPacket>,
+    /// Serialized RTCP packets, kept on the wire format like a real socket
+    control_queue: Vec<Vec<u8>>,
     sequence: u16,
     _ssrc: u32,
 }
@@ -189,6 +192,7 @@ impl LoopbackTransport {
     pub fn new() -> Self {
         Self {
             queue: Vec::new(),
+            control_queue: Vec::new(),
             sequence: 0,
             _ssrc: rand::random(),
         }
@@ -197,10 +201,26 @@ impl LoopbackTransport {
     pub fn with_ssrc(ssrc: u32) -> Self {
         Self {
             queue: Vec::new(),
+            control_queue: Vec::new(),
             sequence: 0,
             _ssrc: ssrc,
         }
     }
+
+    pub fn send_report(&mut self, report: &RtcpReceiverReport) -> anyhow::Result<()> {
+        self.control_queue.push(report.serialize());
+        Ok(())
+    }
+
+    pub fn poll_report(&mut self) -> anyhow::Result<Option<RtcpReceiverReport>> {
+        if self.control_queue.is_empty() {
+            return Ok(None);
+        }
+        let bytes = self.control_queue.remove(0);
+        RtcpReceiverReport::deserialize(&bytes)
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("malformed RTCP packet"))
+    }
 }
 
 impl Default for LoopbackTransport {
@@ -232,6 +252,7 @@ pub fn serialize_audio_block(block: &AudioBlock) -> Vec<u8> {
 
 pub fn deserialize_audio_block(bytes: &[u8]) -> anyhow::Result<AudioBlock> {
     let block: AudioBlock = bincode::deserialize(bytes)?;
+    block.validate()?;
     Ok(block)
 }
 
@@ -248,3 +269,385 @@ pub fn audio_block_to_rtp(
 pub fn rtp_to_audio_block(packet: &RtpPacket) -> anyhow::Result<AudioBlock> {
     deserialize_audio_block(&packet.payload)
 }
+
+/// Packet size `audio_block_to_rtp_chunks` targets to stay clear of
+/// fragmentation on typical WiFi/VPN paths
+pub const DEFAULT_RTP_MTU: usize = 1200;
+
+/// Chunk index and chunk count (u16 BE each) ahead of every chunk payload
+const CHUNK_HEADER_BYTES: usize = 4;
+const RTP_HEADER_BYTES: usize = 12;
+
+/// Split a block over as many RTP packets as needed to keep each serialized
+/// packet within `mtu` bytes.
+///
+/// Packets take consecutive sequence numbers from `sequence` and share
+/// `timestamp`; the last one has the marker bit set. Each payload starts with
+/// the chunk index and count so `RtpReassembler` can tolerate reordering.
+/// Both are u16, so a payload too large for 65535 chunks at `mtu` gets
+/// bigger chunks instead, and those packets exceed `mtu`.
+pub fn audio_block_to_rtp_chunks(
+    block: &AudioBlock,
+    mtu: usize,
+    sequence: u16,
+    timestamp: u32,
+    ssrc: u32,
+) -> Vec<RtpPacket> {
+    let payload = serialize_audio_block(block);
+    let chunk_bytes = mtu
+        .saturating_sub(RTP_HEADER_BYTES + CHUNK_HEADER_BYTES)
+        .max(1)
+        .max((payload.len() + u16::MAX as usize - 1) / u16::MAX as usize);
+    let count = ((payload.len() + chunk_bytes - 1) / chunk_bytes).max(1);
+
+    (0..count)
+        .map(|index| {
+            let start = (index * chunk_bytes).min(payload.len());
+            let end = (start + chunk_bytes).min(payload.len());
+
+            let mut chunk = Vec::with_capacity(CHUNK_HEADER_BYTES + end - start);
+            chunk.extend_from_slice(&(index as u16).to_be_bytes());
+            chunk.extend_from_slice(&(count as u16).to_be_bytes());
+            chunk.extend_from_slice(&payload[start..end]);
+
+            let mut packet =
+                RtpPacket::new(sequence.wrapping_add(index as u16), timestamp, ssrc, chunk);
+            packet.header.marker = index + 1 == count;
+            packet
+        })
+        .collect()
+}
+
+/// Rebuilds blocks split by `audio_block_to_rtp_chunks`.
+///
+/// Chunks of one block may arrive in any order. A chunk from a different
+/// block (new SSRC or timestamp) discards an incomplete one.
+#[derive(Debug, Default)]
+pub struct RtpReassembler {
+    key: Option<(u32, u32)>,
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+impl RtpReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a chunk; returns the block once its last missing chunk arrives
+    pub fn push(&mut self, packet: &RtpPacket) -> anyhow::Result<Option<AudioBlock>> {
+        let payload = &packet.payload;
+        anyhow::ensure!(
+            payload.len() >= CHUNK_HEADER_BYTES,
+            "RTP chunk shorter than its header"
+        );
+        let index = u16::from_be_bytes([payload[0], payload[1]]) as usize;
+        let count = u16::from_be_bytes([payload[2], payload[3]]) as usize;
+        anyhow::ensure!(index < count, "RTP chunk {index} of {count}");
+
+        let key = (packet.header.ssrc.0, packet.header.timestamp.0);
+        if self.key != Some(key) || self.chunks.len() != count {
+            self.key = Some(key);
+            self.chunks = vec![None; count];
+            self.received = 0;
+        }
+
+        if self.chunks[index].is_none() {
+            self.chunks[index] = Some(payload[CHUNK_HEADER_BYTES..].to_vec());
+            self.received += 1;
+        }
+        if self.received < count {
+            return Ok(None);
+        }
+
+        let bytes: Vec<u8> = self.chunks.drain(..).flatten().flatten().collect();
+        self.key = None;
+        self.received = 0;
+        deserialize_audio_block(&bytes).map(Some)
+    }
+
+    /// Drop any partially received block
+    pub fn reset(&mut self) {
+        self.key = None;
+        self.chunks.clear();
+        self.received = 0;
+    }
+}
+
+/// How audio blocks are encoded into RTP payloads
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Codec {
+    /// bincode-serialized f32 samples, as `audio_block_to_rtp`
+    #[default]
+    RawF32,
+    /// One mono Opus stream per channel (needs the `opus-codec` feature).
+    /// Blocks must be a multiple of 2.5 ms at 8/12/16/24/48 kHz; each is
+    /// coded as Opus frames of up to 20 ms.
+    Opus { bitrate: u32 },
+}
+
+/// Sending side of a codec; Opus carries encoder state from block to block,
+/// so use one per stream
+pub struct PayloadEncoder {
+    codec: Codec,
+    #[cfg(feature = "opus-codec")]
+    opus: Option<opus_payload::Encoder>,
+}
+
+impl PayloadEncoder {
+    pub fn new(codec: Codec) -> Self {
+        Self {
+            codec,
+            #[cfg(feature = "opus-codec")]
+            opus: None,
+        }
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    pub fn encode(&mut self, block: &AudioBlock) -> anyhow::Result<Vec<u8>> {
+        match self.codec {
+            Codec::RawF32 => Ok(serialize_audio_block(block)),
+            #[cfg(feature = "opus-codec")]
+            Codec::Opus { bitrate } => {
+                opus_payload::Encoder::encode(&mut self.opus, block, bitrate)
+            }
+            #[cfg(not(feature = "opus-codec"))]
+            Codec::Opus { .. } => Err(opus_disabled()),
+        }
+    }
+}
+
+/// Receiving side of a codec, one per stream like `PayloadEncoder`
+pub struct PayloadDecoder {
+    codec: Codec,
+    #[cfg(feature = "opus-codec")]
+    opus: Option<opus_payload::Decoder>,
+}
+
+impl PayloadDecoder {
+    pub fn new(codec: Codec) -> Self {
+        Self {
+            codec,
+            #[cfg(feature = "opus-codec")]
+            opus: None,
+        }
+    }
+
+    pub fn codec(&self) -> Codec {
+        self.codec
+    }
+
+    pub fn decode(&mut self, payload: &[u8]) -> anyhow::Result<AudioBlock> {
+        match self.codec {
+            Codec::RawF32 => deserialize_audio_block(payload),
+            #[cfg(feature = "opus-codec")]
+            Codec::Opus { .. } => opus_payload::Decoder::decode(&mut self.opus, payload),
+            #[cfg(not(feature = "opus-codec"))]
+            Codec::Opus { .. } => Err(opus_disabled()),
+        }
+    }
+}
+
+#[cfg(not(feature = "opus-codec"))]
+fn opus_disabled() -> anyhow::Error {
+    anyhow::anyhow!("Opus payloads need the `opus-codec` feature")
+}
+
+/// `audio_block_to_rtp` with the payload coded by `encoder`
+pub fn audio_block_to_rtp_with_codec(
+    block: &AudioBlock,
+    sequence: u16,
+    timestamp: u32,
+    ssrc: u32,
+    encoder: &mut PayloadEncoder,
+) -> anyhow::Result<RtpPacket> {
+    let payload = encoder.encode(block)?;
+    Ok(RtpPacket::new(sequence, timestamp, ssrc, payload))
+}
+
+/// `rtp_to_audio_block` for payloads coded by a matching `PayloadEncoder`
+pub fn rtp_to_audio_block_with_codec(
+    packet: &RtpPacket,
+    decoder: &mut PayloadDecoder,
+) -> anyhow::Result<AudioBlock> {
+    decoder.decode(&packet.payload)
+}
+
+#[cfg(feature = "opus-codec")]
+mod opus_payload {
+    //! Payload: channel count (u8), sample rate and frame count (u32 LE),
+    //! then for each Opus frame and channel a u16 LE length and the packet.
+
+    use crate::AudioBlock;
+    use anyhow::{anyhow, ensure};
+    use opus::{Application, Bitrate, Channels};
+
+    /// Sample rates libopus runs at
+    const SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+    /// Opus frame sizes in 2.5 ms units, longest first (20 ms down to 2.5 ms)
+    const FRAME_UNITS: [usize; 4] = [8, 4, 2, 1];
+    /// Largest single Opus packet (RFC 6716 section 3.2.1)
+    const MAX_PACKET_BYTES: usize = 1275;
+    /// Smallest coded frame: the u16 length plus the Opus TOC byte
+    const MIN_FRAME_BYTES: usize = 3;
+    const HEADER_BYTES: usize = 9;
+
+    /// Longest Opus frame size that tiles a block of `frames`
+    fn frame_size(sample_rate: u32, frames: usize) -> anyhow::Result<usize> {
+        ensure!(
+            SAMPLE_RATES.contains(&sample_rate),
+            "Opus does not support {sample_rate} Hz"
+        );
+        let unit = sample_rate as usize / 400;
+        FRAME_UNITS
+            .iter()
+            .map(|units| units * unit)
+            .find(|size| frames % size == 0)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Opus needs blocks that are a multiple of 2.5 ms, \
+                     got {frames} frames at {sample_rate} Hz"
+                )
+            })
+    }
+
+    pub(super) struct Encoder {
+        sample_rate: u32,
+        bitrate: u32,
+        channels: Vec<opus::Encoder>,
+    }
+
+    impl Encoder {
+        fn new(sample_rate: u32, channels: usize, bitrate: u32) -> anyhow::Result<Self> {
+            let channels = (0..channels)
+                .map(|_| -> anyhow::Result<opus::Encoder> {
+                    let mut encoder =
+                        opus::Encoder::new(sample_rate, Channels::Mono, Application::Audio)?;
+                    encoder.set_bitrate(Bitrate::Bits(bitrate as i32))?;
+                    Ok(encoder)
+                })
+                .collect::<anyhow::Result<_>>()?;
+
+            Ok(Self {
+                sample_rate,
+                bitrate,
+                channels,
+            })
+        }
+
+        /// Encode `block`, (re)creating the encoders in `state` when the
+        /// stream format changes
+        pub(super) fn encode(
+            state: &mut Option<Self>,
+            block: &AudioBlock,
+            bitrate: u32,
+        ) -> anyhow::Result<Vec<u8>> {
+            block.validate()?;
+            let channel_count = u8::try_from(block.channels.len())
+                .map_err(|_| anyhow!("too many channels for Opus payload"))?;
+            let frames = block.frame_len();
+            let size = frame_size(block.sample_rate, frames)?;
+
+            let reusable = state.as_ref().is_some_and(|enc| {
+                enc.sample_rate == block.sample_rate
+                    && enc.bitrate == bitrate
+                    && enc.channels.len() == block.channels.len()
+            });
+            if !reusable {
+                *state = Some(Self::new(block.sample_rate, block.channels.len(), bitrate)?);
+            }
+            let encoders = &mut state.as_mut().expect("encoder just created").channels;
+
+            let mut out = Vec::with_capacity(HEADER_BYTES);
+            out.push(channel_count);
+            out.extend_from_slice(&block.sample_rate.to_le_bytes());
+            out.extend_from_slice(&(frames as u32).to_le_bytes());
+
+            let mut packet = [0u8; MAX_PACKET_BYTES];
+            for start in (0..frames).step_by(size) {
+                for (encoder, samples) in encoders.iter_mut().zip(&block.channels) {
+                    let len = encoder.encode_float(&samples[start..start + size], &mut packet)?;
+                    out.extend_from_slice(&(len as u16).to_le_bytes());
+                    out.extend_from_slice(&packet[..len]);
+                }
+            }
+
+            Ok(out)
+        }
+    }
+
+    pub(super) struct Decoder {
+        sample_rate: u32,
+        channels: Vec<opus::Decoder>,
+    }
+
+    impl Decoder {
+        pub(super) fn decode(
+            state: &mut Option<Self>,
+            payload: &[u8],
+        ) -> anyhow::Result<AudioBlock> {
+            ensure!(payload.len() >= HEADER_BYTES, "truncated Opus payload");
+            let channel_count = payload[0] as usize;
+            let sample_rate = u32::from_le_bytes([payload[1], payload[2], payload[3], payload[4]]);
+            let frames =
+                u32::from_le_bytes([payload[5], payload[6], payload[7], payload[8]]) as usize;
+            let size = frame_size(sample_rate, frames)?;
+
+            // The header is untrusted: check the payload can hold that many
+            // frames before allocating for them
+            ensure!(channel_count > 0, "Opus payload has no channels");
+            let coded_frames = (frames / size)
+                .checked_mul(channel_count)
+                .ok_or_else(|| anyhow!("Opus payload frame count overflows"))?;
+            ensure!(
+                coded_frames <= (payload.len() - HEADER_BYTES) / MIN_FRAME_BYTES,
+                "Opus payload too short for {frames} frames of {channel_count} channels"
+            );
+
+            let reusable = state.as_ref().is_some_and(|dec| {
+                dec.sample_rate == sample_rate && dec.channels.len() == channel_count
+            });
+            if !reusable {
+                let channels = (0..channel_count)
+                    .map(|_| opus::Decoder::new(sample_rate, Channels::Mono))
+                    .collect::<Result<_, _>>()?;
+                *state = Some(Self {
+                    sample_rate,
+                    channels,
+                });
+            }
+            let decoders = &mut state.as_mut().expect("decoder just created").channels;
+
+            let mut channels: Vec<Vec<f32>> = (0..channel_count)
+                .map(|_| Vec::with_capacity(frames))
+                .collect();
+            let mut pcm = vec![0.0f32; size];
+            let mut pos = HEADER_BYTES;
+            for _ in 0..frames / size {
+                for (decoder, out) in decoders.iter_mut().zip(&mut channels) {
+                    let len_bytes = payload
+                        .get(pos..pos + 2)
+                        .ok_or_else(|| anyhow!("truncated Opus payload"))?;
+                    let len = u16::from_le_bytes([len_bytes[0], len_bytes[1]]) as usize;
+                    pos += 2;
+                    let packet = payload
+                        .get(pos..pos + len)
+                        .ok_or_else(|| anyhow!("truncated Opus payload"))?;
+                    pos += len;
+
+                    let decoded = decoder.decode_float(packet, &mut pcm, false)?;
+                    ensure!(decoded == size, "Opus frame decoded to {decoded} samples");
+                    out.extend_from_slice(&pcm);
+                }
+            }
+
+            Ok(AudioBlock {
+                sample_rate,
+                channels,
+            })
+        }
+    }
+}
@@ -2,6 +2,16 @@
 
 //! 3D VBAP (Vector Base Amplitude Panning) for spatial audio rendering
 
+/// Per-sample one-pole coefficient for gain smoothing in `Vbap3D::render_buffer`
+/// (a time constant of about 100 samples)
+const GAIN_SMOOTHING: f32 = 0.01;
+
+/// Angular radius of the virtual source cloud at full spread in `Vbap3D::render_spread`
+const MAX_SPREAD_DEG: f32 = 90.0;
+
+/// Virtual sources on each ring around the target direction
+const SPREAD_RING_POINTS: usize = 8;
+
 /// 3D position in Cartesian coordinates
 #[derive(Clone, Debug, PartialEq)]
 pub struct Vec3 {
@@ -133,8 +143,9 @@ impl SpeakerTriplet {
         })
     }
 
-    /// Calculate gains for a sound source position
-    pub fn calculate_gains(&self, source: &Vec3) -> Option<[f32; 3]> {
+    /// Gains before power normalization; negative when the source lies
+    /// outside the triplet
+    fn raw_gains(&self, source: &Vec3) -> [f32; 3] {
         let src_norm = source.normalize();
 
         // Multiply inverse matrix with source vector to get gains
@@ -147,6 +158,12 @@ impl SpeakerTriplet {
         let g3 = self.inverse_matrix[2][0] * src_norm.x
             + self.inverse_matrix[2][1] * src_norm.y
             + self.inverse_matrix[2][2] * src_norm.z;
+        [g1, g2, g3]
+    }
+
+    /// Calculate gains for a sound source position
+    pub fn calculate_gains(&self, source: &Vec3) -> Option<[f32; 3]> {
+        let [g1, g2, g3] = self.raw_gains(source);
 
         // Check if all gains are non-negative (source is inside the triplet)
         if g1 >= 0.0 && g2 >= 0.0 && g3 >= 0.0 {
@@ -223,19 +240,181 @@ impl Vbap3D {
     pub fn render(&self, source: &Vec3) -> Vec<f32> {
         let mut gains = vec![0.0; self.speakers.len()];
 
-        // Find the triplet that contains the source
-        for triplet in &self.triplets {
-            if let Some(trip_gains) = triplet.calculate_gains(source) {
-                for (i, &speaker_idx) in triplet.speakers.iter().enumerate() {
-                    gains[speaker_idx] = trip_gains[i];
+        // Triplets overlap, so several may contain the source. The raw gains
+        // sum to 1 only when the source sits on a speaker and grow as the
+        // triangle widens, so the smallest sum picks the tightest triplet.
+        let best = self
+            .triplets
+            .iter()
+            .filter_map(|triplet| {
+                let trip_gains = triplet.calculate_gains(source)?;
+                let spread: f32 = triplet.raw_gains(source).iter().sum();
+                Some((spread, triplet, trip_gains))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        if let Some((_, triplet, trip_gains)) = best {
+            for (i, &speaker_idx) in triplet.speakers.iter().enumerate() {
+                gains[speaker_idx] = trip_gains[i];
+            }
+        }
+
+        gains
+    }
+
+    /// Like `render`, but a source outside every triplet still reaches the
+    /// layout: it is panned to the triplet whose centroid is angularly
+    /// nearest, with negative gains clipped. Layouts without triplets route
+    /// to the nearest speaker; equally near speakers share it at constant
+    /// power.
+    pub fn render_with_fallback(&self, source: &Vec3) -> Vec<f32> {
+        let mut gains = self.render(source);
+        let direction = source.normalize();
+        if gains.iter().any(|&g| g > 0.0) || direction.length() == 0.0 {
+            return gains;
+        }
+
+        let centroid = |triplet: &SpeakerTriplet| {
+            let [a, b, c] = triplet.speakers.map(|i| &self.speakers[i].position);
+            Vec3::new(a.x + b.x + c.x, a.y + b.y + c.y, a.z + b.z + c.z).normalize()
+        };
+        let nearest_triplet = self.triplets.iter().max_by(|a, b| {
+            centroid(a)
+                .dot(&direction)
+                .total_cmp(&centroid(b).dot(&direction))
+        });
+        if let Some(triplet) = nearest_triplet {
+            let clipped = triplet.raw_gains(&direction).map(|g| g.max(0.0));
+            let norm = clipped.iter().map(|g| g * g).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                for (&speaker_idx, g) in triplet.speakers.iter().zip(clipped) {
+                    gains[speaker_idx] = g / norm;
                 }
-                break;
+                return gains;
             }
         }
 
+        let closeness: Vec<f32> = self
+            .speakers
+            .iter()
+            .map(|s| s.position.dot(&direction))
+            .collect();
+        let best = closeness.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        let nearest: Vec<usize> = (0..closeness.len())
+            .filter(|&i| closeness[i] >= best - 1e-4)
+            .collect();
+        let share = 1.0 / (nearest.len() as f32).sqrt();
+        for i in nearest {
+            gains[i] = share;
+        }
         gains
     }
 
+    /// Pan with a widened phantom image.
+    ///
+    /// `spread` runs from 0.0 (a point source, same as `render_with_fallback`)
+    /// to 1.0, where virtual sources reach 90° from the target.
+    /// The gains of the target and two rings of virtual sources around it
+    /// are summed and renormalized to constant power.
+    pub fn render_spread(&self, source: &Vec3, spread: f32) -> Vec<f32> {
+        let direction = source.normalize();
+        let spread = spread.clamp(0.0, 1.0);
+        if spread == 0.0 || direction.length() == 0.0 {
+            return self.render_with_fallback(source);
+        }
+
+        // Orthonormal basis around the target direction
+        let reference = if direction.z.abs() < 0.9 {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let u = direction.cross(&reference).normalize();
+        let v = direction.cross(&u);
+
+        let mut gains = self.render_with_fallback(&direction);
+        let radius = (spread * MAX_SPREAD_DEG).to_radians();
+        for ring_radius in [radius * 0.5, radius] {
+            let (sin_r, cos_r) = ring_radius.sin_cos();
+            for point in 0..SPREAD_RING_POINTS {
+                let angle = std::f32::consts::TAU * point as f32 / SPREAD_RING_POINTS as f32;
+                let (sin_a, cos_a) = angle.sin_cos();
+                let virtual_source = Vec3::new(
+                    cos_r * direction.x + sin_r * (cos_a * u.x + sin_a * v.x),
+                    cos_r * direction.y + sin_r * (cos_a * u.y + sin_a * v.y),
+                    cos_r * direction.z + sin_r * (cos_a * u.z + sin_a * v.z),
+                );
+                for (g, vg) in gains
+                    .iter_mut()
+                    .zip(self.render_with_fallback(&virtual_source))
+                {
+                    *g += vg;
+                }
+            }
+        }
+
+        let norm = gains.iter().map(|g| g * g).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            for g in &mut gains {
+                *g /= norm;
+            }
+        }
+        gains
+    }
+
+    /// Pan a mono signal along a trajectory, returning `[speaker][sample]`
+    ///
+    /// Trajectory points are spread evenly from the first sample to the
+    /// last. Gains are interpolated linearly between points, renormalized
+    /// to constant power and then smoothed with a one-pole filter, so jumps
+    /// between triplets don't cause zipper noise. An empty trajectory
+    /// renders silence.
+    pub fn render_buffer(&self, mono: &[f32], trajectory: &[Vec3]) -> Vec<Vec<f32>> {
+        let mut output = vec![vec![0.0; mono.len()]; self.speakers.len()];
+        if trajectory.is_empty() || mono.is_empty() {
+            return output;
+        }
+
+        let point_gains: Vec<Vec<f32>> = trajectory.iter().map(|p| self.render(p)).collect();
+        let last_point = trajectory.len() - 1;
+        let span = mono.len().saturating_sub(1).max(1) as f32;
+
+        let mut target = vec![0.0; self.speakers.len()];
+        let mut smoothed = vec![0.0; self.speakers.len()];
+        for (i, &sample) in mono.iter().enumerate() {
+            let pos = i as f32 / span * last_point as f32;
+            let from = (pos.floor() as usize).min(last_point);
+            let to = (from + 1).min(last_point);
+            let frac = pos - from as f32;
+
+            for ((t, &a), &b) in target
+                .iter_mut()
+                .zip(&point_gains[from])
+                .zip(&point_gains[to])
+            {
+                *t = a + (b - a) * frac;
+            }
+            let norm = target.iter().map(|g| g * g).sum::<f32>().sqrt();
+            if norm > 0.0 {
+                target.iter_mut().for_each(|g| *g /= norm);
+            }
+
+            if i == 0 {
+                smoothed.copy_from_slice(&target);
+            } else {
+                for (s, &t) in smoothed.iter_mut().zip(&target) {
+                    *s += (t - *s) * GAIN_SMOOTHING;
+                }
+            }
+
+            for (channel, &gain) in output.iter_mut().zip(&smoothed) {
+                channel[i] = sample * gain;
+            }
+        }
+
+        output
+    }
+
     pub fn speaker_count(&self) -> usize {
         self.speakers.len()
     }
@@ -245,6 +424,79 @@ impl Vbap3D {
     }
 }
 
+/// 2D VBAP over a ring of horizontal speakers
+///
+/// Sources are panned between the two speakers adjacent in azimuth.
+/// Elevation is ignored. Where the gap between neighbors is 180° or more,
+/// the source snaps to the nearer speaker instead.
+pub struct Vbap2D {
+    /// Speaker indices with their azimuths, sorted by azimuth
+    ring: Vec<(usize, f32)>,
+}
+
+impl Vbap2D {
+    pub fn new(speakers: Vec<Speaker3D>) -> Self {
+        let mut ring: Vec<(usize, f32)> = speakers
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i, azimuth_deg(&s.position)))
+            .collect();
+        ring.sort_by(|a, b| a.1.total_cmp(&b.1));
+        Self { ring }
+    }
+
+    /// Render a sound source to speaker gains
+    pub fn render(&self, source: &Vec3) -> Vec<f32> {
+        let mut gains = vec![0.0; self.ring.len()];
+        match self.ring.len() {
+            0 => return gains,
+            1 => {
+                gains[self.ring[0].0] = 1.0;
+                return gains;
+            }
+            _ => {}
+        }
+
+        let source_az = azimuth_deg(source);
+        for (k, &(a, az_a)) in self.ring.iter().enumerate() {
+            let (b, az_b) = self.ring[(k + 1) % self.ring.len()];
+            let span = (az_b - az_a).rem_euclid(360.0);
+            let offset = (source_az - az_a).rem_euclid(360.0);
+            // Coincident speakers leave no arc to pan across
+            if span == 0.0 || offset > span {
+                continue;
+            }
+
+            if span >= 180.0 {
+                let nearer = if offset <= span - offset { a } else { b };
+                gains[nearer] = 1.0;
+            } else {
+                // Solve source = g_a * a + g_b * b in the horizontal plane
+                let (sa, ca) = az_a.to_radians().sin_cos();
+                let (sb, cb) = az_b.to_radians().sin_cos();
+                let (ss, cs) = source_az.to_radians().sin_cos();
+                let det = sa * cb - sb * ca;
+                let g_a = (ss * cb - sb * cs) / det;
+                let g_b = (sa * cs - ss * ca) / det;
+                let norm = (g_a * g_a + g_b * g_b).sqrt();
+                gains[a] = g_a.max(0.0) / norm;
+                gains[b] = g_b.max(0.0) / norm;
+            }
+            break;
+        }
+        gains
+    }
+
+    pub fn speaker_count(&self) -> usize {
+        self.ring.len()
+    }
+}
+
+/// Azimuth of a direction in degrees, 0° at the front and positive to the left
+fn azimuth_deg(v: &Vec3) -> f32 {
+    v.x.atan2(v.y).to_degrees()
+}
+
 /// Create a standard speaker layout
 pub fn create_stereo_layout() -> Vec<Speaker3D> {
     vec![
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use audio_ninja::ble::*;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 #[test]
@@ -13,6 +14,80 @@ fn test_speaker_role_channel_index() {
     assert_eq!(SpeakerRole::SurroundRight.to_channel_index(), 5);
 }
 
+#[test]
+fn test_speaker_role_matches_layout_channel_order() {
+    let layout = audio_ninja::SpeakerLayout::from_preset("7.1").unwrap();
+    for role in [
+        SpeakerRole::FrontLeft,
+        SpeakerRole::FrontRight,
+        SpeakerRole::Center,
+        SpeakerRole::LFE,
+        SpeakerRole::SurroundLeft,
+        SpeakerRole::SurroundRight,
+        SpeakerRole::BackLeft,
+        SpeakerRole::BackRight,
+    ] {
+        let index = role.to_channel_index();
+        let role = audio_ninja::SpeakerRole::from(role);
+        assert_eq!(
+            audio_ninja::mapping::channel_index(&role, &layout),
+            Some(index)
+        );
+    }
+}
+
+#[test]
+fn test_speaker_role_round_trips_through_layout_role() {
+    let roles = [
+        SpeakerRole::FrontLeft,
+        SpeakerRole::FrontRight,
+        SpeakerRole::Center,
+        SpeakerRole::LFE,
+        SpeakerRole::SurroundLeft,
+        SpeakerRole::SurroundRight,
+        SpeakerRole::BackLeft,
+        SpeakerRole::BackRight,
+        SpeakerRole::TopFrontLeft,
+        SpeakerRole::TopFrontRight,
+        SpeakerRole::TopBackLeft,
+        SpeakerRole::TopBackRight,
+        SpeakerRole::Custom("Ceiling".into()),
+    ];
+    for role in roles {
+        let layout_role = audio_ninja::SpeakerRole::from(role.clone());
+        assert_eq!(SpeakerRole::try_from(layout_role.clone()).unwrap(), role);
+
+        // Stored BLE configs deserialize straight into layout roles
+        let json = serde_json::to_string(&role).unwrap();
+        let parsed: audio_ninja::SpeakerRole = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, layout_role);
+    }
+
+    assert_eq!(
+        audio_ninja::SpeakerRole::from(SpeakerRole::LFE),
+        audio_ninja::SpeakerRole::Subwoofer
+    );
+    assert_eq!(
+        serde_json::to_string(&audio_ninja::SpeakerRole::SideLeft).unwrap(),
+        "\"SideLeft\""
+    );
+}
+
+#[test]
+fn test_layout_only_roles_have_no_ble_equivalent() {
+    for role in [
+        audio_ninja::SpeakerRole::FrontHeightLeft,
+        audio_ninja::SpeakerRole::FrontHeightRight,
+        audio_ninja::SpeakerRole::RearHeightLeft,
+        audio_ninja::SpeakerRole::RearHeightRight,
+    ] {
+        assert!(matches!(
+            SpeakerRole::try_from(role.clone()),
+            Err(BleError::UnsupportedRole(r)) if r == role
+        ));
+    }
+}
+
 #[test]
 fn test_speaker_identity_creation() {
     let identity = SpeakerIdentity {
@@ -176,6 +251,117 @@ fn test_ble_peripheral_pairing_no_pin() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_ble_peripheral_pin_validation_and_bonding() {
+    let identity = SpeakerIdentity {
+        id: "test_speaker".into(),
+        name: "Test Speaker".into(),
+        role: SpeakerRole::FrontLeft,
+        mac_address: "AA:BB:CC:DD:EE:FF".into(),
+    };
+
+    let mut peripheral = BlePeripheral::new(identity);
+    peripheral.set_expected_pin("482913");
+    peripheral.connect().unwrap();
+
+    let request = |pin: &str, master: &str| PairingRequest {
+        pin_code: Some(pin.into()),
+        master_id: master.into(),
+    };
+
+    assert!(matches!(
+        peripheral.pair(request("000000", "master001")),
+        Err(BleError::PairingFailed(_))
+    ));
+    assert!(matches!(peripheral.status(), ConnectionStatus::Connected));
+    assert!(!peripheral.is_bonded_to("master001"));
+
+    peripheral.pair(request("482913", "master001")).unwrap();
+    assert!(matches!(peripheral.status(), ConnectionStatus::Paired));
+    assert!(peripheral.is_bonded_to("master001"));
+
+    match peripheral.pair(request("482913", "master002")) {
+        Err(BleError::AlreadyBonded(master)) => assert_eq!(master, "master001"),
+        other => panic!("expected AlreadyBonded, got {other:?}"),
+    }
+    assert!(!peripheral.is_bonded_to("master002"));
+
+    // The bonded master may pair again
+    peripheral.pair(request("482913", "master001")).unwrap();
+}
+
+#[test]
+fn test_ble_peripheral_status_notifications() {
+    let identity = SpeakerIdentity {
+        id: "test_speaker".into(),
+        name: "Test Speaker".into(),
+        role: SpeakerRole::FrontLeft,
+        mac_address: "AA:BB:CC:DD:EE:FF".into(),
+    };
+
+    let mut peripheral = BlePeripheral::new(identity);
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    peripheral.subscribe_status(Arc::new(move |status: &ConnectionStatus| {
+        sink.lock().unwrap().push(status.clone());
+    }));
+
+    peripheral.connect().unwrap();
+    peripheral
+        .pair(PairingRequest {
+            pin_code: Some("1234".into()),
+            master_id: "master001".into(),
+        })
+        .unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![ConnectionStatus::Connected, ConnectionStatus::Paired]
+    );
+
+    // The notifiable characteristic carries the latest status
+    let value = peripheral
+        .read_characteristic(&characteristic_uuids::CONNECTION_STATUS)
+        .unwrap();
+    assert_eq!(value, wire::encode_status(&ConnectionStatus::Paired));
+    assert_eq!(
+        wire::decode_status(&value).unwrap(),
+        ConnectionStatus::Paired
+    );
+}
+
+#[test]
+fn test_ble_central_status_change() {
+    let central = BleCentral::new();
+    let identity = SpeakerIdentity {
+        id: "speaker1".into(),
+        name: "Speaker 1".into(),
+        role: SpeakerRole::FrontLeft,
+        mac_address: "AA:BB:CC:DD:EE:01".into(),
+    };
+    let mut peripheral = BlePeripheral::new(identity);
+    peripheral.connect().unwrap();
+    central.connect("speaker1", peripheral).unwrap();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let sink = seen.clone();
+    central
+        .on_status_change(
+            "speaker1",
+            Arc::new(move |status: &ConnectionStatus| {
+                sink.lock().unwrap().push(status.clone());
+            }),
+        )
+        .unwrap();
+    assert!(matches!(
+        central.on_status_change("nonexistent", Arc::new(|_: &ConnectionStatus| {})),
+        Err(BleError::DeviceNotFound(_))
+    ));
+
+    central.disconnect("speaker1").unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![ConnectionStatus::Disconnected]);
+}
+
 #[test]
 fn test_ble_peripheral_read_write_characteristic() {
     let identity = SpeakerIdentity {
@@ -205,11 +391,12 @@ fn test_ble_central_creation() {
 }
 
 #[test]
+#[cfg(not(feature = "ble"))]
 fn test_ble_central_scan() {
     let central = BleCentral::new();
 
     let devices = central.scan(Duration::from_secs(1)).unwrap();
-    // Placeholder implementation returns empty
+    // Without the `ble` feature scanning is a no-op
     assert_eq!(devices.len(), 0);
 }
 
@@ -227,6 +414,7 @@ fn test_ble_central_connect_disconnect() {
     let peripheral = BlePeripheral::new(identity);
 
     central.connect("speaker1", peripheral).unwrap();
+
     assert_eq!(central.list_devices().len(), 1);
 
     central.disconnect("speaker1").unwrap();
@@ -282,6 +470,111 @@ fn test_ble_central_write_calibration() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_wire_identity_layout_is_pinned() {
+    // version, role (FrontLeft), MAC, id "spk1", name "Left"
+    let bytes = [
+        0x01, 0x00, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF, 0x04, b's', b'p', b'k', b'1', 0x04, b'L',
+        b'e', b'f', b't',
+    ];
+    let identity = SpeakerIdentity {
+        id: "spk1".into(),
+        name: "Left".into(),
+        role: SpeakerRole::FrontLeft,
+        mac_address: "AA:BB:CC:DD:EE:FF".into(),
+    };
+
+    assert_eq!(wire::decode_identity(&bytes).unwrap(), identity);
+    assert_eq!(wire::encode_identity(&identity).unwrap(), bytes);
+}
+
+#[test]
+fn test_wire_custom_role_round_trip() {
+    let identity = SpeakerIdentity {
+        id: "spk9".into(),
+        name: "Ceiling".into(),
+        role: SpeakerRole::Custom("Voice of God".into()),
+        mac_address: "01:02:03:04:05:06".into(),
+    };
+
+    let bytes = wire::encode_identity(&identity).unwrap();
+    assert_eq!(bytes[1], 0xFF);
+    assert_eq!(wire::decode_identity(&bytes).unwrap(), identity);
+}
+
+#[test]
+fn test_wire_calibration_layout_is_pinned() {
+    assert_eq!(wire::encode_trim_db(-3.0), [0x01, 0x00, 0x00, 0x40, 0xC0]);
+    assert_eq!(wire::encode_delay_ms(1.5), [0x01, 0x00, 0x00, 0xC0, 0x3F]);
+    assert_eq!(
+        wire::decode_trim_db(&[0x01, 0x00, 0x00, 0x40, 0xC0]).unwrap(),
+        -3.0
+    );
+    assert_eq!(
+        wire::decode_delay_ms(&[0x01, 0x00, 0x00, 0xC0, 0x3F]).unwrap(),
+        1.5
+    );
+}
+
+#[test]
+fn test_wire_status_layout_is_pinned() {
+    assert_eq!(
+        wire::encode_status(&ConnectionStatus::Disconnected),
+        [0x01, 0x00]
+    );
+    assert_eq!(wire::encode_status(&ConnectionStatus::Paired), [0x01, 0x03]);
+
+    let error = [0x01, 0x04, 0x04, b'b', b'u', b's', b'y'];
+    assert_eq!(
+        wire::encode_status(&ConnectionStatus::Error("busy".into())),
+        error
+    );
+    assert_eq!(
+        wire::decode_status(&error).unwrap(),
+        ConnectionStatus::Error("busy".into())
+    );
+    assert!(matches!(
+        wire::decode_status(&[0x01, 0x05]),
+        Err(BleError::InvalidWireFormat(_))
+    ));
+
+    // Long messages are cut on a character boundary
+    let long = ConnectionStatus::Error("é".repeat(200));
+    let bytes = wire::encode_status(&long);
+    assert_eq!(bytes[2], 254);
+    assert_eq!(
+        wire::decode_status(&bytes).unwrap(),
+        ConnectionStatus::Error("é".repeat(127))
+    );
+}
+
+#[test]
+fn test_wire_rejects_bad_values() {
+    let identity = SpeakerIdentity {
+        id: "spk1".into(),
+        name: "Left".into(),
+        role: SpeakerRole::FrontLeft,
+        mac_address: "AA:BB:CC:DD:EE:FF".into(),
+    };
+    let mut bytes = wire::encode_identity(&identity).unwrap();
+
+    bytes[0] = 2;
+    assert!(matches!(
+        wire::decode_identity(&bytes),
+        Err(BleError::InvalidWireFormat(_))
+    ));
+    assert!(matches!(
+        wire::decode_trim_db(&[0x01, 0x00, 0x00]),
+        Err(BleError::InvalidWireFormat(_))
+    ));
+
+    let bad_mac = SpeakerIdentity {
+        mac_address: "not-a-mac".into(),
+        ..identity
+    };
+    assert!(wire::encode_identity(&bad_mac).is_err());
+}
+
 #[test]
 fn test_ble_central_device_not_found() {
     let central = BleCentral::new();
@@ -92,6 +92,68 @@ fn test_design_high_shelf() {
     assert_eq!(shelf.gain_db, -3.0);
 }
 
+#[test]
+fn test_butterworth_is_3db_down_at_cutoff() {
+    let sr = 48000;
+    let q = std::f32::consts::FRAC_1_SQRT_2;
+    let db = |filter: &audio_ninja::dsp::BiquadFilter, freq: f32| {
+        filter.coeffs.magnitude_db_curve(&[freq], sr)[0]
+    };
+
+    let lowpass = design_lowpass(1000.0, q, sr);
+    assert!((db(&lowpass, 1000.0) + 3.01).abs() < 0.05);
+    assert!(db(&lowpass, 50.0).abs() < 0.01);
+    assert!(db(&lowpass, 10000.0) < -35.0);
+
+    let highpass = design_highpass(40.0, q, sr);
+    assert!((db(&highpass, 40.0) + 3.01).abs() < 0.05);
+    assert!(db(&highpass, 1000.0).abs() < 0.01);
+}
+
+#[test]
+fn test_linkwitz_riley_pair_is_6db_down_at_crossover() {
+    let sr = 48000;
+    let (low, high) = design_linkwitz_riley_pair(80.0, sr);
+
+    assert_eq!(low.len(), 2);
+    assert_eq!(high.len(), 2);
+    assert!((low.magnitude_db(80.0, sr) + 6.02).abs() < 0.1);
+    assert!((high.magnitude_db(80.0, sr) + 6.02).abs() < 0.1);
+    assert!(low.magnitude_db(20.0, sr).abs() < 0.05);
+    assert!(high.magnitude_db(1000.0, sr).abs() < 0.05);
+}
+
+#[test]
+fn test_linkwitz_riley_pair_sums_flat() {
+    let sr = 48000;
+    let crossover = 80.0;
+
+    for freq in [20.0, 50.0, 80.0, 120.0, 500.0, 2000.0] {
+        let (mut low, mut high) = design_linkwitz_riley_pair(crossover, sr);
+        let tone: Vec<f32> = (0..sr as usize)
+            .map(|n| (2.0 * std::f32::consts::PI * freq * n as f32 / sr as f32).sin())
+            .collect();
+        let mut low_band = tone.clone();
+        let mut high_band = tone.clone();
+        low.process_block(&mut low_band);
+        high.process_block(&mut high_band);
+
+        // Skip the filters' settling time
+        let settled = sr as usize / 2..;
+        let peak = low_band[settled.clone()]
+            .iter()
+            .zip(&high_band[settled])
+            .map(|(l, h)| (l + h).abs())
+            .fold(0.0f32, f32::max);
+        assert!(
+            (20.0 * peak.log10()).abs() < 0.1,
+            "sum at {} Hz is {:.3} dB",
+            freq,
+            20.0 * peak.log10()
+        );
+    }
+}
+
 #[test]
 fn test_extract_ir_from_sweep() {
     let sweep = generate_log_sweep(48000, Duration::from_millis(100), 20, 20000);
@@ -106,6 +168,63 @@ fn test_extract_ir_from_sweep() {
     assert!(peak.is_some());
 }
 
+#[test]
+fn test_extract_ir_recovers_delay_and_gain() {
+    let sweep = generate_log_sweep(48000, Duration::from_millis(200), 20, 20000);
+    let delay = 240;
+    let mut recorded = vec![0.0; delay];
+    recorded.extend(sweep.iter().map(|x| 0.5 * x));
+
+    let ir = extract_ir_from_sweep(&recorded, &sweep, 48000);
+    let reference_ir = extract_ir_from_sweep(&sweep, &sweep, 48000);
+
+    assert_eq!(ir.len(), 4800);
+    assert_eq!(find_ir_peak(&ir), Some(delay));
+    // Band-limiting spreads the impulse, so compare against an undelayed,
+    // unity-gain capture
+    let gain = ir[delay] / reference_ir[0];
+    assert!((gain - 0.5).abs() < 0.01, "gain {}", gain);
+}
+
+#[test]
+fn test_extract_ir_recorded_shorter_than_reference() {
+    let sweep = generate_log_sweep(48000, Duration::from_millis(200), 20, 20000);
+    let recorded = &sweep[..sweep.len() / 2];
+
+    let ir = extract_ir_from_sweep(recorded, &sweep, 48000);
+
+    assert_eq!(ir.len(), 4800);
+    assert!(ir.iter().all(|x| x.is_finite()));
+    assert_eq!(find_ir_peak(&ir), Some(0));
+}
+
+#[test]
+fn test_extract_ir_zero_energy_reference() {
+    let recorded = generate_log_sweep(48000, Duration::from_millis(50), 20, 20000);
+
+    let ir = extract_ir_from_sweep(&recorded, &vec![0.0; 1000], 48000);
+    assert_eq!(ir.len(), 4800);
+    assert!(ir.iter().all(|&x| x == 0.0));
+
+    let ir = extract_ir_from_sweep(&recorded, &[], 48000);
+    assert!(ir.iter().all(|&x| x == 0.0));
+}
+
+#[test]
+fn test_extract_ir_non_power_of_two_lengths() {
+    // 1000 + 1000 samples pads to 2048, shorter than the 100 ms window
+    let sweep = generate_log_sweep(10000, Duration::from_millis(100), 20, 4000);
+    assert_eq!(sweep.len(), 1000);
+    let mut recorded = vec![0.0; 37];
+    recorded.extend_from_slice(&sweep);
+
+    let ir = extract_ir_from_sweep(&recorded, &sweep, 10000);
+
+    assert_eq!(ir.len(), 1000);
+    assert!(ir.iter().all(|x| x.is_finite()));
+    assert_eq!(find_ir_peak(&ir), Some(37));
+}
+
 #[test]
 fn test_measurement_config_log_sweep() {
     let cfg = MeasurementConfig {
@@ -135,6 +254,81 @@ fn test_measurement_config_mls() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_measure_fills_peak_and_magnitude_response() {
+    let cfg = MeasurementConfig {
+        sweep_duration: Duration::from_millis(200),
+        sample_rate: 48000,
+        sweep_type: SweepType::LogSweep {
+            start_hz: 20,
+            end_hz: 20000,
+        },
+    };
+
+    let result = ReferenceCalibrator.measure(&cfg).unwrap();
+
+    assert_eq!(result.peak_index, Some(0));
+    let magnitude = result.magnitude_response.unwrap();
+    assert_eq!(magnitude.len(), DEFAULT_MAGNITUDE_BINS);
+
+    // The ideal loopback is flat across the swept band
+    let frequencies = magnitude_response_frequencies(48000, DEFAULT_MAGNITUDE_BINS);
+    for (freq, db) in frequencies.iter().zip(&magnitude) {
+        if (100.0..10000.0).contains(freq) {
+            assert!(db.abs() < 1.0, "{} Hz reads {} dB", freq, db);
+        }
+    }
+}
+
+#[test]
+fn test_magnitude_response_of_impulse_is_flat() {
+    let mut ir = vec![0.0; 4800];
+    ir[0] = 1.0;
+
+    let magnitude = compute_magnitude_response(&ir, 48000, 64);
+
+    assert_eq!(magnitude.len(), 64);
+    assert!(magnitude.iter().all(|db| db.abs() < 1e-3));
+}
+
+#[test]
+fn test_magnitude_response_shows_rolloff() {
+    // Two-tap average: unity at DC, a null at Nyquist
+    let ir = [0.5, 0.5];
+
+    let magnitude = compute_magnitude_response(&ir, 48000, 32);
+    let frequencies = magnitude_response_frequencies(48000, 32);
+
+    assert!((frequencies[0] - 20.0).abs() < 1e-3);
+    assert!((frequencies[31] - 24000.0).abs() < 1.0);
+    assert!(magnitude[0].abs() < 0.1);
+    assert!(magnitude[31] < -20.0);
+    assert!(magnitude.windows(2).all(|w| w[1] <= w[0] + 1e-3));
+}
+
+#[test]
+fn test_magnitude_response_smoothing_and_edge_cases() {
+    assert!(compute_magnitude_response(&[1.0], 48000, 0).is_empty());
+    assert_eq!(compute_magnitude_response(&[], 48000, 8), vec![-120.0; 8]);
+    assert!(compute_magnitude_response(&[0.0; 512], 48000, 8)
+        .iter()
+        .all(|&db| db == -120.0));
+
+    // A comb filter's notches are averaged out by wider smoothing
+    let mut comb = vec![0.0; 64];
+    comb[0] = 1.0;
+    comb[48] = 1.0;
+    let narrow = compute_magnitude_response_smoothed(&comb, 48000, 128, 1.0 / 24.0);
+    let wide = compute_magnitude_response_smoothed(&comb, 48000, 128, 1.0);
+    let spread = |db: &[f32]| {
+        let (lo, hi) = db[64..]
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &x| (lo.min(x), hi.max(x)));
+        hi - lo
+    };
+    assert!(spread(&wide) < spread(&narrow));
+}
+
 #[test]
 fn test_calibration_solution() {
     let result = MeasurementResult {
@@ -149,6 +343,193 @@ fn test_calibration_solution() {
     assert!(solution.is_ok());
 }
 
+fn measurement_from_ir(impulse_response: Vec<f32>) -> MeasurementResult {
+    MeasurementResult {
+        peak_index: find_ir_peak(&impulse_response),
+        impulse_response,
+        sample_rate: 48000,
+        magnitude_response: None,
+    }
+}
+
+#[test]
+fn test_solve_silent_measurement_is_neutral() {
+    for ir in [vec![0.0; 1000], vec![]] {
+        let solution = ReferenceCalibrator.solve(&measurement_from_ir(ir)).unwrap();
+        assert_eq!(solution.delays, vec![Duration::ZERO]);
+        assert_eq!(solution.trims_db, vec![0.0]);
+        assert!(solution.peq.is_empty());
+    }
+}
+
+#[test]
+fn test_solve_flat_impulse_needs_no_correction() {
+    let mut ir = vec![0.0; 4800];
+    ir[0] = 1.0;
+
+    let solution = ReferenceCalibrator.solve(&measurement_from_ir(ir)).unwrap();
+
+    assert_eq!(solution.delays, vec![Duration::ZERO]);
+    assert!(solution.trims_db[0].abs() < 1e-3);
+    assert!(solution.peq.is_empty());
+}
+
+#[test]
+fn test_solve_corrects_delay_level_and_resonance() {
+    use audio_ninja::dsp::BiquadCascade;
+
+    // Speaker 2 ms away, 6 dB quiet, with an 8 dB resonance at 1 kHz
+    let delay = 96;
+    let mut ir = vec![0.0; 4800];
+    ir[delay] = 0.5;
+    BiquadCascade::new(vec![design_peq(1000.0, 8.0, 2.0, 48000)]).process_block(&mut ir);
+
+    let solution = ReferenceCalibrator
+        .solve(&measurement_from_ir(ir.clone()))
+        .unwrap();
+
+    assert_eq!(solution.delays, vec![compute_delay(delay, 48000)]);
+    assert!(
+        (3.0..7.0).contains(&solution.trims_db[0]),
+        "trim {}",
+        solution.trims_db[0]
+    );
+
+    assert!((1..=MAX_PEQ_BANDS).contains(&solution.peq.len()));
+    let correction = BiquadCascade::new(solution.peq.clone());
+    assert!(correction.magnitude_db(1000.0, 48000) < -5.0);
+
+    // Measured response with the correction applied stays close to flat
+    let magnitude = compute_magnitude_response(&ir, 48000, DEFAULT_MAGNITUDE_BINS);
+    let frequencies = magnitude_response_frequencies(48000, DEFAULT_MAGNITUDE_BINS);
+    let corrected: Vec<f32> = frequencies
+        .iter()
+        .zip(&magnitude)
+        .filter(|(f, _)| (40.0..16000.0).contains(*f))
+        .map(|(&f, db)| db + correction.magnitude_db(f, 48000))
+        .collect();
+    let mean = corrected.iter().sum::<f32>() / corrected.len() as f32;
+    let worst = corrected
+        .iter()
+        .map(|db| (db - mean).abs())
+        .fold(0.0, f32::max);
+    assert!(worst < 2.0, "worst deviation {} dB", worst);
+}
+
+fn impulse_block(channels: usize, frames: usize) -> audio_ninja::AudioBlock {
+    let mut block = audio_ninja::AudioBlock::silence(channels, frames, 48000);
+    for samples in &mut block.channels {
+        samples[0] = 1.0;
+    }
+    block
+}
+
+#[test]
+fn test_solution_apply_delays_and_trims() {
+    let solution = CalibrationSolution {
+        delays: vec![Duration::ZERO, Duration::from_millis(1)],
+        trims_db: vec![-6.0, 0.0],
+        peq: vec![],
+        fir: None,
+    };
+    let mut block = impulse_block(3, 256);
+
+    solution.apply(&mut block);
+
+    let gain = 10f32.powf(-6.0 / 20.0);
+    assert!((block.channels[0][0] - gain).abs() < 1e-6);
+    assert_eq!(block.channels[1][0], 0.0);
+    assert_eq!(block.channels[1][48], 1.0);
+    assert_eq!(block.channels[1].iter().filter(|&&x| x != 0.0).count(), 1);
+    // Channel beyond the solution passes through
+    assert_eq!(block.channels[2], impulse_block(1, 256).channels[0]);
+}
+
+#[test]
+fn test_solution_apply_runs_peq_on_covered_channels() {
+    use audio_ninja::dsp::BiquadCascade;
+
+    let peq = vec![design_peq(1000.0, 6.0, 1.0, 48000)];
+    let solution = CalibrationSolution {
+        delays: vec![Duration::ZERO],
+        trims_db: vec![0.0],
+        peq: peq.clone(),
+        fir: None,
+    };
+    let mut block = impulse_block(2, 512);
+
+    solution.apply(&mut block);
+
+    let mut expected = impulse_block(1, 512).channels.remove(0);
+    BiquadCascade::new(peq).process_block(&mut expected);
+    assert_eq!(block.channels[0], expected);
+    assert_eq!(block.channels[1], impulse_block(1, 512).channels[0]);
+}
+
+#[test]
+fn test_solution_apply_delay_longer_than_block() {
+    let solution = CalibrationSolution {
+        delays: vec![Duration::from_millis(10)],
+        trims_db: vec![],
+        peq: vec![],
+        fir: None,
+    };
+    let mut block = impulse_block(1, 64);
+
+    solution.apply(&mut block);
+
+    assert!(block.channels[0].iter().all(|&x| x == 0.0));
+}
+
+#[test]
+fn test_applier_blockwise_matches_one_shot() {
+    use audio_ninja::dsp::FirFilter;
+
+    let sr = 48000;
+    let solution = CalibrationSolution {
+        delays: vec![Duration::from_millis(2), Duration::ZERO],
+        trims_db: vec![-3.0, 2.0],
+        peq: vec![design_peq(1000.0, 6.0, 1.0, sr)],
+        fir: Some(FirFilter::new(vec![0.5, 0.3, 0.2])),
+    };
+    let sweep = generate_log_sweep(sr, Duration::from_millis(50), 20, 20000);
+    let signal = audio_ninja::AudioBlock {
+        sample_rate: sr,
+        channels: vec![sweep.clone(), sweep.clone(), sweep],
+    };
+
+    let mut one_shot = signal.clone();
+    solution.apply(&mut one_shot);
+
+    let mut applier = CalibrationApplier::new(&solution, sr);
+    let mut streamed = vec![Vec::new(); 3];
+    for start in (0..signal.frame_len()).step_by(100) {
+        let end = (start + 100).min(signal.frame_len());
+        let mut block = audio_ninja::AudioBlock {
+            sample_rate: sr,
+            channels: signal
+                .channels
+                .iter()
+                .map(|ch| ch[start..end].to_vec())
+                .collect(),
+        };
+        applier.process(&mut block);
+        for (out, ch) in streamed.iter_mut().zip(block.channels) {
+            out.extend(ch);
+        }
+    }
+
+    for (streamed, one_shot) in streamed.iter().zip(&one_shot.channels) {
+        assert_eq!(streamed.len(), one_shot.len());
+        for (a, b) in streamed.iter().zip(one_shot) {
+            assert!((a - b).abs() < 1e-5, "{} vs {}", a, b);
+        }
+    }
+    // The 2 ms delay spans many 100-sample blocks without losing audio
+    assert!(streamed[0][..96].iter().all(|&x| x == 0.0));
+    assert!(streamed[0][96..].iter().any(|&x| x != 0.0));
+}
+
 #[test]
 fn test_camilladsp_config_to_yaml() {
     use audio_ninja::dsp::BiquadCoefficients;
@@ -156,16 +537,16 @@ fn test_camilladsp_config_to_yaml() {
     let solution = CalibrationSolution {
         delays: vec![Duration::from_millis(5), Duration::ZERO],
         trims_db: vec![-2.0, 0.0],
-        peq: vec![audio_ninja::dsp::BiquadFilter {
-            coeffs: BiquadCoefficients {
+        peq: vec![audio_ninja::dsp::BiquadFilter::new(
+            BiquadCoefficients {
                 b0: 1.0,
                 b1: 0.0,
                 b2: 0.0,
                 a1: 0.0,
                 a2: 0.0,
             },
-            gain_db: 3.0,
-        }],
+            3.0,
+        )],
         fir: None,
     };
 
@@ -185,9 +566,7 @@ fn test_brutefir_config() {
         delays: vec![],
         trims_db: vec![],
         peq: vec![],
-        fir: Some(FirFilter {
-            taps: vec![0.0, 0.5, 1.0, 0.5, 0.0],
-        }),
+        fir: Some(FirFilter::new(vec![0.0, 0.5, 1.0, 0.5, 0.0])),
     };
 
     let config = solution_to_brutefir(&solution, 48000);
@@ -0,0 +1,83 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use audio_ninja::control::{Automation, RampShape};
+use std::time::Duration;
+
+/// Tick in 480-frame blocks at 48kHz (10 ms)
+const BLOCK: Duration = Duration::from_millis(10);
+
+fn ramp_gain(shape: RampShape) -> Automation {
+    let mut automation = Automation::new();
+    automation.schedule("master_gain_db", -60.0, 0.0, Duration::from_secs(1), shape);
+    automation
+}
+
+fn tick_blocks(automation: &mut Automation, blocks: usize) {
+    for _ in 0..blocks {
+        automation.tick(BLOCK);
+    }
+}
+
+#[test]
+fn test_linear_gain_ramp_midpoint() {
+    let mut automation = ramp_gain(RampShape::Linear);
+    assert_eq!(automation.current_value("master_gain_db"), Some(-60.0));
+
+    tick_blocks(&mut automation, 50);
+    let mid = automation.current_value("master_gain_db").unwrap();
+    assert!((mid + 30.0).abs() < 1e-3, "midpoint = {}", mid);
+    assert!(automation.is_ramping("master_gain_db"));
+
+    tick_blocks(&mut automation, 60);
+    assert_eq!(automation.current_value("master_gain_db"), Some(0.0));
+    assert!(!automation.is_ramping("master_gain_db"));
+}
+
+#[test]
+fn test_ramp_shapes_at_midpoint() {
+    let mut s_curve = ramp_gain(RampShape::SCurve);
+    let mut exponential = ramp_gain(RampShape::Exponential);
+    tick_blocks(&mut s_curve, 50);
+    tick_blocks(&mut exponential, 50);
+
+    // S-curve is symmetric, exponential lags behind linear
+    let s_mid = s_curve.current_value("master_gain_db").unwrap();
+    assert!((s_mid + 30.0).abs() < 1e-3, "s-curve midpoint = {}", s_mid);
+    let exp_mid = exponential.current_value("master_gain_db").unwrap();
+    assert!(exp_mid < -45.0, "exponential midpoint = {}", exp_mid);
+
+    // S-curve eases in: the first block moves less than a linear step
+    let mut s_curve = ramp_gain(RampShape::SCurve);
+    tick_blocks(&mut s_curve, 1);
+    assert!(s_curve.current_value("master_gain_db").unwrap() < -59.4);
+}
+
+#[test]
+fn test_reschedule_and_cancel() {
+    let mut automation = ramp_gain(RampShape::Linear);
+    automation.schedule(
+        "binaural_azimuth",
+        0.0,
+        90.0,
+        Duration::from_millis(200),
+        RampShape::Linear,
+    );
+    tick_blocks(&mut automation, 10);
+    assert_eq!(automation.current_value("binaural_azimuth"), Some(45.0));
+
+    automation.cancel("binaural_azimuth");
+    tick_blocks(&mut automation, 10);
+    assert_eq!(automation.current_value("binaural_azimuth"), Some(45.0));
+
+    // A zero-length ramp jumps straight to the end value
+    automation.schedule(
+        "binaural_azimuth",
+        45.0,
+        -30.0,
+        Duration::ZERO,
+        RampShape::SCurve,
+    );
+    assert_eq!(automation.current_value("binaural_azimuth"), Some(-30.0));
+    assert!(!automation.is_ramping("binaural_azimuth"));
+    assert!(automation.current_value("unknown").is_none());
+}
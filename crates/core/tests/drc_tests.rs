@@ -10,17 +10,14 @@ fn peak_linear(samples: &[f32]) -> f32 {
 #[test]
 fn test_drc_reduces_peaks_without_makeup_gain() {
     let sr = 48000;
-    let frames = 2000;
+    let frames = 4000;
+    let burst = 1000;
 
-    // Mono block with transient peaks over threshold
+    // Mono block with sustained bursts over threshold
     let mut samples = vec![0.0f32; frames];
-    // Create some peaks
-    for i in (100..frames).step_by(200) {
-        if i < frames {
-            samples[i] = 0.9;
-        }
-        if i + 1 < frames {
-            samples[i + 1] = 0.7;
+    for start in (100..frames).step_by(2 * burst) {
+        for s in samples.iter_mut().skip(start).take(burst) {
+            *s = 0.9;
         }
     }
 
@@ -28,14 +25,15 @@ fn test_drc_reduces_peaks_without_makeup_gain() {
         sample_rate: sr,
         channels: vec![samples.clone()],
     };
-    let before_peak = peak_linear(&block.channels[0]);
 
-    let mut drc = DynamicRangeControl::new(4.0, -20.0, 5.0, 80.0, sr);
+    let mut drc = DynamicRangeControl::new(4.0, -20.0, 0.0, 5.0, 80.0, sr);
     drc.set_makeup_gain(0.0); // focus on compression effect
     let mut processed = block.clone();
     drc.process(&mut processed);
 
-    let after_peak = peak_linear(&processed.channels[0]);
+    // Once the attack window has elapsed the burst is held well below its input level
+    let before_peak = peak_linear(&block.channels[0][800..1100]);
+    let after_peak = peak_linear(&processed.channels[0][800..1100]);
     assert!(
         after_peak < before_peak * 0.96,
         "peak not sufficiently reduced: before {} after {}",
@@ -43,3 +41,172 @@ fn test_drc_reduces_peaks_without_makeup_gain() {
         after_peak
     );
 }
+
+#[test]
+fn test_drc_attack_ramps_in_gradually() {
+    let sr = 48000;
+    let attack_ms = 10.0;
+    let attack_samples = (sr as f32 * attack_ms / 1000.0) as usize;
+
+    // Step from silence to a level well above threshold
+    let mut drc = DynamicRangeControl::new(4.0, -20.0, 0.0, attack_ms, 100.0, sr);
+    let mut block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![vec![0.8; attack_samples * 5]],
+    };
+    drc.process(&mut block);
+
+    let gain: Vec<f32> = block.channels[0].iter().map(|s| s / 0.8).collect();
+
+    // No snap to full reduction on the first sample
+    assert!(gain[0] > 0.99, "first sample gain {}", gain[0]);
+
+    // Reduction deepens monotonically through the attack window
+    for pair in gain[..attack_samples].windows(2) {
+        assert!(pair[1] <= pair[0] + 1e-6);
+    }
+    assert!(gain[attack_samples / 4] > gain[attack_samples]);
+
+    // And settles close to the static curve after several time constants
+    let settled = *gain.last().unwrap();
+    let static_gain = 1.0 / (0.8f32 / 0.1).powf(0.75);
+    assert!(
+        (settled - static_gain).abs() < 0.02,
+        "settled {} static {}",
+        settled,
+        static_gain
+    );
+    assert!(gain[attack_samples / 4] > settled * 1.5);
+}
+
+#[test]
+fn test_drc_stereo_link_applies_equal_gain() {
+    let sr = 48000;
+    // Transient hard-panned left over a steady quiet right channel
+    let mut left = vec![0.0f32; 2400];
+    for s in left.iter_mut().skip(480).take(960) {
+        *s = 0.9;
+    }
+    let right = vec![0.05f32; 2400];
+    let block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![left.clone(), right.clone()],
+    };
+
+    let mut linked = DynamicRangeControl::new(4.0, -20.0, 0.0, 1.0, 50.0, sr);
+    linked.set_stereo_link(true);
+    let mut processed = block.clone();
+    linked.process(&mut processed);
+
+    for i in 480..1440 {
+        let left_gain = processed.channels[0][i] / left[i];
+        let right_gain = processed.channels[1][i] / right[i];
+        assert!((left_gain - right_gain).abs() < 1e-6, "frame {}", i);
+    }
+    // The quiet right channel is pulled down with the left transient
+    assert!(processed.channels[1][1400] < 0.05 * 0.5);
+
+    // Unlinked, the right channel never crosses threshold on its own
+    let mut unlinked = DynamicRangeControl::new(4.0, -20.0, 0.0, 1.0, 50.0, sr);
+    let mut right_only = AudioBlock {
+        sample_rate: sr,
+        channels: vec![right.clone()],
+    };
+    unlinked.process(&mut right_only);
+    assert_eq!(right_only.channels[0], right);
+}
+
+#[test]
+fn test_drc_unlinked_channels_compress_independently() {
+    let sr = 48000;
+    // Loud left channel next to a quiet right channel below threshold
+    let left = vec![0.9f32; 4800];
+    let right = vec![0.05f32; 4800];
+    let mut block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![left.clone(), right.clone()],
+    };
+
+    let mut drc = DynamicRangeControl::new(4.0, -20.0, 0.0, 1.0, 50.0, sr);
+    drc.process(&mut block);
+
+    // The left channel is compressed...
+    assert!(block.channels[0][4799] < 0.9 * 0.5);
+    // ...and the right channel is not pulled down with it
+    assert_eq!(block.channels[1], right);
+    assert!(drc.current_reduction_db() < -6.0);
+}
+
+/// Gain (dB) applied to a slow level ramp from -40 dBFS to 0 dBFS
+fn ramp_gain_curve(knee_db: Option<f32>) -> (f32, Vec<f32>) {
+    let sr = 48000;
+    let frames = 4800;
+    let step_db = 40.0 / frames as f32;
+
+    let input: Vec<f32> = (0..frames)
+        .map(|i| 10.0f32.powf((-40.0 + i as f32 * step_db) / 20.0))
+        .collect();
+
+    let mut drc = DynamicRangeControl::new(4.0, -20.0, 0.0, 0.0, 100.0, sr);
+    if let Some(knee) = knee_db {
+        drc.set_knee_db(knee);
+    }
+    let mut block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![input.clone()],
+    };
+    drc.process(&mut block);
+
+    let gain_db = block.channels[0]
+        .iter()
+        .zip(&input)
+        .map(|(out, inp)| 20.0 * (out / inp).log10())
+        .collect();
+    (step_db, gain_db)
+}
+
+/// Largest change in slope of the gain curve between neighbouring steps
+fn max_slope_change(step_db: f32, gain_db: &[f32]) -> f32 {
+    let slopes: Vec<f32> = gain_db
+        .windows(2)
+        .map(|w| (w[1] - w[0]) / step_db)
+        .collect();
+    slopes
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn test_drc_soft_knee_smooths_threshold() {
+    let (step_db, hard) = ramp_gain_curve(None);
+    let (_, soft) = ramp_gain_curve(Some(12.0));
+
+    // Hard knee: slope jumps from 0 to -(1 - 1/ratio) at the threshold
+    assert!(max_slope_change(step_db, &hard) > 0.3);
+    // Soft knee: slope eases in across the knee region
+    assert!(max_slope_change(step_db, &soft) < 0.05);
+
+    // Compression starts below the threshold and matches the hard curve above the knee
+    let at = |level_db: f32| ((level_db + 40.0) / step_db) as usize;
+    assert_eq!(hard[at(-24.0)], 0.0);
+    assert!(soft[at(-24.0)] < 0.0);
+    assert!((soft[at(-5.0)] - hard[at(-5.0)]).abs() < 0.01);
+}
+
+#[test]
+fn test_drc_zero_knee_matches_hard_knee() {
+    let (_, default) = ramp_gain_curve(None);
+    let (_, zero) = ramp_gain_curve(Some(0.0));
+    assert_eq!(default, zero);
+}
+
+#[test]
+fn test_drc_knee_from_constructor_matches_setter() {
+    let sr = 48000;
+    let from_new = DynamicRangeControl::new(4.0, -20.0, 12.0, 0.0, 100.0, sr);
+    let mut from_setter = DynamicRangeControl::new(4.0, -20.0, 0.0, 0.0, 100.0, sr);
+    from_setter.set_knee_db(12.0);
+    assert_eq!(from_new.knee_db(), 12.0);
+    assert_eq!(from_new, from_setter);
+}
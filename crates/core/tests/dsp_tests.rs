@@ -0,0 +1,407 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use audio_ninja::calibration::design_peq;
+use audio_ninja::dsp::{
+    design_linkwitz_riley, fft, ifft, resample, resample_with_quality, BassManager, BiquadCascade,
+    Complex32, FirFilter, LrOrder, ResampleQuality, StreamingResampler,
+};
+use audio_ninja::{AudioBlock, SpeakerLayout};
+use std::f32::consts::PI;
+
+fn sine(freq_hz: f32, sample_rate: u32, frames: usize) -> Vec<f32> {
+    (0..frames)
+        .map(|n| (2.0 * PI * freq_hz * n as f32 / sample_rate as f32).sin())
+        .collect()
+}
+
+fn peak(samples: &[f32]) -> f32 {
+    samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max)
+}
+
+#[test]
+fn test_cascade_of_peqs_sums_gains() {
+    let sr = 48000;
+    let low = design_peq(100.0, 6.0, 2.0, sr);
+    let high = design_peq(4000.0, -4.0, 2.0, sr);
+    let cascade = BiquadCascade::new(vec![low.clone(), high.clone()]);
+
+    let low_only = BiquadCascade::new(vec![low]);
+    let high_only = BiquadCascade::new(vec![high]);
+
+    for freq in [100.0, 4000.0] {
+        let combined = cascade.magnitude_db(freq, sr);
+        let summed = low_only.magnitude_db(freq, sr) + high_only.magnitude_db(freq, sr);
+        assert!((combined - summed).abs() < 1e-3);
+    }
+
+    // Bands are far enough apart that each center sees mostly its own gain
+    assert!((cascade.magnitude_db(100.0, sr) - 6.0).abs() < 0.25);
+    assert!((cascade.magnitude_db(4000.0, sr) + 4.0).abs() < 0.25);
+}
+
+#[test]
+fn test_cascade_state_persists_across_blocks() {
+    let sr = 48000;
+    let filters = vec![
+        design_peq(250.0, 4.0, 1.0, sr),
+        design_peq(2000.0, -3.0, 1.5, sr),
+    ];
+    let input = sine(440.0, sr, 1000);
+
+    let mut whole = input.clone();
+    BiquadCascade::new(filters.clone()).process_block(&mut whole);
+
+    let mut split = input.clone();
+    let mut cascade = BiquadCascade::new(filters);
+    let (first, second) = split.split_at_mut(373);
+    cascade.process_block(first);
+    cascade.process_block(second);
+
+    for (a, b) in whole.iter().zip(&split) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_cascade_applies_gain_and_resets() {
+    let sr = 48000;
+    let mut cascade = BiquadCascade::new(vec![design_peq(1000.0, 6.0, 1.0, sr)]);
+
+    let mut samples = sine(1000.0, sr, 4800);
+    cascade.process_block(&mut samples);
+
+    // Steady-state amplitude after the filter settles: +6 dB ~= 2x
+    let settled = peak(&samples[2400..]);
+    assert!((settled - 1.995).abs() < 0.05, "peak {}", settled);
+
+    cascade.reset();
+    let mut silence = vec![0.0; 64];
+    cascade.process_block(&mut silence);
+    assert!(silence.iter().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_biquad_split_blocks_match_whole_signal() {
+    let sr = 48000;
+    let design = design_peq(1000.0, 6.0, 1.0, sr);
+    let input = sine(700.0, sr, 1000);
+
+    let mut whole = input.clone();
+    design.clone().process_block(&mut whole);
+
+    let mut split = input.clone();
+    let mut filter = design.clone();
+    let (first, second) = split.split_at_mut(500);
+    filter.process_block(first);
+    filter.process_block(second);
+    assert_eq!(split, whole);
+
+    // State doesn't affect equality, and reset starts from silence again
+    assert_eq!(filter, design);
+    filter.reset();
+    let mut again = input.clone();
+    filter.process_block(&mut again);
+    assert_eq!(again, whole);
+}
+
+#[test]
+fn test_peq_frequency_response() {
+    let sr = 48000;
+    let peq = design_peq(1000.0, 6.0, 1.0, sr);
+
+    let curve = peq.coeffs.magnitude_db_curve(&[20.0, 1000.0, 20000.0], sr);
+    assert!((curve[1] - 6.0).abs() < 0.05, "{} dB at 1 kHz", curve[1]);
+    assert!(curve[0].abs() < 0.1, "{} dB at 20 Hz", curve[0]);
+    assert!(curve[2].abs() < 0.2, "{} dB at 20 kHz", curve[2]);
+
+    // A peaking filter is phase-neutral at its center and shifts either side
+    let (magnitude, phase) = peq.coeffs.frequency_response(1000.0, sr);
+    assert!((magnitude - 1.995).abs() < 0.01);
+    assert!(phase.abs() < 1e-3, "phase {} at center", phase);
+    assert!(peq.coeffs.frequency_response(700.0, sr).1 > 0.05);
+    assert!(peq.coeffs.frequency_response(1400.0, sr).1 < -0.05);
+
+    let cascade = BiquadCascade::new(vec![peq.clone(), peq]);
+    let doubled = cascade.magnitude_db_curve(&[1000.0], sr);
+    assert!((doubled[0] - 12.0).abs() < 0.1);
+}
+
+fn naive_convolution(taps: &[f32], input: &[f32]) -> Vec<f32> {
+    (0..input.len())
+        .map(|n| {
+            taps.iter()
+                .enumerate()
+                .filter(|(k, _)| *k <= n)
+                .map(|(k, &h)| h * input[n - k])
+                .sum()
+        })
+        .collect()
+}
+
+/// Deterministic pseudo-random signal in [-1, 1]
+fn noise(len: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1u32 << 23) as f32 - 1.0
+        })
+        .collect()
+}
+
+#[test]
+fn test_fir_partitioned_matches_naive_convolution() {
+    let taps: Vec<f32> = noise(1000, 7).iter().map(|t| t * 0.05).collect();
+    let input = noise(3000, 11);
+    let expected = naive_convolution(&taps, &input);
+
+    let mut fir = FirFilter::new(taps);
+    fir.set_partition_size(256);
+
+    // Block size deliberately unrelated to the partition size
+    let mut output = input.clone();
+    for block in output.chunks_mut(700) {
+        fir.process_block(block);
+    }
+
+    for (n, (a, b)) in output.iter().zip(&expected).enumerate() {
+        assert!((a - b).abs() < 1e-4, "sample {}: {} vs {}", n, a, b);
+    }
+}
+
+#[test]
+fn test_fir_direct_matches_naive_convolution() {
+    let taps = vec![0.25, -0.5, 1.0, 0.5, 0.125];
+    let input = noise(500, 3);
+    let expected = naive_convolution(&taps, &input);
+
+    let mut fir = FirFilter::new(taps);
+    let mut output = input.clone();
+    for block in output.chunks_mut(37) {
+        fir.process_block(block);
+    }
+
+    for (a, b) in output.iter().zip(&expected) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_fir_reset_clears_history() {
+    let mut fir = FirFilter::new(noise(300, 5));
+    fir.set_partition_size(64);
+
+    let mut block = noise(128, 9);
+    fir.process_block(&mut block);
+
+    fir.reset();
+    let mut impulse = vec![0.0; 300];
+    impulse[0] = 1.0;
+    fir.process_block(&mut impulse);
+
+    for (a, b) in impulse.iter().zip(&fir.taps) {
+        assert!((a - b).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_fft_round_trip() {
+    let input = noise(512, 1);
+    let mut buf: Vec<Complex32> = input.iter().map(|&x| Complex32::new(x, 0.0)).collect();
+
+    fft(&mut buf);
+    ifft(&mut buf);
+
+    for (a, b) in buf.iter().zip(&input) {
+        assert!((a.re - b).abs() < 1e-5);
+        assert!(a.im.abs() < 1e-5);
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+#[test]
+fn test_linkwitz_riley_sums_flat_at_crossover() {
+    let sr = 48000;
+    let crossover = 120.0;
+
+    for order in [LrOrder::Lr2, LrOrder::Lr4, LrOrder::Lr8] {
+        let (lp, hp) = design_linkwitz_riley(crossover, order, sr);
+        let mut low_cascade = BiquadCascade::new(lp);
+        let mut high_cascade = BiquadCascade::new(hp);
+
+        // Each half is -6 dB at the crossover
+        assert!((low_cascade.magnitude_db(crossover, sr) + 6.02).abs() < 0.1);
+        assert!((high_cascade.magnitude_db(crossover, sr) + 6.02).abs() < 0.1);
+
+        let input = sine(crossover, sr, sr as usize);
+        let mut low = input.clone();
+        let mut high = input.clone();
+        low_cascade.process_block(&mut low);
+        high_cascade.process_block(&mut high);
+
+        let summed: Vec<f32> = low.iter().zip(&high).map(|(l, h)| l + h).collect();
+        let settled = peak(&summed[sr as usize / 2..]);
+        assert!(
+            (settled - 1.0).abs() < 0.02,
+            "LR{} sum peak {} at crossover",
+            order.order(),
+            settled
+        );
+    }
+}
+
+#[test]
+fn test_bass_manager_routes_lows_to_subwoofer() {
+    let sr = 48000;
+    let layout = SpeakerLayout::surround_5_1();
+    let mut manager = BassManager::for_layout(80.0, sr, &layout);
+    let sub = manager.subwoofer_channel().expect("5.1 has a subwoofer");
+
+    let frames = sr as usize;
+    let mut block = AudioBlock::silence(layout.speakers.len(), frames, sr);
+    let front_left = (0..layout.speakers.len()).find(|&i| i != sub).unwrap();
+    block.channels[front_left] = sine(30.0, sr, frames);
+
+    manager.process(&mut block);
+
+    let tail = frames / 2..;
+    let sub_rms = rms(&block.channels[sub][tail.clone()]);
+    let main_rms = rms(&block.channels[front_left][tail]);
+    assert!(sub_rms > 0.6, "sub rms {}", sub_rms);
+    assert!(main_rms < 0.05, "main rms {}", main_rms);
+}
+
+#[test]
+fn test_bass_manager_widens_stereo_input_on_5_1() {
+    let sr = 48000;
+    let layout = SpeakerLayout::surround_5_1();
+    let mut manager = BassManager::for_layout(80.0, sr, &layout);
+    let sub = manager.subwoofer_channel().expect("5.1 has a subwoofer");
+    assert!(sub >= 2);
+
+    let frames = sr as usize;
+    let mut block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![sine(30.0, sr, frames), vec![0.0; frames]],
+    };
+    manager.process(&mut block);
+
+    assert_eq!(block.channels.len(), layout.speakers.len());
+    assert!(block.channels.iter().all(|ch| ch.len() == frames));
+    let tail = frames / 2..;
+    let sub_rms = rms(&block.channels[sub][tail.clone()]);
+    let left_rms = rms(&block.channels[0][tail]);
+    assert!(sub_rms > 0.6, "sub rms {}", sub_rms);
+    assert!(left_rms < 0.05, "left rms {}", left_rms);
+}
+
+#[test]
+fn test_bass_manager_without_subwoofer_is_passthrough() {
+    let sr = 48000;
+    let mut manager = BassManager::for_layout(80.0, sr, &SpeakerLayout::stereo());
+    assert_eq!(manager.subwoofer_channel(), None);
+
+    let input = AudioBlock {
+        sample_rate: sr,
+        channels: vec![sine(30.0, sr, 512), sine(50.0, sr, 512)],
+    };
+    let mut block = input.clone();
+    manager.process(&mut block);
+
+    assert_eq!(block, input);
+}
+
+#[test]
+fn test_resample_keeps_pitch() {
+    for (from, to) in [(44100, 48000), (48000, 44100)] {
+        let input = AudioBlock {
+            sample_rate: from,
+            channels: vec![sine(1000.0, from, from as usize / 10); 2],
+        };
+
+        let output = resample(&input, to);
+        assert_eq!(output.sample_rate, to);
+        assert_eq!(output.channels.len(), 2);
+        assert_eq!(output.frame_len(), to as usize / 10);
+
+        // Away from the edges the output is the same 1 kHz tone sampled at the new rate
+        let frames = output.frame_len();
+        let expected = sine(1000.0, to, frames);
+        let settled = output.channels[0][64..frames - 64]
+            .iter()
+            .zip(&expected[64..frames - 64]);
+        for (n, (actual, expected)) in settled.enumerate() {
+            assert!(
+                (actual - expected).abs() < 2e-3,
+                "{} -> {} frame {}: {} vs {}",
+                from,
+                to,
+                n + 64,
+                actual,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn test_streaming_resampler_matches_one_shot() {
+    for (from, to) in [(44100, 48000), (48000, 44100)] {
+        let input = AudioBlock {
+            sample_rate: from,
+            channels: vec![sine(1000.0, from, 5000), sine(250.0, from, 5000)],
+        };
+        let expected = resample(&input, to);
+
+        // Pull fixed output blocks the way a render loop does
+        let mut resampler = StreamingResampler::new(from, to, ResampleQuality::default());
+        let mut streamed = AudioBlock::silence(2, 0, to);
+        let mut read = 0;
+        while read < input.frame_len() {
+            let frames = resampler
+                .input_frames_for(512)
+                .min(input.frame_len() - read);
+            let chunk = AudioBlock {
+                sample_rate: from,
+                channels: input
+                    .channels
+                    .iter()
+                    .map(|c| c[read..read + frames].to_vec())
+                    .collect(),
+            };
+            read += frames;
+            let output = resampler.process(&chunk);
+            if read < input.frame_len() {
+                assert_eq!(output.frame_len(), 512, "{} -> {}", from, to);
+            }
+            streamed.append(&output).unwrap();
+        }
+        streamed.append(&resampler.flush()).unwrap();
+
+        assert_eq!(streamed.frame_len(), expected.frame_len());
+        for (a, b) in streamed.channels.iter().zip(&expected.channels) {
+            for (n, (x, y)) in a.iter().zip(b).enumerate() {
+                assert!((x - y).abs() < 1e-5, "{} -> {} frame {}", from, to, n);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_resample_filters_content_above_new_nyquist() {
+    // 23 kHz is representable at 48 kHz but not at 44.1 kHz
+    let input = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![sine(23000.0, 48000, 4800)],
+    };
+
+    let output = resample_with_quality(&input, 44100, ResampleQuality::Best);
+    let tail = &output.channels[0][200..output.frame_len() - 200];
+    assert!(rms(tail) < 0.01, "aliased rms {}", rms(tail));
+
+    // Same rate is a plain copy
+    assert_eq!(resample(&input, 48000), input);
+}
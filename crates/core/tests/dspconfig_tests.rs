@@ -0,0 +1,185 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use audio_ninja::dsp::PeqBand;
+use audio_ninja::dspconfig::{DspChainConfig, DspConfigError, StageConfig};
+use audio_ninja::pipeline::{AudioStage, Pipeline};
+use audio_ninja::AudioBlock;
+
+fn sample_chain() -> DspChainConfig {
+    DspChainConfig {
+        sample_rate: 48000,
+        stages: vec![
+            StageConfig::Peq {
+                bands: vec![
+                    PeqBand {
+                        freq_hz: 120.0,
+                        gain_db: -4.0,
+                        q: 2.0,
+                    },
+                    PeqBand {
+                        freq_hz: 3000.0,
+                        gain_db: 2.5,
+                        q: 1.0,
+                    },
+                ],
+            },
+            StageConfig::Drc {
+                ratio: 4.0,
+                threshold_db: -20.0,
+                attack_ms: 5.0,
+                release_ms: 80.0,
+                makeup_gain_db: 3.0,
+                knee_db: 6.0,
+                stereo_link: true,
+            },
+            StageConfig::Loudness { target_lufs: -23.0 },
+            StageConfig::Headroom {
+                headroom_db: 1.0,
+                lookahead_ms: 3.0,
+                true_peak: true,
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_chain_config_pipeline_round_trip() {
+    let config = sample_chain();
+    let pipeline = config.build();
+
+    assert_eq!(pipeline.sample_rate(), 48000);
+    assert_eq!(
+        pipeline.stage_names(),
+        vec!["peq", "drc", "loudness", "limiter"]
+    );
+
+    let restored = DspChainConfig::from_pipeline(&pipeline).unwrap();
+    assert_eq!(restored, config);
+}
+
+#[test]
+fn test_chain_config_keeps_fractional_timings() {
+    // Neither time is a whole number of samples at 44.1 kHz
+    let config = DspChainConfig {
+        sample_rate: 44100,
+        stages: vec![
+            StageConfig::Drc {
+                ratio: 3.0,
+                threshold_db: -18.0,
+                attack_ms: 0.7,
+                release_ms: 123.45,
+                makeup_gain_db: 0.0,
+                knee_db: 0.0,
+                stereo_link: false,
+            },
+            StageConfig::Headroom {
+                headroom_db: 1.0,
+                lookahead_ms: 1.5,
+                true_peak: false,
+            },
+        ],
+    };
+
+    let restored = DspChainConfig::from_pipeline(&config.build()).unwrap();
+    assert_eq!(restored, config);
+}
+
+#[test]
+fn test_chain_config_toml_round_trip() {
+    let config = sample_chain();
+    let toml = config.to_toml().unwrap();
+
+    assert!(toml.contains("[[stages]]"));
+    assert!(toml.contains("type = \"drc\""));
+    assert_eq!(DspChainConfig::from_toml(&toml).unwrap(), config);
+}
+
+#[test]
+fn test_chain_config_json_round_trip() {
+    let config = sample_chain();
+    let json = config.to_json().unwrap();
+
+    assert_eq!(DspChainConfig::from_json(&json).unwrap(), config);
+}
+
+#[test]
+fn test_chain_config_parses_handwritten_toml() {
+    let toml = r#"
+        sample_rate = 44100
+
+        [[stages]]
+        type = "drc"
+        ratio = 2.0
+        threshold_db = -18.0
+        attack_ms = 10.0
+        release_ms = 100.0
+
+        [[stages]]
+        type = "headroom"
+        headroom_db = 2.0
+        lookahead_ms = 5.0
+    "#;
+
+    let config = DspChainConfig::from_toml(toml).unwrap();
+    assert_eq!(config.sample_rate, 44100);
+    assert_eq!(config.stages.len(), 2);
+    assert!(matches!(
+        config.stages[0],
+        StageConfig::Drc {
+            makeup_gain_db, ..
+        } if makeup_gain_db == 0.0
+    ));
+
+    let pipeline = config.build();
+    assert_eq!(pipeline.total_latency_samples(), 220);
+}
+
+#[test]
+fn test_chain_config_rejects_unknown_stage() {
+    let toml = r#"
+        sample_rate = 48000
+
+        [[stages]]
+        type = "reverb"
+        size = 0.5
+    "#;
+
+    assert!(matches!(
+        DspChainConfig::from_toml(toml),
+        Err(DspConfigError::Parse(_))
+    ));
+}
+
+#[test]
+fn test_from_pipeline_rejects_opaque_stage() {
+    struct Opaque;
+
+    impl AudioStage for Opaque {
+        fn name(&self) -> &'static str {
+            "opaque"
+        }
+
+        fn process(&mut self, _block: &mut AudioBlock) {}
+    }
+
+    let mut pipeline = Pipeline::new(48000);
+    pipeline.add_stage(Opaque);
+
+    assert!(matches!(
+        DspChainConfig::from_pipeline(&pipeline),
+        Err(DspConfigError::UnsupportedStage("opaque"))
+    ));
+}
+
+#[test]
+fn test_chain_config_file_round_trip() {
+    let config = sample_chain();
+    let dir = std::env::temp_dir();
+
+    for name in ["audio-ninja-chain.toml", "audio-ninja-chain.json"] {
+        let path = dir.join(format!("{}-{}", std::process::id(), name));
+        config.save(&path).unwrap();
+        assert_eq!(DspChainConfig::load(&path).unwrap(), config);
+        std::fs::remove_file(path).ok();
+    }
+}
@@ -74,6 +74,92 @@ fn test_loss_statistics_with_gaps() {
     assert_eq!(stats.max_consecutive_losses, 2);
 }
 
+#[test]
+fn test_loss_statistics_ignore_reordered_packets() {
+    let mut stats = LossStatistics::new();
+
+    // 3 arrives after 4: counted neither as a 65k gap nor twice
+    for seq in [0, 1, 2, 4, 3, 5, 6] {
+        stats.update(seq);
+    }
+    assert_eq!(stats.total_lost, 1);
+    assert_eq!(stats.total_expected, 7);
+    assert_eq!(stats.loss_bursts, 1);
+    assert_eq!(stats.extended_highest_sequence(), 6);
+
+    // Duplicates change nothing either
+    stats.update(6);
+    stats.update(5);
+    assert_eq!(stats.total_lost, 1);
+    assert_eq!(stats.total_expected, 7);
+}
+
+#[test]
+fn test_loss_statistics_reordered_across_wrap() {
+    let mut stats = LossStatistics::new();
+
+    // 65535 turns up after the sequence has already wrapped to 0
+    for seq in [65533, 65534, 0, 65535, 1, 2] {
+        stats.update(seq);
+    }
+    assert_eq!(stats.total_lost, 1);
+    assert_eq!(stats.total_expected, 6);
+    assert_eq!(stats.extended_highest_sequence(), (1 << 16) | 2);
+
+    // A later packet wrapping nowhere adds no second cycle
+    stats.update(3);
+    assert_eq!(stats.extended_highest_sequence(), (1 << 16) | 3);
+}
+
+#[test]
+fn test_loss_statistics_resync_after_large_jump() {
+    let mut stats = LossStatistics::new();
+
+    for seq in 0..10 {
+        stats.update(seq);
+    }
+    // A lone stray far ahead is dropped; two in a row mean a restart
+    stats.update(40000);
+    assert_eq!(stats.extended_highest_sequence(), 9);
+    stats.update(20000);
+    stats.update(20001);
+    stats.update(20002);
+    assert_eq!(stats.total_lost, 0);
+    assert_eq!(stats.extended_highest_sequence(), 20002);
+}
+
+#[test]
+fn test_burst_summary_without_loss_is_zero() {
+    let mut stats = LossStatistics::new();
+    assert_eq!(stats.burst_summary(), BurstSummary::default());
+
+    for i in 0..50 {
+        stats.update(i);
+    }
+    let summary = stats.burst_summary();
+    assert_eq!(summary, BurstSummary::default());
+    assert!(!summary.mean_burst_length.is_nan());
+}
+
+#[test]
+fn test_burst_summary_gilbert_elliott_estimate() {
+    let mut stats = LossStatistics::new();
+
+    // Received: 0..=9, 13..=19, 21..=29 -> bursts of 3 (10-12) and 1 (20)
+    for seq in (0..10).chain(13..20).chain(21..30) {
+        stats.update(seq);
+    }
+
+    let summary = stats.burst_summary();
+    assert_eq!(summary.loss_bursts, 2);
+    assert!((summary.mean_burst_length - 2.0).abs() < 1e-12);
+    assert!((summary.burst_density - 2.0 / 30.0).abs() < 1e-12);
+    // 26 received packets give 25 observed transitions out of the good state
+    assert!((summary.p_good_to_bad - 2.0 / 25.0).abs() < 1e-12);
+    // Mean burst length is 1 / P(bad -> good)
+    assert!((summary.p_bad_to_good - 0.5).abs() < 1e-12);
+}
+
 #[test]
 fn test_loss_statistics_recovery() {
     let mut stats = LossStatistics::new();
@@ -146,6 +232,60 @@ fn test_loss_concealment_interpolate() {
     }
 }
 
+#[test]
+fn test_loss_concealment_waveform_similarity_keeps_phase() {
+    let sr = 48000;
+    let freq = 440.0;
+    let sine = |n: usize| (std::f32::consts::TAU * freq * n as f32 / sr as f32).sin() * 0.8;
+
+    let mut concealer = LossConcealer::new(ConcealmentStrategy::WaveformSimilarity);
+    concealer.update(AudioBlock {
+        sample_rate: sr,
+        channels: vec![(0..960).map(sine).collect(); 2],
+    });
+    let concealed = concealer.conceal(sr, 2, 960);
+    assert_eq!(concealed.channels.len(), 2);
+    assert_eq!(concealed.channels[0].len(), 960);
+
+    // The first periods follow the sine that was cut off, in phase
+    let period = (sr as f32 / freq) as usize;
+    for n in 0..3 * period {
+        let expected = sine(960 + n);
+        assert!(
+            (concealed.channels[0][n] - expected).abs() < 0.05,
+            "sample {}: {} vs {}",
+            n,
+            concealed.channels[0][n],
+            expected
+        );
+    }
+    // No step at the splice
+    assert!((concealed.channels[0][0] - sine(960)).abs() < 0.02);
+
+    // Then fades out rather than cutting off
+    let peak = |range: std::ops::Range<usize>| {
+        concealed.channels[0][range]
+            .iter()
+            .map(|x| x.abs())
+            .fold(0.0f32, f32::max)
+    };
+    assert!(peak(3 * period + 240..3 * period + 480) < peak(0..period));
+    assert!(peak(900..960) < 0.5 * peak(0..period));
+}
+
+#[test]
+fn test_loss_concealment_waveform_similarity_short_block_fades() {
+    // Too short to search for a pitch period: falls back to a fade-out
+    let mut concealer = LossConcealer::new(ConcealmentStrategy::WaveformSimilarity);
+    concealer.update(AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![1.0; 10]],
+    });
+    let concealed = concealer.conceal(48000, 1, 10);
+    assert_eq!(concealed.channels[0][0], 1.0);
+    assert!(concealed.channels[0][9] < 0.15);
+}
+
 #[test]
 fn test_fec_receiver_basic() {
     let mut receiver = FecReceiver::new(4, ConcealmentStrategy::Silence);
@@ -199,6 +339,81 @@ fn test_fec_receiver_with_update() {
     assert_eq!(concealed.channels, block.channels);
 }
 
+/// Send `count` packets through an encoder, returning the packets and the
+/// FEC packet of each completed group
+fn encoded_stream(group_size: usize, count: u16) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    let mut encoder = XorFec::new(group_size);
+    let mut packets = Vec::new();
+    let mut fecs = Vec::new();
+    for seq in 0..count {
+        let packet = vec![seq as u8, seq as u8 ^ 0x5a, 3 * seq as u8];
+        if let Some(fec) = encoder.encode(&packet) {
+            fecs.push(fec);
+        }
+        packets.push(packet);
+    }
+    (packets, fecs)
+}
+
+#[test]
+fn test_fec_receiver_delivers_recovered_packet_in_order() {
+    let (packets, fecs) = encoded_stream(4, 8);
+    let mut receiver = FecReceiver::new(4, ConcealmentStrategy::Silence);
+
+    for seq in [0u16, 2, 3] {
+        receiver.process_packet(seq, packets[seq as usize].clone());
+    }
+    // Packet 1 might still be recovered: hold everything behind it
+    assert_eq!(receiver.take_ready_packets(), vec![(0, packets[0].clone())]);
+
+    receiver.process_fec_packet(0, fecs[0].clone());
+    assert_eq!(receiver.statistics().total_recovered, 1);
+    let ready = receiver.take_ready_packets();
+    let expected: Vec<(u16, Vec<u8>)> = (1..4)
+        .map(|seq| (seq, packets[seq as usize].clone()))
+        .collect();
+    assert_eq!(ready, expected);
+
+    // The next group flows straight through
+    for seq in 4u16..8 {
+        receiver.process_packet(seq, packets[seq as usize].clone());
+    }
+    let sequences: Vec<u16> = receiver
+        .take_ready_packets()
+        .iter()
+        .map(|(seq, _)| *seq)
+        .collect();
+    assert_eq!(sequences, vec![4, 5, 6, 7]);
+    assert!(receiver.take_ready_packets().is_empty());
+}
+
+#[test]
+fn test_fec_receiver_skips_unrecoverable_gap() {
+    let (packets, fecs) = encoded_stream(4, 8);
+    let mut receiver = FecReceiver::new(4, ConcealmentStrategy::Silence);
+
+    // Two losses in one group are beyond XOR FEC
+    for seq in [0u16, 3] {
+        receiver.process_packet(seq, packets[seq as usize].clone());
+    }
+    receiver.process_fec_packet(0, fecs[0].clone());
+    for seq in [4u16, 5] {
+        receiver.process_packet(seq, packets[seq as usize].clone());
+    }
+
+    let sequences: Vec<u16> = receiver
+        .take_ready_packets()
+        .iter()
+        .map(|(seq, _)| *seq)
+        .collect();
+    assert_eq!(sequences, vec![0, 3, 4, 5]);
+    assert_eq!(receiver.statistics().total_recovered, 0);
+
+    // Stragglers from the skipped gap are dropped
+    receiver.process_packet(1, packets[1].clone());
+    assert!(receiver.take_ready_packets().is_empty());
+}
+
 #[test]
 fn test_loss_rate_calculation() {
     let mut stats = LossStatistics::new();
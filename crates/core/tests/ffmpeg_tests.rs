@@ -0,0 +1,143 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the ffprobe/ffmpeg command-line wrappers
+
+use audio_ninja::ffmpeg::*;
+use std::time::Duration;
+
+const PCM_PROBE: &str = r#"{
+    "streams": [
+        {
+            "index": 0,
+            "codec_name": "pcm_s24le",
+            "codec_type": "audio",
+            "sample_rate": "96000",
+            "channels": 6,
+            "bits_per_sample": 24,
+            "duration": "2.500000"
+        }
+    ],
+    "format": {
+        "format_name": "wav",
+        "duration": "2.500000"
+    }
+}"#;
+
+const OPUS_PROBE: &str = r#"{
+    "streams": [
+        {
+            "index": 0,
+            "codec_name": "opus",
+            "codec_type": "audio",
+            "sample_rate": "48000",
+            "channels": 2,
+            "bits_per_sample": 0
+        }
+    ],
+    "format": {
+        "format_name": "ogg"
+    }
+}"#;
+
+#[test]
+fn test_probe_json_pcm() {
+    let info = MediaInfo::from_ffprobe_json(PCM_PROBE).unwrap();
+
+    assert_eq!(info.codec, "pcm_s24le");
+    assert_eq!(info.channels, 6);
+    assert_eq!(info.sample_rate, 96000);
+    assert_eq!(info.bit_depth, Some(24));
+    assert_eq!(info.duration, Some(Duration::from_millis(2500)));
+}
+
+#[test]
+fn test_probe_json_lossy_without_bit_depth() {
+    let info = MediaInfo::from_ffprobe_json(OPUS_PROBE).unwrap();
+
+    assert_eq!(info.codec, "opus");
+    assert_eq!(info.channels, 2);
+    assert_eq!(info.bit_depth, None);
+    assert_eq!(info.duration, None);
+}
+
+#[test]
+fn test_probe_json_no_audio_stream() {
+    let json = r#"{"streams": [], "format": {}}"#;
+    assert!(matches!(
+        MediaInfo::from_ffprobe_json(json),
+        Err(FfmpegError::Format(_))
+    ));
+    assert!(MediaInfo::from_ffprobe_json("not json").is_err());
+}
+
+#[cfg(feature = "ffmpeg-support")]
+mod with_ffmpeg {
+    use super::*;
+    use audio_ninja::output::{write_wav, BitDepth};
+    use audio_ninja::AudioBlock;
+
+    /// Write a 16-bit PCM WAV with `channels` channels of silence
+    fn write_wav_fixture(channels: u16, sample_rate: u32, frames: u32) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "audio-ninja-ffprobe-{}ch-{}.wav",
+            channels,
+            std::process::id()
+        ));
+        let block = AudioBlock::silence(channels as usize, frames as usize, sample_rate);
+        write_wav(&path, &block, BitDepth::Int16).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_probe_fixture_channel_count() {
+        let path = write_wav_fixture(6, 48000, 4800);
+        let info = FfmpegProbe::probe(&path).unwrap();
+
+        assert_eq!(info.channels, 6);
+        assert_eq!(info.sample_rate, 48000);
+        assert_eq!(info.bit_depth, Some(16));
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_decode_stream_5_1_at_native_rate() {
+        let path = write_wav_fixture(6, 44100, 3000);
+        let mut stream = DecodeStream::open(&path, 1024).unwrap();
+
+        // Known before anything is decoded
+        assert_eq!(stream.channels(), 6);
+        assert_eq!(stream.sample_rate(), 44100);
+
+        let mut frames = Vec::new();
+        while let Some(block) = stream.next_block().unwrap() {
+            assert_eq!(block.channels.len(), 6);
+            assert_eq!(block.sample_rate, 44100);
+            frames.push(block.frame_len());
+        }
+        assert_eq!(frames, vec![1024, 1024, 952]);
+        assert!(stream.next().is_none());
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_decode_stream_reports_missing_file() {
+        let path = std::env::temp_dir().join("audio-ninja-missing-input.flac");
+        assert!(DecodeStream::open(&path, 1024).is_err());
+    }
+
+    #[test]
+    fn test_decode_fixture_to_blocks() {
+        let path = write_wav_fixture(2, 48000, 4800);
+        let blocks = FfmpegDecoder::decode_to_blocks(&path, 24000).unwrap();
+
+        let total: usize = blocks.iter().map(|b| b.frame_len()).sum();
+        assert_eq!(total, 2400);
+        assert!(blocks
+            .iter()
+            .all(|b| b.channels.len() == 2 && b.sample_rate == 24000));
+
+        std::fs::remove_file(path).ok();
+    }
+}
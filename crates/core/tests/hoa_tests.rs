@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use audio_ninja::hoa::*;
+use audio_ninja::hrtf::{HrtfDatabase, HrtfDataset};
 
 #[test]
 fn test_ambisonic_order_first() {
@@ -327,3 +328,124 @@ fn test_decode_buffer_wrong_channel_count() {
     let input = vec![vec![1.0; 10]; 4]; // 4 channels instead of 9
     decoder.decode_buffer(&input);
 }
+
+#[test]
+fn test_decode_buffer_matches_per_sample_decode() {
+    let speakers = create_7_1_4_hoa_layout();
+    let decoder = HoaDecoder::new(AmbisonicOrder::SECOND, DecodingMode::MaxRE, speakers);
+
+    // One second of 48 kHz second-order material
+    let num_samples = 48000;
+    let input: Vec<Vec<f32>> = (0..decoder.channel_count())
+        .map(|ch| {
+            (0..num_samples)
+                .map(|i| {
+                    let t = i as f32 / 48000.0;
+                    (2.0 * std::f32::consts::PI * (110.0 + 55.0 * ch as f32) * t).sin()
+                        / (ch + 1) as f32
+                })
+                .collect()
+        })
+        .collect();
+
+    let started = std::time::Instant::now();
+    let output = decoder.decode_buffer(&input);
+    let elapsed = started.elapsed();
+
+    assert_eq!(output.len(), decoder.speaker_count());
+    for i in 0..num_samples {
+        let frame: Vec<f32> = input.iter().map(|ch| ch[i]).collect();
+        let expected = decoder.decode(&frame);
+        for (spk, &value) in expected.iter().enumerate() {
+            assert_eq!(output[spk][i].to_bits(), value.to_bits());
+        }
+    }
+
+    // Generous bound; the buffer decode must stay far faster than real time
+    assert!(elapsed < std::time::Duration::from_secs(1), "{:?}", elapsed);
+}
+
+/// First-order plane wave (ACN order W, Y, Z, X, orthonormal) carrying `signal`
+fn first_order_plane_wave(azimuth_deg: f32, signal: &[f32]) -> Vec<Vec<f32>> {
+    let az = azimuth_deg.to_radians();
+    let w = 0.5 * (1.0 / std::f32::consts::PI).sqrt();
+    let k1 = (3.0 / (4.0 * std::f32::consts::PI)).sqrt();
+    [w, k1 * az.sin(), 0.0, k1 * az.cos()]
+        .iter()
+        .map(|&h| signal.iter().map(|&x| h * x).collect())
+        .collect()
+}
+
+/// Energy-weighted arrival time of a response, in samples
+fn energy_centroid(samples: &[f32]) -> f32 {
+    let total: f32 = samples.iter().map(|x| x * x).sum();
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, x)| i as f32 * x * x)
+        .sum::<f32>()
+        / total
+}
+
+#[test]
+fn test_render_hoa_binaural_lateralizes() {
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    db.load_default_kemar().unwrap();
+    let mut impulse = vec![0.0; 1024];
+    impulse[0] = 1.0;
+
+    // Positive azimuth is to the left: the left ear hears it first
+    let (left, right) = render_hoa_binaural(
+        &first_order_plane_wave(90.0, &impulse),
+        AmbisonicOrder::FIRST,
+        &db,
+    );
+    assert_eq!(left.len(), 1024);
+    assert_eq!(right.len(), 1024);
+    assert!(energy_centroid(&left) < energy_centroid(&right));
+
+    let (left, right) = render_hoa_binaural(
+        &first_order_plane_wave(-90.0, &impulse),
+        AmbisonicOrder::FIRST,
+        &db,
+    );
+    assert!(energy_centroid(&right) < energy_centroid(&left));
+}
+
+#[test]
+fn test_render_hoa_binaural_higher_order() {
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    db.load_default_kemar().unwrap();
+
+    let input = vec![vec![0.1; 256]; AmbisonicOrder::THIRD.channel_count()];
+    let (left, right) = render_hoa_binaural(&input, AmbisonicOrder::THIRD, &db);
+    assert_eq!(left.len(), 256);
+    assert!(left.iter().chain(&right).any(|&x| x != 0.0));
+    assert!(left.iter().chain(&right).all(|x| x.is_finite()));
+}
+
+#[test]
+fn test_rotate_soundfield_yaw_moves_front_to_side() {
+    let speakers = create_7_1_4_hoa_layout();
+    let decoder = HoaDecoder::new(AmbisonicOrder::FIRST, DecodingMode::Basic, speakers.clone());
+    let loudest = |channels: &[Vec<f32>]| {
+        let frame: Vec<f32> = channels.iter().map(|c| c[0]).collect();
+        let gains = decoder.decode(&frame);
+        (0..gains.len())
+            .max_by(|&a, &b| gains[a].abs().total_cmp(&gains[b].abs()))
+            .unwrap()
+    };
+
+    let mut field = first_order_plane_wave(0.0, &[1.0; 16]);
+    assert_eq!(speakers[loudest(&field)].azimuth, 0.0);
+
+    rotate_soundfield(&mut field, AmbisonicOrder::FIRST, 90.0, 0.0, 0.0);
+    assert_eq!(speakers[loudest(&field)].azimuth, 90.0);
+    assert!(field.iter().all(|c| c.iter().all(|&x| x == c[0])));
+
+    // Turning back restores the original field
+    rotate_soundfield(&mut field, AmbisonicOrder::FIRST, -90.0, 0.0, 0.0);
+    for (rotated, original) in field.iter().zip(first_order_plane_wave(0.0, &[1.0; 16])) {
+        assert!((rotated[0] - original[0]).abs() < 1e-5);
+    }
+}
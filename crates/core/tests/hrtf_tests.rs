@@ -98,6 +98,55 @@ fn test_headphone_profile_variants() {
     assert_eq!(profiles.len(), 4);
 }
 
+/// RMS of the left ear after the output has settled
+fn rendered_rms(profile: HeadphoneProfile, freq_hz: f32) -> f32 {
+    let sr = 48000;
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, sr);
+    let mut impulse = vec![0.0; 16];
+    impulse[0] = 1.0;
+    db.add_response(
+        &HrtfPosition::new(0.0, 0.0, 1.0),
+        HrtfImpulseResponse::new(impulse.clone(), impulse),
+    );
+    let renderer = BinauralRenderer::new(db, profile);
+
+    let input: Vec<f32> = (0..9600)
+        .map(|n| 0.5 * (2.0 * std::f32::consts::PI * freq_hz * n as f32 / sr as f32).sin())
+        .collect();
+    let (left, _) = renderer
+        .render(&input, &HrtfPosition::new(0.0, 0.0, 1.0))
+        .unwrap();
+
+    let tail = &left[4800..9600];
+    (tail.iter().map(|s| s * s).sum::<f32>() / tail.len() as f32).sqrt()
+}
+
+#[test]
+fn test_closed_back_boosts_bass_relative_to_flat() {
+    let flat = rendered_rms(HeadphoneProfile::Flat, 50.0);
+    let closed = rendered_rms(HeadphoneProfile::ClosedBack, 50.0);
+    let boost_db = 20.0 * (closed / flat).log10();
+    assert!(boost_db > 4.0, "bass boost {} dB", boost_db);
+
+    // Midrange is left close to flat
+    let flat_mid = rendered_rms(HeadphoneProfile::Flat, 500.0);
+    let closed_mid = rendered_rms(HeadphoneProfile::ClosedBack, 500.0);
+    assert!((20.0 * (closed_mid / flat_mid).log10()).abs() < 1.5);
+}
+
+#[test]
+fn test_custom_headphone_profile() {
+    use audio_ninja::calibration::design_low_shelf;
+
+    let cut = vec![design_low_shelf(200.0, -6.0, 48000)];
+    assert_eq!(HeadphoneProfile::Custom(cut.clone()).filters(48000), cut);
+    assert!(HeadphoneProfile::Flat.filters(48000).is_empty());
+
+    let flat = rendered_rms(HeadphoneProfile::Flat, 50.0);
+    let custom = rendered_rms(HeadphoneProfile::Custom(cut), 50.0);
+    assert!(20.0 * (custom / flat).log10() < -4.0);
+}
+
 #[test]
 fn test_hrtf_database_creation() {
     let db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
@@ -158,6 +207,55 @@ fn test_hrtf_database_multiple_distances() {
     }
 }
 
+#[test]
+fn test_hrtf_database_nearest_distance() {
+    let mut db = HrtfDatabase::new(HrtfDataset::MitKemar, 48000);
+    db.add_response(
+        &HrtfPosition::new(0.0, 0.0, 0.5),
+        HrtfImpulseResponse::new(vec![0.5; 64], vec![0.5; 64]),
+    );
+    db.add_response(
+        &HrtfPosition::new(0.0, 0.0, 2.0),
+        HrtfImpulseResponse::new(vec![0.2; 64], vec![0.2; 64]),
+    );
+
+    let near = db.get_response(&HrtfPosition::new(0.0, 0.0, 0.6)).unwrap();
+    assert_eq!(near.left[0], 0.5);
+
+    let far = db.get_response(&HrtfPosition::new(0.0, 0.0, 1.8)).unwrap();
+    assert_eq!(far.left[0], 0.2);
+}
+
+#[test]
+fn test_hrtf_near_field_compensation() {
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    let mut impulse = vec![0.0; 64];
+    impulse[0] = 1.0;
+    db.add_response(
+        &HrtfPosition::new(0.0, 0.0, 1.0),
+        HrtfImpulseResponse::new(impulse.clone(), impulse),
+    );
+    db.set_near_field_compensation(true);
+
+    // DC gain of the response is the low-frequency boost
+    let dc_gain_db = |ir: &[f32]| 20.0 * ir.iter().sum::<f32>().log10();
+
+    let reference = db.get_response(&HrtfPosition::new(0.0, 0.0, 1.0)).unwrap();
+    assert!(dc_gain_db(&reference.left).abs() < 0.01);
+
+    let close = db.get_response(&HrtfPosition::new(0.0, 0.0, 0.5)).unwrap();
+    assert!((dc_gain_db(&close.left) - near_field_boost_db(0.5)).abs() < 0.1);
+    assert!((near_field_boost_db(0.5) - 6.0).abs() < 0.01);
+
+    // A database measured at several distances is left alone
+    db.add_response(
+        &HrtfPosition::new(0.0, 0.0, 0.5),
+        HrtfImpulseResponse::new(vec![1.0], vec![1.0]),
+    );
+    let measured = db.get_response(&HrtfPosition::new(0.0, 0.0, 0.5)).unwrap();
+    assert_eq!(measured.left, vec![1.0]);
+}
+
 #[test]
 fn test_binaural_renderer_flat_profile() {
     let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
@@ -300,6 +398,107 @@ fn test_binaural_render_buffer_multi_channel() {
     assert_eq!(output.len(), 2); // Stereo output
 }
 
+#[test]
+fn test_binaural_render_buffer_per_source_positions() {
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    db.load_default_kemar().unwrap();
+    let renderer = BinauralRenderer::new(db, HeadphoneProfile::Flat);
+
+    let tone: Vec<f32> = (0..512)
+        .map(|n| 0.3 * (2.0 * std::f32::consts::PI * 500.0 * n as f32 / 48000.0).sin())
+        .collect();
+    let input = vec![tone.clone(), tone.iter().map(|s| 0.5 * s).collect()];
+
+    let sides = [
+        HrtfPosition::new(90.0, 0.0, 1.0),
+        HrtfPosition::new(-90.0, 0.0, 1.0),
+    ];
+    let spread = renderer.render_buffer(&input, &sides).unwrap();
+
+    let front = [
+        HrtfPosition::new(0.0, 0.0, 1.0),
+        HrtfPosition::new(0.0, 0.0, 1.0),
+    ];
+    let centered = renderer.render_buffer(&input, &front).unwrap();
+
+    let diff: f32 = spread[0]
+        .iter()
+        .zip(&centered[0])
+        .chain(spread[1].iter().zip(&centered[1]))
+        .map(|(a, b)| (a - b).abs())
+        .sum();
+    assert!(diff > 1.0, "difference {}", diff);
+
+    // A single position still applies to every channel
+    let single = renderer
+        .render_buffer(&input, &[HrtfPosition::new(0.0, 0.0, 1.0)])
+        .unwrap();
+    assert_eq!(single, centered);
+}
+
+#[test]
+fn test_binaural_render_buffer_position_count_mismatch() {
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    db.load_default_kemar().unwrap();
+    let renderer = BinauralRenderer::new(db, HeadphoneProfile::Flat);
+
+    let input = vec![vec![0.1; 64], vec![0.1; 64], vec![0.1; 64]];
+    let positions = vec![
+        HrtfPosition::new(30.0, 0.0, 1.0),
+        HrtfPosition::new(-30.0, 0.0, 1.0),
+    ];
+    assert!(renderer.render_buffer(&input, &positions).is_err());
+}
+
+/// Lag of `right` relative to `left` with the highest cross-correlation
+fn correlation_lag(left: &[f32], right: &[f32], max_lag: isize) -> isize {
+    (-max_lag..=max_lag)
+        .max_by(|&a, &b| {
+            let corr = |lag: isize| -> f32 {
+                left.iter()
+                    .enumerate()
+                    .filter_map(|(i, l)| {
+                        let j = usize::try_from(i as isize + lag).ok()?;
+                        right.get(j).map(|r| l * r)
+                    })
+                    .sum()
+            };
+            corr(a).total_cmp(&corr(b))
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_binaural_itd_matches_cross_correlation_lag() {
+    // About 0.6 ms, a typical ITD for a source at the side of the head
+    let itd = 28;
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    let mut impulse = vec![0.0; 32];
+    impulse[0] = 1.0;
+    db.add_response(
+        &HrtfPosition::new(90.0, 0.0, 1.0),
+        HrtfImpulseResponse::with_delays(impulse.clone(), impulse, 0, itd),
+    );
+    let renderer = BinauralRenderer::new(db, HeadphoneProfile::Flat);
+
+    let pos = HrtfPosition::new(90.0, 0.0, 1.0);
+    assert_eq!(renderer.itd_samples(&pos).unwrap(), itd as isize);
+
+    // Deterministic broadband noise
+    let mut state = 1u32;
+    let input: Vec<f32> = (0..1024)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+        })
+        .collect();
+
+    let (left, right) = renderer.render(&input, &pos).unwrap();
+    assert_eq!(left.len(), right.len());
+    assert_eq!(left.len(), input.len() + BINAURAL_TAIL_SAMPLES);
+    assert_eq!(correlation_lag(&left, &right, 48), itd as isize);
+}
+
 #[test]
 fn test_binaural_render_buffer_empty() {
     let mut db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
@@ -387,3 +586,79 @@ fn test_multiple_renderer_instances() {
     let (_left1, _right1) = renderer1.render(&input, &pos).unwrap();
     let (_left2, _right2) = renderer2.render(&input, &pos).unwrap();
 }
+
+fn crossfeed_renderer(amount: f32) -> BinauralRenderer {
+    let db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    BinauralRenderer::new(
+        db,
+        HeadphoneProfile::Crossfeed {
+            amount,
+            delay_us: 300.0,
+        },
+    )
+}
+
+/// Energy ratio of the driven ear to the other, in dB
+fn separation_db(left: &[f32], right: &[f32]) -> f32 {
+    let energy = |x: &[f32]| x.iter().map(|s| s * s).sum::<f32>();
+    10.0 * (energy(left) / energy(right).max(f32::MIN_POSITIVE)).log10()
+}
+
+#[test]
+fn test_crossfeed_separation_decreases_with_amount() {
+    // Left-only 200 Hz tone
+    let left: Vec<f32> = (0..4800)
+        .map(|i| (2.0 * std::f32::consts::PI * 200.0 * i as f32 / 48000.0).sin())
+        .collect();
+    let right = vec![0.0; left.len()];
+
+    let separations: Vec<f32> = [0.0, 0.2, 0.5, 1.0]
+        .iter()
+        .map(|&amount| {
+            let (l, r) = crossfeed_renderer(amount)
+                .render_crossfeed(&left, &right)
+                .unwrap();
+            assert_eq!(l.len(), left.len());
+            separation_db(&l, &r)
+        })
+        .collect();
+
+    assert!(
+        separations[0] > 100.0,
+        "no crossfeed leaks {}",
+        separations[0]
+    );
+    assert!(
+        separations.windows(2).all(|w| w[1] < w[0]),
+        "{:?}",
+        separations
+    );
+}
+
+#[test]
+fn test_crossfeed_delays_and_low_passes_the_bleed() {
+    let mut left = vec![0.0; 480];
+    left[0] = 1.0;
+    let right = vec![0.0; 480];
+
+    let (_, r) = crossfeed_renderer(0.5)
+        .render_crossfeed(&left, &right)
+        .unwrap();
+
+    // 300 us at 48 kHz
+    assert!(r[..14].iter().all(|&x| x == 0.0));
+    assert!(r[14] > 0.0);
+    // A one-pole low-pass smears the impulse into a decaying tail
+    assert!(r[15] > 0.0 && r[15] < r[14]);
+}
+
+#[test]
+fn test_crossfeed_requires_crossfeed_profile() {
+    let db = HrtfDatabase::new(HrtfDataset::Kemar, 48000);
+    let renderer = BinauralRenderer::new(db, HeadphoneProfile::Flat);
+    assert!(renderer.render_crossfeed(&[0.0; 8], &[0.0; 8]).is_err());
+
+    assert!(crossfeed_renderer(0.5)
+        .render_crossfeed(&[0.0; 8], &[0.0; 4])
+        .is_err());
+}
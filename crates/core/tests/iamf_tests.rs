@@ -144,3 +144,230 @@ fn test_vbap_stereo_center() {
     let sum_squared: f32 = gains.iter().map(|g| g * g).sum();
     assert!((sum_squared - 1.0).abs() < 0.01);
 }
+
+fn leb128(mut value: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn obu(obu_type: u8, payload: &[u8]) -> Vec<u8> {
+    let mut out = vec![obu_type << 3];
+    out.extend(leb128(payload.len() as u32));
+    out.extend_from_slice(payload);
+    out
+}
+
+fn sequence_header_obu() -> Vec<u8> {
+    obu(31, b"iamf\x00\x00")
+}
+
+fn opus_codec_config_obu(codec_config_id: u32) -> Vec<u8> {
+    let mut p = leb128(codec_config_id);
+    p.extend_from_slice(b"Opus");
+    p.extend(leb128(960));
+    p.extend_from_slice(&(-4i16).to_be_bytes());
+    // OpusHead: version, channels, pre-skip, input rate, output gain, mapping family
+    p.extend_from_slice(&[1, 2]);
+    p.extend_from_slice(&312u16.to_be_bytes());
+    p.extend_from_slice(&48000u32.to_be_bytes());
+    p.extend_from_slice(&0i16.to_be_bytes());
+    p.push(0);
+    obu(0, &p)
+}
+
+fn channel_element_obu(element_id: u32, codec_config_id: u32, layouts: &[u8]) -> Vec<u8> {
+    let mut p = leb128(element_id);
+    p.push(0); // channel-based
+    p.extend(leb128(codec_config_id));
+    p.extend(leb128(1));
+    p.extend(leb128(0));
+    p.extend(leb128(0)); // no parameters
+    p.push((layouts.len() as u8) << 5);
+    for &layout in layouts {
+        p.extend_from_slice(&[layout << 4, 1, 1]);
+    }
+    obu(1, &p)
+}
+
+fn mix_presentation_obu(presentation_id: u32, element_id: u32, layouts: &[(u8, f32)]) -> Vec<u8> {
+    let param_definition = |id: u32| {
+        let mut p = leb128(id);
+        p.extend(leb128(48000));
+        p.push(0x80);
+        p.extend_from_slice(&0i16.to_be_bytes());
+        p
+    };
+
+    let mut p = leb128(presentation_id);
+    p.extend(leb128(1));
+    p.extend_from_slice(b"en-us\0Main\0");
+    p.extend(leb128(1)); // sub-mixes
+    p.extend(leb128(1)); // audio elements
+    p.extend(leb128(element_id));
+    p.extend_from_slice(b"Bed\0");
+    p.push(0x40); // binaural headphone rendering
+    p.extend(leb128(0));
+    p.extend(param_definition(100));
+    p.extend(param_definition(101));
+    p.extend(leb128(layouts.len() as u32));
+    for &(layout, lufs) in layouts {
+        p.push(layout);
+        p.push(0); // loudness info type
+        p.extend_from_slice(&((lufs * 256.0) as i16).to_be_bytes());
+        p.extend_from_slice(&(-256i16).to_be_bytes());
+    }
+    obu(2, &p)
+}
+
+fn descriptor_obus() -> Vec<u8> {
+    let mut bytes = sequence_header_obu();
+    bytes.extend(opus_codec_config_obu(7));
+    // 2.0 base layer scaled up to 5.1.2
+    bytes.extend(channel_element_obu(3, 7, &[1, 3]));
+    // Loudspeaker sound system C (5.1.2) and binaural
+    bytes.extend(mix_presentation_obu(
+        42,
+        3,
+        &[(0x80 | (2 << 2), -24.0), (0xc0, -18.0)],
+    ));
+    bytes
+}
+
+#[test]
+fn test_iamf_parse_descriptors() {
+    let desc = parse(&descriptor_obus()).unwrap();
+
+    assert_eq!(desc.codec_configs.len(), 1);
+    let codec = &desc.codec_configs[0];
+    assert_eq!(codec.codec_config_id, 7);
+    assert_eq!(codec.samples_per_frame, 960);
+    assert_eq!(codec.audio_roll_distance, -4);
+    assert_eq!(
+        codec.codec,
+        CodecConfig::Opus {
+            sample_rate: 48000,
+            channels: 2
+        }
+    );
+
+    assert_eq!(desc.audio_elements.len(), 1);
+    let element = &desc.audio_elements[0];
+    assert_eq!(element.element_id, 3);
+    assert_eq!(element.element_type, AudioElementType::ChannelBased);
+    let AudioElementConfig::Channel { layers } = &element.config else {
+        panic!("expected channel-based config");
+    };
+    let layouts: Vec<_> = layers.iter().map(|l| l.layout.name()).collect();
+    assert_eq!(layouts, ["2.0", "5.1.2"]);
+
+    assert_eq!(desc.mix_presentations.len(), 1);
+    let mix = &desc.mix_presentations[0];
+    assert_eq!(mix.presentation_id, 42);
+    assert_eq!(mix.annotations, ["Main"]);
+    let sub_mix = &mix.sub_mixes[0];
+    assert_eq!(sub_mix.elements[0].element_id, 3);
+    assert_eq!(
+        sub_mix.elements[0].rendering_config,
+        RenderingConfig::Binaural
+    );
+    assert_eq!(
+        sub_mix.layouts[0].layout,
+        PlaybackLayout::Loudspeakers("5.1.2".to_string())
+    );
+    assert_eq!(sub_mix.layouts[0].loudness.integrated_lufs, -24.0);
+    assert_eq!(sub_mix.layouts[0].loudness.digital_peak_db, -1.0);
+    assert_eq!(sub_mix.layouts[1].layout, PlaybackLayout::Binaural);
+    assert_eq!(sub_mix.layouts[1].loudness.integrated_lufs, -18.0);
+}
+
+#[test]
+fn test_iamf_parse_stops_at_audio_data() {
+    let mut bytes = descriptor_obus();
+    bytes.extend(obu(4, &[])); // temporal delimiter
+    bytes.extend(obu(25, &[0xff; 8])); // never reached
+
+    let desc = parse(&bytes).unwrap();
+    assert_eq!(desc.mix_presentations.len(), 1);
+}
+
+#[test]
+fn test_iamf_parse_rejects_reserved_obu() {
+    let mut bytes = sequence_header_obu();
+    bytes.extend(obu(24, &[0; 4]));
+
+    assert!(matches!(parse(&bytes), Err(IamfError::UnsupportedObu(24))));
+}
+
+#[test]
+fn test_iamf_parse_errors() {
+    let bytes = descriptor_obus();
+    assert!(matches!(
+        parse(&bytes[..bytes.len() - 3]),
+        Err(IamfError::Parse(_))
+    ));
+
+    // Descriptors without a leading IA sequence header
+    assert!(matches!(
+        parse(&opus_codec_config_obu(1)),
+        Err(IamfError::Parse(_))
+    ));
+}
+
+#[test]
+fn test_iamf_descriptor_stream_config() {
+    let cfg = parse(&descriptor_obus()).unwrap().stream_config().unwrap();
+
+    assert_eq!(cfg.sample_rate, 48000);
+    assert_eq!(cfg.channel_count, 8);
+    assert_eq!(cfg.frame_duration, Duration::from_millis(20));
+    assert_eq!(cfg.channel_elements[0].channel_labels.len(), 8);
+    assert_eq!(
+        cfg.channel_elements[0].channel_labels[3],
+        SpeakerRole::Subwoofer
+    );
+    assert_eq!(cfg.mix_presentations[0].loudness_lufs, Some(-24.0));
+
+    let mut decoder = ReferenceIamfDecoder::new();
+    decoder.configure(cfg).unwrap();
+    let block = decoder.decode_block(&[]).unwrap();
+    assert_eq!(block.audio.channels.len(), 8);
+    assert_eq!(block.audio.frame_len(), 960);
+}
+
+#[test]
+fn test_iamf_to_speaker_layout() {
+    let mut bytes = descriptor_obus();
+    bytes.extend(mix_presentation_obu(43, 3, &[(0x80, -16.0)]));
+    let desc = parse(&bytes).unwrap();
+
+    let layout = to_speaker_layout(&desc, "5.1.2").unwrap();
+    assert_eq!(layout.speakers.len(), 8);
+    assert!(layout
+        .speakers
+        .iter()
+        .any(|s| s.role == SpeakerRole::TopFrontLeft));
+
+    let selection = select_layout(&desc, "stereo").unwrap();
+    assert_eq!(selection.presentation_id, 43);
+    assert_eq!(selection.layout.speakers.len(), 2);
+    assert_eq!(selection.loudness.integrated_lufs, -16.0);
+
+    let selection = select_layout(&desc, "5.1.2").unwrap();
+    assert_eq!(selection.presentation_id, 42);
+    assert_eq!(
+        selection.normalization_gain_db(&audio_ninja::loudness::LoudnessTarget::Television),
+        1.0
+    );
+
+    // Known layout without a matching mix presentation, and an unknown name
+    assert!(to_speaker_layout(&desc, "7.1.4").is_none());
+    assert!(to_speaker_layout(&desc, "binaural").is_none());
+}
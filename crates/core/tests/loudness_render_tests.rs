@@ -1,8 +1,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
-use audio_ninja::loudness::{LoudnessMeter, LoudnessTarget};
-use audio_ninja::render::{ReferenceRenderer, RenderOptions, Renderer};
-use audio_ninja::AudioBlock;
+use audio_ninja::loudness::{LoudnessMeter, LoudnessNormalizer, LoudnessTarget};
+use audio_ninja::render::{ReferenceRenderer, RenderMetrics, RenderMode, RenderOptions, Renderer};
+use audio_ninja::{AudioBlock, SpeakerLayout};
+use std::time::Duration;
 
 fn peak_linear(samples: &[f32]) -> f32 {
     samples.iter().map(|s| s.abs()).fold(0.0f32, f32::max)
@@ -20,9 +21,12 @@ fn test_renderer_normalizes_to_streaming_target() {
 
     // Create stereo block with moderate level
     let frames = 4800;
+    let tone: Vec<f32> = (0..frames)
+        .map(|i| 0.2 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / sr as f32).sin())
+        .collect();
     let block = AudioBlock {
         sample_rate: sr,
-        channels: vec![vec![0.2; frames], vec![0.2; frames]],
+        channels: vec![tone.clone(), tone],
     };
 
     let opts = RenderOptions::default();
@@ -72,3 +76,292 @@ fn test_renderer_headroom_limits_peaks() {
         "peak should not increase after limiting"
     );
 }
+
+#[test]
+fn test_two_pass_file_normalization() {
+    let sr = 48000;
+    let section = |amplitude: f32| AudioBlock {
+        sample_rate: sr,
+        channels: vec![
+            (0..sr as usize)
+                .map(|i| {
+                    amplitude * (2.0 * std::f32::consts::PI * 440.0 * i as f32 / sr as f32).sin()
+                })
+                .collect();
+            2
+        ],
+    };
+
+    // Loud intro, quiet verse, loud outro
+    let mut file = vec![section(0.5), section(0.05), section(0.5)];
+    let normalizer = LoudnessNormalizer::new(sr, LoudnessTarget::StreamingMusic);
+
+    let measured = normalizer.measure_file(&file);
+    let gain_db = normalizer.file_gain_db(measured);
+    for block in &mut file {
+        normalizer.apply_fixed_gain(block, gain_db);
+    }
+
+    let lufs = normalizer.measure_file(&file);
+    assert!(
+        (lufs - (-14.0)).abs() < 0.05,
+        "normalized file loudness {} LUFS not at -14",
+        lufs
+    );
+
+    // A single fixed gain keeps the dynamics between sections intact
+    let ratio = peak_linear(&file[0].channels[0]) / peak_linear(&file[1].channels[0]);
+    assert!((ratio - 10.0).abs() < 0.01, "section ratio {}", ratio);
+}
+
+#[test]
+fn test_measure_file_reads_reference_tone() {
+    // EBU Tech 3341 case 1: 997 Hz at -23 dBFS on both channels, ten
+    // one-second blocks, then silence that the absolute gate must ignore
+    let sr = 48000;
+    let amplitude = db_to_linear(-23.0);
+    let tone: Vec<f32> = (0..10 * sr as usize)
+        .map(|i| amplitude * (2.0 * std::f32::consts::PI * 997.0 * i as f32 / sr as f32).sin())
+        .collect();
+    let mut file: Vec<AudioBlock> = tone
+        .chunks(sr as usize)
+        .map(|chunk| AudioBlock {
+            sample_rate: sr,
+            channels: vec![chunk.to_vec(); 2],
+        })
+        .collect();
+    file.push(AudioBlock::silence(2, 5 * sr as usize, sr));
+
+    let normalizer = LoudnessNormalizer::new(sr, LoudnessTarget::Television);
+    let lufs = normalizer.measure_file(&file);
+    assert!(
+        (lufs - (-23.0)).abs() < 0.1,
+        "reference tone read {} LUFS",
+        lufs
+    );
+    assert!(normalizer.file_gain_db(lufs).abs() < 0.1);
+}
+
+fn loud_block(sr: u32, frames: usize) -> AudioBlock {
+    AudioBlock {
+        sample_rate: sr,
+        channels: vec![vec![0.99; frames], vec![0.99; frames]],
+    }
+}
+
+#[test]
+fn test_render_options_headroom_drives_limiter() {
+    let sr = 48000;
+    let mut renderer = ReferenceRenderer::new(sr);
+    let gentle = RenderOptions {
+        headroom_db: 1.0,
+        target_loudness: None,
+        ..RenderOptions::default()
+    };
+    let strict = RenderOptions {
+        headroom_db: 6.0,
+        ..gentle.clone()
+    };
+
+    let output = renderer.render(loud_block(sr, 1000), &gentle);
+    assert_eq!(renderer.headroom_db(), 1.0);
+    let gentle_peak = peak_linear(&output.channels[0]);
+    assert!(gentle_peak <= db_to_linear(-1.0) * 1.01);
+    assert!(gentle_peak > db_to_linear(-3.0));
+
+    // Same renderer, more headroom requested: the limiter follows the options
+    let output = renderer.render(loud_block(sr, 1000), &strict);
+    assert_eq!(renderer.headroom_db(), 6.0);
+    let strict_peak = peak_linear(&output.channels[0]);
+    assert!(
+        strict_peak <= db_to_linear(-6.0) * 1.01,
+        "peak {} exceeds -6dB",
+        strict_peak
+    );
+    assert!(strict_peak < gentle_peak);
+}
+
+#[test]
+fn test_render_options_enable_drc_applies_default_compressor() {
+    let sr = 48000;
+    let opts = RenderOptions {
+        target_loudness: None,
+        headroom_db: 20.0,
+        ..RenderOptions::default()
+    };
+    let quiet_then_loud = || AudioBlock {
+        sample_rate: sr,
+        channels: vec![[vec![0.01; 4800], vec![0.05; 4800]].concat()],
+    };
+
+    let mut plain_renderer = ReferenceRenderer::new(sr);
+    let plain = plain_renderer.render(quiet_then_loud(), &opts);
+    // Without DRC the signal only picks up the limiter lookahead delay
+    let lookahead = plain_renderer.headroom_lookahead_samples();
+    let input = quiet_then_loud();
+    assert!(plain.channels[0][..lookahead].iter().all(|&x| x == 0.0));
+    assert_eq!(
+        plain.channels[0][lookahead..],
+        input.channels[0][..input.channels[0].len() - lookahead]
+    );
+
+    let compressed = ReferenceRenderer::new(sr).render(
+        quiet_then_loud(),
+        &RenderOptions {
+            enable_drc: true,
+            ..opts.clone()
+        },
+    );
+    assert_ne!(compressed.channels, plain.channels);
+
+    // An explicitly configured compressor takes precedence over the default
+    let mut explicit = ReferenceRenderer::new(sr);
+    explicit.enable_drc(8.0, -40.0);
+    let explicit_out = explicit.render(
+        quiet_then_loud(),
+        &RenderOptions {
+            enable_drc: true,
+            ..opts
+        },
+    );
+    assert_ne!(explicit_out.channels, compressed.channels);
+}
+
+#[test]
+fn test_render_options_max_latency_caps_lookahead() {
+    let sr = 48000;
+    let mut renderer = ReferenceRenderer::new(sr);
+    renderer.set_headroom_lookahead_ms(5.0);
+
+    let opts = RenderOptions {
+        target_loudness: None,
+        max_latency: Duration::from_millis(2),
+        ..RenderOptions::default()
+    };
+    renderer.render(loud_block(sr, 256), &opts);
+    assert_eq!(renderer.headroom_lookahead_samples(), 96);
+
+    // A looser budget restores the requested lookahead
+    let opts = RenderOptions {
+        max_latency: Duration::from_millis(100),
+        ..opts
+    };
+    renderer.render(loud_block(sr, 256), &opts);
+    assert_eq!(renderer.headroom_lookahead_samples(), 240);
+}
+
+#[test]
+fn test_last_metrics_report_gain_reduction() {
+    let sr = 48000;
+    let mut renderer = ReferenceRenderer::new(sr);
+    assert_eq!(*renderer.last_metrics(), RenderMetrics::default());
+
+    let opts = RenderOptions {
+        target_loudness: None,
+        ..RenderOptions::default()
+    };
+    renderer.render(loud_block(sr, 4800), &opts);
+
+    let metrics = renderer.last_metrics();
+    assert_eq!(metrics.drc_reduction_db, 0.0);
+    assert!(metrics.limiter_active);
+    assert!(
+        metrics.limiter_reduction_db < -2.0,
+        "limiter reduction {}",
+        metrics.limiter_reduction_db
+    );
+    let input_lufs = LoudnessMeter::new(sr).measure_integrated_loudness(&loud_block(sr, 4800));
+    assert!((metrics.input_lufs - input_lufs).abs() < 0.01);
+    assert!(metrics.output_lufs < metrics.input_lufs);
+    assert!(
+        metrics.peak_db <= -3.0 + 0.1,
+        "peak {} dBFS",
+        metrics.peak_db
+    );
+
+    // With compression the DRC reports its own reduction
+    renderer.render(
+        loud_block(sr, 4800),
+        &RenderOptions {
+            enable_drc: true,
+            ..opts
+        },
+    );
+    let metrics = renderer.last_metrics();
+    assert!(
+        metrics.drc_reduction_db < -1.0,
+        "drc reduction {}",
+        metrics.drc_reduction_db
+    );
+}
+
+#[test]
+fn test_upmix_mode_renders_one_channel_per_speaker() {
+    let sr = 48000;
+    let stereo = || AudioBlock {
+        sample_rate: sr,
+        channels: vec![vec![0.1; 4800], vec![0.1; 4800]],
+    };
+    let mut opts = RenderOptions {
+        target_layout: SpeakerLayout::from_preset("7.1").unwrap(),
+        target_loudness: None,
+        ..RenderOptions::default()
+    };
+
+    // Passthrough stays the default
+    let output = ReferenceRenderer::new(sr).render(stereo(), &opts);
+    assert_eq!(output.channels.len(), 2);
+
+    opts.mode = RenderMode::Upmix;
+    let output = ReferenceRenderer::new(sr).render(stereo(), &opts);
+    assert_eq!(output.channels.len(), 8);
+    // Center and surrounds are fed, LFE is left to bass management
+    let lfe = opts
+        .target_layout
+        .speakers
+        .iter()
+        .position(|s| s.role == audio_ninja::SpeakerRole::Subwoofer)
+        .unwrap();
+    for (i, channel) in output.channels.iter().enumerate() {
+        let peak = peak_linear(channel);
+        if i == lfe {
+            assert_eq!(peak, 0.0);
+        } else {
+            assert!(peak > 0.01, "speaker {} silent", i);
+        }
+    }
+}
+
+#[test]
+fn test_bass_management_without_subwoofer_leaves_signal_untouched() {
+    let sr = 48000;
+    let frames = sr as usize;
+    let opts = RenderOptions {
+        target_layout: SpeakerLayout::stereo(),
+        target_loudness: None,
+        ..RenderOptions::default()
+    };
+    let tone: Vec<f32> = (0..frames)
+        .map(|n| 0.2 * (2.0 * std::f32::consts::PI * 30.0 * n as f32 / sr as f32).sin())
+        .collect();
+    let block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![tone.clone(), vec![0.0; frames]],
+    };
+
+    let mut renderer = ReferenceRenderer::new(sr);
+    renderer.enable_bass_management(80.0);
+    let output = renderer.render(block, &opts);
+
+    // Stereo has no subwoofer, so the lows stay on the left speaker
+    let energy = |samples: &[f32]| samples.iter().map(|s| s * s).sum::<f32>();
+    let input_energy = energy(&tone[frames / 2..]);
+    let left_energy = energy(&output.channels[0][frames / 2..]);
+    assert!(
+        (left_energy / input_energy - 1.0).abs() < 0.05,
+        "left energy {} vs {}",
+        left_energy,
+        input_energy
+    );
+    assert!(output.channels[1].iter().all(|&s| s == 0.0));
+}
@@ -0,0 +1,480 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use audio_ninja::mapping::{
+    available_layouts, canonical_order, channel_index, downmix, downmix_with_options,
+    layout_from_name, reorder_channels, ChannelOrder, DownmixMatrix, DownmixOptions, LayoutBuilder,
+    StereoUpmixer,
+};
+use audio_ninja::{AudioBlock, Position3, SpeakerLayout, SpeakerRole};
+use std::collections::HashSet;
+
+fn role_index(layout: &SpeakerLayout, role: SpeakerRole) -> usize {
+    layout.speakers.iter().position(|s| s.role == role).unwrap()
+}
+
+fn energy(samples: &[f32]) -> f32 {
+    samples.iter().map(|s| s * s).sum()
+}
+
+fn center_only(layout: &SpeakerLayout, frames: usize) -> Vec<Vec<f32>> {
+    let center = role_index(layout, SpeakerRole::Center);
+    let mut channels = vec![vec![0.0; frames]; layout.speakers.len()];
+    channels[center] = (0..frames).map(|i| (i as f32 * 0.05).sin()).collect();
+    channels
+}
+
+#[test]
+fn test_phantom_center_on_stereo() {
+    let source = SpeakerLayout::surround_5_1();
+    let target = SpeakerLayout::stereo();
+    let input = center_only(&source, 480);
+
+    let matrix = DownmixMatrix::new(&source, &target);
+    let output = matrix.apply(&input);
+
+    assert_eq!(output.len(), 2);
+    let left = energy(&output[0]);
+    let right = energy(&output[1]);
+    assert!(left > 0.0);
+    assert!((left - right).abs() < 1e-6);
+
+    // Power is preserved across the phantom image
+    let center = energy(&input[role_index(&source, SpeakerRole::Center)]);
+    assert!(((left + right) - center).abs() / center < 1e-4);
+}
+
+#[test]
+fn test_phantom_center_disabled_drops_center() {
+    let source = SpeakerLayout::surround_5_1();
+    let target = SpeakerLayout::stereo();
+    let options = DownmixOptions {
+        phantom_center: false,
+        ..DownmixOptions::default()
+    };
+
+    let matrix = DownmixMatrix::with_options(&source, &target, &options);
+    let output = matrix.apply(&center_only(&source, 480));
+
+    assert!(output.iter().all(|ch| energy(ch) == 0.0));
+}
+
+#[test]
+fn test_center_spread_bleeds_to_front_pair() {
+    let layout = SpeakerLayout::surround_5_1();
+    let center = role_index(&layout, SpeakerRole::Center);
+    let left = role_index(&layout, SpeakerRole::FrontLeft);
+    let right = role_index(&layout, SpeakerRole::FrontRight);
+
+    let dry = DownmixMatrix::new(&layout, &layout);
+    assert_eq!(dry.gain(center, center), 1.0);
+    assert_eq!(dry.gain(left, center), 0.0);
+
+    let options = DownmixOptions {
+        center_spread: 0.5,
+        ..DownmixOptions::default()
+    };
+    let wide = DownmixMatrix::with_options(&layout, &layout, &options);
+    assert!(wide.gain(left, center) > 0.0);
+    assert_eq!(wide.gain(left, center), wide.gain(right, center));
+
+    let power = wide.gain(center, center).powi(2)
+        + wide.gain(left, center).powi(2)
+        + wide.gain(right, center).powi(2);
+    assert!((power - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_surrounds_fold_to_front_and_lfe_dropped() {
+    let source = SpeakerLayout::surround_5_1();
+    let target = SpeakerLayout::stereo();
+    let matrix = DownmixMatrix::new(&source, &target);
+
+    let side_left = role_index(&source, SpeakerRole::SideLeft);
+    let side_right = role_index(&source, SpeakerRole::SideRight);
+    let lfe = role_index(&source, SpeakerRole::Subwoofer);
+
+    assert!(matrix.gain(0, side_left) > 0.0);
+    assert_eq!(matrix.gain(1, side_left), 0.0);
+    assert!(matrix.gain(1, side_right) > 0.0);
+    assert_eq!(matrix.gain(0, lfe), 0.0);
+    assert_eq!(matrix.gain(1, lfe), 0.0);
+}
+
+/// One channel per speaker of `layout`, each a tone at its own frequency
+fn tones(layout: &SpeakerLayout, frames: usize) -> AudioBlock {
+    AudioBlock {
+        sample_rate: 48000,
+        channels: (0..layout.speakers.len())
+            .map(|ch| {
+                (0..frames)
+                    .map(|i| (i as f32 * 0.01 * (ch + 1) as f32).sin())
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_downmix_5_1_to_stereo() {
+    let source = SpeakerLayout::surround_5_1();
+    let target = SpeakerLayout::stereo();
+    let input = tones(&source, 480);
+    let ch = |role| &input.channels[role_index(&source, role)];
+    let fold = std::f32::consts::FRAC_1_SQRT_2;
+
+    let output = downmix(&input, &source, &target);
+    assert_eq!(output.sample_rate, 48000);
+    assert_eq!(output.channels.len(), 2);
+
+    // L = FL + 0.707 C + 0.707 SL, R likewise, LFE dropped
+    for i in 0..480 {
+        let left = ch(SpeakerRole::FrontLeft)[i]
+            + fold * ch(SpeakerRole::Center)[i]
+            + fold * ch(SpeakerRole::SideLeft)[i];
+        let right = ch(SpeakerRole::FrontRight)[i]
+            + fold * ch(SpeakerRole::Center)[i]
+            + fold * ch(SpeakerRole::SideRight)[i];
+        assert!((output.channels[0][i] - left).abs() < 1e-5);
+        assert!((output.channels[1][i] - right).abs() < 1e-5);
+    }
+
+    // Stereo back to stereo is the identity
+    assert_eq!(downmix(&output, &target, &target), output);
+}
+
+#[test]
+fn test_downmix_includes_lfe_when_asked() {
+    let source = SpeakerLayout::surround_5_1();
+    let target = SpeakerLayout::stereo();
+    let lfe = role_index(&source, SpeakerRole::Subwoofer);
+    let mut input = AudioBlock::silence(source.speakers.len(), 64, 48000);
+    input.channels[lfe] = vec![1.0; 64];
+
+    let dropped = downmix(&input, &source, &target);
+    assert!(dropped.channels.iter().all(|ch| energy(ch) == 0.0));
+
+    let options = DownmixOptions {
+        include_lfe: true,
+        ..DownmixOptions::default()
+    };
+    let kept = downmix_with_options(&input, &source, &target, &options);
+    for channel in &kept.channels {
+        assert!((channel[0] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-6);
+    }
+
+    // A target with its own subwoofer keeps the LFE at unity either way
+    let to_2_1 = layout_from_name("2.1").unwrap();
+    let output = downmix(&input, &source, &to_2_1);
+    assert_eq!(
+        output.channels[role_index(&to_2_1, SpeakerRole::Subwoofer)],
+        input.channels[lfe]
+    );
+}
+
+#[test]
+fn test_downmix_7_1_to_5_1() {
+    let source = SpeakerLayout::surround_7_1();
+    let target = SpeakerLayout::surround_5_1();
+    let input = tones(&source, 480);
+
+    let output = downmix(&input, &source, &target);
+    assert_eq!(output.channels.len(), 6);
+
+    // The 5.1 bed passes through; rears fold into the sides at -3 dB
+    let fold = std::f32::consts::FRAC_1_SQRT_2;
+    for (dst, speaker) in target.speakers.iter().enumerate() {
+        let src = role_index(&source, speaker.role.clone());
+        let rear = match speaker.role {
+            SpeakerRole::SideLeft => Some(role_index(&source, SpeakerRole::RearLeft)),
+            SpeakerRole::SideRight => Some(role_index(&source, SpeakerRole::RearRight)),
+            _ => None,
+        };
+        for i in 0..480 {
+            let expected =
+                input.channels[src][i] + rear.map_or(0.0, |r| fold * input.channels[r][i]);
+            assert!(
+                (output.channels[dst][i] - expected).abs() < 1e-5,
+                "{} frame {}",
+                speaker.id,
+                i
+            );
+        }
+    }
+}
+
+#[test]
+fn test_registered_layouts_have_expected_channels() {
+    let expected = [
+        ("stereo", 2),
+        ("2.1", 3),
+        ("3.1", 4),
+        ("quad", 4),
+        ("5.1", 6),
+        ("5.1.2", 8),
+        ("5.1.4", 10),
+        ("7.1", 8),
+        ("7.1.2", 10),
+        ("7.1.4", 12),
+        ("9.1.6", 16),
+    ];
+    assert_eq!(
+        available_layouts(),
+        expected.iter().map(|(name, _)| *name).collect::<Vec<_>>()
+    );
+
+    for (name, channels) in expected {
+        let layout = layout_from_name(name).unwrap();
+        assert_eq!(layout.name, name);
+        assert_eq!(layout.speakers.len(), channels, "{}", name);
+
+        let ids: HashSet<_> = layout.speakers.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids.len(), channels, "{} has duplicate ids", name);
+
+        let subs = layout
+            .speakers
+            .iter()
+            .filter(|s| s.role == SpeakerRole::Subwoofer)
+            .count();
+        assert_eq!(subs, usize::from(name != "stereo" && name != "quad"));
+
+        // The third number of an Atmos name counts the speakers overhead
+        let heights = name
+            .split('.')
+            .nth(2)
+            .map_or(0, |n| n.parse::<usize>().unwrap());
+        let overhead = layout
+            .speakers
+            .iter()
+            .filter(|s| s.position.z > 0.0)
+            .count();
+        assert_eq!(overhead, heights, "{} height speakers", name);
+    }
+
+    // Aliases resolve to the same speakers
+    assert_eq!(
+        layout_from_name("2.0").unwrap().speakers,
+        layout_from_name("stereo").unwrap().speakers
+    );
+    assert!(layout_from_name("6.1").is_none());
+}
+
+#[test]
+fn test_layout_builder() {
+    let front = Position3 {
+        x: 0.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    let layout = LayoutBuilder::new("soundbar")
+        .speaker("C", SpeakerRole::Center, front)
+        .speaker(
+            "SUB",
+            SpeakerRole::Subwoofer,
+            Position3 {
+                x: 0.0,
+                y: 0.5,
+                z: -0.5,
+            },
+        )
+        .build();
+
+    assert_eq!(layout.name, "soundbar");
+    assert_eq!(layout.speakers.len(), 2);
+    assert_eq!(layout.by_id("C").unwrap().max_spl_db, 110.0);
+    assert_eq!(layout.by_id("SUB").unwrap().max_spl_db, 120.0);
+
+    // Re-adding an id replaces the speaker rather than duplicating it
+    let moved = Position3 { z: 0.5, ..front };
+    let layout = LayoutBuilder::new("soundbar")
+        .speaker("C", SpeakerRole::Center, front)
+        .speaker("C", SpeakerRole::Center, moved)
+        .build();
+    assert_eq!(layout.speakers.len(), 1);
+    assert_eq!(layout.speakers[0].position, moved);
+}
+
+#[test]
+fn test_channel_index_5_1_order() {
+    let layout = SpeakerLayout::from_preset("5.1").unwrap();
+    let order = [
+        SpeakerRole::FrontLeft,
+        SpeakerRole::FrontRight,
+        SpeakerRole::Center,
+        SpeakerRole::Subwoofer,
+        SpeakerRole::SideLeft,
+        SpeakerRole::SideRight,
+    ];
+    for (channel, role) in order.iter().enumerate() {
+        assert_eq!(channel_index(role, &layout), Some(channel), "{:?}", role);
+    }
+    assert_eq!(channel_index(&SpeakerRole::RearLeft, &layout), None);
+
+    // Declaration order of the speakers does not change the channel order
+    let mut shuffled = layout.clone();
+    shuffled.speakers.reverse();
+    for (channel, role) in order.iter().enumerate() {
+        assert_eq!(channel_index(role, &shuffled), Some(channel));
+    }
+    assert_eq!(canonical_order(&shuffled), vec![5, 4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn test_channel_index_7_1_order() {
+    let layout = SpeakerLayout::from_preset("7.1").unwrap();
+    assert_eq!(channel_index(&SpeakerRole::Subwoofer, &layout), Some(3));
+    assert_eq!(channel_index(&SpeakerRole::SideLeft, &layout), Some(4));
+    assert_eq!(channel_index(&SpeakerRole::SideRight, &layout), Some(5));
+    assert_eq!(channel_index(&SpeakerRole::RearLeft, &layout), Some(6));
+    assert_eq!(channel_index(&SpeakerRole::RearRight, &layout), Some(7));
+
+    // Wides sit between the bed and the heights
+    let layout = SpeakerLayout::from_preset("9.1.6").unwrap();
+    let ids: Vec<&str> = canonical_order(&layout)
+        .into_iter()
+        .map(|i| layout.speakers[i].id.as_str())
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            "FL", "FR", "C", "LFE", "SL", "SR", "RL", "RR", "WL", "WR", "TFL", "TFR", "TSL", "TSR",
+            "TRL", "TRR"
+        ]
+    );
+}
+
+#[test]
+fn test_stereo_upmix_to_5_1_routes_by_role() {
+    let layout = SpeakerLayout::surround_5_1();
+    let mut upmixer = StereoUpmixer::new(&layout, 48000);
+    assert_eq!(upmixer.target_channels(), 6);
+
+    let left: Vec<f32> = (0..960).map(|i| (i as f32 * 0.05).sin()).collect();
+    let right: Vec<f32> = (0..960).map(|i| (i as f32 * 0.07).sin()).collect();
+    let output = upmixer.process(&[left.clone(), right.clone()]);
+    assert_eq!(output.len(), 6);
+
+    assert_eq!(output[role_index(&layout, SpeakerRole::FrontLeft)], left);
+    assert_eq!(output[role_index(&layout, SpeakerRole::FrontRight)], right);
+    let center = &output[role_index(&layout, SpeakerRole::Center)];
+    for i in 0..960 {
+        let expected = std::f32::consts::FRAC_1_SQRT_2 * 0.5 * (left[i] + right[i]);
+        assert!((center[i] - expected).abs() < 1e-6);
+    }
+    assert!(output[role_index(&layout, SpeakerRole::Subwoofer)]
+        .iter()
+        .all(|&s| s == 0.0));
+
+    // Surrounds are delayed copies, with different delays left and right
+    let surround_left = layout
+        .speakers
+        .iter()
+        .position(|s| matches!(s.role, SpeakerRole::SideLeft | SpeakerRole::RearLeft))
+        .unwrap();
+    let surround_right = layout
+        .speakers
+        .iter()
+        .position(|s| matches!(s.role, SpeakerRole::SideRight | SpeakerRole::RearRight))
+        .unwrap();
+    let onset = |channel: &[f32]| channel.iter().position(|&s| s != 0.0).unwrap();
+    let (sl, sr) = (&output[surround_left], &output[surround_right]);
+    assert!(onset(sl) > 0);
+    assert_ne!(onset(sl), onset(sr));
+    assert!((sl[onset(sl)] - 0.5 * left[1]).abs() < 1e-6);
+}
+
+#[test]
+fn test_upmix_delay_carries_across_blocks() {
+    let layout = SpeakerLayout::from_preset("7.1").unwrap();
+    let rear_left = role_index(&layout, SpeakerRole::RearLeft);
+    let mut upmixer = StereoUpmixer::new(&layout, 48000);
+
+    // Mono impulse at the end of the first block shows up in the next one
+    let mut first = vec![0.0; 480];
+    first[479] = 1.0;
+    let out_first = upmixer.process(&[first]);
+    assert_eq!(out_first.len(), 8);
+    assert!(out_first[rear_left].iter().all(|&s| s == 0.0));
+
+    // 15 ms rear delay: sample 479 + 720 lands at 719 of the second block
+    let out_second = upmixer.process(&[vec![0.0; 960]]);
+    let peak = out_second[rear_left]
+        .iter()
+        .position(|&s| s != 0.0)
+        .unwrap();
+    assert_eq!(peak, 719);
+    assert_eq!(out_second[rear_left][peak], 0.5);
+}
+
+/// Block whose channel for each speaker is filled with the speaker's index
+fn labelled(layout: &SpeakerLayout, order: ChannelOrder) -> AudioBlock {
+    AudioBlock {
+        sample_rate: 48000,
+        channels: order
+            .speaker_indices(layout)
+            .into_iter()
+            .map(|speaker| vec![speaker as f32; 4])
+            .collect(),
+    }
+}
+
+#[test]
+fn test_reorder_5_1_moves_center() {
+    let layout = SpeakerLayout::surround_5_1();
+    let center = role_index(&layout, SpeakerRole::Center) as f32;
+    let lfe = role_index(&layout, SpeakerRole::Subwoofer) as f32;
+
+    // Film files carry the center second and the LFE last
+    let film = labelled(&layout, ChannelOrder::Film);
+    assert_eq!(film.channels[1][0], center);
+    assert_eq!(film.channels[5][0], lfe);
+
+    let smpte = reorder_channels(&film, ChannelOrder::Film, ChannelOrder::Smpte, &layout);
+    assert_eq!(smpte.channels[2][0], center);
+    assert_eq!(smpte.channels[3][0], lfe);
+    assert_eq!(smpte, labelled(&layout, ChannelOrder::Smpte));
+
+    // SMPTE and ITU agree without rear surrounds
+    let itu = reorder_channels(&smpte, ChannelOrder::Smpte, ChannelOrder::Itu, &layout);
+    assert_eq!(itu, smpte);
+
+    let back = reorder_channels(&itu, ChannelOrder::Itu, ChannelOrder::Film, &layout);
+    assert_eq!(back, film);
+}
+
+#[test]
+fn test_reorder_7_1_swaps_surround_pairs() {
+    let layout = SpeakerLayout::surround_7_1();
+    let smpte = labelled(&layout, ChannelOrder::Smpte);
+    let itu = reorder_channels(&smpte, ChannelOrder::Smpte, ChannelOrder::Itu, &layout);
+
+    let label = |role| role_index(&layout, role) as f32;
+    let at = |block: &AudioBlock, ch: usize| block.channels[ch][0];
+    assert_eq!(at(&smpte, 4), label(SpeakerRole::RearLeft));
+    assert_eq!(at(&smpte, 6), label(SpeakerRole::SideLeft));
+    assert_eq!(at(&itu, 4), label(SpeakerRole::SideLeft));
+    assert_eq!(at(&itu, 6), label(SpeakerRole::RearLeft));
+
+    let engine = reorder_channels(&itu, ChannelOrder::Itu, ChannelOrder::Layout, &layout);
+    assert_eq!(engine, labelled(&layout, ChannelOrder::Layout));
+}
+
+#[test]
+fn test_reorder_keeps_extra_channels_and_fills_missing() {
+    let layout = SpeakerLayout::surround_5_1();
+    let mut film = labelled(&layout, ChannelOrder::Film);
+    film.channels.push(vec![9.0; 4]);
+
+    let itu = reorder_channels(&film, ChannelOrder::Film, ChannelOrder::Itu, &layout);
+    assert_eq!(itu.channels.len(), 7);
+    assert_eq!(itu.channels[6], vec![9.0; 4]);
+
+    // Only L, C, R present: the surrounds and LFE come out silent
+    film.channels.truncate(3);
+    let itu = reorder_channels(&film, ChannelOrder::Film, ChannelOrder::Itu, &layout);
+    assert_eq!(itu.channels.len(), 6);
+    assert_eq!(
+        itu.channels[2][0],
+        role_index(&layout, SpeakerRole::Center) as f32
+    );
+    assert_eq!(itu.channels[3], vec![0.0; 4]);
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use audio_ninja::network::*;
+use audio_ninja::transport::{RtpPacket, TransportReceiver, TransportSender};
 use audio_ninja::AudioBlock;
 use std::net::SocketAddr;
 use std::time::Duration;
@@ -52,6 +53,62 @@ fn test_udp_send_recv_roundtrip() {
     assert_eq!(recv_block.sample_rate, 48000);
 }
 
+#[test]
+fn test_udp_rtp_transport_roundtrip() {
+    let placeholder: SocketAddr = "127.0.0.1:9".parse().unwrap();
+    let mut speaker = UdpRtpTransport::new("127.0.0.1:0", placeholder).unwrap();
+    let mut host = UdpRtpTransport::new("127.0.0.1:0", speaker.local_addr().unwrap()).unwrap();
+    speaker.set_peer(host.local_addr().unwrap());
+
+    // Nothing sent yet: poll returns immediately
+    assert!(speaker.poll().unwrap().is_none());
+
+    speaker
+        .set_poll_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+    host.send(RtpPacket::new(4242, 96000, 0xCAFE, vec![9, 8, 7, 6]))
+        .unwrap();
+
+    let packet = speaker.poll().unwrap().expect("packet should arrive");
+    assert_eq!(packet.header.sequence.0, 4242);
+    assert_eq!(packet.header.timestamp.0, 96000);
+    assert_eq!(packet.header.ssrc.0, 0xCAFE);
+    assert_eq!(packet.payload, vec![9, 8, 7, 6]);
+
+    // And back the other way
+    host.set_poll_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+    speaker.send(RtpPacket::new(1, 0, 0xBEEF, vec![1])).unwrap();
+    assert_eq!(host.poll().unwrap().unwrap().header.sequence.0, 1);
+}
+
+#[test]
+fn test_udp_rtp_transport_ignores_other_hosts() {
+    let placeholder: SocketAddr = "127.0.0.1:9".parse().unwrap();
+    let mut speaker = UdpRtpTransport::new("127.0.0.1:0", placeholder).unwrap();
+    let mut host = UdpRtpTransport::new("127.0.0.1:0", speaker.local_addr().unwrap()).unwrap();
+    let mut stranger = UdpRtpTransport::new("127.0.0.1:0", speaker.local_addr().unwrap()).unwrap();
+    speaker.set_peer(host.local_addr().unwrap());
+    speaker
+        .set_poll_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+
+    // Only the stranger sends: nothing comes through
+    stranger
+        .send(RtpPacket::new(666, 0, 0xBAD, vec![0xFF]))
+        .unwrap();
+    assert!(speaker.poll().unwrap().is_none());
+
+    // The stranger's packet is skipped, the peer's is delivered
+    stranger
+        .send(RtpPacket::new(667, 0, 0xBAD, vec![0xFF]))
+        .unwrap();
+    host.send(RtpPacket::new(7, 0, 0xCAFE, vec![1])).unwrap();
+    let packet = speaker.poll().unwrap().expect("peer packet should arrive");
+    assert_eq!(packet.header.ssrc.0, 0xCAFE);
+    assert_eq!(packet.header.sequence.0, 7);
+}
+
 #[test]
 fn test_udp_sequence_increment() {
     let target: SocketAddr = "127.0.0.1:9001".parse().unwrap();
@@ -0,0 +1,226 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use audio_ninja::hrtf::HeadphoneProfile;
+use audio_ninja::render::{ObjectRenderer, ReferenceRenderer, RenderTarget};
+use audio_ninja::vbap::Vec3;
+use audio_ninja::{Position3, SpeakerLayout};
+
+const FL: usize = 0;
+const FR: usize = 1;
+const C: usize = 2;
+const LFE: usize = 3;
+const SL: usize = 4;
+const SR: usize = 5;
+
+fn energy(samples: &[f32]) -> f32 {
+    samples.iter().map(|s| s * s).sum()
+}
+
+fn noise(frames: usize) -> Vec<f32> {
+    let mut state = 7u32;
+    (0..frames)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 8) as f32 / (1u32 << 24) as f32 - 0.5
+        })
+        .collect()
+}
+
+#[test]
+fn test_object_renders_to_5_1_layout() {
+    let layout = SpeakerLayout::from_preset("5.1").unwrap();
+    let mut renderer = ObjectRenderer::new(48000);
+    renderer.add_object(
+        "voice",
+        vec![0.5; 480],
+        Position3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+    );
+
+    let block = renderer.render(&RenderTarget::Speakers(layout.clone()));
+    assert_eq!(block.channels.len(), 6);
+    assert_eq!(block.channels[C].len(), 480);
+    assert!((block.channels[C][0] - 0.5).abs() < 1e-4);
+    for ch in [FL, FR, LFE, SL, SR] {
+        assert!(energy(&block.channels[ch]) < 1e-6, "channel {}", ch);
+    }
+
+    // Between center and front left: a constant-power pair, nothing elsewhere
+    renderer.add_object(
+        "voice",
+        vec![0.5; 480],
+        Position3 {
+            x: -0.25,
+            y: 1.0,
+            z: 0.0,
+        },
+    );
+    assert_eq!(renderer.objects().len(), 1);
+    let block = renderer.render(&RenderTarget::Speakers(layout));
+    let (fl, c) = (block.channels[FL][0], block.channels[C][0]);
+    assert!(fl > 0.1 && c > 0.1);
+    assert!(((fl * fl + c * c).sqrt() - 0.5).abs() < 1e-3);
+    for ch in [FR, LFE, SL, SR] {
+        assert!(energy(&block.channels[ch]) < 1e-6, "channel {}", ch);
+    }
+}
+
+#[test]
+fn test_objects_mix_and_pad_to_longest() {
+    let layout = SpeakerLayout::from_preset("5.1").unwrap();
+    let mut renderer = ObjectRenderer::new(48000);
+    renderer.add_object(
+        "left",
+        vec![0.25; 100],
+        Position3 {
+            x: -0.5,
+            y: 1.0,
+            z: 0.0,
+        },
+    );
+    renderer.add_object(
+        "right",
+        vec![0.25; 200],
+        Position3 {
+            x: 0.5,
+            y: 1.0,
+            z: 0.0,
+        },
+    );
+
+    let block = renderer.render(&RenderTarget::Speakers(layout));
+    assert_eq!(block.channels[FL].len(), 200);
+    assert!((block.channels[FL][50] - 0.25).abs() < 1e-4);
+    assert_eq!(block.channels[FL][150], 0.0);
+    assert!((block.channels[FR][150] - 0.25).abs() < 1e-4);
+
+    assert!(renderer.remove_object("left").is_some());
+    assert!(renderer.remove_object("left").is_none());
+}
+
+#[test]
+fn test_object_renders_to_headphones() {
+    let mut renderer = ObjectRenderer::new(48000);
+    let stem = noise(2048);
+    renderer.add_object(
+        "fx",
+        stem.clone(),
+        Position3 {
+            x: -1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    );
+
+    let left_side = renderer.render(&RenderTarget::Headphones(HeadphoneProfile::Flat));
+    assert_eq!(left_side.channels.len(), 2);
+    assert_eq!(left_side.channels[0].len(), stem.len());
+    assert!(energy(&left_side.channels[0]) > 0.0);
+
+    renderer.add_object(
+        "fx",
+        stem,
+        Position3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    );
+    let right_side = renderer.render(&RenderTarget::Headphones(HeadphoneProfile::Flat));
+
+    // Moving the object across the head swaps which ear leads
+    let lead = |left: &[f32], right: &[f32]| -> isize {
+        (-10isize..=10)
+            .max_by(|&a, &b| {
+                let corr = |lag: isize| -> f32 {
+                    (20..left.len() - 20)
+                        .map(|i| left[i] * right[(i as isize + lag) as usize])
+                        .sum()
+                };
+                corr(a).total_cmp(&corr(b))
+            })
+            .unwrap()
+    };
+    let from_left = lead(&left_side.channels[0], &left_side.channels[1]);
+    let from_right = lead(&right_side.channels[0], &right_side.channels[1]);
+    assert!(from_left > 0, "right ear should lag, got {}", from_left);
+    assert!(from_right < 0, "left ear should lag, got {}", from_right);
+}
+
+/// VBAP direction of a speaker position (azimuth positive to the left)
+fn vbap_direction(p: &Position3) -> Vec3 {
+    let azimuth = (-p.x).atan2(p.y).to_degrees();
+    let elevation = p.z.atan2(p.x.hypot(p.y)).to_degrees();
+    Vec3::from_spherical(azimuth, elevation, 1.0)
+}
+
+#[test]
+fn test_object_renderer_reuses_binaural_renderer_across_profiles() {
+    let position = Position3 {
+        x: -1.0,
+        y: 1.0,
+        z: 0.0,
+    };
+    let mut cached = ObjectRenderer::new(48000);
+    cached.add_object("fx", noise(1024), position);
+    let closed_back = RenderTarget::Headphones(HeadphoneProfile::ClosedBack);
+
+    let flat = cached.render(&RenderTarget::Headphones(HeadphoneProfile::Flat));
+    let switched = cached.render(&closed_back);
+    assert_ne!(flat.channels, switched.channels);
+
+    let mut fresh = ObjectRenderer::new(48000);
+    fresh.add_object("fx", noise(1024), position);
+    assert_eq!(switched.channels, fresh.render(&closed_back).channels);
+
+    // Swapping in an equivalent database keeps the output and the profile
+    let hrtf = cached.hrtf_database().clone();
+    cached.set_hrtf_database(hrtf);
+    assert_eq!(switched.channels, cached.render(&closed_back).channels);
+}
+
+#[test]
+fn test_pan_object_on_speaker_is_unity_there() {
+    for preset in ["5.1", "7.1.4"] {
+        let layout = SpeakerLayout::from_preset(preset).unwrap();
+        let mut renderer = ReferenceRenderer::new(48000);
+        renderer.set_target_layout(layout.clone());
+
+        for (target, speaker) in layout.speakers.iter().enumerate() {
+            if speaker.id == "LFE" {
+                continue;
+            }
+            let block = renderer.pan_object(&[1.0; 64], vbap_direction(&speaker.position));
+            assert_eq!(block.channels.len(), layout.speakers.len());
+            for (ch, channel) in block.channels.iter().enumerate() {
+                let expected = if ch == target { 1.0 } else { 0.0 };
+                assert!(
+                    (channel[0] - expected).abs() < 1e-3,
+                    "{} {}: channel {} gain {}",
+                    preset,
+                    speaker.id,
+                    ch,
+                    channel[0]
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pan_object_between_speakers_keeps_power() {
+    let layout = SpeakerLayout::from_preset("5.1").unwrap();
+    let mut renderer = ReferenceRenderer::new(48000);
+    renderer.set_target_layout(layout);
+
+    // Halfway between C (0 degrees) and FL (30 degrees)
+    let block = renderer.pan_object(&[1.0], Vec3::from_spherical(15.0, 0.0, 2.0));
+    let power: f32 = block.channels.iter().map(|ch| ch[0] * ch[0]).sum();
+    assert!((power - 1.0).abs() < 1e-3, "power {}", power);
+    assert!(block.channels[C][0] > 0.1 && block.channels[FL][0] > 0.1);
+    assert_eq!(block.channels[LFE][0], 0.0);
+    assert_eq!(block.channels[SR][0], 0.0);
+}
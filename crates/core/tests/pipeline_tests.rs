@@ -0,0 +1,355 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use audio_ninja::calibration::design_peq;
+use audio_ninja::dsp::{BassManager, BiquadDesign, ParametricEq, PeqBand};
+use audio_ninja::dspconfig::{DspChainConfig, DspConfigError, StageConfig};
+use audio_ninja::hrtf::{
+    BinauralRenderer, HeadphoneProfile, HrtfDatabase, HrtfDataset, HrtfImpulseResponse,
+    HrtfPosition,
+};
+use audio_ninja::loudness::{DynamicRangeControl, HeadroomManager};
+use audio_ninja::pipeline::{AudioStage, BinauralStage, BiquadStage, Pipeline, PipelineError};
+use audio_ninja::AudioBlock;
+use std::time::Duration;
+
+/// Pure delay standing in for a long linear-phase FIR
+struct FixedLatency(usize);
+
+impl AudioStage for FixedLatency {
+    fn name(&self) -> &'static str {
+        "fixed-latency"
+    }
+
+    fn process(&mut self, _block: &mut AudioBlock) {}
+
+    fn latency_samples(&self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn test_empty_pipeline_has_no_latency() {
+    let pipeline = Pipeline::new(48000);
+
+    assert!(pipeline.is_empty());
+    assert_eq!(pipeline.total_latency_samples(), 0);
+    assert_eq!(pipeline.total_latency(), Duration::ZERO);
+    assert!(pipeline.validate(Duration::ZERO).is_ok());
+}
+
+#[test]
+fn test_total_latency_sums_stages() {
+    let sr = 48000;
+    let mut limiter = HeadroomManager::new(1.0, sr);
+    limiter.set_lookahead_ms(sr, 5.0);
+
+    let mut pipeline = Pipeline::new(sr);
+    pipeline.add_stage(DynamicRangeControl::new(4.0, -20.0, 0.0, 5.0, 80.0, sr));
+    pipeline.add_stage(limiter);
+    pipeline.add_stage(FixedLatency(480));
+
+    assert_eq!(
+        pipeline.stage_names(),
+        vec!["drc", "limiter", "fixed-latency"]
+    );
+    assert_eq!(pipeline.total_latency_samples(), 240 + 480);
+    assert_eq!(pipeline.total_latency(), Duration::from_millis(15));
+    assert!(pipeline.validate(Duration::from_millis(20)).is_ok());
+}
+
+#[test]
+fn test_validate_rejects_chain_over_budget() {
+    let sr = 48000;
+    let mut limiter = HeadroomManager::new(1.0, sr);
+    limiter.set_lookahead_ms(sr, 50.0);
+
+    let mut pipeline = Pipeline::new(sr);
+    pipeline.add_stage(limiter);
+    // 8192-tap linear-phase room correction FIR: ~85ms group delay
+    pipeline.add_stage(FixedLatency(4096));
+
+    let max = Duration::from_millis(100);
+    match pipeline.validate(max) {
+        Err(PipelineError::LatencyExceeded {
+            latency,
+            max: budget,
+        }) => {
+            assert!(latency > Duration::from_millis(100));
+            assert_eq!(budget, max);
+        }
+        other => panic!("expected LatencyExceeded, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_process_runs_stages() {
+    let sr = 48000;
+    let mut pipeline = Pipeline::new(sr);
+    pipeline.add_stage(HeadroomManager::new(6.0, sr));
+
+    let mut block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![vec![1.0; 256]],
+    };
+    pipeline.process(&mut block);
+
+    // -6 dBFS ceiling
+    assert!(block.channels[0].iter().all(|s| s.abs() <= 0.502));
+}
+
+#[test]
+fn test_set_sample_rate_rebuilds_stages() {
+    let mut limiter = HeadroomManager::new(1.0, 48000);
+    limiter.set_lookahead_ms(48000, 5.0);
+
+    let mut pipeline = Pipeline::new(48000);
+    pipeline.add_stage(DynamicRangeControl::new(4.0, -20.0, 0.0, 5.0, 80.0, 48000));
+    pipeline.add_stage(ParametricEq::new(
+        vec![PeqBand {
+            freq_hz: 1000.0,
+            gain_db: -6.0,
+            q: 1.0,
+        }],
+        48000,
+    ));
+    pipeline.add_stage(limiter);
+    assert_eq!(pipeline.total_latency_samples(), 240);
+
+    pipeline.set_sample_rate(44100).unwrap();
+    assert_eq!(pipeline.sample_rate(), 44100);
+    assert_eq!(pipeline.stage_names(), vec!["drc", "peq", "limiter"]);
+    // 5 ms of lookahead at the new rate
+    assert_eq!(pipeline.total_latency_samples(), 220);
+    assert_eq!(
+        pipeline.total_latency(),
+        Duration::from_secs_f64(220.0 / 44100.0)
+    );
+
+    let config = DspChainConfig::from_pipeline(&pipeline).unwrap();
+    match &config.stages[0] {
+        StageConfig::Drc {
+            attack_ms,
+            release_ms,
+            ..
+        } => {
+            assert!((attack_ms - 5.0).abs() < 0.05);
+            assert!((release_ms - 80.0).abs() < 0.05);
+        }
+        other => panic!("unexpected stage {:?}", other),
+    }
+}
+
+#[test]
+fn test_set_sample_rate_rejects_stage_without_config() {
+    let mut pipeline = Pipeline::new(48000);
+    pipeline.add_stage(HeadroomManager::new(1.0, 48000));
+    pipeline.add_stage(BassManager::new(80.0, 48000, &[]));
+
+    assert!(matches!(
+        pipeline.set_sample_rate(44100),
+        Err(DspConfigError::UnsupportedStage("bass_management"))
+    ));
+    assert_eq!(pipeline.sample_rate(), 48000);
+    assert_eq!(pipeline.len(), 2);
+}
+
+fn loud_sine(sr: u32, frames: usize) -> AudioBlock {
+    AudioBlock {
+        sample_rate: sr,
+        channels: vec![(0..frames)
+            .map(|n| (2.0 * std::f32::consts::PI * 440.0 * n as f32 / sr as f32).sin())
+            .collect()],
+    }
+}
+
+#[test]
+fn test_stage_order_matters() {
+    let sr = 48000;
+    let drc = || DynamicRangeControl::new(4.0, -20.0, 0.0, 5.0, 80.0, sr);
+    let limiter = || HeadroomManager::new(6.0, sr);
+
+    let mut drc_first = Pipeline::new(sr).with_stage(drc()).with_stage(limiter());
+    let mut limit_first = Pipeline::new(sr).with_stage(limiter()).with_stage(drc());
+    assert_eq!(drc_first.stage_names(), vec!["drc", "limiter"]);
+    assert_eq!(limit_first.stage_names(), vec!["limiter", "drc"]);
+    assert_eq!(
+        drc_first.total_latency_samples(),
+        limit_first.total_latency_samples()
+    );
+
+    let mut a = loud_sine(sr, 9600);
+    let mut b = a.clone();
+    drc_first.process(&mut a);
+    limit_first.process(&mut b);
+
+    let difference = a.channels[0]
+        .iter()
+        .zip(&b.channels[0])
+        .map(|(x, y)| (x - y).abs())
+        .fold(0.0f32, f32::max);
+    assert!(difference > 1e-3, "max difference {}", difference);
+}
+
+#[test]
+fn test_biquad_stage_filters_each_channel() {
+    let sr = 48000;
+    let filter = design_peq(1000.0, 6.0, 1.0, sr);
+    let mut pipeline = Pipeline::new(sr).with_stage(BiquadStage::from(filter.clone()));
+    assert_eq!(pipeline.stage_names(), vec!["biquad"]);
+
+    let mono = loud_sine(sr, 1024);
+    let mut expected = mono.channels[0].clone();
+    let mut reference = filter;
+    reference.process_block(&mut expected[..512]);
+    reference.process_block(&mut expected[512..]);
+
+    // Two identical channels, fed in two blocks, come out identical and
+    // continuous across the block boundary
+    let mut output = Vec::new();
+    for half in mono.channels[0].chunks(512) {
+        let mut block = AudioBlock {
+            sample_rate: sr,
+            channels: vec![half.to_vec(), half.to_vec()],
+        };
+        pipeline.process(&mut block);
+        assert_eq!(block.channels[0], block.channels[1]);
+        output.extend_from_slice(&block.channels[0]);
+    }
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_binaural_stage_streams_to_stereo() {
+    let sr = 48000;
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, sr);
+    let mut response = vec![0.0; 16];
+    response[0] = 1.0;
+    response[4] = 0.5;
+    let position = HrtfPosition::new(0.0, 0.0, 1.0);
+    db.add_response(
+        &position,
+        HrtfImpulseResponse::new(response.clone(), response),
+    );
+    let renderer = BinauralRenderer::new(db, HeadphoneProfile::Flat);
+    let mut pipeline = Pipeline::new(sr).with_stage(BinauralStage::new(renderer, position.clone()));
+
+    let input = loud_sine(sr, 1000);
+    let mut output = Vec::new();
+    for chunk in input.channels[0].chunks(250) {
+        let mut block = AudioBlock {
+            sample_rate: sr,
+            channels: vec![chunk.to_vec(), chunk.to_vec(), chunk.to_vec()],
+        };
+        pipeline.process(&mut block);
+        assert_eq!(block.channels.len(), 2);
+        assert_eq!(block.frame_len(), 250);
+        output.extend_from_slice(&block.channels[0]);
+    }
+
+    // The echo at 4 samples carries across block boundaries
+    for (n, sample) in output.iter().enumerate() {
+        let echo = n.checked_sub(4).map_or(0.0, |m| 0.5 * input.channels[0][m]);
+        let expected = input.channels[0][n] + echo;
+        assert!((sample - expected).abs() < 1e-5, "sample {}", n);
+    }
+}
+
+fn default_binaural_stage(sr: u32, position: &HrtfPosition) -> BinauralStage {
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, sr);
+    db.load_default_kemar().unwrap();
+    BinauralStage::new(
+        BinauralRenderer::new(db, HeadphoneProfile::ClosedBack),
+        position.clone(),
+    )
+}
+
+#[test]
+fn test_binaural_stage_blockwise_matches_one_shot() {
+    let sr = 48000;
+    // Hard right: the left ear lags by the ITD
+    let position = HrtfPosition::new(90.0, 0.0, 1.0);
+    let mut one_shot = default_binaural_stage(sr, &position);
+    let mut streamed = default_binaural_stage(sr, &position);
+    assert_eq!(one_shot.latency_samples(), 5);
+
+    // Signal followed by enough silence to flush the 256-tap tail
+    let signal_len = 2000;
+    let mut signal = loud_sine(sr, signal_len + 300).channels.remove(0);
+    signal[signal_len..].fill(0.0);
+
+    let mut whole = AudioBlock {
+        sample_rate: sr,
+        channels: vec![signal.clone()],
+    };
+    one_shot.process(&mut whole);
+
+    // Blocks shorter than the impulse response
+    let mut ears = [Vec::new(), Vec::new()];
+    for chunk in signal.chunks(100) {
+        let mut block = AudioBlock {
+            sample_rate: sr,
+            channels: vec![chunk.to_vec()],
+        };
+        streamed.process(&mut block);
+        for (ear, out) in ears.iter_mut().zip(&block.channels) {
+            ear.extend_from_slice(out);
+        }
+    }
+    for (ear, expected) in ears.iter().zip(&whole.channels) {
+        assert_eq!(ear.len(), expected.len());
+        for (n, (a, b)) in ear.iter().zip(expected).enumerate() {
+            assert!((a - b).abs() < 1e-4, "sample {}: {} vs {}", n, a, b);
+        }
+    }
+
+    // And both agree with the renderer's own one-shot output
+    let mut db = HrtfDatabase::new(HrtfDataset::Kemar, sr);
+    db.load_default_kemar().unwrap();
+    let renderer = BinauralRenderer::new(db, HeadphoneProfile::ClosedBack);
+    let (left, right) = renderer.render(&signal[..signal_len], &position).unwrap();
+    for (rendered, streamed) in [left, right].iter().zip(&ears) {
+        for (n, (a, b)) in rendered.iter().zip(streamed).enumerate() {
+            assert!((a - b).abs() < 1e-4, "sample {}: {} vs {}", n, a, b);
+        }
+    }
+}
+
+#[test]
+fn test_set_sample_rate_rebuilds_biquad_and_binaural_stages() {
+    let design = BiquadDesign::Peak {
+        freq_hz: 1000.0,
+        gain_db: 6.0,
+        q: 1.0,
+    };
+    let position = HrtfPosition::new(90.0, 0.0, 1.0);
+    let mut pipeline = Pipeline::new(48000)
+        .with_stage(BiquadStage::designed(design, 48000))
+        .with_stage(default_binaural_stage(48000, &position));
+    assert_eq!(pipeline.total_latency_samples(), 5);
+
+    pipeline.set_sample_rate(44100).unwrap();
+    assert_eq!(pipeline.sample_rate(), 44100);
+    assert_eq!(pipeline.stage_names(), vec!["biquad", "binaural"]);
+    // 100 us of ITD rounds to 4 samples at the new rate
+    assert_eq!(pipeline.total_latency_samples(), 4);
+
+    let config = DspChainConfig::from_pipeline(&pipeline).unwrap();
+    assert_eq!(config.stages[0], StageConfig::Biquad { filter: design });
+    assert!(matches!(
+        &config.stages[1],
+        StageConfig::Binaural {
+            profile: HeadphoneProfile::ClosedBack,
+            azimuth,
+            ..
+        } if *azimuth == 90.0
+    ));
+    let toml = config.to_toml().unwrap();
+    assert_eq!(DspChainConfig::from_toml(&toml).unwrap(), config);
+
+    // Raw coefficients only hold at one rate
+    let mut raw = Pipeline::new(48000).with_stage(BiquadStage::from(design.design(48000)));
+    assert!(matches!(
+        raw.set_sample_rate(44100),
+        Err(DspConfigError::UnsupportedStage("biquad"))
+    ));
+}
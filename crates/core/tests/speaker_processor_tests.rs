@@ -0,0 +1,243 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use audio_ninja::calibration::{design_peq, CalibrationSolution};
+use audio_ninja::dsp::{fft, Complex32};
+use audio_ninja::render::{
+    ReferenceRenderer, RenderOptions, Renderer, SpeakerCorrection, SpeakerProcessor,
+};
+use audio_ninja::{AudioBlock, SpeakerLayout};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+fn impulse_block(frames: usize, at: usize) -> AudioBlock {
+    let mut channel = vec![0.0; frames];
+    channel[at] = 1.0;
+    AudioBlock {
+        sample_rate: 48000,
+        channels: vec![channel.clone(), channel],
+    }
+}
+
+fn peak_index(samples: &[f32]) -> usize {
+    samples
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+#[test]
+fn test_one_ms_delay_shifts_impulse() {
+    let layout = SpeakerLayout::from_preset("stereo").unwrap();
+    let mut processor = SpeakerProcessor::new();
+    processor.set_correction(
+        "FR",
+        SpeakerCorrection {
+            delay: Duration::from_millis(1),
+            trim_db: 0.0,
+            ..SpeakerCorrection::default()
+        },
+    );
+
+    let mut block = impulse_block(256, 10);
+    processor.process(&mut block, &layout);
+
+    // FL has no correction; FR moves by 48 samples at 48 kHz
+    assert_eq!(peak_index(&block.channels[0]), 10);
+    assert_eq!(peak_index(&block.channels[1]), 58);
+    assert!((block.channels[1][58] - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_delay_carries_across_blocks() {
+    let layout = SpeakerLayout::from_preset("stereo").unwrap();
+    let mut processor = SpeakerProcessor::new();
+    processor.set_correction(
+        "FL",
+        SpeakerCorrection {
+            delay: Duration::from_millis(1),
+            trim_db: 0.0,
+            ..SpeakerCorrection::default()
+        },
+    );
+
+    let mut first = impulse_block(32, 20);
+    processor.process(&mut first, &layout);
+    assert!(first.channels[0].iter().all(|&s| s == 0.0));
+
+    let mut second = AudioBlock::silence(2, 64, 48000);
+    processor.process(&mut second, &layout);
+    // 20 + 48 = 68, i.e. frame 36 of the second block
+    assert_eq!(peak_index(&second.channels[0]), 36);
+}
+
+#[test]
+fn test_fractional_delay_interpolates() {
+    let layout = SpeakerLayout::from_preset("stereo").unwrap();
+    let mut processor = SpeakerProcessor::new();
+    // Half a sample at 48 kHz
+    processor.set_correction(
+        "FL",
+        SpeakerCorrection {
+            delay: Duration::from_secs_f64(0.5 / 48000.0),
+            trim_db: -6.0,
+            ..SpeakerCorrection::default()
+        },
+    );
+
+    let mut block = impulse_block(16, 4);
+    processor.process(&mut block, &layout);
+
+    let gain = 10f32.powf(-6.0 / 20.0);
+    assert!((block.channels[0][4] - 0.5 * gain).abs() < 1e-3);
+    assert!((block.channels[0][5] - 0.5 * gain).abs() < 1e-3);
+}
+
+#[test]
+fn test_processor_from_calibration_solution() {
+    let layout = SpeakerLayout::from_preset("stereo").unwrap();
+    let solution = CalibrationSolution {
+        delays: vec![Duration::ZERO, Duration::from_millis(2)],
+        trims_db: vec![-3.0, 0.0],
+        peq: Vec::new(),
+        fir: None,
+    };
+
+    let processor = SpeakerProcessor::from_solution(&layout, &solution);
+    assert_eq!(processor.correction("FL").unwrap().trim_db, -3.0);
+    assert_eq!(
+        processor.correction("FR").unwrap().delay,
+        Duration::from_millis(2)
+    );
+}
+
+#[test]
+fn test_renderer_applies_speaker_processor() {
+    let layout = SpeakerLayout::from_preset("stereo").unwrap();
+    let mut processor = SpeakerProcessor::new();
+    processor.set_correction(
+        "FR",
+        SpeakerCorrection {
+            delay: Duration::from_millis(1),
+            trim_db: 0.0,
+            ..SpeakerCorrection::default()
+        },
+    );
+
+    let mut renderer = ReferenceRenderer::new(48000);
+    renderer.set_speaker_processor(processor);
+    let opts = RenderOptions {
+        target_layout: layout,
+        target_loudness: None,
+        ..RenderOptions::default()
+    };
+
+    let mut block = impulse_block(256, 10);
+    for channel in &mut block.channels {
+        channel[10] = 0.25;
+    }
+    let output = renderer.render(block, &opts);
+    // Both channels also carry the limiter lookahead
+    let lookahead = renderer.headroom_lookahead_samples();
+    assert_eq!(peak_index(&output.channels[0]), 10 + lookahead);
+    assert_eq!(peak_index(&output.channels[1]), 58 + lookahead);
+}
+
+/// Energy of `samples` between `low_hz` and `high_hz`
+fn band_energy(samples: &[f32], sample_rate: u32, low_hz: f32, high_hz: f32) -> f32 {
+    let mut spectrum: Vec<Complex32> = samples.iter().map(|&x| Complex32::new(x, 0.0)).collect();
+    fft(&mut spectrum);
+    let bin_hz = sample_rate as f32 / samples.len() as f32;
+    spectrum[..samples.len() / 2]
+        .iter()
+        .enumerate()
+        .filter(|(bin, _)| (low_hz..=high_hz).contains(&(*bin as f32 * bin_hz)))
+        .map(|(_, c)| c.norm() * c.norm())
+        .sum()
+}
+
+#[test]
+fn test_peq_cut_reduces_band_energy() {
+    let sr = 48000;
+    let frames = 16384;
+    let mut rng = StdRng::seed_from_u64(7);
+    let noise: Vec<f32> = (0..frames).map(|_| rng.random_range(-0.5..0.5)).collect();
+
+    let layout = SpeakerLayout::from_preset("stereo").unwrap();
+    let mut processor = SpeakerProcessor::new();
+    processor.set_correction(
+        "FL",
+        SpeakerCorrection {
+            peq: vec![design_peq(1000.0, -12.0, 2.0, sr)],
+            ..SpeakerCorrection::default()
+        },
+    );
+
+    let mut block = AudioBlock {
+        sample_rate: sr,
+        channels: vec![noise.clone(), noise.clone()],
+    };
+    processor.process(&mut block, &layout);
+
+    // Skip the EQ crossfade and filter settling before measuring
+    let settled = 4096;
+    let input = &noise[settled..settled + 8192];
+    let output = &block.channels[0][settled..settled + 8192];
+
+    let cut = band_energy(output, sr, 900.0, 1100.0) / band_energy(input, sr, 900.0, 1100.0);
+    assert!(
+        10.0 * cut.log10() < -8.0,
+        "band cut {} dB",
+        10.0 * cut.log10()
+    );
+
+    let far = band_energy(output, sr, 6000.0, 12000.0) / band_energy(input, sr, 6000.0, 12000.0);
+    assert!((10.0 * far.log10()).abs() < 0.5);
+
+    // FR has no EQ
+    assert_eq!(block.channels[1], noise);
+}
+
+#[test]
+fn test_peq_update_crossfades_without_clicks() {
+    let sr = 48000;
+    let layout = SpeakerLayout::from_preset("stereo").unwrap();
+    let mut processor = SpeakerProcessor::new();
+    processor.set_peq("FL", vec![design_peq(1000.0, -12.0, 2.0, sr)]);
+
+    let sine = |start: usize, frames: usize| -> Vec<f32> {
+        (start..start + frames)
+            .map(|n| 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / sr as f32).sin())
+            .collect()
+    };
+
+    // Remove the cut mid-stream, away from a zero crossing, where switching
+    // filters outright would jump from the cut level to the full sine
+    let mut output = Vec::new();
+    for (i, start) in (0..500 * 40).step_by(500).enumerate() {
+        if i == 10 {
+            processor.set_peq("FL", Vec::new());
+        }
+        let mut block = AudioBlock {
+            sample_rate: sr,
+            channels: vec![sine(start, 500), sine(start, 500)],
+        };
+        processor.process(&mut block, &layout);
+        output.extend_from_slice(&block.channels[0]);
+    }
+
+    // A 0.5 amplitude 1 kHz sine moves at most ~0.065 per sample
+    let max_step = output[4000..]
+        .windows(2)
+        .map(|w| (w[1] - w[0]).abs())
+        .fold(0.0f32, f32::max);
+    assert!(max_step < 0.08, "step {}", max_step);
+
+    // After the fade the signal is back at full level
+    let tail_peak = output[output.len() - 960..]
+        .iter()
+        .fold(0.0f32, |m, s| m.max(s.abs()));
+    assert!((tail_peak - 0.5).abs() < 0.01);
+}
@@ -1,10 +1,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use audio_ninja::fec::{ConcealmentStrategy, LossConcealer, LossStatistics};
 use audio_ninja::jitter::*;
 use audio_ninja::latency::*;
+use audio_ninja::rtcp::*;
 use audio_ninja::sync::*;
 use audio_ninja::transport::*;
-use audio_ninja::{AudioBlock, Position3, SpeakerDescriptor, SpeakerRole};
+use audio_ninja::{
+    AudioBlock, AudioBlockError, Position3, SpeakerDescriptor, SpeakerLayout, SpeakerRole,
+    SPEED_OF_SOUND,
+};
 use std::time::Duration;
 
 #[test]
@@ -36,6 +41,106 @@ fn test_rtp_packet_serialize_deserialize() {
     assert_eq!(deserialized.payload, payload);
 }
 
+#[test]
+fn test_rtp_payload_encryption_roundtrip() {
+    use audio_ninja::crypto::{PacketIndex, SessionKey, SessionSalt, TAG_BYTES};
+
+    let salt = SessionSalt([3; 16]);
+    let key = SessionKey::derive(b"482913", &salt, "master001", "speaker1");
+    assert_eq!(
+        key,
+        SessionKey::derive(b"482913", &salt, "master001", "speaker1")
+    );
+    assert_ne!(
+        key,
+        SessionKey::derive(b"482913", &salt, "master001", "speaker2")
+    );
+
+    let packet = RtpPacket::new(100, 4800, 0xABCD, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    let sealed = packet.encrypt(&key, PacketIndex::new().next(100)).unwrap();
+    assert_eq!(sealed.header, packet.header);
+    assert_eq!(sealed.payload.len(), packet.payload.len() + TAG_BYTES);
+    assert_ne!(&sealed.payload[..8], &packet.payload[..]);
+
+    // Survives the wire with the header still readable
+    let wire = RtpPacket::deserialize(&sealed.serialize()).unwrap();
+    assert_eq!(wire.header.sequence.0, 100);
+    let index = PacketIndex::new().estimate(wire.header.sequence.0);
+    assert_eq!(wire.decrypt(&key, index).unwrap().payload, packet.payload);
+}
+
+#[test]
+fn test_rtp_session_keys_differ_per_session() {
+    use audio_ninja::crypto::{SessionKey, SessionSalt};
+
+    // Same PIN and pair, but each session exchanges a fresh salt
+    let first = SessionKey::derive(b"482913", &SessionSalt::random(), "master001", "speaker1");
+    let second = SessionKey::derive(b"482913", &SessionSalt::random(), "master001", "speaker1");
+    assert_ne!(first, second);
+
+    let packet = RtpPacket::new(7, 960, 42, vec![0x11; 32]);
+    let sealed = packet.encrypt(&first, 7).unwrap();
+    assert_ne!(sealed.payload, packet.encrypt(&second, 7).unwrap().payload);
+    assert!(sealed.decrypt(&second, 7).is_err());
+}
+
+#[test]
+fn test_rtp_nonce_changes_after_sequence_wrap() {
+    use audio_ninja::crypto::{PacketIndex, SessionKey};
+
+    let key = SessionKey::from_bytes([9; 32]);
+    let mut sender = PacketIndex::new();
+    let mut receiver = PacketIndex::new();
+
+    // Walk the sequence number through a full wrap back to 5
+    let mut seq = 5u16;
+    let first = sender.next(seq);
+    for _ in 0..=u16::MAX {
+        seq = seq.wrapping_add(1);
+        sender.next(seq);
+    }
+    let wrapped = sender.estimate(5);
+    assert_eq!(first, 5);
+    assert_eq!(wrapped, (1 << 16) + 5);
+
+    // Identical header and payload, yet the ciphertext differs
+    let packet = RtpPacket::new(5, 960, 42, vec![0x22; 32]);
+    let before = packet.encrypt(&key, first).unwrap();
+    let after = packet.encrypt(&key, wrapped).unwrap();
+    assert_eq!(before.header, after.header);
+    assert_ne!(before.payload, after.payload);
+
+    // The receiver tracks the rollover from the sequence numbers alone
+    for step in [0u16, 20000, 40000, 60000] {
+        receiver.advance(receiver.estimate(5u16.wrapping_add(step)));
+    }
+    let index = receiver.estimate(5);
+    assert_eq!(index, wrapped);
+    assert_eq!(after.decrypt(&key, index).unwrap().payload, packet.payload);
+    assert!(after.decrypt(&key, first).is_err());
+}
+
+#[test]
+fn test_rtp_payload_tampering_fails_decryption() {
+    use audio_ninja::crypto::SessionKey;
+
+    let key = SessionKey::from_bytes([7; 32]);
+    let sealed = RtpPacket::new(1, 960, 42, vec![0x55; 64])
+        .encrypt(&key, 1)
+        .unwrap();
+
+    let mut flipped = sealed.clone();
+    flipped.payload[3] ^= 0x01;
+    assert!(flipped.decrypt(&key, 1).is_err());
+
+    let mut resequenced = sealed.clone();
+    resequenced.header.sequence = RtpSequence(2);
+    assert!(resequenced.decrypt(&key, 1).is_err());
+
+    assert!(sealed.decrypt(&SessionKey::from_bytes([8; 32]), 1).is_err());
+    assert!(sealed.decrypt(&key, 1).is_ok());
+}
+
 #[test]
 fn test_loopback_transport() {
     let mut transport = LoopbackTransport::with_ssrc(12345);
@@ -67,6 +172,331 @@ fn test_audio_block_to_rtp_roundtrip() {
     assert_eq!(decoded.channels.len(), block.channels.len());
 }
 
+#[test]
+fn test_rtp_chunks_reassemble_7_1_block() {
+    // 10 ms of 7.1 at 48 kHz is ~15 KB raw
+    let block = AudioBlock {
+        sample_rate: 48000,
+        channels: (0..8)
+            .map(|ch| {
+                (0..480)
+                    .map(|i| ((ch * 480 + i) as f32 * 0.01).sin())
+                    .collect()
+            })
+            .collect(),
+    };
+
+    let packets = audio_block_to_rtp_chunks(&block, DEFAULT_RTP_MTU, 65530, 4800, 7);
+    assert!(packets.len() > 10);
+    for (i, packet) in packets.iter().enumerate() {
+        assert!(packet.serialize().len() <= DEFAULT_RTP_MTU);
+        assert_eq!(packet.header.sequence.0, 65530u16.wrapping_add(i as u16));
+        assert_eq!(packet.header.timestamp.0, 4800);
+        assert_eq!(packet.header.marker, i == packets.len() - 1);
+    }
+
+    let mut reassembler = RtpReassembler::new();
+    let (last, rest) = packets.split_last().unwrap();
+    for packet in rest {
+        let wire = RtpPacket::deserialize(&packet.serialize()).unwrap();
+        assert!(reassembler.push(&wire).unwrap().is_none());
+    }
+    assert_eq!(reassembler.push(last).unwrap(), Some(block.clone()));
+
+    // Reordered chunks still rebuild the block
+    let mut reassembler = RtpReassembler::new();
+    let mut rebuilt = None;
+    for packet in packets.iter().rev() {
+        rebuilt = reassembler.push(packet).unwrap();
+    }
+    assert_eq!(rebuilt, Some(block));
+}
+
+#[test]
+fn test_rtp_chunks_count_fits_in_u16() {
+    // ~80 kB of samples at one payload byte per chunk would need more
+    // chunks than the u16 count can express
+    let block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![(0..20_000).map(|i| (i as f32 * 0.01).sin()).collect()],
+    };
+    let packets = audio_block_to_rtp_chunks(&block, 17, 0, 0, 7);
+
+    assert!(packets.len() <= u16::MAX as usize);
+    assert!(packets.last().unwrap().header.marker);
+
+    let mut reassembler = RtpReassembler::new();
+    let mut rebuilt = None;
+    for packet in &packets {
+        rebuilt = reassembler.push(packet).unwrap();
+    }
+    assert_eq!(rebuilt, Some(block));
+}
+
+#[test]
+fn test_rtp_chunks_small_block_is_one_packet() {
+    let block = AudioBlock::silence(2, 16, 48000);
+    let packets = audio_block_to_rtp_chunks(&block, DEFAULT_RTP_MTU, 1, 0, 7);
+
+    assert_eq!(packets.len(), 1);
+    assert!(packets[0].header.marker);
+    assert_eq!(
+        RtpReassembler::new().push(&packets[0]).unwrap(),
+        Some(block)
+    );
+}
+
+#[test]
+fn test_raw_codec_roundtrip_is_exact() {
+    let block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![0.25, -0.5, 0.75], vec![1.0, 0.0, -1.0]],
+    };
+    let mut encoder = PayloadEncoder::new(Codec::RawF32);
+    let mut decoder = PayloadDecoder::new(Codec::default());
+
+    let rtp = audio_block_to_rtp_with_codec(&block, 7, 0, 1, &mut encoder).unwrap();
+    assert_eq!(rtp.payload, audio_block_to_rtp(&block, 7, 0, 1).payload);
+    assert_eq!(
+        rtp_to_audio_block_with_codec(&rtp, &mut decoder).unwrap(),
+        block
+    );
+}
+
+#[cfg(not(feature = "opus-codec"))]
+#[test]
+fn test_opus_codec_needs_feature() {
+    let block = AudioBlock::silence(2, 960, 48000);
+    let mut encoder = PayloadEncoder::new(Codec::Opus { bitrate: 96_000 });
+    assert!(audio_block_to_rtp_with_codec(&block, 0, 0, 1, &mut encoder).is_err());
+}
+
+#[cfg(feature = "opus-codec")]
+#[test]
+fn test_opus_codec_roundtrip_is_close() {
+    use std::f32::consts::PI;
+
+    let codec = Codec::Opus { bitrate: 128_000 };
+    let mut encoder = PayloadEncoder::new(codec);
+    let mut decoder = PayloadDecoder::new(codec);
+    let frames = 960; // 20 ms at 48 kHz
+    let blocks = 15;
+
+    let tone = |n: usize, freq: f32| 0.5 * (2.0 * PI * freq * n as f32 / 48000.0).sin();
+    let mut sent = Vec::new();
+    let mut received = Vec::new();
+    for b in 0..blocks {
+        let block = AudioBlock {
+            sample_rate: 48000,
+            channels: vec![
+                (0..frames).map(|i| tone(b * frames + i, 440.0)).collect(),
+                (0..frames).map(|i| tone(b * frames + i, 1000.0)).collect(),
+            ],
+        };
+        let rtp = audio_block_to_rtp_with_codec(&block, b as u16, 0, 1, &mut encoder).unwrap();
+        assert!(rtp.payload.len() < audio_block_to_rtp(&block, 0, 0, 1).payload.len() / 4);
+
+        let decoded = rtp_to_audio_block_with_codec(&rtp, &mut decoder).unwrap();
+        assert_eq!(decoded.frame_len(), frames);
+        sent.push(block);
+        received.push(decoded);
+    }
+
+    // Opus delays the signal by its lookahead; compare at the best lag
+    // once the codec has settled
+    let concat = |blocks: &[AudioBlock], ch: usize| -> Vec<f32> {
+        blocks
+            .iter()
+            .flat_map(|b| b.channels[ch].iter().copied())
+            .collect()
+    };
+    for ch in 0..2 {
+        let input = concat(&sent, ch);
+        let output = concat(&received, ch);
+        let settled = 5 * frames..(blocks - 1) * frames;
+        let rms_error = (0..frames)
+            .map(|lag| {
+                let sum: f32 = settled
+                    .clone()
+                    .map(|i| (output[i + lag] - input[i]).powi(2))
+                    .sum();
+                (sum / settled.len() as f32).sqrt()
+            })
+            .fold(f32::INFINITY, f32::min);
+        assert!(rms_error < 0.05, "channel {ch} RMS error {rms_error}");
+    }
+
+    // Opus only codes whole 2.5 ms frames
+    let odd = AudioBlock::silence(2, 1000, 48000);
+    assert!(audio_block_to_rtp_with_codec(&odd, 0, 0, 1, &mut encoder).is_err());
+}
+
+#[cfg(feature = "opus-codec")]
+#[test]
+fn test_opus_codec_rejects_malformed_header() {
+    let mut decoder = PayloadDecoder::new(Codec::Opus { bitrate: 96_000 });
+    let header = |channels: u8, frames: u32| {
+        let mut payload = vec![channels];
+        payload.extend_from_slice(&48000u32.to_le_bytes());
+        payload.extend_from_slice(&frames.to_le_bytes());
+        payload
+    };
+
+    // Claims four billion frames with no data behind them
+    let huge = RtpPacket::new(0, 0, 1, header(255, 4_294_967_040));
+    assert!(rtp_to_audio_block_with_codec(&huge, &mut decoder).is_err());
+
+    // One 20 ms frame of two channels needs more than three bytes
+    let mut short = header(2, 960);
+    short.extend_from_slice(&[1, 0, 0xFC]);
+    let short = RtpPacket::new(1, 0, 1, short);
+    assert!(rtp_to_audio_block_with_codec(&short, &mut decoder).is_err());
+
+    let empty = RtpPacket::new(2, 0, 1, header(0, 960));
+    assert!(rtp_to_audio_block_with_codec(&empty, &mut decoder).is_err());
+}
+
+#[test]
+fn test_audio_block_interleaved_ordering() {
+    let block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![1.0, 2.0, 3.0], vec![-1.0, -2.0, -3.0]],
+    };
+
+    let interleaved = block.to_interleaved();
+    assert_eq!(interleaved, vec![1.0, -1.0, 2.0, -2.0, 3.0, -3.0]);
+
+    let restored = AudioBlock::from_interleaved(&interleaved, 2, 48000);
+    assert_eq!(restored, block);
+}
+
+#[test]
+fn test_audio_block_interleaved_ragged_channels() {
+    let block = AudioBlock {
+        sample_rate: 44100,
+        channels: vec![vec![0.5, 0.25], vec![0.75]],
+    };
+
+    assert_eq!(block.to_interleaved(), vec![0.5, 0.75, 0.25, 0.0]);
+
+    let partial = AudioBlock::from_interleaved(&[0.1, 0.2, 0.3], 2, 44100);
+    assert_eq!(partial.channels, vec![vec![0.1, 0.3], vec![0.2, 0.0]]);
+}
+
+#[test]
+fn test_audio_block_apply_gain() {
+    let mut block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![0.5, -0.5], vec![1.0, 0.0]],
+    };
+
+    block.apply_gain(-6.0206);
+    assert!((block.channels[0][0] - 0.25).abs() < 1e-4);
+    assert!((block.channels[0][1] + 0.25).abs() < 1e-4);
+    assert!((block.channels[1][0] - 0.5).abs() < 1e-4);
+    assert_eq!(block.channels[1][1], 0.0);
+}
+
+#[test]
+fn test_audio_block_mix() {
+    let mut block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![0.1, 0.2], vec![0.3, 0.4]],
+    };
+    let other = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![0.5, 0.5], vec![-0.3, 0.1]],
+    };
+
+    block.mix(&other).unwrap();
+    assert_eq!(block.channels, vec![vec![0.6, 0.7], vec![0.0, 0.5]]);
+}
+
+#[test]
+fn test_audio_block_mix_mismatch() {
+    let mut block = AudioBlock::silence(2, 4, 48000);
+
+    let rate = block.mix(&AudioBlock::silence(2, 4, 44100));
+    assert_eq!(
+        rate,
+        Err(AudioBlockError::SampleRateMismatch {
+            expected: 48000,
+            actual: 44100
+        })
+    );
+
+    let channels = block.mix(&AudioBlock::silence(1, 4, 48000));
+    assert_eq!(
+        channels,
+        Err(AudioBlockError::ChannelMismatch {
+            expected: 2,
+            actual: 1
+        })
+    );
+
+    let frames = block.mix(&AudioBlock::silence(2, 3, 48000));
+    assert_eq!(
+        frames,
+        Err(AudioBlockError::FrameMismatch {
+            expected: 4,
+            actual: 3
+        })
+    );
+}
+
+#[test]
+fn test_audio_block_append() {
+    let mut block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![1.0], vec![2.0]],
+    };
+    let tail = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![3.0, 4.0], vec![5.0, 6.0]],
+    };
+
+    block.append(&tail).unwrap();
+    assert_eq!(
+        block.channels,
+        vec![vec![1.0, 3.0, 4.0], vec![2.0, 5.0, 6.0]]
+    );
+    assert_eq!(block.frame_len(), 3);
+
+    assert!(matches!(
+        block.append(&AudioBlock::silence(2, 1, 96000)),
+        Err(AudioBlockError::SampleRateMismatch { .. })
+    ));
+    assert!(matches!(
+        block.append(&AudioBlock::silence(3, 1, 48000)),
+        Err(AudioBlockError::ChannelMismatch { .. })
+    ));
+    assert_eq!(block.frame_len(), 3);
+}
+
+#[test]
+fn test_audio_block_validate_ragged() {
+    assert!(AudioBlock::silence(2, 16, 48000).is_valid());
+    assert!(AudioBlock::from_interleaved(&[0.1, 0.2, 0.3], 2, 48000).is_valid());
+
+    let ragged = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![0.0; 4], vec![0.0; 4], vec![0.0; 3]],
+    };
+    assert!(!ragged.is_valid());
+    assert_eq!(
+        ragged.validate(),
+        Err(AudioBlockError::RaggedChannels {
+            channel: 2,
+            expected: 4,
+            actual: 3
+        })
+    );
+
+    // Decoders reject ragged payloads
+    let rtp = audio_block_to_rtp(&ragged, 1, 0, 0x1234);
+    assert!(rtp_to_audio_block(&rtp).is_err());
+}
+
 #[test]
 fn test_clock_timestamp_skew() {
     let ts1 = ClockTimestamp {
@@ -181,6 +611,31 @@ fn test_jitter_buffer_late_packet() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_jitter_buffer_conceals_underrun() {
+    let mut buffer = JitterBuffer::default();
+    let mut concealer = LossConcealer::new(ConcealmentStrategy::Repeat);
+
+    let block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![0.5; 64], vec![-0.5; 64]],
+    };
+    buffer
+        .push(audio_block_to_rtp(&block, 1, 0, 42))
+        .expect("push should work");
+
+    let decoded = buffer.pop_or_conceal(&mut concealer, 48000, 2, 64);
+    assert_eq!(decoded.channels, block.channels);
+    assert_eq!(buffer.stats().concealed, 0);
+
+    // Nothing buffered: pop fails, the concealer fills the gap
+    assert!(buffer.is_empty());
+    let concealed = buffer.pop_or_conceal(&mut concealer, 48000, 2, 64);
+    assert_eq!(concealed.channels.len(), 2);
+    assert_eq!(concealed.channels[0], vec![0.5; 64]);
+    assert_eq!(buffer.stats().concealed, 1);
+}
+
 #[test]
 fn test_latency_compensator() {
     let mut comp = LatencyCompensator::new();
@@ -213,6 +668,95 @@ fn test_latency_compensator() {
     assert_eq!(comp.max_latency().as_millis(), 28);
 }
 
+#[test]
+fn test_fractional_delay_matches_shifted_sine() {
+    let freq = 1000.0;
+    let sine = |t: f32| (2.0 * std::f32::consts::PI * freq * t / 48000.0).sin();
+    let mut block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![(0..256).map(|n| sine(n as f32)).collect()],
+    };
+
+    apply_fractional_delay(&mut block, 3.4);
+
+    // Away from the block edges the output is the sine sampled 3.4 samples late
+    for n in 8..250 {
+        let expected = sine(n as f32 - 3.4);
+        assert!(
+            (block.channels[0][n] - expected).abs() < 1e-3,
+            "sample {n}: {} vs {expected}",
+            block.channels[0][n]
+        );
+    }
+}
+
+#[test]
+fn test_fractional_delay_whole_samples_is_exact_shift() {
+    let mut block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![1.0, 2.0, 3.0, 4.0, 5.0]],
+    };
+
+    apply_fractional_delay(&mut block, 2.0);
+    assert_eq!(block.channels[0], vec![0.0, 0.0, 1.0, 2.0, 3.0]);
+
+    let mut comp = LatencyCompensator::new();
+    for (id, micros) in [("a", 100), ("b", 120)] {
+        comp.add_speaker(SpeakerLatency {
+            speaker_id: id.into(),
+            network_latency: Duration::from_micros(micros),
+            processing_latency: Duration::ZERO,
+            hardware_latency: Duration::ZERO,
+        });
+    }
+    // 20 us at 48 kHz is just under one sample
+    let delay = comp.delay_samples_for_speaker("a", 48000).unwrap();
+    assert!((delay - 0.96).abs() < 1e-4);
+}
+
+#[test]
+fn test_propagation_delays_align_nearer_speaker() {
+    let speaker = |id: &str, y: f32| SpeakerDescriptor {
+        id: id.into(),
+        role: SpeakerRole::Center,
+        position: Position3 { x: 0.0, y, z: 0.0 },
+        max_spl_db: 110.0,
+        latency: Duration::ZERO,
+    };
+    let layout = SpeakerLayout {
+        name: "depth".into(),
+        speakers: vec![speaker("near", 1.0), speaker("far", 4.43)],
+    };
+    let listener = Position3 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    let delays = layout.propagation_delays(listener, SPEED_OF_SOUND);
+    assert!((delays["near"].as_secs_f32() - 1.0 / 343.0).abs() < 1e-6);
+    assert!((delays["far"].as_secs_f32() - 4.43 / 343.0).abs() < 1e-6);
+
+    let mut comp = LatencyCompensator::new();
+    comp.add_speaker(SpeakerLatency {
+        speaker_id: "far".into(),
+        network_latency: Duration::from_millis(5),
+        processing_latency: Duration::ZERO,
+        hardware_latency: Duration::ZERO,
+    });
+    comp.set_propagation_delays(delays);
+
+    // 3.43 m further away is 10 ms of flight time to make up on the near one
+    assert_eq!(comp.speaker_count(), 2);
+    assert_eq!(comp.delay_for_speaker("far").unwrap(), Duration::ZERO);
+    let near = comp.delay_for_speaker("near").unwrap();
+    assert!(
+        (near.as_secs_f32() - 0.015).abs() < 1e-5,
+        "near delay {:?}",
+        near
+    );
+}
+
 #[test]
 fn test_speaker_buffer() {
     let mut buffer = SpeakerBuffer::new("sp1".into(), Duration::from_millis(50));
@@ -292,3 +836,80 @@ fn test_multi_speaker_sync() {
     let max_lat = sync.max_latency();
     assert_eq!(max_lat.as_millis(), 17);
 }
+
+#[test]
+fn test_rtcp_receiver_report_wire_roundtrip() {
+    let mut report = RtcpReceiverReport::new(0x1122_3344, "renderer@living-room");
+    report.blocks.push(RtcpReportBlock {
+        source: Ssrc(0xDEAD_BEEF),
+        fraction_lost: 26,
+        cumulative_lost: -3,
+        highest_sequence: 0x0001_0005,
+        jitter: 480,
+        last_sr: 0x1234_5678,
+        delay_since_last_sr: 65536,
+    });
+
+    let bytes = report.serialize();
+    // RR header + sender SSRC + one block, then SDES header + padded chunk
+    assert_eq!(bytes.len(), 8 + 24 + 4 + 28);
+    assert_eq!(bytes[0], 0x81);
+    assert_eq!(bytes[1], 201);
+    assert_eq!(u16::from_be_bytes([bytes[2], bytes[3]]), 7);
+    assert_eq!(&bytes[13..16], &[0xff, 0xff, 0xfd]);
+    assert_eq!(bytes[33], 202);
+    assert_eq!(bytes.len() % 4, 0);
+
+    let decoded = RtcpReceiverReport::deserialize(&bytes).expect("deserialize should work");
+    assert_eq!(decoded, report);
+
+    // Truncated packets are rejected rather than misread
+    assert!(RtcpReceiverReport::deserialize(&bytes[..20]).is_none());
+}
+
+#[test]
+fn test_rtcp_report_from_reordered_statistics() {
+    let mut stats = LossStatistics::new();
+    // 65535 arrives late, after the wrap, and stays counted as lost once
+    for seq in [65533u16, 65534, 0, 65535, 1, 2] {
+        stats.update(seq);
+    }
+
+    let report = RtcpReportBlock::from_statistics(42, &stats, &JitterBuffer::default());
+    assert_eq!(report.cumulative_lost, 1);
+    assert_eq!(report.fraction_lost, (1.0 / 6.0 * 256.0) as u8);
+    assert_eq!(report.highest_sequence, (1 << 16) | 2);
+}
+
+#[test]
+fn test_rtcp_report_from_statistics_over_loopback() {
+    let mut stats = LossStatistics::new();
+    let mut jitter = JitterBuffer::default();
+    for seq in [0u16, 1, 2, 5, 6, 7, 8, 9] {
+        stats.update(seq);
+        jitter
+            .push(RtpPacket::new(seq, seq as u32 * 960, 42, vec![]))
+            .expect("push should work");
+    }
+
+    let report = RtcpReceiverReport::from_statistics(7, "rx", 42, &stats, &jitter);
+    let block = &report.blocks[0];
+    assert_eq!(block.source, Ssrc(42));
+    assert_eq!(block.cumulative_lost, 2);
+    assert_eq!(block.fraction_lost, (2.0 / 10.0 * 256.0) as u8);
+    assert_eq!(block.highest_sequence, 9);
+
+    let mut transport = LoopbackTransport::with_ssrc(7);
+    transport
+        .send(RtpPacket::new(10, 9600, 42, vec![1]))
+        .expect("send should work");
+    transport.send_report(&report).expect("send should work");
+
+    assert!(transport.poll().expect("poll should work").is_some());
+    let received = transport
+        .poll_report()
+        .expect("poll should work")
+        .expect("report should be queued");
+    assert_eq!(received, report);
+    assert!(transport.poll_report().expect("poll should work").is_none());
+}
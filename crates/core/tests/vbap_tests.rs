@@ -180,6 +180,72 @@ fn test_vbap_3d_stereo_creation() {
     assert_eq!(vbap.speaker_count(), 2);
 }
 
+#[test]
+fn test_vbap_3d_fallback_overhead_on_stereo() {
+    let vbap = Vbap3D::new(create_stereo_layout());
+    let overhead = Vec3::from_spherical(0.0, 90.0, 1.0);
+
+    // No triplets at all: plain render stays silent
+    assert!(vbap.render(&overhead).iter().all(|&g| g == 0.0));
+
+    // Both speakers are equally near, so they share the source
+    let gains = vbap.render_with_fallback(&overhead);
+    assert!((gains[0] - gains[1]).abs() < 1e-6);
+    let power: f32 = gains.iter().map(|g| g * g).sum();
+    assert!((power - 1.0).abs() < 1e-4);
+
+    // Off to one side, the nearest speaker takes it
+    let gains = vbap.render_with_fallback(&Vec3::from_spherical(60.0, 70.0, 1.0));
+    assert_eq!(gains, vec![1.0, 0.0]);
+}
+
+#[test]
+fn test_vbap_3d_fallback_uses_nearest_triplet() {
+    // Every 5.1 speaker sits at or below ear level
+    let vbap = Vbap3D::new(create_5_1_layout());
+    let overhead = Vec3::from_spherical(0.0, 90.0, 1.0);
+    assert!(vbap.render(&overhead).iter().all(|&g| g == 0.0));
+
+    let gains = vbap.render_with_fallback(&overhead);
+    // Panned within a single triplet
+    let active = gains.iter().filter(|&&g| g > 0.0).count();
+    assert!((1..=3).contains(&active));
+    let power: f32 = gains.iter().map(|g| g * g).sum();
+    assert!((power - 1.0).abs() < 1e-4);
+
+    // Covered sources render exactly as before
+    let front = Vec3::from_spherical(10.0, 0.0, 1.0);
+    assert_eq!(vbap.render_with_fallback(&front), vbap.render(&front));
+}
+
+#[test]
+fn test_vbap_3d_spread_widens_image() {
+    let vbap = Vbap3D::new(create_7_1_4_layout());
+    let front = Vec3::from_spherical(0.0, 0.0, 1.0);
+    let active = |gains: &[f32]| gains.iter().filter(|&&g| g > 1e-6).count();
+
+    // No spread is the point-source pan
+    assert_eq!(
+        vbap.render_spread(&front, 0.0),
+        vbap.render_with_fallback(&front)
+    );
+
+    let counts: Vec<usize> = [0.0, 0.3, 0.6, 1.0]
+        .iter()
+        .map(|&spread| active(&vbap.render_spread(&front, spread)))
+        .collect();
+    for pair in counts.windows(2) {
+        assert!(pair[1] >= pair[0], "{:?}", counts);
+    }
+    assert!(counts[3] > counts[0] + 2, "{:?}", counts);
+
+    for spread in [0.3, 1.0] {
+        let gains = vbap.render_spread(&front, spread);
+        let power: f32 = gains.iter().map(|g| g * g).sum();
+        assert!((power - 1.0).abs() < 1e-4);
+    }
+}
+
 #[test]
 fn test_vbap_3d_render_front() {
     let speakers = create_5_1_layout();
@@ -280,3 +346,114 @@ fn test_vbap_energy_preservation() {
         assert!(energy <= 1.5); // Reasonable upper bound
     }
 }
+
+#[test]
+fn test_render_buffer_pans_tone_front_to_left() {
+    let vbap = Vbap3D::new(create_7_1_4_layout());
+    // Front left, front right and center, then side left
+    let front = [0, 1, 2];
+    let side_left = 4;
+
+    let frames = 48000;
+    let tone: Vec<f32> = (0..frames)
+        .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / 48000.0).sin())
+        .collect();
+    let trajectory: Vec<Vec3> = (0..=9)
+        .map(|step| Vec3::from_spherical(step as f32 * 10.0, 0.0, 1.0))
+        .collect();
+
+    let output = vbap.render_buffer(&tone, &trajectory);
+    assert_eq!(output.len(), vbap.speaker_count());
+    assert!(output.iter().all(|ch| ch.len() == frames));
+
+    let window_energy = |channel: &[f32]| -> Vec<f32> {
+        channel
+            .chunks(4800)
+            .map(|w| w.iter().map(|s| s * s).sum())
+            .collect()
+    };
+    let front_energy: Vec<f32> = (0..10)
+        .map(|w| front.iter().map(|&ch| window_energy(&output[ch])[w]).sum())
+        .collect();
+    let left_energy = window_energy(&output[side_left]);
+
+    // Energy starts at the front and ends at the side
+    assert!(front_energy[0] > 10.0 * left_energy[0]);
+    assert!(left_energy[9] > 10.0 * front_energy[9]);
+
+    // ...moving across steadily rather than jumping
+    for w in left_energy.windows(2) {
+        assert!(w[1] >= w[0] * 0.95, "side energy fell: {:?}", left_energy);
+    }
+
+    // Total power stays constant while panning
+    for w in 0..10 {
+        let total: f32 = output.iter().map(|ch| window_energy(ch)[w]).sum();
+        let expected: f32 = tone[w * 4800..(w + 1) * 4800].iter().map(|s| s * s).sum();
+        assert!((total / expected - 1.0).abs() < 0.05);
+    }
+}
+
+#[test]
+fn test_render_buffer_gains_change_smoothly() {
+    let vbap = Vbap3D::new(create_7_1_4_layout());
+
+    // A constant signal exposes the gain curve directly. One trajectory
+    // point per sample with a hard jump from front to side would switch
+    // gains within a single sample without smoothing
+    let dc = vec![1.0; 4800];
+    let trajectory: Vec<Vec3> = (0..4800)
+        .map(|i| Vec3::from_spherical(if i < 2400 { 0.0 } else { 90.0 }, 0.0, 1.0))
+        .collect();
+    let output = vbap.render_buffer(&dc, &trajectory);
+
+    for channel in &output {
+        let max_step = channel
+            .windows(2)
+            .map(|w| (w[1] - w[0]).abs())
+            .fold(0.0f32, f32::max);
+        assert!(max_step < 0.02, "gain step {}", max_step);
+    }
+}
+
+#[test]
+fn test_render_buffer_empty_trajectory_is_silent() {
+    let vbap = Vbap3D::new(create_5_1_layout());
+    let output = vbap.render_buffer(&[0.5; 64], &[]);
+    assert_eq!(output.len(), 6);
+    assert!(output.iter().flatten().all(|&s| s == 0.0));
+}
+
+#[test]
+fn test_vbap_2d_pans_between_adjacent_speakers() {
+    // Horizontal 5.0 ring, no height
+    let vbap = Vbap2D::new(vec![
+        Speaker3D::new(0, 30.0, 0.0),
+        Speaker3D::new(1, -30.0, 0.0),
+        Speaker3D::new(2, 0.0, 0.0),
+        Speaker3D::new(3, 110.0, 0.0),
+        Speaker3D::new(4, -110.0, 0.0),
+    ]);
+    assert_eq!(vbap.speaker_count(), 5);
+
+    let on_center = vbap.render(&Vec3::from_spherical(0.0, 0.0, 1.0));
+    assert!((on_center[2] - 1.0).abs() < 1e-5);
+
+    let half_left = vbap.render(&Vec3::from_spherical(15.0, 0.0, 1.0));
+    assert!((half_left[0] - half_left[2]).abs() < 1e-5);
+    assert!((half_left[0].powi(2) + half_left[2].powi(2) - 1.0).abs() < 1e-5);
+    assert_eq!(half_left[1] + half_left[3] + half_left[4], 0.0);
+
+    let behind = vbap.render(&Vec3::from_spherical(180.0, 0.0, 1.0));
+    assert!((behind[3] - behind[4]).abs() < 1e-5);
+    assert!(behind[3] > 0.5);
+}
+
+#[test]
+fn test_vbap_2d_wide_gap_snaps_to_nearer_speaker() {
+    let vbap = Vbap2D::new(create_stereo_layout());
+
+    // Stereo leaves a 300° gap behind the listener
+    let gains = vbap.render(&Vec3::from_spherical(150.0, 0.0, 1.0));
+    assert_eq!(gains, vec![1.0, 0.0]);
+}
@@ -3,24 +3,32 @@
 //! REST API handlers
 
 use axum::{
-    extract::{Path, State},
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, State,
+    },
     http::StatusCode,
-    response::Json,
+    response::{IntoResponse, Json, Response},
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    engine::{SpeakerInfo, SpeakerStats},
+    engine::{MasterVolume, SpeakerInfo, SpeakerPosition, SpeakerStats, SpeakerUpdate},
     AppState,
 };
-use audio_ninja::{Position3, SpeakerDescriptor, SpeakerLayout, SpeakerRole};
+use audio_ninja::{
+    calibration::{Calibrator, MeasurementConfig, ReferenceCalibrator, SweepType},
+    mapping::available_layouts,
+    Position3, SpeakerDescriptor, SpeakerLayout, SpeakerRole,
+};
 
 #[derive(Serialize)]
 pub struct StatusResponse {
     status: String,
     version: String,
     uptime_secs: u64,
+    volume: MasterVolume,
 }
 
 #[derive(Serialize)]
@@ -33,7 +41,30 @@ pub struct InfoResponse {
 #[derive(Deserialize)]
 pub struct LayoutRequest {
     preset: Option<String>,
-    speakers: Option<Vec<SpeakerPositionRequest>>,
+    /// Layout name when uploading full speaker descriptors
+    name: Option<String>,
+    speakers: Option<LayoutSpeakers>,
+}
+
+/// Speakers of a custom layout: either full descriptors (a complete
+/// `SpeakerLayout` body) or spherical positions keyed by speaker id
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum LayoutSpeakers {
+    Descriptors(Vec<SpeakerDescriptor>),
+    Positions(Vec<SpeakerPositionRequest>),
+}
+
+#[derive(Deserialize)]
+pub struct CreateSpeakerRequest {
+    /// Speaker id; generated when omitted
+    id: Option<Uuid>,
+    name: String,
+    address: String,
+    role: Option<SpeakerRole>,
+    position: Option<SpeakerPosition>,
+    #[serde(default)]
+    trim_db: f32,
 }
 
 #[derive(Deserialize)]
@@ -44,20 +75,97 @@ pub struct SpeakerPositionRequest {
     distance: f32,
 }
 
-#[derive(Serialize)]
-pub struct ErrorResponse {
-    error: String,
+/// Failure returned by a handler, rendered as
+/// `{ "error": { "code": "...", "message": "..." } }` with its status code
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// 400 Bad Request
+    pub fn bad_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, code, message)
+    }
+
+    /// 404 Not Found
+    pub fn not_found(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, code, message)
+    }
+
+    /// 409 Conflict
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, code, message)
+    }
+
+    fn speaker_not_found(id: &Uuid) -> Self {
+        Self::not_found("speaker_not_found", format!("speaker {id} not found"))
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({
+            "error": {
+                "code": self.code,
+                "message": self.message,
+            }
+        });
+        (self.status, Json(body)).into_response()
+    }
 }
 
 /// GET /api/v1/status
 pub async fn status(State(state): State<AppState>) -> Json<StatusResponse> {
+    let volume = state.engine.read().await.volume;
     Json(StatusResponse {
         status: "running".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_secs: state.started_at.elapsed().as_secs(),
+        volume,
     })
 }
 
+#[derive(Deserialize)]
+pub struct VolumeRequest {
+    gain_db: Option<f32>,
+    mute: Option<bool>,
+}
+
+/// POST /api/v1/volume
+pub async fn set_volume(
+    State(state): State<AppState>,
+    Json(req): Json<VolumeRequest>,
+) -> Result<Json<MasterVolume>, ApiError> {
+    if req.gain_db.is_none() && req.mute.is_none() {
+        return Err(ApiError::bad_request(
+            "invalid_request",
+            "gain_db or mute is required",
+        ));
+    }
+
+    let mut engine = state.engine.write().await;
+    if let Some(gain_db) = req.gain_db {
+        engine
+            .set_master_gain_db(gain_db)
+            .map_err(|e| ApiError::bad_request("invalid_gain", e))?;
+    }
+    if let Some(mute) = req.mute {
+        engine.set_mute(mute);
+    }
+    Ok(Json(engine.volume))
+}
+
 /// GET /api/v1/info
 pub async fn info() -> Json<InfoResponse> {
     Json(InfoResponse {
@@ -80,6 +188,67 @@ pub async fn list_speakers(State(state): State<AppState>) -> Json<Vec<SpeakerInf
     Json(speakers)
 }
 
+/// POST /api/v1/speakers - Manually add a speaker with a known address
+pub async fn create_speaker(
+    State(state): State<AppState>,
+    Json(req): Json<CreateSpeakerRequest>,
+) -> Result<(StatusCode, Json<SpeakerInfo>), ApiError> {
+    if req.name.trim().is_empty() || req.address.trim().is_empty() {
+        return Err(ApiError::bad_request(
+            "invalid_request",
+            "name and address must not be empty",
+        ));
+    }
+    if !req.trim_db.is_finite() {
+        return Err(ApiError::bad_request(
+            "invalid_trim",
+            "trim_db must be finite",
+        ));
+    }
+
+    let mut engine = state.engine.write().await;
+    let id = req.id.unwrap_or_else(Uuid::new_v4);
+    if engine.speakers.contains_key(&id) {
+        return Err(ApiError::conflict(
+            "speaker_exists",
+            format!("speaker {id} already exists"),
+        ));
+    }
+
+    let speaker = SpeakerInfo {
+        id,
+        name: req.name,
+        address: req.address,
+        position: req.position,
+        // Not seen on the network yet
+        online: false,
+        role: req.role,
+        trim_db: req.trim_db,
+    };
+    engine.add_speaker(speaker.clone());
+    Ok((StatusCode::CREATED, Json(speaker)))
+}
+
+/// PATCH /api/v1/speakers/:id - Update role, position or trim
+pub async fn update_speaker(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(update): Json<SpeakerUpdate>,
+) -> Result<Json<SpeakerInfo>, ApiError> {
+    if update.trim_db.is_some_and(|trim| !trim.is_finite()) {
+        return Err(ApiError::bad_request(
+            "invalid_trim",
+            "trim_db must be finite",
+        ));
+    }
+
+    let mut engine = state.engine.write().await;
+    engine
+        .update_speaker(&id, update)
+        .map(Json)
+        .ok_or_else(|| ApiError::speaker_not_found(&id))
+}
+
 /// POST /api/v1/speakers/discover
 pub async fn discover_speakers(State(state): State<AppState>) -> StatusCode {
     let mut engine = state.engine.write().await;
@@ -91,48 +260,74 @@ pub async fn discover_speakers(State(state): State<AppState>) -> StatusCode {
 pub async fn get_speaker(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<SpeakerInfo>, StatusCode> {
+) -> Result<Json<SpeakerInfo>, ApiError> {
     let engine = state.engine.read().await;
     engine
         .speakers
         .get(&id)
         .cloned()
         .map(Json)
-        .ok_or(StatusCode::NOT_FOUND)
+        .ok_or_else(|| ApiError::speaker_not_found(&id))
 }
 
 /// DELETE /api/v1/speakers/:id
-pub async fn remove_speaker(State(state): State<AppState>, Path(id): Path<Uuid>) -> StatusCode {
+pub async fn remove_speaker(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, ApiError> {
     let mut engine = state.engine.write().await;
-    if engine.remove_speaker(&id).is_some() {
-        StatusCode::NO_CONTENT
-    } else {
-        StatusCode::NOT_FOUND
-    }
+    engine
+        .remove_speaker(&id)
+        .map(|_| StatusCode::NO_CONTENT)
+        .ok_or_else(|| ApiError::speaker_not_found(&id))
 }
 
 /// GET /api/v1/layout
-pub async fn get_layout(State(state): State<AppState>) -> Result<Json<SpeakerLayout>, StatusCode> {
+pub async fn get_layout(State(state): State<AppState>) -> Result<Json<SpeakerLayout>, ApiError> {
     let engine = state.engine.read().await;
-    engine.layout.clone().map(Json).ok_or(StatusCode::NOT_FOUND)
+    engine
+        .layout
+        .clone()
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("no_layout", "no layout configured"))
+}
+
+/// GET /api/v1/layout/presets
+pub async fn list_layout_presets() -> Json<Vec<&'static str>> {
+    Json(available_layouts())
 }
 
 /// POST /api/v1/layout
 pub async fn set_layout(
     State(state): State<AppState>,
     Json(request): Json<LayoutRequest>,
-) -> StatusCode {
+) -> Result<StatusCode, ApiError> {
     let mut engine = state.engine.write().await;
 
     // Create layout from preset or custom positions
     let layout = if let Some(preset) = request.preset {
         match SpeakerLayout::from_preset(&preset) {
             Some(layout) => layout,
-            None => return StatusCode::BAD_REQUEST,
+            None => {
+                return Err(ApiError::bad_request(
+                    "invalid_preset",
+                    format!("unknown layout preset '{preset}'"),
+                ))
+            }
         }
-    } else if let Some(speakers) = request.speakers {
+    } else if let Some(LayoutSpeakers::Descriptors(speakers)) = request.speakers {
+        let layout = SpeakerLayout {
+            name: request.name.unwrap_or_else(|| "custom".to_string()),
+            speakers,
+        };
+        validate_layout(&layout).map_err(|e| ApiError::bad_request("invalid_layout", e))?;
+        layout
+    } else if let Some(LayoutSpeakers::Positions(speakers)) = request.speakers {
         if speakers.is_empty() {
-            return StatusCode::BAD_REQUEST;
+            return Err(ApiError::bad_request(
+                "invalid_layout",
+                "layout has no speakers",
+            ));
         }
 
         let descriptors: Vec<SpeakerDescriptor> = speakers
@@ -160,7 +355,10 @@ pub async fn set_layout(
             .collect();
 
         if descriptors.is_empty() {
-            return StatusCode::BAD_REQUEST;
+            return Err(ApiError::bad_request(
+                "invalid_layout",
+                "no speaker has a finite, positive distance",
+            ));
         }
 
         SpeakerLayout {
@@ -168,18 +366,45 @@ pub async fn set_layout(
             speakers: descriptors,
         }
     } else {
-        return StatusCode::BAD_REQUEST;
+        return Err(ApiError::bad_request(
+            "invalid_request",
+            "preset or speakers is required",
+        ));
     };
 
     engine.set_layout(layout);
-    StatusCode::OK
+    Ok(StatusCode::OK)
+}
+
+/// Check a custom layout is usable: at least one speaker, unique ids and
+/// finite positions
+fn validate_layout(layout: &SpeakerLayout) -> Result<(), String> {
+    if layout.speakers.is_empty() {
+        return Err("layout has no speakers".to_string());
+    }
+
+    let mut ids = std::collections::HashSet::new();
+    for speaker in &layout.speakers {
+        if !ids.insert(speaker.id.as_str()) {
+            return Err(format!("duplicate speaker id {}", speaker.id));
+        }
+
+        let Position3 { x, y, z } = speaker.position;
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(format!("speaker {} has a non-finite position", speaker.id));
+        }
+    }
+
+    Ok(())
 }
 
 /// POST /api/v1/transport/play
-pub async fn transport_play(State(state): State<AppState>) -> StatusCode {
+pub async fn transport_play(State(state): State<AppState>) -> Result<StatusCode, ApiError> {
     let mut engine = state.engine.write().await;
-    engine.play();
-    StatusCode::OK
+    engine
+        .play()
+        .map(|()| StatusCode::OK)
+        .map_err(|e| ApiError::conflict("cannot_play", e))
 }
 
 /// POST /api/v1/transport/pause
@@ -198,19 +423,80 @@ pub async fn transport_stop(State(state): State<AppState>) -> StatusCode {
 
 /// GET /api/v1/transport/status
 pub async fn transport_status(State(state): State<AppState>) -> Json<serde_json::Value> {
+    use crate::engine::TransportMode;
+
     let engine = state.engine.read().await;
+    let file = match engine.transport_mode {
+        TransportMode::LiveStream => None,
+        _ => engine.playback.file_path.as_ref(),
+    };
     Json(serde_json::json!({
         "state": format!("{:?}", engine.transport_state),
+        "position_secs": engine.playback_position_secs(),
+        "duration_secs": engine.playback_duration_secs(),
+        "file": file.map(|p| p.to_string_lossy()),
     }))
 }
 
+/// Length of the measurement sweep played through each speaker
+const CALIBRATION_SWEEP_DURATION: std::time::Duration = std::time::Duration::from_millis(100);
+
 /// POST /api/v1/calibration/start
 pub async fn calibration_start(State(state): State<AppState>) -> StatusCode {
-    let mut engine = state.engine.write().await;
-    engine.start_calibration();
+    let (run_id, targets) = {
+        let mut engine = state.engine.write().await;
+        (engine.start_calibration(), engine.calibration_targets())
+    };
+    tokio::spawn(run_calibration(state, run_id, targets));
     StatusCode::ACCEPTED
 }
 
+/// Sweep each speaker in turn, reporting progress as measurements complete
+async fn run_calibration(state: AppState, run_id: u64, targets: Vec<String>) {
+    let config = MeasurementConfig {
+        sweep_duration: CALIBRATION_SWEEP_DURATION,
+        sample_rate: 48000,
+        sweep_type: SweepType::LogSweep {
+            start_hz: 20,
+            end_hz: 20000,
+        },
+    };
+    let mut calibrator = ReferenceCalibrator;
+
+    for speaker in &targets {
+        if !state.engine.read().await.calibration_active(run_id) {
+            return;
+        }
+        if let Err(e) = calibrator.measure(&config) {
+            tracing::warn!("Calibration sweep for {} failed: {}", speaker, e);
+            state.engine.write().await.cancel_calibration();
+            return;
+        }
+        // Give the sweep time to play out before moving on
+        tokio::time::sleep(config.sweep_duration).await;
+
+        let mut engine = state.engine.write().await;
+        if !engine.record_calibration_measurement(run_id, speaker, targets.len()) {
+            return;
+        }
+    }
+
+    state.engine.write().await.finish_calibration(run_id);
+}
+
+/// POST /api/v1/calibration/cancel
+pub async fn calibration_cancel(State(state): State<AppState>) -> Result<StatusCode, ApiError> {
+    let mut engine = state.engine.write().await;
+    if engine.cancel_calibration() {
+        Ok(StatusCode::OK)
+    } else {
+        Err(ApiError::conflict(
+            "no_calibration",
+            "no calibration is running",
+        ))
+    }
+}
+
 /// GET /api/v1/calibration/status
 pub async fn calibration_status(State(state): State<AppState>) -> Json<serde_json::Value> {
     let engine = state.engine.read().await;
@@ -222,12 +508,31 @@ pub async fn calibration_status(State(state): State<AppState>) -> Json<serde_jso
 }
 
 /// POST /api/v1/calibration/apply
-pub async fn calibration_apply(State(state): State<AppState>) -> StatusCode {
+pub async fn calibration_apply(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, ApiError> {
     let mut engine = state.engine.write().await;
-    match engine.apply_calibration() {
-        Ok(_) => StatusCode::OK,
-        Err(_) => StatusCode::BAD_REQUEST,
-    }
+    let applied = engine
+        .apply_calibration()
+        .map_err(|e| ApiError::conflict("no_calibration", e))?;
+
+    let channels: Vec<serde_json::Value> = applied
+        .iter()
+        .enumerate()
+        .map(|(channel, dsp)| {
+            serde_json::json!({
+                "channel": channel,
+                "delay_ms": dsp.delay.as_secs_f64() * 1000.0,
+                "trim_db": dsp.trim_db,
+                "peq_filters": dsp.peq.len(),
+            })
+        })
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "applied": true,
+        "channels": channels,
+    })))
 }
 
 /// GET /api/v1/stats
@@ -241,6 +546,10 @@ pub async fn stats(State(state): State<AppState>) -> Json<serde_json::Value> {
         "online_speakers": online_speakers,
         "transport_state": format!("{:?}", engine.transport_state),
         "has_layout": engine.layout.is_some(),
+        "render_load_percent": engine.metrics.render_load_percent,
+        "buffer_underruns": engine.metrics.buffer_underruns,
+        "current_latency_ms": engine.current_latency_ms(),
+        "sample_rate": engine.sample_rate(),
     }))
 }
 
@@ -250,7 +559,7 @@ pub async fn stats_network(State(state): State<AppState>) -> Json<serde_json::Va
     let active_speakers = engine.speakers.values().filter(|s| s.online).count();
     // Estimate bandwidth based on active speakers and sample rate
     let base_kbps = if matches!(engine.transport_state, crate::engine::TransportState::Playing) {
-        (engine.playback.sample_rate as f64 * 2.0 * 16.0 / 8.0 / 1000.0) * active_speakers.max(1) as f64
+        (engine.sample_rate() as f64 * 2.0 * 16.0 / 8.0 / 1000.0) * active_speakers.max(1) as f64
     } else {
         0.0
     };
@@ -450,18 +759,42 @@ pub async fn stats_audio_levels(State(state): State<AppState>) -> Json<serde_jso
     }))
 }
 
+/// Push interval for live meter frames (~20 Hz)
+const METER_STREAM_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// GET /api/v1/stream/meters - WebSocket pushing output meter frames as JSON
+pub async fn stream_meters(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(move |socket| push_meter_frames(socket, state))
+}
+
+async fn push_meter_frames(mut socket: WebSocket, state: AppState) {
+    let mut ticker = tokio::time::interval(METER_STREAM_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+        let frame = state.engine.read().await.meter_frame();
+        let Ok(text) = serde_json::to_string(&frame) else {
+            break;
+        };
+        if socket.send(Message::Text(text.into())).await.is_err() {
+            // Client went away
+            break;
+        }
+    }
+}
+
 /// GET /api/v1/speakers/:id/stats
 pub async fn speaker_stats(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<SpeakerStats>, StatusCode> {
+) -> Result<Json<SpeakerStats>, ApiError> {
     let engine = state.engine.read().await;
     engine
         .speaker_stats
         .get(&id)
         .cloned()
         .map(Json)
-        .ok_or(StatusCode::NOT_FOUND)
+        .ok_or_else(|| ApiError::speaker_not_found(&id))
 }
 // ===== Audio Input/Output Endpoints =====
 
@@ -504,9 +837,13 @@ pub struct SetTransportModeRequest {
     pub mode: String, // "file", "stream", or "mixed"
 }
 
+/// Seek target: either `position_secs` (preferred) or a raw sample `position`
 #[derive(Deserialize)]
 pub struct SeekRequest {
-    pub position: u64,
+    #[serde(default)]
+    pub position_secs: Option<f64>,
+    #[serde(default)]
+    pub position: Option<u64>,
 }
 
 /// GET /api/v1/input/devices - List all input devices
@@ -567,7 +904,7 @@ pub async fn list_output_devices(State(state): State<AppState>) -> Json<Vec<Outp
 pub async fn select_input_source(
     State(state): State<AppState>,
     Json(req): Json<SelectInputRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let mut engine = state.engine.write().await;
     match engine.select_input_source(&req.source_id) {
         Ok(source) => Ok(Json(serde_json::json!({
@@ -575,7 +912,7 @@ pub async fn select_input_source(
             "source": source.source_type(),
             "device": source.device_name(),
         }))),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(ApiError::not_found("input_not_found", e)),
     }
 }
 
@@ -583,7 +920,7 @@ pub async fn select_input_source(
 pub async fn select_output_device(
     State(state): State<AppState>,
     Json(req): Json<SelectOutputRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let mut engine = state.engine.write().await;
     match engine.select_output_device(&req.device_id) {
         Ok(device) => Ok(Json(serde_json::json!({
@@ -592,7 +929,7 @@ pub async fn select_output_device(
             "device_type": device.device_type.to_string(),
             "channels": device.max_channels,
         }))),
-        Err(_) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(ApiError::not_found("output_not_found", e)),
     }
 }
 
@@ -600,7 +937,7 @@ pub async fn select_output_device(
 pub async fn load_audio_file(
     State(state): State<AppState>,
     Json(req): Json<LoadFileRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let mut engine = state.engine.write().await;
     match engine.load_audio_file(&req.file_path) {
         Ok(_) => Ok(Json(serde_json::json!({
@@ -610,7 +947,7 @@ pub async fn load_audio_file(
         }))),
         Err(e) => {
             eprintln!("Failed to load file: {}", e);
-            Err(StatusCode::BAD_REQUEST)
+            Err(ApiError::bad_request("invalid_file", e))
         }
     }
 }
@@ -619,15 +956,13 @@ pub async fn load_audio_file(
 pub async fn set_transport_mode(
     State(state): State<AppState>,
     Json(req): Json<SetTransportModeRequest>,
-) -> Result<Json<serde_json::Value>, StatusCode> {
+) -> Result<Json<serde_json::Value>, ApiError> {
     use crate::engine::TransportMode;
 
-    let mode = match req.mode.to_lowercase().as_str() {
-        "file" => TransportMode::FilePlayback,
-        "stream" => TransportMode::LiveStream,
-        "mixed" => TransportMode::Mixed,
-        _ => return Err(StatusCode::BAD_REQUEST),
-    };
+    let mode: TransportMode = req
+        .mode
+        .parse()
+        .map_err(|e| ApiError::bad_request("invalid_mode", e))?;
 
     let mut engine = state.engine.write().await;
     engine.set_transport_mode(mode.clone());
@@ -680,13 +1015,69 @@ pub async fn output_status(State(state): State<AppState>) -> Json<serde_json::Va
 pub async fn transport_seek(
     State(state): State<AppState>,
     Json(req): Json<SeekRequest>,
-) -> StatusCode {
+) -> Result<Json<serde_json::Value>, ApiError> {
     let mut engine = state.engine.write().await;
     if engine.playback.file_path.is_none() {
-        return StatusCode::BAD_REQUEST;
+        return Err(ApiError::bad_request("no_file", "no file is loaded"));
     }
-    engine.seek(req.position);
-    StatusCode::OK
+
+    match (req.position_secs, req.position) {
+        (Some(secs), _) => {
+            // Also rejects values too large for a Duration, e.g. 1e30
+            let position = std::time::Duration::try_from_secs_f64(secs).map_err(|_| {
+                ApiError::bad_request(
+                    "invalid_position",
+                    "position_secs must be a finite, non-negative number",
+                )
+            })?;
+            engine
+                .seek_to(position)
+                .map_err(|e| ApiError::bad_request("invalid_position", e))?;
+        }
+        (None, Some(position)) => engine.seek(position),
+        (None, None) => {
+            return Err(ApiError::bad_request(
+                "invalid_request",
+                "position_secs or position is required",
+            ))
+        }
+    }
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "position": engine.playback_position(),
+        "position_secs": engine.playback_position_secs(),
+    })))
+}
+
+/// Longest the shutdown handler waits for queued output to play out
+const SHUTDOWN_DRAIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How often the shutdown handler checks the output buffer
+const SHUTDOWN_DRAIN_POLL: std::time::Duration = std::time::Duration::from_millis(10);
+
+/// POST /api/v1/shutdown
+///
+/// Responds straight away. The transport stops, buffered output drains, the
+/// output stream closes and then the server is told to exit.
+pub async fn shutdown(State(state): State<AppState>) -> StatusCode {
+    let stream = state.engine.write().await.begin_shutdown();
+
+    tokio::spawn(async move {
+        if let Some(mut stream) = stream {
+            let deadline = tokio::time::Instant::now() + SHUTDOWN_DRAIN_TIMEOUT;
+            while stream.buffered_frames() > 0 && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(SHUTDOWN_DRAIN_POLL).await;
+            }
+            if let Err(e) = stream.stop() {
+                tracing::warn!("Failed to close output stream: {}", e);
+            }
+        }
+        tracing::info!("Shutdown requested over the API");
+        state.shutdown.send_replace(true);
+    });
+
+    StatusCode::ACCEPTED
 }
 
 /// GET /api/v1/transport/playback-status - Get playback status
@@ -697,7 +1088,7 @@ pub async fn playback_status(State(state): State<AppState>) -> Json<serde_json::
     Json(serde_json::json!({
         "mode": format!("{:?}", engine.transport_mode),
         "file": playback.file_path.as_ref().map(|p| p.to_string_lossy()),
-        "position": playback.playback_position,
+        "position": engine.playback_position(),
         "total_samples": playback.total_samples,
         "sample_rate": playback.sample_rate,
         "transport_state": format!("{:?}", engine.transport_state),
@@ -3,18 +3,48 @@
 //! Engine state management
 
 use audio_ninja::{
-    input::{InputManager, InputSource},
+    calibration::CalibrationSolution,
+    dsp::{BiquadFilter, ResampleQuality, StreamingResampler},
+    dspconfig::DspChainConfig,
+    input::{AudioFileReader, InputManager, InputSource},
+    loudness::LoudnessMeter,
     network::SpeakerDiscovery,
-    output::{OutputDevice, OutputManager},
-    SpeakerLayout,
+    output::{OutputDevice, OutputError, OutputManager, PlaybackStream},
+    pipeline::Pipeline,
+    render::SpeakerProcessor,
+    AudioBlock, SpeakerLayout, SpeakerRole,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
 use uuid::Uuid;
 
+/// Speed of sound used to turn speaker distances into delays, in m/s
+const SPEED_OF_SOUND_M_S: f32 = 343.0;
+
+/// Processing sample rate of a new engine
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+
+/// Frames rendered per pass of the render loop
+pub const RENDER_BLOCK_FRAMES: usize = 1024;
+
+/// Opens a playback stream at a sample rate and channel count, so the
+/// engine can reopen its output when the processing rate changes
+pub type OutputStreamOpener =
+    Box<dyn Fn(u32, u32) -> Result<Box<dyn PlaybackStream>, OutputError> + Send + Sync>;
+
+/// File holding the persisted engine state inside the state directory
+const STATE_FILE: &str = "engine-state.json";
+
+/// Seek granularity for files without a frame-accurate reader.
+/// Matches the default FLAC block size, the smallest unit those
+/// containers can be entered at without decoding from the start.
+const COARSE_SEEK_FRAMES: u64 = 4096;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpeakerInfo {
     pub id: Uuid,
@@ -22,6 +52,19 @@ pub struct SpeakerInfo {
     pub address: String,
     pub position: Option<SpeakerPosition>,
     pub online: bool,
+    #[serde(default)]
+    pub role: Option<SpeakerRole>,
+    /// Level trim applied to this speaker, in dB
+    #[serde(default)]
+    pub trim_db: f32,
+}
+
+/// Partial update of a speaker's placement; `None` fields are left unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpeakerUpdate {
+    pub role: Option<SpeakerRole>,
+    pub position: Option<SpeakerPosition>,
+    pub trim_db: Option<f32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +94,20 @@ pub enum TransportMode {
     Mixed,
 }
 
+impl std::str::FromStr for TransportMode {
+    type Err = String;
+
+    /// Parse the mode names used by the API and CLI: `file`, `stream`, `mixed`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "file" => Ok(TransportMode::FilePlayback),
+            "stream" => Ok(TransportMode::LiveStream),
+            "mixed" => Ok(TransportMode::Mixed),
+            other => Err(format!("Unknown transport mode: {}", other)),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpeakerStats {
     pub packets_sent: u64,
@@ -60,11 +117,173 @@ pub struct SpeakerStats {
     pub buffer_fill: f32,
 }
 
+/// Smoothing factor for the render load moving average
+const RENDER_LOAD_SMOOTHING: f32 = 0.1;
+
+/// Highest master gain accepted, in dB
+pub const MAX_MASTER_GAIN_DB: f32 = 12.0;
+
+/// Master output volume, applied after limiting as the last stage before output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MasterVolume {
+    pub gain_db: f32,
+    pub mute: bool,
+}
+
+/// Health of the render path, fed by the pipeline and output layers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngineMetrics {
+    /// Render time as a percentage of the audio duration produced (smoothed)
+    pub render_load_percent: f32,
+    /// Output buffer underruns since the engine started
+    pub buffer_underruns: u64,
+    /// Latency added by the DSP pipeline
+    pub pipeline_latency_ms: f32,
+    /// Latency reported by the active output
+    pub output_latency_ms: f32,
+}
+
+/// Level reported for silent channels, in dB
+const METER_FLOOR_DB: f32 = -120.0;
+
+/// Peak and RMS level of one output channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelLevel {
+    pub peak_db: f32,
+    pub rms_db: f32,
+}
+
+/// Snapshot of the output meters, as pushed to live metering clients
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeterFrame {
+    /// Levels of the most recently rendered block, per channel
+    pub channels: Vec<ChannelLevel>,
+    /// Integrated loudness since the meter was last reset (None while silent)
+    pub integrated_lufs: Option<f32>,
+}
+
+/// Peak/RMS tracker and gated integrated loudness on the render output
+pub struct OutputMeter {
+    loudness: LoudnessMeter,
+    levels: Vec<ChannelLevel>,
+}
+
+impl OutputMeter {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            loudness: LoudnessMeter::new(sample_rate),
+            levels: Vec::new(),
+        }
+    }
+
+    /// Feed a rendered block into the meters
+    pub fn process(&mut self, block: &AudioBlock) {
+        let to_db = |linear: f32| {
+            if linear > 0.0 {
+                (20.0 * linear.log10()).max(METER_FLOOR_DB)
+            } else {
+                METER_FLOOR_DB
+            }
+        };
+
+        self.levels = block
+            .channels
+            .iter()
+            .map(|channel| {
+                let peak = channel.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                let mean_square = if channel.is_empty() {
+                    0.0
+                } else {
+                    channel.iter().map(|s| s * s).sum::<f32>() / channel.len() as f32
+                };
+                ChannelLevel {
+                    peak_db: to_db(peak),
+                    rms_db: to_db(mean_square.sqrt()),
+                }
+            })
+            .collect();
+
+        self.loudness.accumulate(block);
+    }
+
+    /// Sample rate the meters run at
+    pub fn sample_rate(&self) -> u32 {
+        self.loudness.sample_rate()
+    }
+
+    /// Weight channels by these roles in the integrated loudness
+    pub fn set_channel_roles(&mut self, roles: &[SpeakerRole]) {
+        self.loudness.set_channel_roles(roles);
+    }
+
+    pub fn frame(&self) -> MeterFrame {
+        let integrated_lufs = Some(self.loudness.integrated_so_far()).filter(|l| l.is_finite());
+
+        MeterFrame {
+            channels: self.levels.clone(),
+            integrated_lufs,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.loudness.reset();
+        self.levels.clear();
+    }
+}
+
+/// Engine configuration that survives daemon restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PersistedState {
+    pub layout: Option<SpeakerLayout>,
+    pub input_source_id: Option<String>,
+    pub output_device_id: Option<String>,
+}
+
+impl PersistedState {
+    /// Load from `dir`, returning the default state if nothing was saved yet
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(STATE_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        serde_json::from_str(&text).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    /// Write to `dir`, replacing the previous file atomically
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let tmp = dir.join(format!("{}.tmp", STATE_FILE));
+        std::fs::write(&tmp, text)
+            .map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+        std::fs::rename(&tmp, dir.join(STATE_FILE)).map_err(|e| e.to_string())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalibrationState {
     pub running: bool,
     pub progress: f32,
     pub measurements: Vec<String>,
+    /// Solution of the last completed run, waiting to be applied
+    #[serde(skip)]
+    pub solution: Option<CalibrationSolution>,
+    /// Identifies the current run so a cancelled run's task can notice it
+    #[serde(skip)]
+    pub run_id: u64,
+}
+
+/// Per-channel correction applied by calibration, in layout channel order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChannelDsp {
+    pub delay: Duration,
+    pub trim_db: f32,
+    pub peq: Vec<BiquadFilter>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,6 +312,37 @@ impl Default for PlaybackState {
     }
 }
 
+/// Delays and trims that align every speaker of `layout` with the farthest one
+fn solve_from_geometry(layout: &SpeakerLayout) -> CalibrationSolution {
+    let distances: Vec<f32> = layout
+        .speakers
+        .iter()
+        .map(|s| {
+            let p = s.position;
+            (p.x * p.x + p.y * p.y + p.z * p.z).sqrt()
+        })
+        .collect();
+    let farthest = distances.iter().copied().fold(0.0f32, f32::max);
+
+    let (delays, trims_db) = distances
+        .iter()
+        .map(|&d| {
+            if farthest <= 0.0 || d <= 0.0 {
+                return (Duration::ZERO, 0.0);
+            }
+            let delay = Duration::from_secs_f32((farthest - d) / SPEED_OF_SOUND_M_S);
+            (delay, 20.0 * (d / farthest).log10())
+        })
+        .unzip();
+
+    CalibrationSolution {
+        delays,
+        trims_db,
+        peq: Vec::new(),
+        fir: None,
+    }
+}
+
 pub struct EngineState {
     pub speakers: HashMap<Uuid, SpeakerInfo>,
     pub layout: Option<SpeakerLayout>,
@@ -101,13 +351,33 @@ pub struct EngineState {
     pub playback: PlaybackState,
     pub speaker_stats: HashMap<Uuid, SpeakerStats>,
     pub calibration: CalibrationState,
+    pub metrics: EngineMetrics,
+    pub volume: MasterVolume,
+    pub channel_dsp: Vec<ChannelDsp>,
+    /// Per-speaker delay and trim for the render output
+    pub speaker_processor: SpeakerProcessor,
+    /// DSP chain the render output runs through; its sample rate is the
+    /// engine's processing rate
+    pub pipeline: Pipeline,
+    pub output_meter: OutputMeter,
     discovery: Option<SpeakerDiscovery>,
+    file_reader: Option<AudioFileReader>,
+    /// Converts the playing file to the engine rate when the two differ
+    resampler: Option<StreamingResampler>,
+    state_dir: Option<PathBuf>,
+    input_source_id: Option<String>,
 
     // Audio I/O managers
     pub input_manager: InputManager,
     pub output_manager: OutputManager,
     pub active_input_source: Option<InputSource>,
     pub active_output_device: Option<OutputDevice>,
+    /// Open playback stream on the active output device
+    pub output_stream: Option<Box<dyn PlaybackStream>>,
+    output_stream_opener: Option<OutputStreamOpener>,
+    /// The output buffer held audio after the last render, so finding it
+    /// empty next time is an underrun
+    output_primed: bool,
 }
 
 impl Default for EngineState {
@@ -129,12 +399,77 @@ impl EngineState {
                 running: false,
                 progress: 0.0,
                 measurements: Vec::new(),
+                solution: None,
+                run_id: 0,
             },
+            metrics: EngineMetrics::default(),
+            volume: MasterVolume::default(),
+            channel_dsp: Vec::new(),
+            speaker_processor: SpeakerProcessor::new(),
+            pipeline: Pipeline::new(DEFAULT_SAMPLE_RATE),
+            output_meter: OutputMeter::new(DEFAULT_SAMPLE_RATE),
             discovery: None,
+            file_reader: None,
+            resampler: None,
+            state_dir: None,
+            input_source_id: None,
             input_manager: InputManager::new(),
             output_manager: OutputManager::new(),
             active_input_source: None,
             active_output_device: None,
+            output_stream: None,
+            output_stream_opener: None,
+            output_primed: false,
+        }
+    }
+
+    /// Create engine state persisted in `dir`, restoring the layout and
+    /// I/O selection saved by a previous run
+    pub fn with_state_dir(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let mut engine = Self::new();
+
+        let saved = PersistedState::load(&dir).unwrap_or_else(|e| {
+            warn!("Ignoring saved engine state: {}", e);
+            PersistedState::default()
+        });
+
+        engine.layout = saved.layout;
+        if let Some(id) = saved.input_source_id {
+            if let Err(e) = engine.select_input_source(&id) {
+                warn!("Saved input source {} unavailable: {}", id, e);
+            }
+        }
+        if let Some(id) = saved.output_device_id {
+            if let Err(e) = engine.select_output_device(&id) {
+                warn!("Saved output device {} unavailable: {}", id, e);
+            }
+        }
+
+        engine.state_dir = Some(dir);
+        engine
+    }
+
+    /// Directory the engine state is persisted in, if any
+    pub fn state_dir(&self) -> Option<&Path> {
+        self.state_dir.as_deref()
+    }
+
+    /// Snapshot of the configuration that is persisted across restarts
+    pub fn persisted_state(&self) -> PersistedState {
+        PersistedState {
+            layout: self.layout.clone(),
+            input_source_id: self.input_source_id.clone(),
+            output_device_id: self.active_output_device.as_ref().map(|d| d.id.clone()),
+        }
+    }
+
+    /// Write the persisted state through to disk when a state directory is set
+    fn persist(&self) {
+        if let Some(dir) = &self.state_dir {
+            if let Err(e) = self.persisted_state().save(dir) {
+                warn!("Failed to persist engine state: {}", e);
+            }
         }
     }
 
@@ -148,59 +483,523 @@ impl EngineState {
         self.speakers.insert(speaker.id, speaker);
     }
 
+    /// Apply a partial update to a known speaker, returning the updated info
+    pub fn update_speaker(&mut self, id: &Uuid, update: SpeakerUpdate) -> Option<SpeakerInfo> {
+        let speaker = self.speakers.get_mut(id)?;
+        if let Some(role) = update.role {
+            speaker.role = Some(role);
+        }
+        if let Some(position) = update.position {
+            speaker.position = Some(position);
+        }
+        if let Some(trim_db) = update.trim_db {
+            speaker.trim_db = trim_db;
+        }
+        Some(speaker.clone())
+    }
+
     pub fn remove_speaker(&mut self, id: &Uuid) -> Option<SpeakerInfo> {
         self.speakers.remove(id)
     }
 
     pub fn set_layout(&mut self, layout: SpeakerLayout) {
         self.layout = Some(layout);
+        self.persist();
     }
 
-    pub fn play(&mut self) {
+    /// Start playback.
+    ///
+    /// File mode needs a loaded file, stream mode a selected input, and mixed
+    /// mode both.
+    pub fn play(&mut self) -> Result<(), String> {
+        let needs_file = self.transport_mode != TransportMode::LiveStream;
+        let needs_input = self.transport_mode != TransportMode::FilePlayback;
+        if needs_file && self.playback.file_path.is_none() {
+            return Err("no file loaded".to_string());
+        }
+        if needs_input && self.active_input_source.is_none() {
+            return Err("no input source selected".to_string());
+        }
+
         self.transport_state = TransportState::Playing;
+        Ok(())
     }
 
     pub fn pause(&mut self) {
+        self.freeze_playback_position();
         self.transport_state = TransportState::Paused;
     }
 
     pub fn stop(&mut self) {
+        self.freeze_playback_position();
         self.transport_state = TransportState::Stopped;
     }
 
+    /// Stop the transport ahead of exiting, saving state and handing back the
+    /// output stream so its buffered audio can play out before it is closed
+    pub fn begin_shutdown(&mut self) -> Option<Box<dyn PlaybackStream>> {
+        self.stop();
+        self.file_reader = None;
+        self.persist();
+        self.output_stream.take()
+    }
+
+    /// Current playback position in frames of the loaded file
+    ///
+    /// With a file reader this is how far the render loop has read, less the
+    /// audio still queued in the output stream, converted from the output
+    /// rate to the file's. Files without a reader play no audio, so their
+    /// position only moves when seeking.
+    pub fn playback_position(&self) -> u64 {
+        let Some(reader) = &self.file_reader else {
+            return self.playback.playback_position;
+        };
+        let queued = self
+            .output_stream
+            .as_ref()
+            .map_or(0, |stream| stream.buffered_frames() as u64);
+        let queued = queued * reader.sample_rate() as u64 / self.sample_rate().max(1) as u64;
+        reader.position_frames().saturating_sub(queued)
+    }
+
+    /// Current playback position in seconds
+    pub fn playback_position_secs(&self) -> f64 {
+        self.playback_position() as f64 / self.playback.sample_rate.max(1) as f64
+    }
+
+    /// Length of the loaded file in seconds; `None` when streaming live input
+    pub fn playback_duration_secs(&self) -> Option<f64> {
+        if self.transport_mode == TransportMode::LiveStream {
+            return None;
+        }
+        self.playback.file_path.as_ref()?;
+        Some(self.playback.total_samples as f64 / self.playback.sample_rate.max(1) as f64)
+    }
+
+    /// Keep the position playback reached when it stops or pauses
+    fn freeze_playback_position(&mut self) {
+        self.playback.playback_position = self.playback_position();
+        // The output is allowed to run dry while nothing plays
+        self.output_primed = false;
+    }
+
     /// Seek to a specific sample position in the loaded file
     pub fn seek(&mut self, position: u64) {
-        self.playback.playback_position = position.min(self.playback.total_samples);
+        let position = position.min(self.playback.total_samples);
+        let sample_rate = self.playback.sample_rate.max(1) as f64;
+        if let Some(reader) = self.file_reader.as_mut() {
+            if reader
+                .seek(Duration::from_secs_f64(position as f64 / sample_rate))
+                .is_err()
+            {
+                return;
+            }
+        }
+        self.resampler = None;
+        self.playback.playback_position = position;
     }
 
-    pub fn start_calibration(&mut self) {
+    /// Seek to a time offset in the loaded file.
+    ///
+    /// WAV files seek to the nearest frame. Other formats snap to the nearest
+    /// container block boundary. Returns the position actually landed on.
+    pub fn seek_to(&mut self, position: Duration) -> Result<Duration, String> {
+        if self.playback.file_path.is_none() {
+            return Err("no file loaded".to_string());
+        }
+
+        let sample_rate = self.playback.sample_rate.max(1);
+        let frame = if let Some(reader) = self.file_reader.as_mut() {
+            reader
+                .seek(position)
+                .map_err(|e| format!("Failed to seek: {}", e))?;
+            reader.position_frames()
+        } else {
+            let target = position.as_secs_f64() * sample_rate as f64;
+            let block = (target / COARSE_SEEK_FRAMES as f64).round() as u64;
+            block
+                .saturating_mul(COARSE_SEEK_FRAMES)
+                .min(self.playback.total_samples)
+        };
+
+        self.resampler = None;
+        self.playback.playback_position = frame;
+        Ok(Duration::from_secs_f64(frame as f64 / sample_rate as f64))
+    }
+
+    /// Start a calibration run and return its id.
+    ///
+    /// The caller drives the run by recording one measurement per layout
+    /// speaker and then finishing it; any earlier run is superseded.
+    pub fn start_calibration(&mut self) -> u64 {
+        self.calibration.run_id += 1;
         self.calibration.running = true;
         self.calibration.progress = 0.0;
         self.calibration.measurements.clear();
+        self.calibration.solution = None;
+        self.calibration.run_id
+    }
+
+    /// Whether `run_id` is still the active calibration run
+    pub fn calibration_active(&self, run_id: u64) -> bool {
+        self.calibration.running && self.calibration.run_id == run_id
+    }
+
+    /// Names of the speakers a calibration run has to measure, in channel order
+    pub fn calibration_targets(&self) -> Vec<String> {
+        self.layout
+            .as_ref()
+            .map(|layout| layout.speakers.iter().map(|s| s.id.clone()).collect())
+            .unwrap_or_default()
     }
 
-    /// Finalize calibration and mark results as applied.
-    pub fn apply_calibration(&mut self) -> Result<(), String> {
-        if !self.calibration.running && self.calibration.measurements.is_empty() {
-            return Err("no calibration session in progress".to_string());
+    /// Record one completed speaker measurement; false if the run was cancelled
+    pub fn record_calibration_measurement(
+        &mut self,
+        run_id: u64,
+        speaker: &str,
+        total: usize,
+    ) -> bool {
+        if !self.calibration_active(run_id) {
+            return false;
         }
+        self.calibration
+            .measurements
+            .push(format!("sweep:{}", speaker));
+        self.calibration.progress =
+            (self.calibration.measurements.len() as f32 / total.max(1) as f32).min(1.0);
+        true
+    }
 
-        if self.calibration.measurements.is_empty() {
-            // Record a placeholder measurement so status reports reflect completion.
-            self.calibration
-                .measurements
-                .push("calibration_run".to_string());
+    /// Finish a run once every speaker has been measured.
+    ///
+    /// Without acoustic capture the solution comes from the layout geometry:
+    /// speakers are time-aligned and level-matched to the farthest one. A run
+    /// with no layout ends without a solution.
+    pub fn finish_calibration(&mut self, run_id: u64) {
+        if !self.calibration_active(run_id) {
+            return;
         }
+        match self.layout.as_ref().map(solve_from_geometry) {
+            Some(solution) => self.complete_calibration(solution),
+            None => self.calibration.running = false,
+        }
+    }
 
+    /// Finish the running calibration with a solution ready to apply
+    pub fn complete_calibration(&mut self, solution: CalibrationSolution) {
         self.calibration.running = false;
         self.calibration.progress = 1.0;
-        Ok(())
+        self.calibration.solution = Some(solution);
+    }
+
+    /// Stop the running calibration; false if none was running
+    pub fn cancel_calibration(&mut self) -> bool {
+        if !self.calibration.running {
+            return false;
+        }
+        self.calibration.running = false;
+        self.calibration.run_id += 1;
+        true
+    }
+
+    /// Push the last completed calibration into the per-channel DSP state
+    pub fn apply_calibration(&mut self) -> Result<&[ChannelDsp], String> {
+        let solution = self
+            .calibration
+            .solution
+            .as_ref()
+            .ok_or_else(|| "no completed calibration to apply".to_string())?;
+
+        let channels = solution.delays.len().max(solution.trims_db.len());
+        self.channel_dsp = (0..channels)
+            .map(|ch| ChannelDsp {
+                delay: solution.delays.get(ch).copied().unwrap_or_default(),
+                trim_db: solution.trims_db.get(ch).copied().unwrap_or(0.0),
+                // Room EQ is shared by every channel in a single solution
+                peq: solution.peq.clone(),
+            })
+            .collect();
+        if let Some(layout) = &self.layout {
+            self.speaker_processor = SpeakerProcessor::from_solution(layout, solution);
+        }
+
+        Ok(&self.channel_dsp)
     }
 
     pub fn update_stats(&mut self, speaker_id: Uuid, stats: SpeakerStats) {
         self.speaker_stats.insert(speaker_id, stats);
     }
 
+    /// Record how long it took to render `frames` of audio
+    pub fn record_render(&mut self, frames: usize, elapsed: Duration) {
+        let sample_rate = self.sample_rate().max(1) as f32;
+        let audio_secs = frames as f32 / sample_rate;
+        if audio_secs <= 0.0 {
+            return;
+        }
+
+        let load = elapsed.as_secs_f32() / audio_secs * 100.0;
+        self.metrics.render_load_percent +=
+            (load - self.metrics.render_load_percent) * RENDER_LOAD_SMOOTHING;
+    }
+
+    /// Set the master gain in dB (at most `MAX_MASTER_GAIN_DB`)
+    pub fn set_master_gain_db(&mut self, gain_db: f32) -> Result<(), String> {
+        if !gain_db.is_finite() || gain_db > MAX_MASTER_GAIN_DB {
+            return Err(format!(
+                "master gain must be finite and at most {} dB",
+                MAX_MASTER_GAIN_DB
+            ));
+        }
+        self.volume.gain_db = gain_db;
+        Ok(())
+    }
+
+    pub fn set_mute(&mut self, mute: bool) {
+        self.volume.mute = mute;
+    }
+
+    /// Apply master gain and mute to a block leaving the limiter
+    pub fn apply_master_volume(&self, block: &mut AudioBlock) {
+        if self.volume.mute {
+            for channel in &mut block.channels {
+                channel.fill(0.0);
+            }
+        } else if self.volume.gain_db != 0.0 {
+            block.apply_gain(self.volume.gain_db);
+        }
+    }
+
+    /// Render the next `frames` of the playing file and send them to the output.
+    ///
+    /// The block is resampled to the engine rate and runs through the DSP
+    /// chain, speaker correction and master volume before it is metered and
+    /// written to the output stream. Render time and output underruns go
+    /// into the metrics. Returns `None` unless a file is playing and has
+    /// audio left.
+    pub fn render_next_block(&mut self, frames: usize) -> Option<AudioBlock> {
+        if !matches!(self.transport_state, TransportState::Playing)
+            || self.transport_mode == TransportMode::LiveStream
+        {
+            return None;
+        }
+
+        let started = Instant::now();
+        let rate = self.sample_rate();
+        let reader = self.file_reader.as_mut()?;
+        let file_rate = reader.sample_rate();
+        let converting = self
+            .resampler
+            .as_ref()
+            .is_some_and(|r| r.source_rate() == file_rate && r.target_rate() == rate);
+        if file_rate == rate {
+            self.resampler = None;
+        } else if !converting {
+            self.resampler = Some(StreamingResampler::new(
+                file_rate,
+                rate,
+                ResampleQuality::default(),
+            ));
+        }
+
+        // Read exactly as much of the file as `frames` lasts at the engine rate
+        let file_frames = match &self.resampler {
+            Some(resampler) => resampler.input_frames_for(frames),
+            None => frames,
+        };
+        let read = match reader.read_block(file_frames) {
+            Ok(read) => read,
+            Err(e) => {
+                warn!("Failed to read the playing file: {}", e);
+                return None;
+            }
+        };
+        let mut block = match (read, self.resampler.as_mut()) {
+            (Some(block), Some(resampler)) => resampler.process(&block),
+            (Some(block), None) => block,
+            // The file has ended; play out what the resampler still holds
+            (None, _) => self
+                .resampler
+                .take()
+                .map(|mut resampler| resampler.flush())
+                .filter(|tail| tail.frame_len() > 0)?,
+        };
+
+        self.pipeline.process(&mut block);
+        if let Some(layout) = &self.layout {
+            self.speaker_processor.process(&mut block, layout);
+        }
+        self.apply_master_volume(&mut block);
+        self.meter_output(&block);
+
+        if let Some(stream) = self.output_stream.as_mut() {
+            let underrun = self.output_primed && stream.buffered_frames() == 0;
+            if let Err(e) = stream.write(&block.channels) {
+                warn!("Failed to write to the output stream: {}", e);
+            }
+            // Streams that don't report their buffer never count underruns
+            self.output_primed = stream.buffered_frames() > 0;
+            if underrun {
+                self.record_underrun();
+            }
+        }
+
+        self.record_render(block.frame_len(), started.elapsed());
+        Some(block)
+    }
+
+    /// Feed a block of render output into the live meters
+    ///
+    /// Output that matches the speaker layout is weighted by its roles in the
+    /// loudness reading; anything else is taken to be in ITU channel order.
+    pub fn meter_output(&mut self, block: &AudioBlock) {
+        let roles: Vec<SpeakerRole> = match &self.layout {
+            Some(layout) if layout.speakers.len() == block.channels.len() => {
+                layout.speakers.iter().map(|s| s.role.clone()).collect()
+            }
+            _ => Vec::new(),
+        };
+        self.output_meter.set_channel_roles(&roles);
+        self.output_meter.process(block);
+    }
+
+    /// Current output meter readings
+    pub fn meter_frame(&self) -> MeterFrame {
+        self.output_meter.frame()
+    }
+
+    /// Record an output buffer underrun
+    pub fn record_underrun(&mut self) {
+        self.metrics.buffer_underruns += 1;
+    }
+
+    /// Sample rate the render path processes at
+    pub fn sample_rate(&self) -> u32 {
+        self.pipeline.sample_rate()
+    }
+
+    /// Switch the render path to a new sample rate without a restart.
+    ///
+    /// The DSP chain is rebuilt with coefficients and timings for the new
+    /// rate, an open output stream is reopened at that rate, the output
+    /// meters start over and speaker correction state is cleared. Fails,
+    /// leaving the engine as it was, if the active output device does not
+    /// support `rate`, the output cannot be reopened or a pipeline stage
+    /// cannot be rebuilt.
+    pub fn set_sample_rate(&mut self, rate: u32) -> Result<(), String> {
+        if rate == 0 {
+            return Err("sample rate must be positive".to_string());
+        }
+        if let Some(device) = &self.active_output_device {
+            if !device.sample_rates.contains(&rate) {
+                return Err(format!(
+                    "output device {} does not support {} Hz",
+                    device.id, rate
+                ));
+            }
+        }
+
+        let previous = self.sample_rate();
+        if rate == previous {
+            return Ok(());
+        }
+
+        let reopened = match &self.output_stream {
+            Some(stream) if stream.sample_rate() != rate => {
+                let open = self
+                    .output_stream_opener
+                    .as_ref()
+                    .ok_or_else(|| format!("cannot reopen the output stream at {} Hz", rate))?;
+                let stream = open(rate, stream.channels())
+                    .map_err(|e| format!("cannot reopen output at {} Hz: {}", rate, e))?;
+                Some(stream)
+            }
+            _ => None,
+        };
+
+        self.pipeline
+            .set_sample_rate(rate)
+            .map_err(|e| format!("cannot rebuild DSP chain at {} Hz: {}", rate, e))?;
+        self.metrics.pipeline_latency_ms = self.pipeline.total_latency().as_secs_f32() * 1000.0;
+        if let Some(stream) = reopened {
+            self.replace_output_stream(stream);
+        } else {
+            // Output buffers hold the same number of frames at either rate
+            self.metrics.output_latency_ms *= previous as f32 / rate as f32;
+        }
+        self.output_meter = OutputMeter::new(rate);
+        self.speaker_processor.reset();
+
+        if self.channel_dsp.iter().any(|dsp| !dsp.peq.is_empty()) {
+            warn!(
+                "Calibration EQ was designed at {} Hz; recalibrate for {} Hz",
+                previous, rate
+            );
+        }
+        info!("Sample rate changed from {} Hz to {} Hz", previous, rate);
+        Ok(())
+    }
+
+    /// Replace the DSP chain with a preset loaded by `DspChainConfig::load`.
+    /// The preset's sample rate becomes the engine's processing rate.
+    pub fn load_dsp_preset(&mut self, path: &Path) -> Result<(), String> {
+        let config = DspChainConfig::load(path)
+            .map_err(|e| format!("cannot load DSP preset {}: {}", path.display(), e))?;
+        if config.sample_rate == 0 {
+            return Err(format!("DSP preset {} has no sample rate", path.display()));
+        }
+
+        self.pipeline = config.build();
+        self.metrics.pipeline_latency_ms = self.pipeline.total_latency().as_secs_f32() * 1000.0;
+        self.output_meter = OutputMeter::new(config.sample_rate);
+        info!(
+            "Loaded DSP preset {} ({} stages at {} Hz)",
+            path.display(),
+            config.stages.len(),
+            config.sample_rate
+        );
+        Ok(())
+    }
+
+    /// Set how output streams are (re)opened, e.g. on the cpal backend
+    pub fn set_output_stream_opener(&mut self, opener: OutputStreamOpener) {
+        self.output_stream_opener = Some(opener);
+    }
+
+    /// Close the current output stream and carry on with `stream`, starting
+    /// it if the old one was playing
+    fn replace_output_stream(&mut self, mut stream: Box<dyn PlaybackStream>) {
+        if let Some(mut old) = self.output_stream.take() {
+            let was_running = old.is_running();
+            if let Err(e) = old.stop() {
+                warn!("Failed to stop output stream: {}", e);
+            }
+            if was_running {
+                if let Err(e) = stream.start() {
+                    warn!("Failed to restart output stream: {}", e);
+                }
+            }
+        }
+        self.metrics.output_latency_ms = stream.latency_ms();
+        self.output_stream = Some(stream);
+    }
+
+    /// Update the pipeline latency from the active DSP chain
+    pub fn set_pipeline_latency(&mut self, pipeline: &Pipeline) {
+        self.metrics.pipeline_latency_ms = pipeline.total_latency().as_secs_f32() * 1000.0;
+    }
+
+    /// Update the latency reported by the active output
+    pub fn set_output_latency_ms(&mut self, latency_ms: f32) {
+        self.metrics.output_latency_ms = latency_ms;
+    }
+
+    /// End-to-end latency of the render path in milliseconds
+    pub fn current_latency_ms(&self) -> f32 {
+        self.metrics.pipeline_latency_ms + self.metrics.output_latency_ms
+    }
+
     // ===== Audio I/O Methods =====
 
     /// Parse WAV file header to extract metadata
@@ -321,7 +1120,14 @@ impl EngineState {
         }
 
         // Parse audio metadata
-        let (sample_rate, _channels, total_samples) = Self::parse_audio_metadata(&path)?;
+        let (sample_rate, _channels, mut total_samples) = Self::parse_audio_metadata(&path)?;
+
+        // WAV files get a streaming reader; its chunk walk gives exact frame counts
+        self.file_reader = AudioFileReader::open(&path).ok();
+        self.resampler = None;
+        if let Some(reader) = &self.file_reader {
+            total_samples = reader.total_frames();
+        }
 
         self.playback.file_path = Some(path);
         self.playback.playback_position = 0;
@@ -381,6 +1187,8 @@ impl EngineState {
         };
 
         self.active_input_source = Some(source.clone());
+        self.input_source_id = Some(source_id.to_string());
+        self.persist();
         Ok(source)
     }
 
@@ -392,6 +1200,10 @@ impl EngineState {
             .map_err(|e| e.to_string())?;
 
         self.active_output_device = Some(device.clone());
+        if !device.sample_rates.contains(&self.sample_rate()) {
+            self.set_sample_rate(device.default_sample_rate)?;
+        }
+        self.persist();
         Ok(device)
     }
 
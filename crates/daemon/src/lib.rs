@@ -5,15 +5,59 @@
 //! This provides the API and engine state for testing.
 
 pub mod api;
+pub mod auth;
 pub mod engine;
 
 pub use engine::EngineState;
 
-use std::{sync::Arc, time::Instant};
-use tokio::sync::RwLock;
+use engine::RENDER_BLOCK_FRAMES;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{watch, RwLock};
 
 #[derive(Clone)]
 pub struct AppState {
     pub engine: Arc<RwLock<EngineState>>,
     pub started_at: Instant,
+    /// Set to true once the daemon should stop serving and exit
+    pub shutdown: watch::Sender<bool>,
+}
+
+impl AppState {
+    pub fn new(engine: EngineState) -> Self {
+        Self {
+            engine: Arc::new(RwLock::new(engine)),
+            started_at: Instant::now(),
+            shutdown: watch::channel(false).0,
+        }
+    }
+
+    /// Resolves once shutdown has been requested
+    pub async fn shutdown_requested(&self) {
+        let mut requested = self.shutdown.subscribe();
+        let _ = requested.wait_for(|&requested| requested).await;
+    }
+
+    /// Drive the render path in real time until shutdown, rendering one
+    /// `RENDER_BLOCK_FRAMES` block per block period at the engine rate
+    pub async fn run_render_loop(self) {
+        loop {
+            let period = {
+                let engine = self.engine.read().await;
+                Duration::from_secs_f64(
+                    RENDER_BLOCK_FRAMES as f64 / engine.sample_rate().max(1) as f64,
+                )
+            };
+            tokio::select! {
+                _ = self.shutdown_requested() => return,
+                _ = tokio::time::sleep(period) => {}
+            }
+            self.engine
+                .write()
+                .await
+                .render_next_block(RENDER_BLOCK_FRAMES);
+        }
+    }
 }
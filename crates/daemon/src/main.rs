@@ -7,16 +7,21 @@
 
 use anyhow::Result;
 use axum::{
-    routing::{delete, get, post},
+    middleware,
+    routing::{delete, get, patch, post},
     Router,
 };
 use clap::Parser;
-use std::{net::SocketAddr, sync::Arc, time::Instant};
-use tokio::sync::RwLock;
+use std::{net::SocketAddr, path::PathBuf};
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 
-use audio_ninja_daemon::{api, engine::EngineState, AppState};
+use audio_ninja_daemon::{
+    api,
+    auth::{self, ApiToken},
+    engine::EngineState,
+    AppState,
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "audio-ninja-daemon")]
@@ -33,6 +38,19 @@ struct Args {
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
+
+    /// Directory for persisting layout and I/O selection across restarts
+    #[arg(long)]
+    state_dir: Option<PathBuf>,
+
+    /// Require `Authorization: Bearer <TOKEN>` on every request except
+    /// status and info
+    #[arg(long, value_name = "TOKEN")]
+    api_token: Option<String>,
+
+    /// DSP chain preset (TOML, or JSON by extension) for the render path
+    #[arg(long, value_name = "FILE")]
+    dsp_preset: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -50,26 +68,41 @@ async fn main() -> Result<()> {
 
     info!("Audio Ninja Daemon starting...");
 
-    // Initialize engine state
-    let engine_state = EngineState::new();
-    let app_state = AppState {
-        engine: Arc::new(RwLock::new(engine_state)),
-        started_at: Instant::now(),
+    // Initialize engine state, restoring any persisted configuration
+    let mut engine_state = match &args.state_dir {
+        Some(dir) => {
+            info!("Persisting engine state in {}", dir.display());
+            EngineState::with_state_dir(dir)
+        }
+        None => EngineState::new(),
     };
+    if let Some(path) = &args.dsp_preset {
+        engine_state
+            .load_dsp_preset(path)
+            .map_err(anyhow::Error::msg)?;
+    }
+    let app_state = AppState::new(engine_state);
+    let shutdown_state = app_state.clone();
+    tokio::spawn(app_state.clone().run_render_loop());
 
     // Build REST API routes
-    let app = Router::new()
+    let routes = Router::new()
         // Status and info
         .route("/api/v1/status", get(api::status))
         .route("/api/v1/info", get(api::info))
+        .route("/api/v1/volume", post(api::set_volume))
+        .route("/api/v1/shutdown", post(api::shutdown))
         // Speaker management
         .route("/api/v1/speakers", get(api::list_speakers))
+        .route("/api/v1/speakers", post(api::create_speaker))
         .route("/api/v1/speakers/discover", post(api::discover_speakers))
         .route("/api/v1/speakers/{id}", get(api::get_speaker))
         .route("/api/v1/speakers/{id}", delete(api::remove_speaker))
+        .route("/api/v1/speakers/{id}", patch(api::update_speaker))
         // Layout configuration
         .route("/api/v1/layout", get(api::get_layout))
         .route("/api/v1/layout", post(api::set_layout))
+        .route("/api/v1/layout/presets", get(api::list_layout_presets))
         // Transport control
         .route("/api/v1/transport/play", post(api::transport_play))
         .route("/api/v1/transport/pause", post(api::transport_pause))
@@ -93,6 +126,7 @@ async fn main() -> Result<()> {
         .route("/api/v1/calibration/start", post(api::calibration_start))
         .route("/api/v1/calibration/status", get(api::calibration_status))
         .route("/api/v1/calibration/apply", post(api::calibration_apply))
+        .route("/api/v1/calibration/cancel", post(api::calibration_cancel))
         // Statistics and monitoring
         .route("/api/v1/stats", get(api::stats))
         .route("/api/v1/stats/network", get(api::stats_network))
@@ -104,21 +138,44 @@ async fn main() -> Result<()> {
             get(api::stats_audio_levels),
         )
         .route("/api/v1/speakers/{id}/stats", get(api::speaker_stats))
-        // Add CORS middleware
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        // Live streams
+        .route("/api/v1/stream/meters", get(api::stream_meters))
         .with_state(app_state);
 
+    let routes = match args.api_token {
+        Some(token) => {
+            anyhow::ensure!(!token.is_empty(), "--api-token must not be empty");
+            info!("API token authentication enabled");
+            routes.layer(middleware::from_fn_with_state(
+                ApiToken::new(token),
+                auth::require_token,
+            ))
+        }
+        None => routes,
+    };
+
+    // CORS wraps authentication so preflight requests never need a token
+    let app = routes.layer(
+        CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any),
+    );
+
     // Start server
     let addr: SocketAddr = format!("{}:{}", args.bind, args.port).parse()?;
     info!("Listening on http://{}", addr);
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            tokio::select! {
+                _ = shutdown_state.shutdown_requested() => {}
+                _ = tokio::signal::ctrl_c() => {}
+            }
+        })
+        .await?;
 
+    info!("Audio Ninja Daemon stopped");
     Ok(())
 }
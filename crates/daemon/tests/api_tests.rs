@@ -4,36 +4,43 @@
 
 use axum::{
     body::Body,
-    http::{Request, StatusCode},
+    http::{Request, Response, StatusCode},
     Router,
 };
 use serde_json::{json, Value};
 use tower::util::ServiceExt; // for `oneshot`
 use uuid::Uuid;
 
+use audio_ninja::output::{OutputError, PlaybackStream};
 use audio_ninja_daemon::AppState;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 /// Helper to create app with test state
 fn create_test_app() -> Router {
     use audio_ninja_daemon::EngineState;
-    use axum::routing::{delete, get, post};
-    use std::sync::Arc;
-    use tokio::sync::RwLock;
 
-    let engine_state = EngineState::new();
-    let app_state = AppState {
-        engine: Arc::new(RwLock::new(engine_state)),
-        started_at: Instant::now(),
-    };
+    create_test_router(AppState::new(EngineState::new()))
+}
+
+/// Helper to build the API routes around an existing state
+fn create_test_router(app_state: AppState) -> Router {
+    use axum::routing::{delete, get, patch, post};
 
     Router::new()
         .route("/api/v1/status", get(audio_ninja_daemon::api::status))
         .route("/api/v1/info", get(audio_ninja_daemon::api::info))
+        .route("/api/v1/volume", post(audio_ninja_daemon::api::set_volume))
+        .route("/api/v1/shutdown", post(audio_ninja_daemon::api::shutdown))
         .route(
             "/api/v1/speakers",
             get(audio_ninja_daemon::api::list_speakers),
         )
+        .route(
+            "/api/v1/speakers",
+            post(audio_ninja_daemon::api::create_speaker),
+        )
         .route(
             "/api/v1/speakers/discover",
             post(audio_ninja_daemon::api::discover_speakers),
@@ -46,8 +53,16 @@ fn create_test_app() -> Router {
             "/api/v1/speakers/{id}",
             delete(audio_ninja_daemon::api::remove_speaker),
         )
+        .route(
+            "/api/v1/speakers/{id}",
+            patch(audio_ninja_daemon::api::update_speaker),
+        )
         .route("/api/v1/layout", get(audio_ninja_daemon::api::get_layout))
         .route("/api/v1/layout", post(audio_ninja_daemon::api::set_layout))
+        .route(
+            "/api/v1/layout/presets",
+            get(audio_ninja_daemon::api::list_layout_presets),
+        )
         .route(
             "/api/v1/transport/play",
             post(audio_ninja_daemon::api::transport_play),
@@ -68,10 +83,26 @@ fn create_test_app() -> Router {
             "/api/v1/transport/load-file",
             post(audio_ninja_daemon::api::load_audio_file),
         )
+        .route(
+            "/api/v1/transport/mode",
+            post(audio_ninja_daemon::api::set_transport_mode),
+        )
+        .route(
+            "/api/v1/input/devices",
+            get(audio_ninja_daemon::api::list_input_devices),
+        )
+        .route(
+            "/api/v1/input/select",
+            post(audio_ninja_daemon::api::select_input_source),
+        )
         .route(
             "/api/v1/transport/playback-status",
             get(audio_ninja_daemon::api::playback_status),
         )
+        .route(
+            "/api/v1/transport/seek",
+            post(audio_ninja_daemon::api::transport_seek),
+        )
         .route(
             "/api/v1/calibration/start",
             post(audio_ninja_daemon::api::calibration_start),
@@ -84,6 +115,10 @@ fn create_test_app() -> Router {
             "/api/v1/calibration/apply",
             post(audio_ninja_daemon::api::calibration_apply),
         )
+        .route(
+            "/api/v1/calibration/cancel",
+            post(audio_ninja_daemon::api::calibration_cancel),
+        )
         .route("/api/v1/stats", get(audio_ninja_daemon::api::stats))
         .route(
             "/api/v1/stats/network",
@@ -109,6 +144,10 @@ fn create_test_app() -> Router {
             "/api/v1/speakers/{id}/stats",
             get(audio_ninja_daemon::api::speaker_stats),
         )
+        .route(
+            "/api/v1/stream/meters",
+            get(audio_ninja_daemon::api::stream_meters),
+        )
         .with_state(app_state)
 }
 
@@ -135,6 +174,447 @@ async fn test_status_endpoint() {
     assert_eq!(body["status"], "running");
     assert!(body["version"].is_string());
     assert!(body["uptime_secs"].is_number());
+    assert_eq!(body["volume"]["gain_db"], 0.0);
+    assert_eq!(body["volume"]["mute"], false);
+}
+
+async fn post_volume(app: &Router, body: Value) -> Response<Body> {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/volume")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_set_volume_reads_back_in_status() {
+    let app = create_test_app();
+
+    let response = post_volume(&app, json!({ "gain_db": -6.0 })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["gain_db"], -6.0);
+    assert_eq!(body["mute"], false);
+
+    // Mute alone keeps the gain
+    let response = post_volume(&app, json!({ "mute": true })).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/api/v1/status")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["volume"]["gain_db"], -6.0);
+    assert_eq!(body["volume"]["mute"], true);
+}
+
+#[tokio::test]
+async fn test_set_volume_rejects_invalid() {
+    let app = create_test_app();
+
+    let response = post_volume(&app, json!({})).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = post_volume(&app, json!({ "gain_db": 40.0 })).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn test_master_volume_applies_gain_and_mute() {
+    use audio_ninja::AudioBlock;
+    use audio_ninja_daemon::engine::EngineState;
+
+    let mut engine = EngineState::new();
+    let mut block = AudioBlock {
+        sample_rate: 48000,
+        channels: vec![vec![0.5; 4], vec![-0.5; 4]],
+    };
+
+    engine.set_master_gain_db(-6.0).unwrap();
+    engine.apply_master_volume(&mut block);
+    assert!((block.channels[0][0] - 0.2506).abs() < 1e-3);
+    assert!((block.channels[1][3] + 0.2506).abs() < 1e-3);
+
+    engine.set_mute(true);
+    engine.apply_master_volume(&mut block);
+    assert!(block.channels.iter().flatten().all(|&s| s == 0.0));
+}
+
+/// Output stream whose buffer plays out a little every time it is polled
+struct DrainingStream {
+    buffered: Arc<AtomicUsize>,
+    /// Frames still buffered when the stream was stopped
+    stopped_with: Arc<Mutex<Option<usize>>>,
+}
+
+impl PlaybackStream for DrainingStream {
+    fn start(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), OutputError> {
+        *self.stopped_with.lock().unwrap() = Some(self.buffered.load(Ordering::SeqCst));
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.stopped_with.lock().unwrap().is_none()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+
+    fn channels(&self) -> u32 {
+        2
+    }
+
+    fn latency_ms(&self) -> f32 {
+        0.0
+    }
+
+    fn write(&mut self, _data: &[Vec<f32>]) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn buffered_frames(&self) -> usize {
+        let left = self.buffered.load(Ordering::SeqCst).saturating_sub(1024);
+        self.buffered.store(left, Ordering::SeqCst);
+        left
+    }
+}
+
+/// Output stream that counts the frames written to it; the test drains
+/// its buffer by hand
+struct CountingStream {
+    buffered: Arc<AtomicUsize>,
+    written: Arc<AtomicUsize>,
+}
+
+impl PlaybackStream for CountingStream {
+    fn start(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), OutputError> {
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        true
+    }
+
+    fn sample_rate(&self) -> u32 {
+        48000
+    }
+
+    fn channels(&self) -> u32 {
+        1
+    }
+
+    fn latency_ms(&self) -> f32 {
+        0.0
+    }
+
+    fn write(&mut self, data: &[Vec<f32>]) -> Result<(), OutputError> {
+        let frames = data.first().map_or(0, Vec::len);
+        self.buffered.fetch_add(frames, Ordering::SeqCst);
+        self.written.fetch_add(frames, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn buffered_frames(&self) -> usize {
+        self.buffered.load(Ordering::SeqCst)
+    }
+}
+
+#[test]
+fn test_render_next_block_feeds_output_meters_and_metrics() {
+    use audio_ninja_daemon::engine::RENDER_BLOCK_FRAMES;
+    use audio_ninja_daemon::EngineState;
+
+    let temp = write_ramp_wav(16000);
+    let buffered = Arc::new(AtomicUsize::new(0));
+    let written = Arc::new(AtomicUsize::new(0));
+    let mut engine = EngineState::new();
+    engine.output_stream = Some(Box::new(CountingStream {
+        buffered: buffered.clone(),
+        written: written.clone(),
+    }));
+    engine
+        .load_audio_file(temp.path().to_str().unwrap())
+        .unwrap();
+
+    // Nothing renders until the transport plays
+    assert!(engine.render_next_block(RENDER_BLOCK_FRAMES).is_none());
+    assert!(engine.meter_frame().channels.is_empty());
+
+    engine.play().unwrap();
+    let block = engine.render_next_block(RENDER_BLOCK_FRAMES).unwrap();
+    // The 8 kHz file is resampled to the 48 kHz engine rate
+    assert_eq!(block.sample_rate, 48000);
+    // Each 8 kHz frame covers six output frames, so a block may run over by up to five
+    assert!((RENDER_BLOCK_FRAMES..RENDER_BLOCK_FRAMES + 6).contains(&block.frame_len()));
+    assert_eq!(written.load(Ordering::SeqCst), block.frame_len());
+    assert_eq!(engine.meter_frame().channels.len(), 1);
+    assert!(engine.metrics.render_load_percent > 0.0);
+    assert_eq!(engine.metrics.buffer_underruns, 0);
+
+    // The output ran dry before the next block arrived
+    buffered.store(0, Ordering::SeqCst);
+    engine.render_next_block(RENDER_BLOCK_FRAMES).unwrap();
+    assert_eq!(engine.metrics.buffer_underruns, 1);
+
+    // Still buffered: no underrun
+    engine.render_next_block(RENDER_BLOCK_FRAMES).unwrap();
+    assert_eq!(engine.metrics.buffer_underruns, 1);
+
+    // Paused, the output may drain without counting
+    engine.pause();
+    buffered.store(0, Ordering::SeqCst);
+    engine.play().unwrap();
+    engine.render_next_block(RENDER_BLOCK_FRAMES).unwrap();
+    assert_eq!(engine.metrics.buffer_underruns, 1);
+}
+
+#[test]
+fn test_render_next_block_resamples_at_the_exact_rate() {
+    use audio_ninja_daemon::engine::RENDER_BLOCK_FRAMES;
+    use audio_ninja_daemon::EngineState;
+
+    // Two seconds at 8 kHz is two seconds at the 48 kHz engine rate
+    let temp = write_ramp_wav(16000);
+    let mut engine = EngineState::new();
+    engine
+        .load_audio_file(temp.path().to_str().unwrap())
+        .unwrap();
+    engine.play().unwrap();
+
+    let mut frames = 0;
+    while let Some(block) = engine.render_next_block(RENDER_BLOCK_FRAMES) {
+        frames += block.frame_len();
+        // The position is what the render has read, in frames of the file
+        assert!(engine.playback_position().abs_diff(frames as u64 / 6) <= 17);
+    }
+    assert_eq!(frames, 96000);
+    assert_eq!(engine.playback_position(), 16000);
+
+    // The clock stands still with the render: pausing keeps the position
+    engine.seek(4000);
+    engine.pause();
+    assert_eq!(engine.playback_position(), 4000);
+    engine.play().unwrap();
+    assert_eq!(engine.playback_position(), 4000);
+}
+
+#[tokio::test]
+async fn test_shutdown_drains_output_and_signals_server() {
+    use audio_ninja_daemon::EngineState;
+
+    let buffered = Arc::new(AtomicUsize::new(4800));
+    let stopped_with = Arc::new(Mutex::new(None));
+    let mut engine = EngineState::new();
+    engine.output_stream = Some(Box::new(DrainingStream {
+        buffered: buffered.clone(),
+        stopped_with: stopped_with.clone(),
+    }));
+
+    let state = AppState::new(engine);
+    let mut shutdown = state.shutdown.subscribe();
+    let app = create_test_router(state.clone());
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/shutdown")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    tokio::time::timeout(
+        std::time::Duration::from_secs(2),
+        shutdown.wait_for(|&requested| requested),
+    )
+    .await
+    .expect("shutdown was not signalled")
+    .unwrap();
+
+    // The stream was closed only after its buffer ran dry
+    assert_eq!(*stopped_with.lock().unwrap(), Some(0));
+    let engine = state.engine.read().await;
+    assert!(engine.output_stream.is_none());
+    assert!(matches!(
+        engine.transport_state,
+        audio_ninja_daemon::engine::TransportState::Stopped
+    ));
+}
+
+#[test]
+fn test_engine_switches_sample_rate() {
+    use audio_ninja::dspconfig::{DspChainConfig, StageConfig};
+    use audio_ninja::output::{DeviceType, OutputDevice};
+    use audio_ninja_daemon::engine::EngineState;
+
+    let mut engine = EngineState::new();
+    engine.pipeline = DspChainConfig {
+        sample_rate: 48000,
+        stages: vec![StageConfig::Headroom {
+            headroom_db: 1.0,
+            lookahead_ms: 5.0,
+            true_peak: false,
+        }],
+    }
+    .build();
+    assert_eq!(engine.sample_rate(), 48000);
+    assert_eq!(engine.pipeline.total_latency_samples(), 240);
+
+    engine.set_sample_rate(44100).unwrap();
+    assert_eq!(engine.sample_rate(), 44100);
+    assert_eq!(engine.pipeline.sample_rate(), 44100);
+    assert_eq!(engine.pipeline.total_latency_samples(), 220);
+    assert_eq!(engine.output_meter.sample_rate(), 44100);
+    assert!((engine.metrics.pipeline_latency_ms - 220.0 / 44.1).abs() < 1e-3);
+
+    // A device that only runs at 44.1 kHz pins the engine there
+    let mut device = OutputDevice::new(
+        "dac".into(),
+        "USB DAC".into(),
+        DeviceType::Speaker,
+        2,
+        44100,
+    );
+    device.sample_rates = vec![44100];
+    engine.active_output_device = Some(device);
+    assert!(engine.set_sample_rate(48000).is_err());
+    assert_eq!(engine.sample_rate(), 44100);
+    assert!(engine.set_sample_rate(0).is_err());
+}
+
+/// Output stream that only records its rate and whether it is playing
+struct RateStream {
+    rate: u32,
+    running: Arc<Mutex<bool>>,
+}
+
+impl PlaybackStream for RateStream {
+    fn start(&mut self) -> Result<(), OutputError> {
+        *self.running.lock().unwrap() = true;
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), OutputError> {
+        *self.running.lock().unwrap() = false;
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        *self.running.lock().unwrap()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.rate
+    }
+
+    fn channels(&self) -> u32 {
+        2
+    }
+
+    fn latency_ms(&self) -> f32 {
+        512.0 / self.rate as f32 * 1000.0
+    }
+
+    fn write(&mut self, _data: &[Vec<f32>]) -> Result<(), OutputError> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_sample_rate_change_reopens_output_and_updates_stats() {
+    use audio_ninja_daemon::EngineState;
+
+    let old_running = Arc::new(Mutex::new(true));
+    let new_running = Arc::new(Mutex::new(false));
+    let mut engine = EngineState::new();
+    engine.output_stream = Some(Box::new(RateStream {
+        rate: 48000,
+        running: old_running.clone(),
+    }));
+
+    // Without a way to reopen the output the rate cannot change
+    assert!(engine.set_sample_rate(44100).is_err());
+    assert_eq!(engine.sample_rate(), 48000);
+
+    let running = new_running.clone();
+    engine.set_output_stream_opener(Box::new(move |rate, channels| {
+        assert_eq!(channels, 2);
+        Ok(Box::new(RateStream {
+            rate,
+            running: running.clone(),
+        }) as Box<dyn PlaybackStream>)
+    }));
+    engine.set_sample_rate(44100).unwrap();
+
+    // The playing stream was swapped for one at the new rate, still playing
+    let stream = engine.output_stream.as_ref().unwrap();
+    assert_eq!(stream.sample_rate(), 44100);
+    assert!(!*old_running.lock().unwrap());
+    assert!(*new_running.lock().unwrap());
+    assert!((engine.metrics.output_latency_ms - 512.0 / 44.1).abs() < 1e-3);
+
+    // Stats follow the engine, not the loaded file
+    engine.record_render(44100, std::time::Duration::from_millis(500));
+    assert!(engine.metrics.render_load_percent > 0.0);
+    let app = create_test_router(AppState::new(engine));
+    let request = Request::builder()
+        .uri("/api/v1/stats")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["sample_rate"], 44100);
+}
+
+#[test]
+fn test_load_dsp_preset_replaces_pipeline() {
+    use audio_ninja_daemon::EngineState;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("preset.toml");
+    std::fs::write(
+        &path,
+        r#"
+            sample_rate = 44100
+
+            [[stages]]
+            type = "drc"
+            ratio = 2.0
+            threshold_db = -18.0
+            attack_ms = 10.0
+            release_ms = 100.0
+
+            [[stages]]
+            type = "headroom"
+            headroom_db = 1.0
+            lookahead_ms = 5.0
+        "#,
+    )
+    .unwrap();
+
+    let mut engine = EngineState::new();
+    engine.load_dsp_preset(&path).unwrap();
+    assert_eq!(engine.sample_rate(), 44100);
+    assert_eq!(engine.pipeline.stage_names(), vec!["drc", "limiter"]);
+    assert!((engine.metrics.pipeline_latency_ms - 220.0 / 44.1).abs() < 1e-3);
+
+    // A missing file leaves the chain alone
+    assert!(engine
+        .load_dsp_preset(&dir.path().join("missing.toml"))
+        .is_err());
+    assert_eq!(engine.sample_rate(), 44100);
 }
 
 #[tokio::test]
@@ -156,6 +636,61 @@ async fn test_info_endpoint() {
     assert!(body["features"].as_array().unwrap().len() >= 4);
 }
 
+/// Test app that requires `secret` as the bearer token
+fn create_authenticated_app() -> Router {
+    use audio_ninja_daemon::{
+        auth::{require_token, ApiToken},
+        EngineState,
+    };
+
+    create_test_router(AppState::new(EngineState::new())).layer(
+        axum::middleware::from_fn_with_state(ApiToken::new("secret"), require_token),
+    )
+}
+
+fn speakers_request(authorization: Option<&str>) -> Request<Body> {
+    let mut request = Request::builder().uri("/api/v1/speakers");
+    if let Some(value) = authorization {
+        request = request.header("authorization", value);
+    }
+    request.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_api_token_required() {
+    let app = create_authenticated_app();
+
+    let response = app.clone().oneshot(speakers_request(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(response.headers()["www-authenticate"], "Bearer");
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["error"]["code"], "unauthorized");
+
+    let response = app
+        .clone()
+        .oneshot(speakers_request(Some("Bearer wrong")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .oneshot(speakers_request(Some("Bearer secret")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_health_endpoints_skip_api_token() {
+    let app = create_authenticated_app();
+
+    for uri in ["/api/v1/status", "/api/v1/info"] {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{uri}");
+    }
+}
+
 #[tokio::test]
 async fn test_list_speakers_empty() {
     let app = create_test_app();
@@ -202,6 +737,104 @@ async fn test_get_speaker_not_found() {
     let response = app.oneshot(request).await.unwrap();
 
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["error"]["code"], "speaker_not_found");
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains(&id.to_string()));
+}
+
+#[tokio::test]
+async fn test_create_and_patch_speaker() {
+    let app = create_test_app();
+    let id = Uuid::new_v4();
+
+    let create = json!({
+        "id": id,
+        "name": "Kitchen",
+        "address": "192.168.1.40:5004",
+        "role": "FrontLeft",
+        "position": { "azimuth": -30.0, "elevation": 0.0, "distance": 2.0 }
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/speakers")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&create).unwrap()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["id"], json!(id));
+    assert_eq!(body["role"], "FrontLeft");
+
+    // Same id again conflicts
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/speakers")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&create).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let patch = json!({ "role": "RearLeft", "trim_db": -1.5 });
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/api/v1/speakers/{}", id))
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&patch).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri(format!("/api/v1/speakers/{}", id))
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["name"], "Kitchen");
+    assert_eq!(body["role"], "RearLeft");
+    assert_eq!(body["trim_db"], -1.5);
+    // Fields not in the patch are untouched
+    assert_eq!(body["position"]["azimuth"], -30.0);
+}
+
+#[tokio::test]
+async fn test_create_speaker_validation() {
+    let app = create_test_app();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/speakers")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "name": "", "address": "10.0.0.2" })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_patch_speaker_not_found() {
+    let app = create_test_app();
+
+    let request = Request::builder()
+        .method("PATCH")
+        .uri(format!("/api/v1/speakers/{}", Uuid::new_v4()))
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "trim_db": 2.0 })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
 
 #[tokio::test]
@@ -292,6 +925,41 @@ async fn test_set_layout_invalid_preset() {
     let response = app.oneshot(request).await.unwrap();
 
     assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["error"]["code"], "invalid_preset");
+    assert!(body["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("invalid"));
+}
+
+#[tokio::test]
+async fn test_layout_presets_are_all_accepted() {
+    let app = create_test_app();
+
+    let request = Request::builder()
+        .uri("/api/v1/layout/presets")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let presets = json_body(response.into_body()).await;
+    let presets = presets.as_array().unwrap();
+    assert!(presets.contains(&json!("7.1.2")));
+
+    for preset in presets {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/layout")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::to_vec(&json!({ "preset": preset })).unwrap(),
+            ))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "preset {}", preset);
+    }
 }
 
 #[tokio::test]
@@ -336,7 +1004,9 @@ async fn test_set_layout_custom() {
 
 #[tokio::test]
 async fn test_transport_play() {
+    let temp = write_ramp_wav(8000);
     let app = create_test_app();
+    load_file(&app, temp.path()).await;
 
     let request = Request::builder()
         .method("POST")
@@ -434,6 +1104,19 @@ async fn test_calibration_status() {
 async fn test_calibration_apply_flow() {
     let app = create_test_app();
 
+    // Calibration solves against the configured layout
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/layout")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "preset": "5.1" })).unwrap(),
+        ))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
     // Begin calibration session
     let request = Request::builder()
         .method("POST")
@@ -444,6 +1127,17 @@ async fn test_calibration_apply_flow() {
     let response = app.clone().oneshot(request).await.unwrap();
     assert_eq!(response.status(), StatusCode::ACCEPTED);
 
+    // Wait for every speaker to be measured
+    let mut status = calibration_status(&app).await;
+    for _ in 0..100 {
+        if !status["running"].as_bool().unwrap() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        status = calibration_status(&app).await;
+    }
+    assert!(!status["running"].as_bool().unwrap());
+
     // Apply calibration
     let request = Request::builder()
         .method("POST")
@@ -454,19 +1148,110 @@ async fn test_calibration_apply_flow() {
     let response = app.clone().oneshot(request).await.unwrap();
     assert_eq!(response.status(), StatusCode::OK);
 
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["applied"], true);
+    let channels = body["channels"].as_array().unwrap();
+    assert_eq!(channels.len(), 6);
+    for channel in channels {
+        assert!(channel["delay_ms"].as_f64().unwrap() >= 0.0);
+        assert!(channel["trim_db"].as_f64().unwrap() <= 0.0);
+    }
+
     // Verify status reflects completion
+    let body = calibration_status(&app).await;
+    assert!(!body["running"].as_bool().unwrap());
+    assert!((body["progress"].as_f64().unwrap() - 1.0).abs() < f64::EPSILON);
+    assert_eq!(body["measurements"].as_u64().unwrap(), 6);
+}
+
+async fn calibration_status(app: &Router) -> Value {
     let request = Request::builder()
         .uri("/api/v1/calibration/status")
         .body(Body::empty())
         .unwrap();
 
-    let response = app.oneshot(request).await.unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
     assert_eq!(response.status(), StatusCode::OK);
+    json_body(response.into_body()).await
+}
 
-    let body = json_body(response.into_body()).await;
-    assert!(!body["running"].as_bool().unwrap());
-    assert!((body["progress"].as_f64().unwrap() - 1.0).abs() < f64::EPSILON);
-    assert!(body["measurements"].as_u64().unwrap() >= 1);
+async fn start_calibration_with_layout(app: &Router) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/layout")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "preset": "7.1.4" })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/calibration/start")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_calibration_progress_advances() {
+    let app = create_test_app();
+    start_calibration_with_layout(&app).await;
+
+    let mut status = calibration_status(&app).await;
+    for _ in 0..100 {
+        if status["progress"].as_f64().unwrap() > 0.0 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        status = calibration_status(&app).await;
+    }
+
+    let progress = status["progress"].as_f64().unwrap();
+    assert!(progress > 0.0 && progress < 1.0, "progress = {}", progress);
+    assert!(status["running"].as_bool().unwrap());
+    assert!(status["measurements"].as_u64().unwrap() >= 1);
+
+    // Nothing to apply while the run is still measuring
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/calibration/apply")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_calibration_cancel() {
+    let app = create_test_app();
+    start_calibration_with_layout(&app).await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/calibration/cancel")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The cancelled run must not keep advancing
+    tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    let status = calibration_status(&app).await;
+    assert!(!status["running"].as_bool().unwrap());
+    assert!(status["measurements"].as_u64().unwrap() <= 1);
+
+    // Cancelling again has nothing to stop
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/calibration/cancel")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
 }
 
 #[tokio::test]
@@ -481,7 +1266,31 @@ async fn test_calibration_apply_without_session() {
 
     let response = app.oneshot(request).await.unwrap();
 
-    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_calibration_apply_before_run_completes() {
+    let app = create_test_app();
+
+    // Without a layout the run cannot complete
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/calibration/start")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/calibration/apply")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
 }
 
 #[tokio::test]
@@ -502,6 +1311,10 @@ async fn test_stats() {
     assert!(body["online_speakers"].is_number());
     assert!(body["transport_state"].is_string());
     assert!(body["has_layout"].is_boolean());
+    assert!(body["render_load_percent"].is_number());
+    assert!(body["buffer_underruns"].is_number());
+    assert!(body["current_latency_ms"].is_number());
+    assert_eq!(body["sample_rate"], 48000);
 }
 
 #[tokio::test]
@@ -560,9 +1373,44 @@ async fn test_layout_workflow() {
     assert!(body["speakers"].is_array());
 }
 
+#[tokio::test]
+async fn test_layout_persists_across_restart() {
+    use audio_ninja_daemon::EngineState;
+    use tokio::sync::RwLock;
+
+    let dir = tempfile::tempdir().unwrap();
+    let engine = Arc::new(RwLock::new(EngineState::with_state_dir(dir.path())));
+    let app = create_test_router(AppState {
+        engine: engine.clone(),
+        started_at: Instant::now(),
+        shutdown: tokio::sync::watch::channel(false).0,
+    });
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/layout")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "preset": "5.1" })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let expected = engine.read().await.layout.clone().unwrap();
+    drop(engine);
+
+    // A fresh engine on the same directory comes back with the layout
+    let restored = EngineState::with_state_dir(dir.path());
+    assert_eq!(restored.layout, Some(expected));
+    assert_eq!(restored.layout.unwrap().speakers.len(), 6);
+}
+
 #[tokio::test]
 async fn test_transport_state_workflow() {
+    let temp = write_ramp_wav(8000);
     let app = create_test_app();
+    load_file(&app, temp.path()).await;
 
     // Check initial state
     let request = Request::builder()
@@ -832,6 +1680,296 @@ async fn test_load_unsupported_format() {
     assert_eq!(response.status(), StatusCode::OK);
 }
 
+/// Write a 16-bit mono WAV at 8kHz whose sample `n` has the value `n`
+fn write_ramp_wav(frames: u32) -> tempfile::NamedTempFile {
+    use audio_ninja::output::{write_wav, BitDepth};
+
+    let temp = tempfile::NamedTempFile::new().unwrap();
+    let block = audio_ninja::AudioBlock {
+        sample_rate: 8000,
+        channels: vec![(0..frames).map(|n| n as f32 / 32768.0).collect()],
+    };
+    write_wav(temp.path(), &block, BitDepth::Int16).unwrap();
+    temp
+}
+
+async fn transport_status(app: &Router) -> Value {
+    let request = Request::builder()
+        .uri("/api/v1/transport/status")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    json_body(response.into_body()).await
+}
+
+#[tokio::test]
+async fn test_transport_status_reports_position_and_duration() {
+    use audio_ninja_daemon::EngineState;
+
+    let temp = write_ramp_wav(16000);
+    // Position follows the file reader, which only the render loop advances
+    let state = AppState::new(EngineState::new());
+    tokio::spawn(state.clone().run_render_loop());
+    let app = create_test_router(state);
+
+    let load_request = json!({
+        "file_path": temp.path().to_string_lossy().to_string()
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/load-file")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&load_request).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let status = transport_status(&app).await;
+    assert_eq!(status["position_secs"].as_f64().unwrap(), 0.0);
+    assert!((status["duration_secs"].as_f64().unwrap() - 2.0).abs() < 1e-9);
+    assert_eq!(
+        status["file"].as_str().unwrap(),
+        temp.path().to_string_lossy()
+    );
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/play")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let status = transport_status(&app).await;
+    assert_eq!(status["state"], "Playing");
+    let position = status["position_secs"].as_f64().unwrap();
+    assert!(position > 0.0 && position <= 2.0, "position = {}", position);
+
+    // Live input has no fixed length
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/mode")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "mode": "stream" })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let status = transport_status(&app).await;
+    assert!(status["duration_secs"].is_null());
+    assert!(status["file"].is_null());
+}
+
+async fn load_file(app: &Router, path: &std::path::Path) {
+    let load_request = json!({ "file_path": path.to_string_lossy().to_string() });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/load-file")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&load_request).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+async fn set_transport_mode(app: &Router, mode: &str) -> Response<Body> {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/mode")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "mode": mode })).unwrap(),
+        ))
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap()
+}
+
+async fn play(app: &Router) -> StatusCode {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/play")
+        .body(Body::empty())
+        .unwrap();
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+async fn select_system_input(app: &Router) {
+    let request = Request::builder()
+        .uri("/api/v1/input/devices")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/input/select")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "source_id": "system" })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_transport_mode_valid() {
+    let app = create_test_app();
+
+    for (mode, expected) in [
+        ("file", "FilePlayback"),
+        ("stream", "LiveStream"),
+        ("mixed", "Mixed"),
+        ("Stream", "LiveStream"),
+    ] {
+        let response = set_transport_mode(&app, mode).await;
+        assert_eq!(response.status(), StatusCode::OK, "mode {}", mode);
+        let body = json_body(response.into_body()).await;
+        assert_eq!(body["mode"], expected);
+    }
+}
+
+#[tokio::test]
+async fn test_transport_mode_invalid() {
+    let app = create_test_app();
+
+    let response = set_transport_mode(&app, "tape").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_play_file_mode_requires_file() {
+    let app = create_test_app();
+    assert_eq!(play(&app).await, StatusCode::CONFLICT);
+
+    let temp = write_ramp_wav(8000);
+    load_file(&app, temp.path()).await;
+    assert_eq!(play(&app).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_play_stream_mode_requires_input() {
+    let app = create_test_app();
+    assert_eq!(
+        set_transport_mode(&app, "stream").await.status(),
+        StatusCode::OK
+    );
+
+    // A loaded file does not help a live stream
+    let temp = write_ramp_wav(8000);
+    load_file(&app, temp.path()).await;
+    assert_eq!(play(&app).await, StatusCode::CONFLICT);
+
+    select_system_input(&app).await;
+    assert_eq!(play(&app).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_play_mixed_mode_requires_file_and_input() {
+    let app = create_test_app();
+    assert_eq!(
+        set_transport_mode(&app, "mixed").await.status(),
+        StatusCode::OK
+    );
+
+    select_system_input(&app).await;
+    assert_eq!(play(&app).await, StatusCode::CONFLICT);
+
+    let temp = write_ramp_wav(8000);
+    load_file(&app, temp.path()).await;
+    assert_eq!(play(&app).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_transport_seek_without_file() {
+    let app = create_test_app();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/seek")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "position_secs": 1.0 })).unwrap(),
+        ))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_transport_seek_secs() {
+    let temp = write_ramp_wav(16000);
+    let app = create_test_app();
+
+    let load_request = json!({
+        "file_path": temp.path().to_string_lossy().to_string()
+    });
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/load-file")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&load_request).unwrap()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/seek")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "position_secs": 1.0 })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["position"], 8000);
+    assert_eq!(body["position_secs"], 1.0);
+
+    // Negative offsets are rejected
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/seek")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "position_secs": -1.0 })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // So are offsets too large for a Duration
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/transport/seek")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "position_secs": 1e30 })).unwrap(),
+        ))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["error"]["code"], "invalid_position");
+
+    let request = Request::builder()
+        .uri("/api/v1/transport/playback-status")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body["position"], 8000);
+    assert_eq!(body["total_samples"], 16000);
+}
+
 // ===== New Stats Sub-Endpoint Tests =====
 
 #[tokio::test]
@@ -952,6 +2090,93 @@ async fn test_set_layout_7_1() {
     assert_eq!(response.status(), StatusCode::OK);
 }
 
+fn custom_room_layout() -> Value {
+    json!({
+        "name": "study",
+        "speakers": [
+            {
+                "id": "desk-left",
+                "role": "FrontLeft",
+                "position": { "x": -0.5, "y": 1.0, "z": 0.25 },
+                "max_spl_db": 105.0,
+                "latency": 0.0
+            },
+            {
+                "id": "desk-right",
+                "role": "FrontRight",
+                "position": { "x": 0.5, "y": 1.0, "z": 0.25 },
+                "max_spl_db": 105.0,
+                "latency": 0.0
+            },
+            {
+                "id": "shelf",
+                "role": { "Custom": "Shelf" },
+                "position": { "x": 0.0, "y": -2.0, "z": 1.5 },
+                "max_spl_db": 98.0,
+                "latency": 0.0015
+            }
+        ]
+    })
+}
+
+#[tokio::test]
+async fn test_set_layout_full_descriptors_roundtrip() {
+    let app = create_test_app();
+    let layout = custom_room_layout();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/layout")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&layout).unwrap()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/api/v1/layout")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response.into_body()).await;
+    assert_eq!(body, layout);
+}
+
+#[tokio::test]
+async fn test_set_layout_full_descriptors_rejects_invalid() {
+    let app = create_test_app();
+
+    // Duplicate speaker ids
+    let mut duplicate = custom_room_layout();
+    duplicate["speakers"][2]["id"] = json!("desk-left");
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/layout")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(&duplicate).unwrap()))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Empty speaker list
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/layout")
+        .header("content-type", "application/json")
+        .body(Body::from(
+            serde_json::to_vec(&json!({ "name": "empty", "speakers": [] })).unwrap(),
+        ))
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 #[tokio::test]
 async fn test_set_layout_7_1_4() {
     let app = create_test_app();
@@ -1038,3 +2263,56 @@ async fn test_set_layout_all_presets() {
         );
     }
 }
+
+#[tokio::test]
+async fn test_stream_meters_websocket() {
+    use audio_ninja::AudioBlock;
+    use audio_ninja_daemon::EngineState;
+    use futures_util::StreamExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let tone = |amplitude: f32| -> Vec<f32> {
+        (0..48000)
+            .map(|n| amplitude * (2.0 * std::f32::consts::PI * 1000.0 * n as f32 / 48000.0).sin())
+            .collect()
+    };
+    let mut engine = EngineState::new();
+    engine.meter_output(&AudioBlock {
+        sample_rate: 48000,
+        channels: vec![tone(0.5), tone(0.25)],
+    });
+    let app = create_test_router(AppState::new(engine));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let (mut socket, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}/api/v1/stream/meters", addr))
+            .await
+            .unwrap();
+
+    for _ in 0..3 {
+        let message = tokio::time::timeout(std::time::Duration::from_secs(2), socket.next())
+            .await
+            .expect("meter frame within timeout")
+            .unwrap()
+            .unwrap();
+        let Message::Text(text) = message else {
+            panic!("expected a text frame, got {:?}", message);
+        };
+        let frame: Value = serde_json::from_str(&text).unwrap();
+
+        let channels = frame["channels"].as_array().unwrap();
+        assert_eq!(channels.len(), 2);
+        for channel in channels {
+            assert!(channel["peak_db"].is_number());
+            assert!(channel["rms_db"].is_number());
+        }
+        let left_peak = channels[0]["peak_db"].as_f64().unwrap();
+        assert!((left_peak - (-6.02)).abs() < 0.1, "left peak {}", left_peak);
+        assert!(frame["integrated_lufs"].is_number());
+    }
+}
@@ -11,19 +11,43 @@
     windows_subsystem = "windows"
 )]
 
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::api::dialog::blocking::FileDialogBuilder;
+use tauri::Manager;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, handshake::client::Request, http, Message},
+};
 use uuid::Uuid;
 
 // Daemon API client configuration
 const DAEMON_URL: &str = "http://127.0.0.1:8080/api/v1";
 
+/// Environment variable holding the token of a daemon started with --api-token
+const API_TOKEN_ENV: &str = "AUDIO_NINJA_API_TOKEN";
+
+/// Event carrying a `MeterFrame` to the frontend
+const METER_EVENT: &str = "meters";
+
+/// Minimum spacing between meter events (~20 Hz)
+const METER_EMIT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Wait before reconnecting a dropped meter stream
+const METER_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+
 // Application state
 struct AppState {
     daemon_url: String,
     http_client: reqwest::Client,
+    /// Bearer token sent with every daemon request
+    api_token: Option<String>,
+    /// Background task forwarding the daemon meter stream, while subscribed
+    meter_task: Option<JoinHandle<()>>,
 }
 
 impl AppState {
@@ -31,7 +55,100 @@ impl AppState {
         Self {
             daemon_url: DAEMON_URL.to_string(),
             http_client: reqwest::Client::new(),
+            api_token: None,
+            meter_task: None,
+        }
+    }
+
+    /// Authenticate REST calls and the meter stream with `token`
+    fn with_api_token(mut self, token: String) -> Result<Self, String> {
+        let value = reqwest::header::HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|e| format!("API token is not a valid header value: {}", e))?;
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::AUTHORIZATION, value);
+
+        self.http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .map_err(|e| e.to_string())?;
+        self.api_token = Some(token);
+        Ok(self)
+    }
+
+    /// WebSocket URL of the daemon meter stream
+    fn meter_stream_url(&self) -> String {
+        let url = &self.daemon_url;
+        let ws = if let Some(rest) = url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            url.clone()
+        };
+        format!("{}/stream/meters", ws.trim_end_matches('/'))
+    }
+
+    /// Forward meter frames to `emit`, replacing any running subscription
+    fn start_meter_stream<F>(&mut self, emit: F)
+    where
+        F: Fn(MeterFrame) + Send + 'static,
+    {
+        self.stop_meter_stream();
+        let url = self.meter_stream_url();
+        let api_token = self.api_token.clone();
+        self.meter_task = Some(tokio::spawn(forward_meter_frames(url, api_token, emit)));
+    }
+
+    /// Cancel the meter subscription; false if none was running
+    fn stop_meter_stream(&mut self) -> bool {
+        match self.meter_task.take() {
+            Some(task) => {
+                task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Handshake request for the meter stream, carrying the API token if any
+fn meter_stream_request(url: &str, api_token: Option<&str>) -> Result<Request, String> {
+    let mut request = url.into_client_request().map_err(|e| e.to_string())?;
+    if let Some(token) = api_token {
+        let value =
+            http::HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|e| e.to_string())?;
+        request
+            .headers_mut()
+            .insert(http::header::AUTHORIZATION, value);
+    }
+    Ok(request)
+}
+
+/// Relay frames from the daemon meter WebSocket, reconnecting until aborted
+async fn forward_meter_frames<F>(url: String, api_token: Option<String>, emit: F)
+where
+    F: Fn(MeterFrame) + Send + 'static,
+{
+    loop {
+        let Ok(request) = meter_stream_request(&url, api_token.as_deref()) else {
+            return;
+        };
+        if let Ok((mut socket, _)) = connect_async(request).await {
+            let mut last_emit: Option<Instant> = None;
+            while let Some(Ok(message)) = socket.next().await {
+                let Message::Text(text) = message else {
+                    continue;
+                };
+                if last_emit.is_some_and(|at| at.elapsed() < METER_EMIT_INTERVAL) {
+                    continue;
+                }
+                if let Ok(frame) = serde_json::from_str::<MeterFrame>(&text) {
+                    emit(frame);
+                    last_emit = Some(Instant::now());
+                }
+            }
         }
+        tokio::time::sleep(METER_RECONNECT_DELAY).await;
     }
 }
 
@@ -43,6 +160,10 @@ pub struct SpeakerInfo {
     pub address: String,
     pub position: Option<SpeakerPosition>,
     pub online: bool,
+    #[serde(default)]
+    pub role: Option<serde_json::Value>,
+    #[serde(default)]
+    pub trim_db: f32,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -62,6 +183,13 @@ pub struct StatusResponse {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransportStatus {
     pub state: String,
+    #[serde(default)]
+    pub position_secs: f64,
+    /// `None` while streaming live input
+    #[serde(default)]
+    pub duration_secs: Option<f64>,
+    #[serde(default)]
+    pub file: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,6 +205,51 @@ pub struct StatsResponse {
     pub online_speakers: usize,
     pub transport_state: String,
     pub has_layout: bool,
+    #[serde(default)]
+    pub render_load_percent: f32,
+    #[serde(default)]
+    pub buffer_underruns: u64,
+    #[serde(default)]
+    pub current_latency_ms: f32,
+    #[serde(default)]
+    pub sample_rate: u32,
+}
+
+/// Body of `POST /speakers`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NewSpeaker {
+    pub name: String,
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<SpeakerPosition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_db: Option<f32>,
+}
+
+/// Body of `PATCH /speakers/{id}`; unset fields are left unchanged
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SpeakerPatch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<SpeakerPosition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_db: Option<f32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChannelLevel {
+    pub peak_db: f32,
+    pub rms_db: f32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MeterFrame {
+    pub channels: Vec<ChannelLevel>,
+    #[serde(default)]
+    pub integrated_lufs: Option<f32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -303,9 +476,143 @@ async fn get_speaker_stats(
         .map_err(|e| e.to_string())
 }
 
+// ===== Requests with daemon error reporting =====
+
+impl AppState {
+    fn add_speaker_request(&self, speaker: &NewSpeaker) -> reqwest::Result<reqwest::Request> {
+        self.http_client
+            .post(format!("{}/speakers", self.daemon_url))
+            .json(speaker)
+            .build()
+    }
+
+    fn update_speaker_request(
+        &self,
+        id: Uuid,
+        patch: &SpeakerPatch,
+    ) -> reqwest::Result<reqwest::Request> {
+        self.http_client
+            .patch(format!("{}/speakers/{}", self.daemon_url, id))
+            .json(patch)
+            .build()
+    }
+
+    fn apply_calibration_request(&self) -> reqwest::Result<reqwest::Request> {
+        self.http_client
+            .post(format!("{}/calibration/apply", self.daemon_url))
+            .build()
+    }
+
+    fn load_file_request(&self, file_path: &str) -> reqwest::Result<reqwest::Request> {
+        self.http_client
+            .post(format!("{}/transport/load-file", self.daemon_url))
+            .json(&serde_json::json!({ "file_path": file_path }))
+            .build()
+    }
+
+    /// Send `request`, turning transport errors and non-2xx replies into
+    /// "Failed to <action>: ..." messages for the frontend
+    async fn send_checked(
+        &self,
+        request: reqwest::Result<reqwest::Request>,
+        action: &str,
+    ) -> Result<reqwest::Response, String> {
+        let request = request.map_err(|e| format!("Failed to {}: {}", action, e))?;
+        let response = self
+            .http_client
+            .execute(request)
+            .await
+            .map_err(|e| format!("Failed to {}: {}", action, e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Failed to {}: daemon returned {}", action, status));
+        }
+        Ok(response)
+    }
+}
+
+#[tauri::command]
+async fn add_speaker(
+    speaker: NewSpeaker,
+    state: tauri::State<'_, Arc<RwLock<AppState>>>,
+) -> Result<SpeakerInfo, String> {
+    let app = state.read().await;
+    app.send_checked(app.add_speaker_request(&speaker), "add speaker")
+        .await?
+        .json()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn update_speaker(
+    id: Uuid,
+    patch: SpeakerPatch,
+    state: tauri::State<'_, Arc<RwLock<AppState>>>,
+) -> Result<SpeakerInfo, String> {
+    let app = state.read().await;
+    app.send_checked(app.update_speaker_request(id, &patch), "update speaker")
+        .await?
+        .json()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn apply_calibration(
+    state: tauri::State<'_, Arc<RwLock<AppState>>>,
+) -> Result<serde_json::Value, String> {
+    let app = state.read().await;
+    app.send_checked(app.apply_calibration_request(), "apply calibration")
+        .await?
+        .json()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn load_file(
+    file_path: String,
+    state: tauri::State<'_, Arc<RwLock<AppState>>>,
+) -> Result<serde_json::Value, String> {
+    let app = state.read().await;
+    app.send_checked(app.load_file_request(&file_path), "load file")
+        .await?
+        .json()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Start emitting `meters` events with live output levels
+#[tauri::command]
+async fn subscribe_meters(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, Arc<RwLock<AppState>>>,
+) -> Result<(), String> {
+    let mut app = state.write().await;
+    app.start_meter_stream(move |frame| {
+        let _ = app_handle.emit_all(METER_EVENT, frame);
+    });
+    Ok(())
+}
+
+/// Stop emitting `meters` events
+#[tauri::command]
+async fn unsubscribe_meters(state: tauri::State<'_, Arc<RwLock<AppState>>>) -> Result<(), String> {
+    state.write().await.stop_meter_stream();
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
-    let app_state = Arc::new(RwLock::new(AppState::new()));
+    let mut app_state = AppState::new();
+    if let Ok(token) = std::env::var(API_TOKEN_ENV) {
+        app_state = app_state
+            .with_api_token(token)
+            .unwrap_or_else(|e| panic!("{}: {}", API_TOKEN_ENV, e));
+    }
+    let app_state = Arc::new(RwLock::new(app_state));
 
     tauri::Builder::default()
         .manage(app_state)
@@ -323,7 +630,197 @@ async fn main() {
             get_calibration_status,
             get_stats,
             get_speaker_stats,
+            add_speaker,
+            update_speaker,
+            apply_calibration,
+            load_file,
+            subscribe_meters,
+            unsubscribe_meters,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_stream_url() {
+        let app = AppState::new();
+        assert_eq!(
+            app.meter_stream_url(),
+            "ws://127.0.0.1:8080/api/v1/stream/meters"
+        );
+    }
+
+    #[test]
+    fn test_meter_stream_request_carries_token() {
+        let app = AppState::new();
+        let url = app.meter_stream_url();
+
+        let request = meter_stream_request(&url, Some("secret")).unwrap();
+        assert_eq!(
+            request.headers()[http::header::AUTHORIZATION],
+            "Bearer secret"
+        );
+
+        let request = meter_stream_request(&url, None).unwrap();
+        assert!(!request.headers().contains_key(http::header::AUTHORIZATION));
+    }
+
+    #[test]
+    fn test_with_api_token_rejects_invalid_header_value() {
+        assert!(AppState::new().with_api_token("bad\ntoken".into()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_send_checked_sends_api_token() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = stream.read(&mut buf).await.unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}")
+                .await
+                .unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_lowercase()
+        });
+
+        let mut app = AppState::new().with_api_token("secret".into()).unwrap();
+        app.daemon_url = format!("http://{}/api/v1", addr);
+        app.send_checked(app.apply_calibration_request(), "apply calibration")
+            .await
+            .unwrap();
+
+        let request = server.await.unwrap();
+        assert!(
+            request.contains("authorization: bearer secret"),
+            "{}",
+            request
+        );
+    }
+
+    fn body_json(request: &reqwest::Request) -> serde_json::Value {
+        let bytes = request.body().and_then(|b| b.as_bytes()).expect("body");
+        serde_json::from_slice(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_add_speaker_request() {
+        let app = AppState::new();
+        let speaker = NewSpeaker {
+            name: "Left".to_string(),
+            address: "192.168.1.20:5000".to_string(),
+            role: Some(serde_json::json!("FrontLeft")),
+            position: None,
+            trim_db: Some(-1.5),
+        };
+
+        let request = app.add_speaker_request(&speaker).unwrap();
+        assert_eq!(request.method(), reqwest::Method::POST);
+        assert_eq!(
+            request.url().as_str(),
+            "http://127.0.0.1:8080/api/v1/speakers"
+        );
+        assert_eq!(
+            body_json(&request),
+            serde_json::json!({
+                "name": "Left",
+                "address": "192.168.1.20:5000",
+                "role": "FrontLeft",
+                "trim_db": -1.5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_update_speaker_request() {
+        let app = AppState::new();
+        let id = Uuid::new_v4();
+        let patch = SpeakerPatch {
+            trim_db: Some(2.0),
+            ..SpeakerPatch::default()
+        };
+
+        let request = app.update_speaker_request(id, &patch).unwrap();
+        assert_eq!(request.method(), reqwest::Method::PATCH);
+        assert_eq!(
+            request.url().as_str(),
+            format!("http://127.0.0.1:8080/api/v1/speakers/{}", id)
+        );
+        assert_eq!(body_json(&request), serde_json::json!({ "trim_db": 2.0 }));
+    }
+
+    #[test]
+    fn test_apply_calibration_request() {
+        let app = AppState::new();
+
+        let request = app.apply_calibration_request().unwrap();
+        assert_eq!(request.method(), reqwest::Method::POST);
+        assert_eq!(
+            request.url().as_str(),
+            "http://127.0.0.1:8080/api/v1/calibration/apply"
+        );
+        assert!(request.body().is_none());
+    }
+
+    #[test]
+    fn test_load_file_request() {
+        let app = AppState::new();
+
+        let request = app.load_file_request("/music/track.wav").unwrap();
+        assert_eq!(request.method(), reqwest::Method::POST);
+        assert_eq!(
+            request.url().as_str(),
+            "http://127.0.0.1:8080/api/v1/transport/load-file"
+        );
+        assert_eq!(
+            body_json(&request),
+            serde_json::json!({ "file_path": "/music/track.wav" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_checked_reports_unreachable_daemon() {
+        let mut app = AppState::new();
+        app.daemon_url = "http://127.0.0.1:9/api/v1".to_string();
+
+        let err = app
+            .send_checked(app.apply_calibration_request(), "apply calibration")
+            .await
+            .unwrap_err();
+        assert!(err.starts_with("Failed to apply calibration:"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_meter_subscription_handle_lifecycle() {
+        let mut app = AppState::new();
+        // Nothing listens here, so the task keeps retrying until aborted
+        app.daemon_url = "http://127.0.0.1:9/api/v1".to_string();
+        assert!(!app.stop_meter_stream());
+
+        app.start_meter_stream(|_| {});
+        let first = app.meter_task.as_ref().expect("task stored").abort_handle();
+        assert!(!first.is_finished());
+
+        // Resubscribing replaces and cancels the running task
+        app.start_meter_stream(|_| {});
+        assert!(app.meter_task.is_some());
+        for _ in 0..10 {
+            if first.is_finished() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert!(first.is_finished());
+
+        assert!(app.stop_meter_stream());
+        assert!(app.meter_task.is_none());
+        assert!(!app.stop_meter_stream());
+    }
+}